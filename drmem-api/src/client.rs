@@ -85,6 +85,7 @@ pub enum Request {
         name: device::Name,
         start: Option<DateTime<Utc>>,
         end: Option<DateTime<Utc>>,
+        aggregate: Option<device::Aggregate>,
         rpy_chan: oneshot::Sender<Result<device::DataStream<device::Reading>>>,
     },
 }
@@ -114,6 +115,36 @@ impl RequestChan {
         name: device::Name,
         start: Option<DateTime<Utc>>,
         end: Option<DateTime<Utc>>,
+    ) -> Result<device::DataStream<device::Reading>> {
+        self.monitor(name, start, end, None).await
+    }
+
+    /// Makes a request to monitor the device, `name`, with its
+    /// readings downsampled as described by `aggregate`.
+    ///
+    /// If sucessful, a stream is returned which yields a reading for
+    /// each aggregation interval. An interval is reported once a
+    /// reading from a later interval arrives, or when the stream
+    /// reaches the `end` time.
+    pub async fn monitor_aggregate(
+        &self,
+        name: device::Name,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        aggregate: device::Aggregate,
+    ) -> Result<device::DataStream<device::Reading>> {
+        self.monitor(name, start, end, Some(aggregate)).await
+    }
+
+    // Sends a `MonitorDevice` request to the core and waits for the
+    // reply.
+
+    async fn monitor(
+        &self,
+        name: device::Name,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        aggregate: Option<device::Aggregate>,
     ) -> Result<device::DataStream<device::Reading>> {
         // Create our reply channel and build the request message.
 
@@ -123,6 +154,7 @@ impl RequestChan {
             rpy_chan: tx,
             start,
             end,
+            aggregate,
         };
 
         // Send the message.
@@ -3,7 +3,7 @@
 
 use crate::types::{device, Error};
 use std::future::Future;
-use std::{convert::Infallible, pin::Pin, sync::Arc};
+use std::{convert::Infallible, pin::Pin, sync::Arc, time::Duration};
 use tokio::sync::{mpsc, oneshot, Mutex};
 use toml::value;
 
@@ -42,6 +42,7 @@ pub enum Request {
         dev_name: device::Name,
         dev_units: Option<String>,
         max_history: Option<usize>,
+        max_age: Option<Duration>,
         rpy_chan: oneshot::Sender<Result<ReportReading>>,
     },
 
@@ -56,6 +57,7 @@ pub enum Request {
         dev_name: device::Name,
         dev_units: Option<String>,
         max_history: Option<usize>,
+        max_age: Option<Duration>,
         rpy_chan: oneshot::Sender<
             Result<(ReportReading, RxDeviceSetting, Option<device::Value>)>,
         >,
@@ -68,10 +70,14 @@ pub enum Request {
 ///
 /// This type wraps the `mpsc::Sender<>` and defines a set of helper
 /// methods to send requests and receive replies with the core.
+///
+/// The handle also holds the `max_age` setting of the driver
+/// instance, which is sent with each device registration.
 #[derive(Clone)]
 pub struct RequestChan {
     driver_name: Name,
     prefix: device::Path,
+    max_age: Option<Duration>,
     req_chan: mpsc::Sender<Request>,
 }
 
@@ -79,11 +85,13 @@ impl RequestChan {
     pub fn new(
         driver_name: Name,
         prefix: &device::Path,
+        max_age: Option<Duration>,
         req_chan: &mpsc::Sender<Request>,
     ) -> Self {
         RequestChan {
             driver_name,
             prefix: prefix.clone(),
+            max_age,
             req_chan: req_chan.clone(),
         }
     }
@@ -125,6 +133,7 @@ impl RequestChan {
                 dev_name: device::Name::build(self.prefix.clone(), name),
                 dev_units: units.map(String::from),
                 max_history,
+                max_age: self.max_age,
                 rpy_chan: tx,
             })
             .await;
@@ -177,6 +186,7 @@ impl RequestChan {
                 dev_name: device::Name::build(self.prefix.clone(), name),
                 dev_units: units.map(String::from),
                 max_history,
+                max_age: self.max_age,
                 rpy_chan: tx,
             })
             .await;
@@ -244,6 +254,10 @@ pub trait API: Send {
     /// redis won't prune the history to less than the limit. However
     /// there may be more than the limit -- it just won't grow without
     /// bound.
+    ///
+    /// The configuration can also specify a `max_age`, which limits
+    /// how long data points are kept. It isn't passed to the driver;
+    /// `drc` includes it when registering devices.
 
     fn create_instance(
         cfg: &DriverConfig,
@@ -0,0 +1,33 @@
+use std::time;
+
+/// Selects how the readings in an aggregation interval are combined
+/// into one value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFn {
+    /// The smallest value. Booleans, integers and floats are
+    /// compared; other readings are ignored.
+    Min,
+    /// The largest value. Booleans, integers and floats are
+    /// compared; other readings are ignored.
+    Max,
+    /// The average, as a float. Booleans count as 0 or 1, so the
+    /// mean of a boolean device is the fraction of its readings
+    /// that were `true`. Other readings are ignored.
+    Mean,
+    /// The last reading of the interval.
+    Last,
+    /// The number of readings in the interval.
+    Count,
+}
+
+/// Asks for a device's readings to be downsampled.
+///
+/// Time is divided into intervals of length `interval`, aligned with
+/// the Unix epoch. The readings of each interval are combined, using
+/// `function`, into one reading whose timestamp is the start of the
+/// interval. Intervals without readings are skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aggregate {
+    pub interval: time::Duration,
+    pub function: AggregateFn,
+}
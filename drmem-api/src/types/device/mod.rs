@@ -6,6 +6,9 @@ use tokio_stream::Stream;
 mod value;
 pub use value::Value;
 
+mod aggregate;
+pub use aggregate::{Aggregate, AggregateFn};
+
 /// Represents the value of a device at a specific moment.
 ///
 /// When a client monitors a device, it receives a stream of readings
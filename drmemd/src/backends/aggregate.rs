@@ -0,0 +1,286 @@
+// Downsamples a device's readings, as described by a
+// `device::Aggregate`. Back-ends feed readings, in order, to an
+// `Aggregator`, which returns a reading for an interval once a
+// reading from a later interval arrives. When the readings end, the
+// last interval is returned by `flush()`.
+
+use drmem_api::device;
+use std::time;
+use tokio_stream::StreamExt;
+
+// The readings of the current interval.
+
+struct Bucket {
+    start: time::SystemTime,
+    count: i32,
+    last: device::Value,
+    best: Option<(f64, device::Value)>,
+    sum: f64,
+    n: u32,
+}
+
+// Returns the numeric form of the values that can be compared and
+// averaged.
+
+fn numeric(v: &device::Value) -> Option<f64> {
+    match v {
+        device::Value::Bool(v) => Some(if *v { 1.0 } else { 0.0 }),
+        device::Value::Int(v) => Some(*v as f64),
+        device::Value::Flt(v) => Some(*v),
+        _ => None,
+    }
+}
+
+pub struct Aggregator {
+    agg: device::Aggregate,
+    bucket: Option<Bucket>,
+}
+
+impl Aggregator {
+    pub fn new(agg: device::Aggregate) -> Self {
+        Aggregator { agg, bucket: None }
+    }
+
+    // Returns the start of the interval which holds `ts`.
+
+    fn interval_start(&self, ts: time::SystemTime) -> time::SystemTime {
+        let us = ts
+            .duration_since(time::UNIX_EPOCH)
+            .map(|d| d.as_micros())
+            .unwrap_or(0);
+        let width = self.agg.interval.as_micros().max(1);
+
+        time::UNIX_EPOCH
+            + time::Duration::from_micros(
+                u64::try_from(us - us % width).unwrap_or(u64::MAX),
+            )
+    }
+
+    // Adds a reading. If it starts a new interval, the reading which
+    // summarizes the previous interval is returned.
+
+    pub fn push(&mut self, rdg: device::Reading) -> Option<device::Reading> {
+        let start = self.interval_start(rdg.ts);
+        let result = match &self.bucket {
+            Some(bucket) if bucket.start != start => self.flush(),
+            _ => None,
+        };
+        let bucket = self.bucket.get_or_insert_with(|| Bucket {
+            start,
+            count: 0,
+            last: rdg.value.clone(),
+            best: None,
+            sum: 0.0,
+            n: 0,
+        });
+
+        bucket.count = bucket.count.saturating_add(1);
+
+        if let Some(x) = numeric(&rdg.value) {
+            bucket.sum += x;
+            bucket.n += 1;
+
+            let better = match (&bucket.best, self.agg.function) {
+                (None, _) => true,
+                (Some((b, _)), device::AggregateFn::Min) => x < *b,
+                (Some((b, _)), device::AggregateFn::Max) => x > *b,
+                _ => false,
+            };
+
+            if better {
+                bucket.best = Some((x, rdg.value.clone()))
+            }
+        }
+        bucket.last = rdg.value;
+        result
+    }
+
+    // Returns the reading which summarizes the current interval, if
+    // the interval has a value, and starts a new one.
+
+    pub fn flush(&mut self) -> Option<device::Reading> {
+        let bucket = self.bucket.take()?;
+        let value = match self.agg.function {
+            device::AggregateFn::Min | device::AggregateFn::Max => {
+                bucket.best.map(|(_, v)| v)
+            }
+            device::AggregateFn::Mean => (bucket.n > 0)
+                .then(|| device::Value::Flt(bucket.sum / bucket.n as f64)),
+            device::AggregateFn::Last => Some(bucket.last),
+            device::AggregateFn::Count => {
+                Some(device::Value::Int(bucket.count))
+            }
+        };
+
+        value.map(|value| device::Reading {
+            ts: bucket.start,
+            value,
+        })
+    }
+}
+
+// Downsamples a stream of readings.
+
+pub fn stream(
+    strm: device::DataStream<device::Reading>,
+    agg: device::Aggregate,
+) -> device::DataStream<device::Reading> {
+    Box::pin(futures::stream::unfold(
+        Some((strm, Aggregator::new(agg))),
+        |state| async move {
+            let (mut strm, mut acc) = state?;
+
+            loop {
+                match strm.next().await {
+                    Some(rdg) => {
+                        if let Some(result) = acc.push(rdg) {
+                            return Some((result, Some((strm, acc))));
+                        }
+                    }
+
+                    // The readings ended. Report the last interval
+                    // and then end the stream.
+                    None => return acc.flush().map(|result| (result, None)),
+                }
+            }
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rdg(secs: u64, value: device::Value) -> device::Reading {
+        device::Reading {
+            ts: time::UNIX_EPOCH + time::Duration::from_secs(secs),
+            value,
+        }
+    }
+
+    fn run(
+        function: device::AggregateFn,
+        data: &[(u64, device::Value)],
+    ) -> Vec<device::Reading> {
+        let mut acc = Aggregator::new(device::Aggregate {
+            interval: time::Duration::from_secs(10),
+            function,
+        });
+        let mut result: Vec<_> = data
+            .iter()
+            .filter_map(|(secs, v)| acc.push(rdg(*secs, v.clone())))
+            .collect();
+
+        result.extend(acc.flush());
+        result
+    }
+
+    #[test]
+    fn test_numbers() {
+        let data = [
+            (100, device::Value::Int(4)),
+            (103, device::Value::Flt(1.5)),
+            (109, device::Value::Int(8)),
+            (125, device::Value::Int(2)),
+        ];
+
+        assert_eq!(
+            run(device::AggregateFn::Min, &data),
+            vec![
+                rdg(100, device::Value::Flt(1.5)),
+                rdg(120, device::Value::Int(2))
+            ]
+        );
+        assert_eq!(
+            run(device::AggregateFn::Max, &data),
+            vec![
+                rdg(100, device::Value::Int(8)),
+                rdg(120, device::Value::Int(2))
+            ]
+        );
+        assert_eq!(
+            run(device::AggregateFn::Mean, &data),
+            vec![
+                rdg(100, device::Value::Flt(4.5)),
+                rdg(120, device::Value::Flt(2.0))
+            ]
+        );
+        assert_eq!(
+            run(device::AggregateFn::Last, &data),
+            vec![
+                rdg(100, device::Value::Int(8)),
+                rdg(120, device::Value::Int(2))
+            ]
+        );
+        assert_eq!(
+            run(device::AggregateFn::Count, &data),
+            vec![
+                rdg(100, device::Value::Int(3)),
+                rdg(120, device::Value::Int(1))
+            ]
+        );
+        assert_eq!(run(device::AggregateFn::Count, &[]), vec![]);
+    }
+
+    #[test]
+    fn test_other_types() {
+        let bools = [
+            (0, device::Value::Bool(true)),
+            (1, device::Value::Bool(false)),
+            (2, device::Value::Bool(false)),
+            (3, device::Value::Bool(false)),
+        ];
+
+        assert_eq!(
+            run(device::AggregateFn::Mean, &bools),
+            vec![rdg(0, device::Value::Flt(0.25))]
+        );
+        assert_eq!(
+            run(device::AggregateFn::Max, &bools),
+            vec![rdg(0, device::Value::Bool(true))]
+        );
+
+        // Strings can't be compared or averaged, but they can be
+        // counted.
+
+        let strs = [
+            (0, device::Value::Str("a".into())),
+            (1, device::Value::Str("b".into())),
+        ];
+
+        assert_eq!(run(device::AggregateFn::Min, &strs), vec![]);
+        assert_eq!(run(device::AggregateFn::Mean, &strs), vec![]);
+        assert_eq!(
+            run(device::AggregateFn::Last, &strs),
+            vec![rdg(0, device::Value::Str("b".into()))]
+        );
+        assert_eq!(
+            run(device::AggregateFn::Count, &strs),
+            vec![rdg(0, device::Value::Int(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream() {
+        let data = vec![
+            rdg(5, device::Value::Int(1)),
+            rdg(15, device::Value::Int(2)),
+            rdg(16, device::Value::Int(3)),
+        ];
+        let strm = stream(
+            Box::pin(tokio_stream::iter(data)),
+            device::Aggregate {
+                interval: time::Duration::from_secs(10),
+                function: device::AggregateFn::Count,
+            },
+        );
+
+        assert_eq!(
+            strm.collect::<Vec<_>>().await,
+            vec![
+                rdg(0, device::Value::Int(1)),
+                rdg(10, device::Value::Int(2))
+            ]
+        );
+    }
+}
@@ -0,0 +1,535 @@
+// Exports and imports the devices, and their history, of a back-end.
+// The archive is line-delimited JSON. Each device is described by a
+// `device` record, which is followed by its readings, oldest first:
+//
+//     {"type":"device","name":"sump:state","driver":"sump","units":null}
+//     {"type":"reading","name":"sump:state","ts":"2024-06-01T12:00:00.000000Z","value":{"int":1}}
+//
+// Since the archive is written and read through the `Store` trait,
+// history can be moved between any of the back-ends.
+
+use super::{store, Store};
+use crate::config;
+use chrono::{DateTime, SecondsFormat, Utc};
+use drmem_api::{device, Error, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+use tokio_stream::StreamExt;
+
+// The number of readings passed to `Store::import_readings()` at a
+// time.
+
+const CHUNK: usize = 1000;
+
+// The archive's form of `device::Value`.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Value {
+    Bool(bool),
+    Int(i32),
+    Flt(f64),
+    Str(String),
+    Color([u8; 4]),
+}
+
+impl From<&device::Value> for Value {
+    fn from(value: &device::Value) -> Self {
+        match value {
+            device::Value::Bool(v) => Value::Bool(*v),
+            device::Value::Int(v) => Value::Int(*v),
+            device::Value::Flt(v) => Value::Flt(*v),
+            device::Value::Str(v) => Value::Str(v.to_string()),
+            device::Value::Color(v) => {
+                Value::Color([v.red, v.green, v.blue, v.alpha])
+            }
+        }
+    }
+}
+
+impl From<Value> for device::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Bool(v) => device::Value::Bool(v),
+            Value::Int(v) => device::Value::Int(v),
+            Value::Flt(v) => device::Value::Flt(v),
+            Value::Str(v) => device::Value::Str(v.into()),
+            Value::Color([r, g, b, a]) => {
+                device::Value::Color(palette::LinSrgba::new(r, g, b, a))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Record {
+    Device {
+        name: String,
+        driver: String,
+        units: Option<String>,
+    },
+    Reading {
+        name: String,
+        ts: String,
+        value: Value,
+    },
+}
+
+// Writes a record, as a line of the archive.
+
+fn write_record(out: &mut impl Write, rec: &Record) -> Result<()> {
+    serde_json::to_writer(&mut *out, rec)
+        .map_err(io::Error::from)
+        .and_then(|_| out.write_all(b"\n"))
+        .map_err(|e| {
+            Error::OperationError(format!("couldn't write archive : {}", e))
+        })
+}
+
+// Writes the devices of the back-end, and their history, to `out`.
+// Returns the number of readings that were written.
+
+pub async fn export<S: Store + ?Sized>(
+    db: &mut S,
+    out: &mut impl Write,
+) -> Result<usize> {
+    let mut devices = db.get_device_info(None).await?;
+    let mut total = 0;
+
+    devices.sort_by_cached_key(|v| v.name.to_string());
+
+    for info in devices {
+        let name = info.name.to_string();
+
+        write_record(
+            out,
+            &Record::Device {
+                name: name.clone(),
+                driver: info.driver.to_string(),
+                units: info.units.clone(),
+            },
+        )?;
+
+        let (Some(first), Some(last)) = (info.first_point, info.last_point)
+        else {
+            continue;
+        };
+
+        // The stream would wait for new readings once the history is
+        // read, so stop at the last point reported by the back-end.
+
+        let mut strm = db
+            .monitor_device(
+                info.name.clone(),
+                Some(first.ts.into()),
+                Some(last.ts.into()),
+            )
+            .await?;
+
+        while let Some(rdg) = strm.next().await {
+            write_record(
+                out,
+                &Record::Reading {
+                    name: name.clone(),
+                    ts: DateTime::<Utc>::from(rdg.ts)
+                        .to_rfc3339_opts(SecondsFormat::Micros, true),
+                    value: (&rdg.value).into(),
+                },
+            )?;
+            total += 1;
+
+            if rdg.ts >= last.ts {
+                break;
+            }
+        }
+    }
+    Ok(total)
+}
+
+// Parses one line of an archive. `idx` is the line's index, which is
+// used in error messages.
+
+fn parse_record(idx: usize, line: &str) -> Result<Record> {
+    serde_json::from_str(line)
+        .map_err(|e| Error::ParseError(format!("line {}: {}", idx + 1, e)))
+}
+
+// Adds the readings collected for a device to the back-end.
+
+async fn save<S: Store + ?Sized>(
+    db: &mut S,
+    dev: &mut Option<(device::Name, Vec<device::Reading>)>,
+) -> Result<usize> {
+    match dev {
+        Some((name, readings)) if !readings.is_empty() => {
+            let total = readings.len();
+
+            db.import_readings(name, std::mem::take(readings)).await?;
+            Ok(total)
+        }
+        _ => Ok(0),
+    }
+}
+
+// Reads an archive and adds its devices and readings to the
+// back-end. Devices are registered as read-only devices of the
+// driver which owned them. Returns the number of readings imported.
+
+pub async fn import<S: Store + ?Sized>(
+    db: &mut S,
+    input: impl BufRead,
+) -> Result<usize> {
+    let mut dev: Option<(device::Name, Vec<device::Reading>)> = None;
+    let mut total = 0;
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| {
+            Error::OperationError(format!("couldn't read archive : {}", e))
+        })?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_record(idx, &line)? {
+            Record::Device {
+                name,
+                driver,
+                units,
+            } => {
+                total += save(db, &mut dev).await?;
+
+                let name = name.parse::<device::Name>().map_err(|_| {
+                    Error::ParseError(format!(
+                        "line {}: bad device name '{}'",
+                        idx + 1,
+                        name
+                    ))
+                })?;
+
+                // The function that reports readings isn't needed;
+                // readings are imported with their timestamps.
+
+                let _ = db
+                    .register_read_only_device(
+                        &driver,
+                        &name,
+                        units.as_ref(),
+                        None,
+                        None,
+                    )
+                    .await?;
+                dev = Some((name, vec![]))
+            }
+
+            Record::Reading { name, ts, value } => {
+                let ts = DateTime::parse_from_rfc3339(&ts).map_err(|_| {
+                    Error::ParseError(format!(
+                        "line {}: bad timestamp '{}'",
+                        idx + 1,
+                        ts
+                    ))
+                })?;
+
+                match &mut dev {
+                    Some((dev_name, readings))
+                        if dev_name.to_string() == name =>
+                    {
+                        readings.push(device::Reading {
+                            ts: ts.into(),
+                            value: value.into(),
+                        })
+                    }
+                    _ => {
+                        return Err(Error::ParseError(format!(
+                        "line {}: reading of '{}' doesn't follow its device",
+                        idx + 1,
+                        name
+                    )))
+                    }
+                }
+
+                if dev.as_ref().is_some_and(|v| v.1.len() >= CHUNK) {
+                    total += save(db, &mut dev).await?;
+                }
+            }
+        }
+    }
+    total += save(db, &mut dev).await?;
+    Ok(total)
+}
+
+// Runs the `export` or `import` subcommand on the back-end
+// described by `cfg`.
+
+pub async fn run(
+    cfg: &store::config::Config,
+    archive: &config::Archive,
+) -> Result<()> {
+    let mut db = store::open(cfg).await?;
+
+    match archive {
+        config::Archive::Export(file) => {
+            let mut out = std::fs::File::create(file)
+                .map(io::BufWriter::new)
+                .map_err(|e| {
+                Error::OperationError(format!(
+                    "couldn't create '{}' : {}",
+                    file, e
+                ))
+            })?;
+            let total = export(&mut db, &mut out).await?;
+
+            out.flush().map_err(|e| {
+                Error::OperationError(format!(
+                    "couldn't write '{}' : {}",
+                    file, e
+                ))
+            })?;
+            println!("exported {} readings to '{}'", total, file)
+        }
+
+        config::Archive::Import(file) => {
+            let input = std::fs::File::open(file)
+                .map(io::BufReader::new)
+                .map_err(|e| {
+                    Error::OperationError(format!(
+                        "couldn't open '{}' : {}",
+                        file, e
+                    ))
+                })?;
+            let total = import(&mut db, input).await?;
+
+            println!("imported {} readings from '{}'", total, file)
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let values = [
+            (device::Value::Bool(true), r#"{"bool":true}"#),
+            (device::Value::Int(-3), r#"{"int":-3}"#),
+            (device::Value::Flt(1.5), r#"{"flt":1.5}"#),
+            (
+                device::Value::Str("a\n\"b\"".into()),
+                r#"{"str":"a\n\"b\""}"#,
+            ),
+            (
+                device::Value::Color(palette::LinSrgba::new(1, 2, 3, 4)),
+                r#"{"color":[1,2,3,4]}"#,
+            ),
+        ];
+
+        for (value, json) in values {
+            let v = Value::from(&value);
+
+            assert_eq!(serde_json::to_string(&v).unwrap(), json);
+            assert_eq!(device::Value::from(v), value);
+        }
+
+        let rec = Record::Device {
+            name: "sump:state".into(),
+            driver: "sump".into(),
+            units: None,
+        };
+        let line = serde_json::to_string(&rec).unwrap();
+
+        assert_eq!(
+            line,
+            r#"{"type":"device","name":"sump:state","driver":"sump","units":null}"#
+        );
+        assert_eq!(parse_record(0, &line).unwrap(), rec);
+
+        assert!(parse_record(0, "{}").is_err());
+        assert!(parse_record(0, r#"{"type":"reading","name":"a:b"}"#).is_err());
+        assert!(parse_record(
+            0,
+            r#"{"type":"reading","name":"a:b","ts":"","value":{"hex":1}}"#
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "simple-backend")]
+    fn name(s: &str) -> device::Name {
+        s.parse().unwrap()
+    }
+
+    #[cfg(feature = "simple-backend")]
+    #[tokio::test]
+    async fn test_round_trip() {
+        let mut db = store::open(&store::config::Config::default())
+            .await
+            .unwrap();
+        let units = String::from("°F");
+
+        let (f, _, _) = db
+            .register_read_write_device(
+                "memory",
+                &name("t:a"),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        f(device::Value::Bool(true)).await;
+
+        let f = db
+            .register_read_only_device(
+                "weather",
+                &name("t:b"),
+                Some(&units),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        f(device::Value::Flt(72.5)).await;
+
+        let f = db
+            .register_read_only_device(
+                "weather",
+                &name("t:c"),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        f(device::Value::Str("line 1\nline 2".into())).await;
+
+        let _ = db
+            .register_read_only_device(
+                "weather",
+                &name("t:d"),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut archive = vec![];
+
+        assert_eq!(export(&mut db, &mut archive).await.unwrap(), 3);
+        assert_eq!(archive.iter().filter(|v| **v == b'\n').count(), 7);
+
+        // Import the archive into an empty store. Exporting it should
+        // produce the same archive.
+
+        let mut copy = store::open(&store::config::Config::default())
+            .await
+            .unwrap();
+
+        assert_eq!(import(&mut copy, archive.as_slice()).await.unwrap(), 3);
+
+        // Importing the archive again doesn't change the store.
+
+        import(&mut copy, archive.as_slice()).await.unwrap();
+
+        let mut again = vec![];
+
+        assert_eq!(export(&mut copy, &mut again).await.unwrap(), 3);
+        assert_eq!(
+            String::from_utf8(again).unwrap(),
+            String::from_utf8(archive).unwrap()
+        );
+
+        let info = copy.get_device_info(Some("t:b")).await.unwrap();
+
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].units, Some(units));
+        assert_eq!(info[0].driver.as_ref(), "weather");
+        assert_eq!(
+            info[0].last_point.as_ref().map(|v| &v.value),
+            Some(&device::Value::Flt(72.5))
+        );
+    }
+
+    #[cfg(feature = "simple-backend")]
+    #[tokio::test]
+    async fn test_import_errors() {
+        let mut db = store::open(&store::config::Config::default())
+            .await
+            .unwrap();
+
+        // Readings have to follow the record of their device.
+
+        assert!(import(
+            &mut db,
+            r#"{"type":"reading","name":"t:a","ts":"2024-06-01T00:00:00Z","value":{"int":1}}"#
+                .as_bytes()
+        )
+        .await
+        .is_err());
+        assert!(import(
+            &mut db,
+            concat!(
+                r#"{"type":"device","name":"t:a","driver":"x","units":null}"#,
+                "\n",
+                r#"{"type":"reading","name":"t:b","ts":"2024-06-01T00:00:00Z","value":{"int":1}}"#
+            )
+            .as_bytes()
+        )
+        .await
+        .is_err());
+
+        // Bad names and timestamps are reported.
+
+        assert!(import(
+            &mut db,
+            r#"{"type":"device","name":"bad","driver":"x","units":null}"#
+                .as_bytes()
+        )
+        .await
+        .is_err());
+        assert!(import(
+            &mut db,
+            concat!(
+                r#"{"type":"device","name":"t:c","driver":"x","units":null}"#,
+                "\n",
+                r#"{"type":"reading","name":"t:c","ts":"yesterday","value":{"int":1}}"#
+            )
+            .as_bytes()
+        )
+        .await
+        .is_err());
+
+        // Blank lines are ignored and the newest reading is kept.
+
+        assert_eq!(
+            import(
+                &mut db,
+                concat!(
+                    r#"{"type":"device","name":"t:d","driver":"x","units":null}"#,
+                    "\n\n",
+                    r#"{"type":"reading","name":"t:d","ts":"2024-06-01T00:00:00Z","value":{"int":1}}"#,
+                    "\n",
+                    r#"{"type":"reading","name":"t:d","ts":"2024-06-01T00:00:01Z","value":{"int":2}}"#,
+                    "\n"
+                )
+                .as_bytes()
+            )
+            .await
+            .unwrap(),
+            2
+        );
+
+        let info = db.get_device_info(Some("t:d")).await.unwrap();
+
+        assert_eq!(
+            info[0].last_point.as_ref().map(|v| &v.value),
+            Some(&device::Value::Int(2))
+        );
+    }
+}
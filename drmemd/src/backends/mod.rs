@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use drmem_api::{client, device, driver, Result};
+use std::time;
 
 // Defines the trait that a back-end needs to implement to provide
 // storage for -- and access to -- the state of each driver's devices.
@@ -23,6 +24,9 @@ pub trait Store {
     //   units returned by the device.
     // - `max_history` is a hint as to how large an archive the user
     //   specifies should be used for this device.
+    // - `max_age` is a hint as to how long the user wants readings
+    //   of this device to be kept. Back-ends which don't keep a
+    //   history can ignore it.
     //
     // On success, this function returns a pair. The first element is
     // a closure the driver uses to report updates. The second element
@@ -35,6 +39,7 @@ pub trait Store {
         name: &device::Name,
         units: Option<&String>,
         max_history: Option<usize>,
+        max_age: Option<time::Duration>,
     ) -> Result<driver::ReportReading>;
 
     // Called when a read-write device is to be registered with the
@@ -53,6 +58,9 @@ pub trait Store {
     //   units returned by the device.
     // - `max_history` is a hint as to how large an archive the user
     //   specifies should be used for this device.
+    // - `max_age` is a hint as to how long the user wants readings
+    //   of this device to be kept. Back-ends which don't keep a
+    //   history can ignore it.
     //
     // On success, this function returns a 3-tuple. The first element
     // is a closure the driver uses to report updates. The second
@@ -66,6 +74,7 @@ pub trait Store {
         name: &device::Name,
         units: Option<&String>,
         max_history: Option<usize>,
+        max_age: Option<time::Duration>,
     ) -> Result<(
         driver::ReportReading,
         driver::RxDeviceSetting,
@@ -85,6 +94,18 @@ pub trait Store {
         pattern: Option<&str>,
     ) -> Result<Vec<client::DevInfoReply>>;
 
+    // Adds readings, with their original timestamps, to the history
+    // of a registered device. It's used to import an archive. The
+    // readings are in chronological order and aren't sent to the
+    // device's monitors. Back-ends which only keep the latest
+    // reading should keep the last one, if it's newer than theirs.
+
+    async fn import_readings(
+        &mut self,
+        name: &device::Name,
+        readings: Vec<device::Reading>,
+    ) -> Result<()>;
+
     // Sends a request to a driver to set its device to the specified
     // value.
 
@@ -116,8 +137,29 @@ pub trait Store {
         start: Option<DateTime<Utc>>,
         end: Option<DateTime<Utc>>,
     ) -> Result<device::DataStream<device::Reading>>;
+
+    // Creates a stream that yields a device's readings, downsampled
+    // as described by `agg`. The default implementation aggregates
+    // the stream returned by `monitor_device()`. Back-ends which can
+    // read their history more efficiently should override it.
+
+    async fn monitor_aggregate(
+        &mut self,
+        name: device::Name,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        agg: device::Aggregate,
+    ) -> Result<device::DataStream<device::Reading>> {
+        Ok(aggregate::stream(
+            self.monitor_device(name, start, end).await?,
+            agg,
+        ))
+    }
 }
 
+pub mod aggregate;
+pub mod archive;
+
 #[cfg(feature = "simple-backend")]
 pub mod simple;
 #[cfg(feature = "simple-backend")]
@@ -127,3 +169,11 @@ pub use simple as store;
 pub mod redis;
 #[cfg(feature = "redis-backend")]
 pub use redis as store;
+
+#[cfg(feature = "sqlite-backend")]
+pub mod sqlite;
+#[cfg(feature = "sqlite-backend")]
+pub use sqlite as store;
+
+#[cfg(all(test, any(feature = "simple-backend", feature = "sqlite-backend")))]
+mod stream_tests;
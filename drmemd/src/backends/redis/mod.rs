@@ -1,4 +1,4 @@
-use crate::backends::Store;
+use crate::backends::{aggregate, Store};
 use async_trait::async_trait;
 use chrono::*;
 use drmem_api::{
@@ -10,7 +10,7 @@ use futures::task::{Context, Poll};
 use futures::Future;
 use redis::{
     aio,
-    streams::{StreamId, StreamInfoStreamReply},
+    streams::{StreamId, StreamInfoStreamReply, StreamRangeReply},
 };
 use std::collections::HashMap;
 use std::convert::TryInto;
@@ -574,6 +574,20 @@ impl RedisStore {
         redis::Cmd::xrevrange_count(name, "+", "-", 1usize)
     }
 
+    // Returns up to `count` entries of a device's history, starting
+    // at the ID `start` and ending at the ID `end`.
+
+    fn range_cmd(
+        name: &str,
+        start: &str,
+        end: &str,
+        count: usize,
+    ) -> redis::Cmd {
+        let name = Self::hist_key(name);
+
+        redis::Cmd::xrange_count(name, start, end, count)
+    }
+
     fn match_pattern_cmd(pattern: Option<&str>) -> redis::Cmd {
         // Take the pattern from the caller and append "#info" since
         // we only want to look at device information keys.
@@ -643,6 +657,62 @@ impl RedisStore {
         redis::Cmd::xadd_maxlen(key, opts, "*", &data)
     }
 
+    // Generates a redis command that adds a reading to a device's
+    // history, using the reading's timestamp as the entry's ID.
+
+    fn import_value_cmd(key: &str, rdg: &device::Reading) -> redis::Cmd {
+        let data = [("value", to_redis(&rdg.value))];
+
+        redis::Cmd::xadd(key, ReadingStream::ts_to_id(rdg.ts), &data)
+    }
+
+    // Returns the readings which can be added to a stream whose top
+    // entry has the timestamp `last`. Redis rejects an entry whose ID
+    // isn't greater than the top entry's, so readings at, or before,
+    // it are dropped. They're normally already in the history because
+    // an archive is being imported again. IDs have a resolution of a
+    // microsecond, so readings are compared at that resolution.
+
+    fn readings_after(
+        readings: Vec<device::Reading>,
+        last: Option<time::SystemTime>,
+    ) -> Vec<device::Reading> {
+        let us = |ts: time::SystemTime| {
+            ts.duration_since(time::UNIX_EPOCH)
+                .map(|d| d.as_micros())
+                .unwrap_or(0)
+        };
+        let mut top = last.map(us).unwrap_or(0);
+
+        readings
+            .into_iter()
+            .filter(|rdg| {
+                let id = us(rdg.ts);
+
+                if id > top {
+                    top = id;
+                    true
+                } else {
+                    false
+                }
+            })
+            .collect()
+    }
+
+    // Generates a redis command that removes the entries of a
+    // device's history which are older than `oldest`. Like the
+    // `max_history` limit, the trimming is approximate so redis can
+    // remove whole nodes of the stream.
+
+    fn trim_by_age_cmd(key: &str, oldest: time::SystemTime) -> redis::Cmd {
+        let opts = redis::streams::StreamTrimOptions::minid(
+            redis::streams::StreamTrimmingMode::Approx,
+            ReadingStream::ts_to_id(oldest),
+        );
+
+        redis::Cmd::xtrim_options(key, &opts)
+    }
+
     fn hash_to_info(
         st: &SettingTable,
         name: &device::Name,
@@ -871,48 +941,48 @@ impl RedisStore {
     }
 
     // Creates a closure for a driver to report a device's changing
-    // values.
+    // values. If `max_age` is specified, the old entries of the
+    // history are trimmed in the same pipeline that adds the value.
 
     fn mk_report_func(
         &self,
         name: &str,
         max_history: Option<usize>,
+        max_age: Option<time::Duration>,
     ) -> ReportReading {
         let db_con = self.db_con.clone();
         let name = String::from(name);
 
-        if let Some(mh) = max_history {
-            Box::new(move |v| {
-                let mut db_con = db_con.clone();
-                let hist_key = Self::hist_key(&name);
-                let name = name.clone();
+        Box::new(move |v| {
+            let mut db_con = db_con.clone();
+            let hist_key = Self::hist_key(&name);
+            let name = name.clone();
 
-                Box::pin(async move {
-                    if let Err(e) =
-                        Self::report_bounded_new_value_cmd(&hist_key, &v, mh)
-                            .query_async::<()>(&mut db_con)
-                            .await
-                    {
-                        warn!("couldn't save {} data to redis ... {}", &name, e)
-                    }
-                })
-            })
-        } else {
-            Box::new(move |v| {
-                let mut db_con = db_con.clone();
-                let hist_key = Self::hist_key(&name);
-                let name = name.clone();
-
-                Box::pin(async move {
-                    if let Err(e) = Self::report_new_value_cmd(&hist_key, &v)
-                        .query_async::<()>(&mut db_con)
-                        .await
-                    {
-                        warn!("couldn't save {} data to redis ... {}", &name, e)
-                    }
-                })
+            Box::pin(async move {
+                let mut pipe = redis::pipe();
+
+                if let Some(mh) = max_history {
+                    pipe.add_command(Self::report_bounded_new_value_cmd(
+                        &hist_key, &v, mh,
+                    ))
+                    .ignore();
+                } else {
+                    pipe.add_command(Self::report_new_value_cmd(&hist_key, &v))
+                        .ignore();
+                }
+
+                if let Some(oldest) = max_age
+                    .and_then(|age| time::SystemTime::now().checked_sub(age))
+                {
+                    pipe.add_command(Self::trim_by_age_cmd(&hist_key, oldest))
+                        .ignore();
+                }
+
+                if let Err(e) = pipe.query_async::<()>(&mut db_con).await {
+                    warn!("couldn't save {} data to redis ... {}", &name, e)
+                }
             })
-        }
+        })
     }
 }
 
@@ -926,6 +996,7 @@ impl Store for RedisStore {
         name: &device::Name,
         units: Option<&String>,
         max_history: Option<usize>,
+        max_age: Option<time::Duration>,
     ) -> Result<ReportReading> {
         let name = name.to_string();
 
@@ -936,7 +1007,7 @@ impl Store for RedisStore {
 
             info!("'{}' has been successfully created", &name);
         }
-        Ok(self.mk_report_func(&name, max_history))
+        Ok(self.mk_report_func(&name, max_history, max_age))
     }
 
     async fn register_read_write_device(
@@ -945,6 +1016,7 @@ impl Store for RedisStore {
         name: &device::Name,
         units: Option<&String>,
         max_history: Option<usize>,
+        max_age: Option<time::Duration>,
     ) -> Result<(ReportReading, RxDeviceSetting, Option<device::Value>)> {
         let sname = name.to_string();
 
@@ -963,12 +1035,54 @@ impl Store for RedisStore {
         }
 
         Ok((
-            self.mk_report_func(&sname, max_history),
+            self.mk_report_func(&sname, max_history, max_age),
             rx,
             self.last_value(&sname).await.map(|v| v.value),
         ))
     }
 
+    // Imports readings into a device's history. Redis requires the
+    // IDs of a stream to increase, so readings which aren't newer
+    // than the device's history are skipped. This lets an
+    // interrupted import be run again. The readings are sent in
+    // pipelines of `PAGE` commands.
+
+    async fn import_readings(
+        &mut self,
+        name: &device::Name,
+        readings: Vec<device::Reading>,
+    ) -> Result<()> {
+        const PAGE: usize = 1000;
+
+        let name = name.to_string();
+        let hist_key = Self::hist_key(&name);
+        let total = readings.len();
+        let last = self.last_value(&name).await.map(|v| v.ts);
+        let readings = Self::readings_after(readings, last);
+
+        if readings.len() < total {
+            info!(
+                "skipping {} readings of {} that are already in its history",
+                total - readings.len(),
+                &name
+            )
+        }
+
+        for page in readings.chunks(PAGE) {
+            let mut pipe = redis::pipe();
+
+            for rdg in page {
+                pipe.add_command(Self::import_value_cmd(&hist_key, rdg))
+                    .ignore();
+            }
+
+            pipe.query_async::<()>(&mut self.db_con)
+                .await
+                .map_err(xlat_err)?;
+        }
+        Ok(())
+    }
+
     // Implement the request to pull device information. Any task with
     // a client channel can make this request although the primary
     // client will be from GraphQL requests.
@@ -1127,6 +1241,138 @@ impl Store for RedisStore {
             }
         }
     }
+
+    // Downsamples a device's readings. The history is read in pages
+    // with XRANGE, rather than a reading at a time with XREAD. If
+    // the requested range goes past the last reading, the live
+    // readings follow the history.
+
+    async fn monitor_aggregate(
+        &mut self,
+        name: device::Name,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        agg: device::Aggregate,
+    ) -> Result<device::DataStream<device::Reading>> {
+        const PAGE: usize = 1000;
+
+        // Without a start time, there's no history to read.
+
+        let Some(start) = start.map(time::SystemTime::from) else {
+            return Ok(aggregate::stream(
+                self.monitor_device(name, None, end).await?,
+                agg,
+            ));
+        };
+
+        let (start, end) = match end.map(time::SystemTime::from) {
+            Some(end) => {
+                (std::cmp::min(start, end), Some(std::cmp::max(start, end)))
+            }
+            None => (start, None),
+        };
+
+        let con = match Self::make_connection(&self.cfg, None, None).await {
+            Ok(con) => con,
+            Err(e) => {
+                error!("couldn't make a connection : {}", e);
+
+                return Ok(Box::pin(tokio_stream::empty())
+                    as device::DataStream<device::Reading>);
+            }
+        };
+
+        let name = name.to_string();
+        let key = RedisStore::hist_key(&name);
+        let last = self.last_value(&name).await.map(|v| v.ts);
+
+        // The history ends at the last reading, or the end time, if
+        // it's earlier.
+
+        let hist_end = last
+            .filter(|last| *last >= start)
+            .map(|last| end.map_or(last, |end| std::cmp::min(end, last)));
+
+        let history: device::DataStream<device::Reading> = match hist_end {
+            Some(hist_end) => {
+                let to = ReadingStream::ts_to_id(hist_end);
+                let from = ReadingStream::ts_to_id(start);
+
+                Box::pin(futures::StreamExt::flatten(futures::stream::unfold(
+                    Some((con.clone(), from)),
+                    move |state| {
+                        let name = name.clone();
+                        let to = to.clone();
+
+                        async move {
+                            let (mut con, from) = state?;
+                            let reply: StreamRangeReply =
+                                match Self::range_cmd(&name, &from, &to, PAGE)
+                                    .query_async(&mut con)
+                                    .await
+                                {
+                                    Ok(reply) => reply,
+                                    Err(e) => {
+                                        error!(
+                                            "couldn't read history of {} : {}",
+                                            &name, e
+                                        );
+                                        return None;
+                                    }
+                                };
+
+                            // A full page means there may be more
+                            // entries. The next page starts after the
+                            // last ID of this one.
+
+                            let next = reply
+                                .ids
+                                .last()
+                                .filter(|_| reply.ids.len() == PAGE)
+                                .map(|v| (con, format!("({}", v.id)));
+                            let readings: Vec<device::Reading> = reply
+                                .ids
+                                .iter()
+                                .filter_map(|v| {
+                                    Self::stream_id_to_reading(v).ok()
+                                })
+                                .collect();
+
+                            Some((tokio_stream::iter(readings), next))
+                        }
+                    },
+                )))
+            }
+            None => Box::pin(tokio_stream::empty()),
+        };
+
+        // If the range extends past the last reading, follow the
+        // history with the live readings.
+
+        let strm = match (end, last) {
+            (Some(end), Some(last)) if end <= last => history,
+            _ => {
+                let after = st_minus_1us(start);
+                let after =
+                    last.map_or(after, |last| std::cmp::max(last, after));
+
+                Box::pin(history.chain(ReadingStream::new(
+                    con,
+                    &key,
+                    Some(after),
+                )))
+            }
+        };
+
+        let strm = if let Some(end) = end {
+            Box::pin(strm.take_while(move |v| v.ts <= end))
+                as device::DataStream<device::Reading>
+        } else {
+            strm
+        };
+
+        Ok(aggregate::stream(strm, agg))
+    }
 }
 
 pub async fn open(cfg: &config::Config) -> Result<impl Store> {
@@ -1499,6 +1745,97 @@ $1\r\n1\r\n"
         );
     }
 
+    #[test]
+    fn test_range_cmd() {
+        let cmd = RedisStore::range_cmd("device", "1000-0", "(2000-5", 100);
+
+        assert_eq!(
+            &cmd.get_packed_command(),
+            b"*6\r
+$6\r\nXRANGE\r
+$11\r\ndevice#hist\r
+$6\r\n1000-0\r
+$7\r\n(2000-5\r
+$5\r\nCOUNT\r
+$3\r\n100\r\n"
+        );
+    }
+
+    #[test]
+    fn test_import_value_cmd() {
+        let rdg = device::Reading {
+            ts: time::UNIX_EPOCH + time::Duration::from_micros(1234567),
+            value: true.into(),
+        };
+
+        assert_eq!(
+            &RedisStore::import_value_cmd("key", &rdg).get_packed_command(),
+            b"*5\r
+$4\r\nXADD\r
+$3\r\nkey\r
+$8\r\n1234-567\r
+$5\r\nvalue\r
+$2\r\nBT\r\n"
+        );
+    }
+
+    #[test]
+    fn test_readings_after() {
+        let rdg = |us, v: i32| device::Reading {
+            ts: time::UNIX_EPOCH + time::Duration::from_micros(us),
+            value: v.into(),
+        };
+        let archive = vec![rdg(10, 1), rdg(20, 2), rdg(30, 3)];
+
+        // Importing into an empty stream adds everything. Importing
+        // the same readings again adds nothing.
+
+        assert_eq!(RedisStore::readings_after(archive.clone(), None), archive);
+        assert_eq!(
+            RedisStore::readings_after(archive.clone(), Some(archive[2].ts)),
+            vec![]
+        );
+
+        // An interrupted import continues after the top entry.
+
+        assert_eq!(
+            RedisStore::readings_after(archive.clone(), Some(archive[0].ts)),
+            vec![rdg(20, 2), rdg(30, 3)]
+        );
+
+        // Readings in the same microsecond would get the same ID, as
+        // would a reading at the epoch ("0-0" isn't a valid ID.)
+
+        let sub_us = device::Reading {
+            ts: archive[0].ts + time::Duration::from_nanos(500),
+            value: 5.into(),
+        };
+
+        assert_eq!(
+            RedisStore::readings_after(
+                vec![rdg(0, 0), archive[0].clone(), sub_us, rdg(20, 2)],
+                None
+            ),
+            vec![rdg(10, 1), rdg(20, 2)]
+        );
+    }
+
+    #[test]
+    fn test_trim_by_age_cmd() {
+        let oldest = time::UNIX_EPOCH + time::Duration::from_micros(1234567);
+        let cmd = RedisStore::trim_by_age_cmd("key", oldest);
+
+        assert_eq!(
+            &cmd.get_packed_command(),
+            b"*5\r
+$5\r\nXTRIM\r
+$3\r\nkey\r
+$5\r\nMINID\r
+$1\r\n~\r
+$8\r\n1234-567\r\n"
+        );
+    }
+
     #[test]
     fn test_parsing_last_value() {
         const NAME: &str = "device";
@@ -1,11 +1,25 @@
 use serde_derive::Deserialize;
+use std::time::Duration;
 
 #[derive(Deserialize, Clone)]
-pub struct Config {}
+pub struct Config {
+    pub snapshot: Option<String>,
+    pub snapshot_interval: Option<u64>,
+}
 
 impl Config {
     pub const fn new() -> Config {
-        Config {}
+        Config {
+            snapshot: None,
+            snapshot_interval: None,
+        }
+    }
+
+    // Returns how often the snapshot file is written. The interval
+    // is specified in seconds and defaults to a minute.
+
+    pub fn get_snapshot_interval(&self) -> Duration {
+        Duration::from_secs(self.snapshot_interval.unwrap_or(60).max(1))
     }
 }
 
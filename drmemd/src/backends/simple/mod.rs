@@ -3,7 +3,9 @@
 //! This is the simplest data-store available. It only saves the last
 //! value for each device. It also doesn't provide persistent storage
 //! for device meta-information so, after a restart, that information
-//! is reset to its default state.
+//! is reset to its default state. The last values can be kept across
+//! restarts by configuring a snapshot file (see the `snapshot`
+//! module.)
 //!
 //! This back-end is useful for installations that don't require
 //! historical information but, instead, are doing real-time control
@@ -39,6 +41,7 @@ type ReadingState = (
 
 pub mod config;
 mod glob;
+mod snapshot;
 
 struct DeviceInfo {
     owner: driver::Name,
@@ -66,10 +69,19 @@ impl DeviceInfo {
     }
 }
 
-struct SimpleStore(HashMap<device::Name, DeviceInfo>);
+struct SimpleStore(
+    HashMap<device::Name, DeviceInfo>,
+    Option<snapshot::Snapshot>,
+);
+
+pub async fn open(cfg: &config::Config) -> Result<impl Store> {
+    let snapshot = if let Some(file) = &cfg.snapshot {
+        Some(snapshot::Snapshot::open(file, cfg.get_snapshot_interval()).await?)
+    } else {
+        None
+    };
 
-pub async fn open(_cfg: &config::Config) -> Result<impl Store> {
-    Ok(SimpleStore(HashMap::new()))
+    Ok(SimpleStore(HashMap::new(), snapshot))
 }
 
 // Builds the `ReportReading` function. Drivers will call specialized
@@ -145,6 +157,7 @@ impl Store for SimpleStore {
         name: &device::Name,
         units: Option<&String>,
         _max_history: Option<usize>,
+        _max_age: Option<time::Duration>,
     ) -> Result<ReportReading> {
         // Check to see if the device name already exists.
 
@@ -160,6 +173,13 @@ impl Store for SimpleStore {
                     None,
                 ));
 
+                // If there's a snapshot, the device gets its saved
+                // reading.
+
+                if let Some(snapshot) = &self.1 {
+                    snapshot.restore(name, &di.reading)
+                }
+
                 // Create and return the closure that the driver will
                 // use to report updates.
 
@@ -193,6 +213,7 @@ impl Store for SimpleStore {
         name: &device::Name,
         units: Option<&String>,
         _max_history: Option<usize>,
+        _max_age: Option<time::Duration>,
     ) -> Result<(ReportReading, RxDeviceSetting, Option<device::Value>)> {
         // Check to see if the device name already exists.
 
@@ -212,10 +233,22 @@ impl Store for SimpleStore {
                     Some(tx_sets),
                 ));
 
+                // If there's a snapshot, the device gets its saved
+                // reading, which is returned to the driver as the
+                // device's previous value.
+
+                if let Some(snapshot) = &self.1 {
+                    snapshot.restore(name, &di.reading)
+                }
+
+                let prev = di.reading.lock().ok().and_then(|data| {
+                    data.1.as_ref().map(|rdg| rdg.value.clone())
+                });
+
                 // Create and return the closure that the driver will
                 // use to report updates.
 
-                Ok((mk_report_func(di, name), rx_sets, None))
+                Ok((mk_report_func(di, name), rx_sets, prev))
             }
 
             // The device already exists. If it was created from a
@@ -252,6 +285,29 @@ impl Store for SimpleStore {
         }
     }
 
+    // Imports readings into a device. Only the latest reading is
+    // kept and only if it's newer than the device's reading.
+
+    async fn import_readings(
+        &mut self,
+        name: &device::Name,
+        readings: Vec<device::Reading>,
+    ) -> Result<()> {
+        let di = self.0.get(name).ok_or(Error::NotFound)?;
+
+        if let Some(rdg) = readings.into_iter().max_by_key(|v| v.ts) {
+            let mut data = di.reading.lock().map_err(|_| {
+                Error::OperationError(format!("couldn't lock {}", name))
+            })?;
+
+            if rdg.ts > data.2 {
+                data.2 = rdg.ts;
+                data.1 = Some(rdg)
+            }
+        }
+        Ok(())
+    }
+
     async fn get_device_info(
         &mut self,
         pattern: Option<&str>,
@@ -454,11 +510,10 @@ impl Store for SimpleStore {
 #[cfg(test)]
 mod tests {
     use super::{mk_report_func, DeviceInfo, SimpleStore};
-    use crate::backends::Store;
+    use crate::backends::{stream_tests, Store};
     use drmem_api::device;
     use std::{collections::HashMap, time};
     use tokio::sync::{mpsc::error::TryRecvError, oneshot};
-    use tokio::time::interval;
     use tokio_stream::StreamExt;
 
     #[test]
@@ -470,415 +525,41 @@ mod tests {
 
     #[tokio::test]
     async fn test_read_live_stream() {
-        let mut db = SimpleStore(HashMap::new());
-        let name = "test:device".parse::<device::Name>().unwrap();
-
-        if let Ok(f) = db
-            .register_read_only_device("test", &name, None, None)
+        stream_tests::read_live_stream(&mut SimpleStore(HashMap::new(), None))
             .await
-        {
-            // Test that priming the history with one value returns
-            // the entire sequence.
-
-            {
-                let data = vec![1, 2, 3];
-
-                f(device::Value::Int(data[0])).await;
-
-                let s = db
-                    .monitor_device(name.clone(), None, None)
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in &data[1..] {
-                    f(device::Value::Int(*ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(1)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(2)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(3)
-                );
-                assert!(s.try_next().await.is_err());
-            }
-
-            // Test that priming the history with two values only
-            // returns the latest and all remaining.
-
-            {
-                let data = vec![1, 2, 3, 4];
-
-                f(device::Value::Int(data[0])).await;
-                f(device::Value::Int(data[1])).await;
-
-                let s = db
-                    .monitor_device(name.clone(), None, None)
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in &data[2..] {
-                    f(device::Value::Int(*ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(2)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(3)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(4)
-                );
-                assert!(s.try_next().await.is_err());
-            }
-        } else {
-            panic!("error registering read-only device on empty database")
-        }
     }
 
     #[tokio::test]
     async fn test_read_start_stream() {
-        let mut db = SimpleStore(HashMap::new());
-        let name = "test:device".parse::<device::Name>().unwrap();
-
-        if let Ok(f) = db
-            .register_read_only_device("test", &name, None, None)
+        stream_tests::read_start_stream(&mut SimpleStore(HashMap::new(), None))
             .await
-        {
-            // Verify that monitoring device, starting now, picks up
-            // all future inserted data.
-
-            {
-                let data = vec![1, 2, 3];
-
-                let s = db
-                    .monitor_device(
-                        name.clone(),
-                        Some(time::SystemTime::now().into()),
-                        None,
-                    )
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                assert!(s.try_next().await.is_err());
-
-                for ii in data {
-                    f(device::Value::Int(ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(1)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(2)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(3)
-                );
-                assert!(s.try_next().await.is_err());
-            }
-
-            // Verify that, if the latest point is before the starting
-            // timestamp, it doesn't get returned.
-
-            {
-                let data = vec![1, 2, 3];
-
-                f(device::Value::Int(data[0])).await;
-
-                let s = db
-                    .monitor_device(
-                        name.clone(),
-                        Some(time::SystemTime::now().into()),
-                        None,
-                    )
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in &data[1..] {
-                    f(device::Value::Int(*ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(2)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(3)
-                );
-                assert!(s.try_next().await.is_err());
-            }
-        } else {
-            panic!("error registering read-only device on empty database")
-        }
     }
 
     #[tokio::test]
     async fn test_read_end_stream() {
-        let mut db = SimpleStore(HashMap::new());
-        let name = "test:device".parse::<device::Name>().unwrap();
-
-        if let Ok(f) = db
-            .register_read_only_device("test", &name, None, None)
+        stream_tests::read_end_stream(&mut SimpleStore(HashMap::new(), None))
             .await
-        {
-            // Verify that, if the latest point is before the starting
-            // timestamp, it doesn't get returned.
-
-            {
-                let data = vec![1, 2, 3];
-
-                f(device::Value::Int(data[0])).await;
-
-                let s = db
-                    .monitor_device(
-                        name.clone(),
-                        None,
-                        Some(time::SystemTime::now().into()),
-                    )
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in &data[1..] {
-                    f(device::Value::Int(*ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(1)
-                );
-                assert_eq!(s.try_next().await.unwrap(), None);
-            }
-        } else {
-            panic!("error registering read-only device on empty database")
-        }
     }
 
     #[tokio::test]
     async fn test_read_start_end_stream() {
-        let mut db = SimpleStore(HashMap::new());
-        let name = "test:device".parse::<device::Name>().unwrap();
-
-        if let Ok(f) = db
-            .register_read_only_device("test", &name, None, None)
-            .await
-        {
-            // Verify that, if both times are before the data, nothing
-            // is returned.
-
-            {
-                let data = vec![1, 2, 3, 4, 5];
-
-                let mut interval = interval(time::Duration::from_millis(100));
-
-                let now = time::SystemTime::now();
-                let s = db
-                    .monitor_device(
-                        name.clone(),
-                        Some(
-                            now.checked_sub(time::Duration::from_millis(500))
-                                .unwrap()
-                                .into(),
-                        ),
-                        Some(
-                            now.checked_sub(time::Duration::from_millis(250))
-                                .unwrap()
-                                .into(),
-                        ),
-                    )
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in data {
-                    interval.tick().await;
-                    f(device::Value::Int(ii)).await;
-                }
-
-                assert_eq!(s.try_next().await.unwrap(), None);
-            }
-
-            // Verify that, if the latest point is before the starting
-            // timestamp, it doesn't get returned.
-
-            {
-                let data = vec![1, 2, 3, 4, 5];
-
-                f(device::Value::Int(data[0])).await;
-                let mut interval = interval(time::Duration::from_millis(100));
-
-                let now = time::SystemTime::now();
-                let s = db
-                    .monitor_device(
-                        name.clone(),
-                        Some(now.into()),
-                        Some(
-                            now.checked_add(time::Duration::from_millis(250))
-                                .unwrap()
-                                .into(),
-                        ),
-                    )
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in &data[1..] {
-                    interval.tick().await;
-                    f(device::Value::Int(*ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(2)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(3)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(4)
-                );
-                assert_eq!(s.try_next().await.unwrap(), None);
-            }
-
-            // Verify that, if the latest point is after the starting
-            // timestamp, it isn't part of the results.
-
-            {
-                let data = vec![1, 2, 3, 4, 5];
-
-                let mut interval = interval(time::Duration::from_millis(100));
-
-                let now = time::SystemTime::now();
-                let s = db
-                    .monitor_device(
-                        name.clone(),
-                        Some(now.into()),
-                        Some(
-                            now.checked_add(time::Duration::from_millis(250))
-                                .unwrap()
-                                .into(),
-                        ),
-                    )
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in data {
-                    interval.tick().await;
-                    f(device::Value::Int(ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(1)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(2)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(3)
-                );
-                assert_eq!(s.try_next().await.unwrap(), None);
-            }
-
-            // Verify that, if the latest point is after the starting
-            // timestamp, it isn't part of the results.
-
-            {
-                let data = vec![1, 2, 3, 4, 5];
-
-                let mut interval = interval(time::Duration::from_millis(100));
-
-                let now = time::SystemTime::now();
-                let s = db
-                    .monitor_device(
-                        name.clone(),
-                        Some(
-                            now.checked_add(time::Duration::from_millis(150))
-                                .unwrap()
-                                .into(),
-                        ),
-                        Some(
-                            now.checked_add(time::Duration::from_millis(350))
-                                .unwrap()
-                                .into(),
-                        ),
-                    )
-                    .await
-                    .unwrap()
-                    .timeout(time::Duration::from_millis(100));
-
-                tokio::pin!(s);
-
-                for ii in data {
-                    interval.tick().await;
-                    f(device::Value::Int(ii)).await;
-                }
-
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(3)
-                );
-                assert_eq!(
-                    s.try_next().await.unwrap().unwrap().value,
-                    device::Value::Int(4)
-                );
-                assert_eq!(s.try_next().await.unwrap(), None);
-            }
-        } else {
-            panic!("error registering read-only device on empty database")
-        }
+        stream_tests::read_start_end_stream(&mut SimpleStore(
+            HashMap::new(),
+            None,
+        ))
+        .await
     }
 
     #[tokio::test]
     async fn test_ro_registration() {
-        let mut db = SimpleStore(HashMap::new());
+        let mut db = SimpleStore(HashMap::new(), None);
         let name = "misc:junk".parse::<device::Name>().unwrap();
 
         // Register a device named "junk" and associate it with the
         // driver named "test". We don't define units for this device.
 
         if let Ok(f) = db
-            .register_read_only_device("test", &name, None, None)
+            .register_read_only_device("test", &name, None, None, None)
             .await
         {
             // Make sure the device was defined and the setting
@@ -905,7 +586,7 @@ mod tests {
             // driver name results in an error.
 
             assert!(db
-                .register_read_only_device("test2", &name, None, None)
+                .register_read_only_device("test2", &name, None, None, None)
                 .await
                 .is_err());
 
@@ -913,7 +594,7 @@ mod tests {
             // driver name is successful.
 
             if let Ok(f) = db
-                .register_read_only_device("test", &name, None, None)
+                .register_read_only_device("test", &name, None, None, None)
                 .await
             {
                 // Also, verify that the device update channel wasn't
@@ -932,14 +613,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_rw_registration() {
-        let mut db = SimpleStore(HashMap::new());
+        let mut db = SimpleStore(HashMap::new(), None);
         let name = "misc:junk".parse::<device::Name>().unwrap();
 
         // Register a device named "junk" and associate it with the
         // driver named "test". We don't define units for this device.
 
         if let Ok((f, mut set_chan, None)) = db
-            .register_read_write_device("test", &name, None, None)
+            .register_read_write_device("test", &name, None, None, None)
             .await
         {
             // Make sure the device was defined and a setting channel
@@ -987,7 +668,7 @@ mod tests {
             // didn't affect the setting channel.
 
             assert!(db
-                .register_read_only_device("test2", &name, None, None)
+                .register_read_only_device("test2", &name, None, None, None)
                 .await
                 .is_err());
             assert_eq!(
@@ -999,7 +680,7 @@ mod tests {
             // driver name is successful.
 
             if let Ok((f, _, Some(device::Value::Int(1)))) = db
-                .register_read_write_device("test", &name, None, None)
+                .register_read_write_device("test", &name, None, None, None)
                 .await
             {
                 assert_eq!(
@@ -1021,6 +702,97 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_snapshot() {
+        let file = std::env::temp_dir()
+            .join(format!("drmem-snapshot-{}.txt", std::process::id()));
+        let cfg = super::config::Config {
+            snapshot: Some(file.to_string_lossy().into()),
+            snapshot_interval: None,
+        };
+        let rw = "misc:rw".parse::<device::Name>().unwrap();
+        let ro = "misc:ro".parse::<device::Name>().unwrap();
+
+        // Save a reading from two devices. Dropping the store writes
+        // the snapshot.
+
+        {
+            let mut db = super::open(&cfg).await.unwrap();
+
+            match db
+                .register_read_write_device("test", &rw, None, None, None)
+                .await
+            {
+                Ok((f, _, None)) => f(device::Value::Int(5)).await,
+                _ => panic!("couldn't register read-write device"),
+            }
+
+            let f = db
+                .register_read_only_device("test", &ro, None, None, None)
+                .await
+                .unwrap();
+
+            f(device::Value::Str("text".into())).await;
+        }
+
+        // The read-write device gets its previous value back. The
+        // read-only device isn't registered, but its reading is kept
+        // in the next snapshot.
+
+        {
+            let mut db = super::open(&cfg).await.unwrap();
+
+            assert!(matches!(
+                db.register_read_write_device("test", &rw, None, None, None)
+                    .await,
+                Ok((_, _, Some(device::Value::Int(5))))
+            ));
+
+            let s = db
+                .monitor_device(rw.clone(), None, None)
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(5)
+            );
+        }
+
+        {
+            let mut db = super::open(&cfg).await.unwrap();
+            let f = db
+                .register_read_only_device("test", &ro, None, None, None)
+                .await
+                .unwrap();
+            let s = db
+                .monitor_device(ro.clone(), None, None)
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Str("text".into())
+            );
+
+            // New readings come after the restored one.
+
+            f(device::Value::Str("more".into())).await;
+
+            let next = s.try_next().await.unwrap().unwrap();
+
+            assert_eq!(next.value, device::Value::Str("more".into()));
+        }
+
+        let _ = std::fs::remove_file(&file);
+    }
+
     #[tokio::test]
     async fn test_closure() {
         let di = DeviceInfo::create(String::from("test"), None, None);
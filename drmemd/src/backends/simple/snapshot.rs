@@ -0,0 +1,328 @@
+// The simple back-end only keeps the last reading of each device in
+// memory. If the `snapshot` file is configured, those readings are
+// saved in it periodically, and when the store is dropped at
+// shutdown. At startup, the saved readings are loaded and each
+// device gets its saved reading back when it's registered. This lets
+// read-write devices restore their previous setting.
+//
+// The file holds a line for each device:
+//
+//     NAME TIMESTAMP TYPE VALUE
+//
+// TIMESTAMP is in microseconds since the epoch. TYPE is a letter
+// giving the type of VALUE: `B` (true or false), `I` (an integer),
+// `D` (a float), `S` (a string, with backslashes, newlines and
+// carriage returns escaped) or `C` (a color, as 8 hex digits.)
+
+use super::ReadingState;
+use drmem_api::{device, Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time,
+};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+// The readings loaded from the file, for devices that haven't been
+// registered, and the readings of the registered devices.
+
+struct State {
+    saved: HashMap<device::Name, device::Reading>,
+    devices: HashMap<device::Name, Arc<Mutex<ReadingState>>>,
+}
+
+impl State {
+    // Returns the contents of the snapshot file. The devices are
+    // sorted by name so the file only changes when a reading does.
+
+    fn contents(&self) -> String {
+        let mut readings: BTreeMap<String, device::Reading> = self
+            .saved
+            .iter()
+            .map(|(name, rdg)| (name.to_string(), rdg.clone()))
+            .collect();
+
+        for (name, reading) in &self.devices {
+            if let Some(rdg) = reading.lock().ok().and_then(|v| v.1.clone()) {
+                readings.insert(name.to_string(), rdg);
+            }
+        }
+
+        readings
+            .iter()
+            .map(|(name, rdg)| format!("{} {}\n", name, encode(rdg)))
+            .collect()
+    }
+}
+
+// Escapes the characters which would break the line format.
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+// Reverses `escape()`.
+
+fn unescape(s: &str) -> Option<String> {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next()? {
+                '\\' => result.push('\\'),
+                'n' => result.push('\n'),
+                'r' => result.push('\r'),
+                _ => return None,
+            }
+        } else {
+            result.push(ch)
+        }
+    }
+    Some(result)
+}
+
+// Encodes the timestamp, type and value of a reading.
+
+fn encode(rdg: &device::Reading) -> String {
+    let us = rdg
+        .ts
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| u64::try_from(d.as_micros()).unwrap_or(u64::MAX))
+        .unwrap_or(0);
+
+    match &rdg.value {
+        device::Value::Bool(v) => format!("{} B {}", us, v),
+        device::Value::Int(v) => format!("{} I {}", us, v),
+        device::Value::Flt(v) => format!("{} D {}", us, v),
+        device::Value::Str(v) => format!("{} S {}", us, escape(v)),
+        device::Value::Color(v) => format!(
+            "{} C {:02x}{:02x}{:02x}{:02x}",
+            us, v.red, v.green, v.blue, v.alpha
+        ),
+    }
+}
+
+// Decodes a line of the snapshot file.
+
+fn decode(line: &str) -> Option<(device::Name, device::Reading)> {
+    let mut fields = line.splitn(4, ' ');
+    let name = fields.next()?.parse::<device::Name>().ok()?;
+    let ts = time::UNIX_EPOCH.checked_add(time::Duration::from_micros(
+        fields.next()?.parse().ok()?,
+    ))?;
+    let value = match (fields.next()?, fields.next()?) {
+        ("B", v) => device::Value::Bool(v.parse().ok()?),
+        ("I", v) => device::Value::Int(v.parse().ok()?),
+        ("D", v) => device::Value::Flt(v.parse().ok()?),
+        ("S", v) => device::Value::Str(unescape(v)?.into()),
+        ("C", v) if v.len() == 8 => {
+            let [r, g, b, a] = u32::from_str_radix(v, 16).ok()?.to_be_bytes();
+
+            device::Value::Color(palette::LinSrgba::new(r, g, b, a))
+        }
+        _ => return None,
+    };
+
+    Some((name, device::Reading { ts, value }))
+}
+
+// Parses the contents of a snapshot file. Lines which can't be
+// decoded are reported and skipped; a damaged snapshot shouldn't
+// keep `drmemd` from starting.
+
+fn parse(contents: &str) -> HashMap<device::Name, device::Reading> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .filter_map(|(idx, line)| {
+            let entry = decode(line);
+
+            if entry.is_none() {
+                warn!("ignoring line {} of the snapshot", idx + 1)
+            }
+            entry
+        })
+        .collect()
+}
+
+// Writes the snapshot. The contents go to a temporary file, which
+// then replaces the snapshot, so an interrupted write doesn't lose
+// the previous snapshot.
+
+fn write(file: &str, contents: &str) -> io::Result<()> {
+    let tmp = format!("{}.tmp", file);
+
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, file)
+}
+
+pub struct Snapshot {
+    file: String,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl Snapshot {
+    // Loads the snapshot file, if it exists, and starts the task
+    // which saves the readings every `interval`.
+
+    pub async fn open(file: &str, interval: time::Duration) -> Result<Self> {
+        let saved = match tokio::fs::read_to_string(file).await {
+            Ok(contents) => parse(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(Error::BackendError(format!(
+                    "couldn't read snapshot '{}' : {}",
+                    file, e
+                )))
+            }
+        };
+
+        info!("loaded {} readings from '{}'", saved.len(), file);
+
+        let state = Arc::new(Mutex::new(State {
+            saved,
+            devices: HashMap::new(),
+        }));
+        let task = {
+            let file = file.to_string();
+            let state = state.clone();
+
+            tokio::spawn(async move {
+                let mut timer = tokio::time::interval(interval);
+                let mut prev = None;
+
+                timer.tick().await;
+                loop {
+                    timer.tick().await;
+
+                    let Ok(contents) = state.lock().map(|s| s.contents())
+                    else {
+                        break;
+                    };
+
+                    if prev.as_ref() != Some(&contents) {
+                        match write(&file, &contents) {
+                            Ok(()) => prev = Some(contents),
+                            Err(e) => warn!("couldn't save snapshot -- {}", e),
+                        }
+                    }
+                }
+            })
+        };
+
+        Ok(Snapshot {
+            file: file.to_string(),
+            state,
+            task,
+        })
+    }
+
+    // Adds a newly registered device to the snapshot. If a reading was
+    // saved for it, it becomes the device's reading.
+
+    pub fn restore(
+        &self,
+        name: &device::Name,
+        reading: &Arc<Mutex<ReadingState>>,
+    ) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(rdg) = state.saved.remove(name) {
+                if let Ok(mut data) = reading.lock() {
+                    data.2 = rdg.ts;
+                    data.1 = Some(rdg);
+                }
+            }
+            state.devices.insert(name.clone(), reading.clone());
+        }
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        self.task.abort();
+
+        if let Ok(state) = self.state.lock() {
+            match write(&self.file, &state.contents()) {
+                Ok(()) => info!("saved snapshot to '{}'", &self.file),
+                Err(e) => warn!("couldn't save snapshot -- {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(us: u64, value: device::Value) -> device::Reading {
+        device::Reading {
+            ts: time::UNIX_EPOCH + time::Duration::from_micros(us),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        let values = [
+            device::Value::Bool(true),
+            device::Value::Int(-42),
+            device::Value::Flt(0.1),
+            device::Value::Flt(f64::INFINITY),
+            device::Value::Str("a \\ b\nc\rd e".into()),
+            device::Value::Str("".into()),
+            device::Value::Color(palette::LinSrgba::new(1, 0x80, 0xff, 0)),
+        ];
+
+        for value in values {
+            let rdg = reading(1_700_000_000_123_456, value);
+            let line = format!("dev:x {}", encode(&rdg));
+
+            assert!(!line.contains('\n'));
+            assert_eq!(decode(&line), Some(("dev:x".parse().unwrap(), rdg)));
+        }
+
+        assert_eq!(
+            encode(&reading(5, device::Value::Str("a\nb".into()))),
+            "5 S a\\nb"
+        );
+        assert_eq!(
+            encode(&reading(
+                5,
+                device::Value::Color(palette::LinSrgba::new(1, 2, 3, 4))
+            )),
+            "5 C 01020304"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let saved = parse(
+            "dev:a 10 I 5\n\
+             \n\
+             dev:b 20 X 5\n\
+             dev:c 30 S bad\\escape\n\
+             dev:d 40 C 123\n\
+             dev:e -1 B true\n\
+             bad 50 B true\n\
+             dev:f 60 B yes\n\
+             dev:g 70 S \n",
+        );
+
+        assert_eq!(saved.len(), 2);
+        assert_eq!(
+            saved.get(&"dev:a".parse().unwrap()),
+            Some(&reading(10, device::Value::Int(5)))
+        );
+        assert_eq!(
+            saved.get(&"dev:g".parse().unwrap()),
+            Some(&reading(70, device::Value::Str("".into())))
+        );
+    }
+}
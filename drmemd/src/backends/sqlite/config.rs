@@ -0,0 +1,33 @@
+use serde_derive::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    pub file: Option<String>,
+}
+
+impl Config {
+    pub const fn new() -> Config {
+        Config { file: None }
+    }
+
+    // Returns the path of the database file. Debug builds use a
+    // different default file so development doesn't disturb the
+    // data of an installed `drmemd`.
+
+    #[cfg(debug_assertions)]
+    pub fn get_file(&self) -> &str {
+        self.file.as_deref().unwrap_or("drmem-debug.db")
+    }
+    #[cfg(not(debug_assertions))]
+    pub fn get_file(&self) -> &str {
+        self.file.as_deref().unwrap_or("drmem.db")
+    }
+}
+
+pub static DEF: Config = Config::new();
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
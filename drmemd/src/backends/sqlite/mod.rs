@@ -0,0 +1,1103 @@
+//! Provides an SQLite storage back-end for the DrMem control system.
+//!
+//! This back-end keeps the device meta-information and the history
+//! of each device in a local database file, so it needs no external
+//! server. It's meant for small installations, like single-board
+//! computers, which want history but don't want to run redis.
+//!
+//! The database has two tables. `devices` holds the name, driver and
+//! units of every device that was registered. `readings` holds the
+//! history of the devices. Timestamps are saved as microseconds
+//! since the epoch. Each value is saved with a one letter code for
+//! its type (using the same letters as the redis back-end) so it's
+//! read back as the same `device::Value` variant.
+//!
+//! SQLite calls are synchronous and, on the slow storage of a
+//! single-board computer, a commit can wait on the disk. So the calls
+//! are made on tokio's blocking threads, which keeps them from
+//! stalling drivers and clients. The database uses WAL mode with
+//! `synchronous=NORMAL`, which only syncs the disk at checkpoints;
+//! a power failure can lose the last readings, but can't corrupt the
+//! database.
+
+use crate::backends::Store;
+use async_trait::async_trait;
+use chrono::*;
+use drmem_api::{
+    client, device,
+    driver::{ReportReading, RxDeviceSetting, TxDeviceSetting},
+    Error, Result,
+};
+use rusqlite::{params, types, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::{
+    sync::{Arc, Mutex},
+    time,
+};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt,
+};
+use tracing::{debug, error, info, warn};
+
+pub mod config;
+
+const CHAN_SIZE: usize = 20;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS devices (
+    name TEXT PRIMARY KEY,
+    driver TEXT NOT NULL,
+    units TEXT
+);
+
+CREATE TABLE IF NOT EXISTS readings (
+    device TEXT NOT NULL,
+    ts INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    value,
+    PRIMARY KEY (device, ts)
+) WITHOUT ROWID;
+";
+
+// Translates an SQLite error into a DrMem error. The message is
+// kept so the context of the error can be rebuilt.
+
+fn xlat_err(e: rusqlite::Error) -> Error {
+    Error::BackendError(format!("{}", &e))
+}
+
+// Returns the type code and the SQLite value used to save a
+// `device::Value`.
+
+fn to_sql(val: &device::Value) -> (&'static str, types::Value) {
+    match val {
+        device::Value::Bool(v) => ("B", types::Value::Integer(*v as i64)),
+        device::Value::Int(v) => ("I", types::Value::Integer(*v as i64)),
+        device::Value::Flt(v) => ("D", types::Value::Real(*v)),
+        device::Value::Str(v) => ("S", types::Value::Text(v.to_string())),
+        device::Value::Color(v) => (
+            "C",
+            types::Value::Blob(vec![v.red, v.green, v.blue, v.alpha]),
+        ),
+    }
+}
+
+// Rebuilds a `device::Value` from its type code and SQLite value.
+
+fn from_sql(kind: &str, val: types::Value) -> Result<device::Value> {
+    match (kind, val) {
+        ("B", types::Value::Integer(v)) => Ok(device::Value::Bool(v != 0)),
+        ("I", types::Value::Integer(v)) => i32::try_from(v)
+            .map(device::Value::Int)
+            .map_err(|_| Error::TypeError),
+        ("D", types::Value::Real(v)) => Ok(device::Value::Flt(v)),
+        ("S", types::Value::Text(v)) => Ok(device::Value::Str(v.into())),
+        ("C", types::Value::Blob(v)) => match v[..] {
+            [r, g, b, a] => {
+                Ok(device::Value::Color(palette::LinSrgba::new(r, g, b, a)))
+            }
+            _ => Err(Error::TypeError),
+        },
+        _ => Err(Error::TypeError),
+    }
+}
+
+// Converts a timestamp to the microseconds saved in the database.
+// Timestamps outside the range of an `i64` are clipped.
+
+fn ts_to_us(ts: time::SystemTime) -> i64 {
+    ts.duration_since(time::UNIX_EPOCH)
+        .map(|d| i64::try_from(d.as_micros()).unwrap_or(i64::MAX))
+        .unwrap_or(0)
+}
+
+// Converts microseconds, from the database, to a timestamp.
+
+fn us_to_ts(us: i64) -> time::SystemTime {
+    time::UNIX_EPOCH + time::Duration::from_micros(us.max(0) as u64)
+}
+
+// Holds the channel which sends a device's new readings to its
+// monitors. `last` is the timestamp of the latest reading and
+// `owner` is the driver which registered the device while `drmemd`
+// has been running.
+
+struct Channel {
+    tx: broadcast::Sender<device::Reading>,
+    last: time::SystemTime,
+    owner: Option<String>,
+}
+
+// The database connection and the reading channels. They're kept
+// behind one mutex so a monitor can read the history and subscribe
+// to new readings without missing any.
+
+struct Db {
+    con: Connection,
+    chans: HashMap<device::Name, Channel>,
+}
+
+impl Db {
+    // Returns the reading channel of a device, creating it if it
+    // doesn't exist.
+
+    fn channel(&mut self, name: &device::Name) -> Result<&mut Channel> {
+        if !self.chans.contains_key(name) {
+            let last = self
+                .con
+                .query_row(
+                    "SELECT MAX(ts) FROM readings WHERE device = ?1",
+                    params![name.to_string()],
+                    |row| row.get::<_, Option<i64>>(0),
+                )
+                .map_err(xlat_err)?
+                .map(us_to_ts)
+                .unwrap_or(time::UNIX_EPOCH);
+            let (tx, _) = broadcast::channel(CHAN_SIZE);
+
+            self.chans.insert(
+                name.clone(),
+                Channel {
+                    tx,
+                    last,
+                    owner: None,
+                },
+            );
+        }
+        Ok(self.chans.get_mut(name).unwrap())
+    }
+
+    // Associates a device with a driver. A device can't be registered
+    // by two drivers. The driver and units are saved in the
+    // database, replacing the ones saved by a previous run.
+
+    fn register(
+        &mut self,
+        driver: &str,
+        name: &device::Name,
+        units: Option<&String>,
+    ) -> Result<()> {
+        let chan = self.channel(name)?;
+
+        match &chan.owner {
+            Some(owner) if owner != driver => return Err(Error::InUse),
+            Some(_) => (),
+            None => chan.owner = Some(driver.into()),
+        }
+
+        self.con
+            .execute(
+                "INSERT INTO devices (name, driver, units) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (name) DO UPDATE
+                 SET driver = excluded.driver, units = excluded.units",
+                params![name.to_string(), driver, units],
+            )
+            .map(|_| ())
+            .map_err(xlat_err)
+    }
+
+    // Returns `true` if the device is in the database.
+
+    fn exists(&self, name: &device::Name) -> Result<bool> {
+        self.con
+            .query_row(
+                "SELECT 1 FROM devices WHERE name = ?1",
+                params![name.to_string()],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|v| v.is_some())
+            .map_err(xlat_err)
+    }
+
+    // Runs a query which returns readings.
+
+    fn readings(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<device::Reading>> {
+        let mut stmt = self.con.prepare(sql).map_err(xlat_err)?;
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, types::Value>(2)?,
+                ))
+            })
+            .map_err(xlat_err)?;
+
+        rows.map(|row| {
+            let (ts, kind, value) = row.map_err(xlat_err)?;
+
+            Ok(device::Reading {
+                ts: us_to_ts(ts),
+                value: from_sql(&kind, value)?,
+            })
+        })
+        .collect()
+    }
+
+    // Returns the oldest or the latest reading of a device.
+
+    fn end_point(
+        &self,
+        name: &device::Name,
+        latest: bool,
+    ) -> Result<Option<device::Reading>> {
+        let sql = if latest {
+            "SELECT ts, kind, value FROM readings WHERE device = ?1
+             ORDER BY ts DESC LIMIT 1"
+        } else {
+            "SELECT ts, kind, value FROM readings WHERE device = ?1
+             ORDER BY ts ASC LIMIT 1"
+        };
+
+        self.readings(sql, params![name.to_string()])
+            .map(|v| v.into_iter().next())
+    }
+
+    // Returns a page of a device's history: up to `limit` readings
+    // from `from` through `to`, inclusive.
+
+    fn history(
+        &self,
+        name: &device::Name,
+        from: time::SystemTime,
+        to: time::SystemTime,
+        limit: usize,
+    ) -> Result<Vec<device::Reading>> {
+        self.readings(
+            "SELECT ts, kind, value FROM readings
+             WHERE device = ?1 AND ts >= ?2 AND ts <= ?3
+             ORDER BY ts ASC LIMIT ?4",
+            params![
+                name.to_string(),
+                ts_to_us(from),
+                ts_to_us(to),
+                i64::try_from(limit).unwrap_or(i64::MAX)
+            ],
+        )
+    }
+
+    // Saves a new reading and sends it to the device's monitors. Like
+    // the other back-ends, readings are kept in order: a timestamp
+    // that isn't after the previous one is moved 1 𝜇s past it. If
+    // `max_history` is specified, older readings are removed so
+    // that only that many remain. If `max_age` is specified, readings
+    // older than that are removed.
+
+    fn insert(
+        &mut self,
+        name: &device::Name,
+        mut ts: time::SystemTime,
+        value: device::Value,
+        max_history: Option<usize>,
+        max_age: Option<time::Duration>,
+    ) -> Result<()> {
+        let last = self.channel(name)?.last;
+
+        if ts <= last {
+            ts = last
+                .checked_add(time::Duration::from_micros(1))
+                .unwrap_or(last)
+        }
+
+        let key = name.to_string();
+        let (kind, sql_value) = to_sql(&value);
+        let tx = self.con.transaction().map_err(xlat_err)?;
+
+        tx.execute(
+            "INSERT INTO readings (device, ts, kind, value)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![key, ts_to_us(ts), kind, sql_value],
+        )
+        .map_err(xlat_err)?;
+
+        if let Some(mh) = max_history {
+            tx.execute(
+                "DELETE FROM readings WHERE device = ?1 AND ts <=
+                 (SELECT ts FROM readings WHERE device = ?1
+                  ORDER BY ts DESC LIMIT 1 OFFSET ?2)",
+                params![key, mh.max(1) as i64],
+            )
+            .map_err(xlat_err)?;
+        }
+
+        if let Some(oldest) = max_age.and_then(|age| ts.checked_sub(age)) {
+            tx.execute(
+                "DELETE FROM readings WHERE device = ?1 AND ts < ?2",
+                params![key, ts_to_us(oldest)],
+            )
+            .map_err(xlat_err)?;
+        }
+        tx.commit().map_err(xlat_err)?;
+
+        let chan = self.channel(name)?;
+
+        chan.last = ts;
+        let _ = chan.tx.send(device::Reading { ts, value });
+        Ok(())
+    }
+
+    // Saves readings with their own timestamps. A reading with the
+    // timestamp of a saved reading replaces it. The readings aren't
+    // sent to the monitors.
+
+    fn import(
+        &mut self,
+        name: &device::Name,
+        readings: &[device::Reading],
+    ) -> Result<()> {
+        let key = name.to_string();
+        let tx = self.con.transaction().map_err(xlat_err)?;
+
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT OR REPLACE INTO readings (device, ts, kind, value)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(xlat_err)?;
+
+            for rdg in readings {
+                let (kind, sql_value) = to_sql(&rdg.value);
+
+                stmt.execute(params![key, ts_to_us(rdg.ts), kind, sql_value])
+                    .map_err(xlat_err)?;
+            }
+        }
+        tx.commit().map_err(xlat_err)?;
+
+        if let Some(ts) = readings.iter().map(|v| v.ts).max() {
+            let chan = self.channel(name)?;
+
+            chan.last = chan.last.max(ts);
+        }
+        Ok(())
+    }
+}
+
+// Runs `f` with the locked database on one of tokio's blocking
+// threads and returns its result.
+
+async fn with_db<T, F>(db: &Arc<Mutex<Db>>, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Db) -> Result<T> + Send + 'static,
+{
+    let db = db.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut db = db.lock().map_err(|_| {
+            Error::OperationError("unable to lock database".into())
+        })?;
+
+        f(&mut db)
+    })
+    .await
+    .map_err(|e| {
+        Error::OperationError(format!("database task failed : {}", e))
+    })?
+}
+
+/// Defines a context that uses an SQLite database for storage.
+pub struct SqliteStore {
+    db: Arc<Mutex<Db>>,
+    table: HashMap<device::Name, TxDeviceSetting>,
+}
+
+impl SqliteStore {
+    // Creates the store, adding the tables to the database if they
+    // don't exist.
+
+    fn new(con: Connection) -> Result<Self> {
+        con.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
+            .map_err(xlat_err)?;
+        con.pragma_update(None, "synchronous", "NORMAL")
+            .map_err(xlat_err)?;
+        con.execute_batch(SCHEMA).map_err(xlat_err)?;
+
+        Ok(SqliteStore {
+            db: Arc::new(Mutex::new(Db {
+                con,
+                chans: HashMap::new(),
+            })),
+            table: HashMap::new(),
+        })
+    }
+
+    // Creates a closure for a driver to report a device's changing
+    // values.
+
+    fn mk_report_func(
+        &self,
+        name: &device::Name,
+        max_history: Option<usize>,
+        max_age: Option<time::Duration>,
+    ) -> ReportReading {
+        let db = self.db.clone();
+        let name = name.clone();
+
+        Box::new(move |v| {
+            // Determine the timestamp *before* we wait for the
+            // database.
+
+            let ts = time::SystemTime::now();
+            let db = db.clone();
+            let name = name.clone();
+
+            Box::pin(async move {
+                let dev = name.clone();
+
+                if let Err(e) = with_db(&db, move |db| {
+                    db.insert(&dev, ts, v, max_history, max_age)
+                })
+                .await
+                {
+                    warn!("couldn't save {} data to sqlite ... {}", &name, e)
+                }
+            })
+        })
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    // Registers a read-only device in the database.
+
+    async fn register_read_only_device(
+        &mut self,
+        driver: &str,
+        name: &device::Name,
+        units: Option<&String>,
+        max_history: Option<usize>,
+        max_age: Option<time::Duration>,
+    ) -> Result<ReportReading> {
+        debug!("registering '{}' as read-only", &name);
+
+        let (driver, dev, units) =
+            (driver.to_string(), name.clone(), units.cloned());
+
+        with_db(&self.db, move |db| {
+            db.register(&driver, &dev, units.as_ref())
+        })
+        .await?;
+        Ok(self.mk_report_func(name, max_history, max_age))
+    }
+
+    // Registers a read-write device in the database. The device's
+    // latest saved value is returned so the driver can restore it.
+
+    async fn register_read_write_device(
+        &mut self,
+        driver: &str,
+        name: &device::Name,
+        units: Option<&String>,
+        max_history: Option<usize>,
+        max_age: Option<time::Duration>,
+    ) -> Result<(ReportReading, RxDeviceSetting, Option<device::Value>)> {
+        debug!("registering '{}' as read-write", &name);
+
+        let (driver, dev, units) =
+            (driver.to_string(), name.clone(), units.cloned());
+        let last = with_db(&self.db, move |db| {
+            db.register(&driver, &dev, units.as_ref())?;
+            db.end_point(&dev, true).map(|v| v.map(|v| v.value))
+        })
+        .await?;
+        let (tx, rx) = mpsc::channel(CHAN_SIZE);
+
+        if self.table.insert(name.clone(), tx).is_some() {
+            warn!("{} already had a setting channel", &name);
+        }
+        Ok((self.mk_report_func(name, max_history, max_age), rx, last))
+    }
+
+    // Imports readings into a device's history.
+
+    async fn import_readings(
+        &mut self,
+        name: &device::Name,
+        readings: Vec<device::Reading>,
+    ) -> Result<()> {
+        let name = name.clone();
+
+        with_db(&self.db, move |db| db.import(&name, &readings)).await
+    }
+
+    // Returns the information of the devices in the database. The
+    // pattern is matched with SQLite's `GLOB` operator, which uses
+    // the same wildcards as redis but doesn't support escaping them
+    // with a backslash.
+
+    async fn get_device_info(
+        &mut self,
+        pattern: Option<&str>,
+    ) -> Result<Vec<client::DevInfoReply>> {
+        let pattern = pattern.unwrap_or("*").to_string();
+        let mut devices = with_db(&self.db, move |db| {
+            let mut stmt = db
+                .con
+                .prepare(
+                    "SELECT d.name, d.driver, d.units, COUNT(r.ts)
+                     FROM devices d LEFT JOIN readings r ON r.device = d.name
+                     WHERE d.name GLOB ?1 GROUP BY d.name ORDER BY d.name",
+                )
+                .map_err(xlat_err)?;
+            let rows = stmt
+                .query_map(params![pattern], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, u32>(3)?,
+                    ))
+                })
+                .map_err(xlat_err)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(xlat_err)?;
+            let mut devices = vec![];
+
+            for (name, driver, units, total_points) in rows {
+                // Only process entries that are valid device names.
+
+                if let Ok(name) = name.parse::<device::Name>() {
+                    devices.push(client::DevInfoReply {
+                        settable: false,
+                        first_point: db.end_point(&name, false)?,
+                        last_point: db.end_point(&name, true)?,
+                        name,
+                        units,
+                        total_points,
+                        driver: driver.into(),
+                    })
+                }
+            }
+            Ok(devices)
+        })
+        .await?;
+
+        for dev in &mut devices {
+            dev.settable = self.table.contains_key(&dev.name)
+        }
+        Ok(devices)
+    }
+
+    async fn set_device(
+        &self,
+        name: device::Name,
+        value: device::Value,
+    ) -> Result<device::Value> {
+        if let Some(tx) = self.table.get(&name) {
+            let (tx_rpy, rx_rpy) = oneshot::channel();
+
+            match tx.send((value, tx_rpy)).await {
+                Ok(()) => match rx_rpy.await {
+                    Ok(reply) => reply,
+                    Err(_) => Err(Error::MissingPeer(
+                        "driver broke connection".to_string(),
+                    )),
+                },
+                Err(_) => Err(Error::MissingPeer(
+                    "driver is ignoring settings".to_string(),
+                )),
+            }
+        } else {
+            Err(Error::NotFound)
+        }
+    }
+
+    async fn get_setting_chan(
+        &self,
+        name: device::Name,
+        _own: bool,
+    ) -> Result<TxDeviceSetting> {
+        self.table.get(&name).cloned().ok_or(Error::NotFound)
+    }
+
+    // Handles a request to monitor a device. The stream starts with
+    // the saved readings -- the latest one, if there's no start
+    // time, or the ones since the start time -- followed by new
+    // readings. If there's an end time, the stream ends when a
+    // reading goes past it.
+
+    async fn monitor_device(
+        &mut self,
+        name: device::Name,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<device::DataStream<device::Reading>> {
+        let (start, end): (Option<time::SystemTime>, Option<time::SystemTime>) =
+            match (
+                start.map(time::SystemTime::from),
+                end.map(time::SystemTime::from),
+            ) {
+                (Some(start), Some(end)) => {
+                    (Some(start.min(end)), Some(start.max(end)))
+                }
+                range => range,
+            };
+
+        const PAGE: usize = 1000;
+
+        // Subscribe, and get the timestamp of the last reading, while
+        // holding the lock. The history goes through that reading and
+        // the live readings follow it, so no reading is missed, or
+        // sent twice. Without a start time, the history is the last
+        // reading.
+
+        let dev = name.clone();
+        let (chan, last, latest) = with_db(&self.db, move |db| {
+            if !db.exists(&dev)? {
+                return Err(Error::NotFound);
+            }
+
+            let latest = match start {
+                None => db.end_point(&dev, true)?,
+                Some(_) => None,
+            };
+            let chan = db.channel(&dev)?;
+
+            Ok((chan.tx.subscribe(), chan.last, latest))
+        })
+        .await?;
+
+        // The history is read a page at a time, without holding the
+        // lock in between, so a long range doesn't have to fit in
+        // memory.
+
+        let history: device::DataStream<device::Reading> = match start {
+            None => Box::pin(tokio_stream::iter(latest)),
+            Some(start) => {
+                let to = end.map_or(last, |end| end.min(last));
+                let db = self.db.clone();
+                let name = name.clone();
+
+                Box::pin(futures::StreamExt::flatten(futures::stream::unfold(
+                    Some(start).filter(|start| *start <= to),
+                    move |from| {
+                        let db = db.clone();
+                        let name = name.clone();
+
+                        async move {
+                            let from = from?;
+                            let dev = name.clone();
+                            let page = match with_db(&db, move |db| {
+                                db.history(&dev, from, to, PAGE)
+                            })
+                            .await
+                            {
+                                Ok(page) => page,
+                                Err(e) => {
+                                    error!(
+                                        "couldn't read history of {} : {}",
+                                        &name, e
+                                    );
+                                    return None;
+                                }
+                            };
+
+                            // A full page means there may be more
+                            // readings. The next page starts after
+                            // the last reading of this one.
+
+                            let next = page
+                                .last()
+                                .filter(|_| page.len() == PAGE)
+                                .map(|v| v.ts + time::Duration::from_micros(1));
+
+                            Some((tokio_stream::iter(page), next))
+                        }
+                    },
+                )))
+            }
+        };
+
+        // If the range ends before the last reading, the history is
+        // all that's sent.
+
+        if end.is_some_and(|end| end <= last) {
+            return Ok(history);
+        }
+
+        // Broadcast channels report when a client is too slow in
+        // reading values. The DrMem core doesn't expect these errors
+        // so they're filtered out, but reported to the log.
+
+        let live = BroadcastStream::new(chan)
+            .filter_map(move |entry| match entry {
+                Ok(v) => Some(v),
+                Err(BroadcastStreamRecvError::Lagged(count)) => {
+                    warn!("missed {} readings of {}", count, &name);
+                    None
+                }
+            })
+            .filter(move |v| {
+                v.ts > last && start.is_none_or(|start| v.ts >= start)
+            });
+        let strm = history.chain(live);
+
+        if let Some(end) = end {
+            Ok(Box::pin(strm.take_while(move |v| v.ts <= end)))
+        } else {
+            Ok(Box::pin(strm))
+        }
+    }
+}
+
+pub async fn open(cfg: &config::Config) -> Result<impl Store> {
+    info!("opening database '{}'", cfg.get_file());
+
+    Connection::open(cfg.get_file())
+        .map_err(xlat_err)
+        .and_then(SqliteStore::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::stream_tests;
+
+    fn store() -> SqliteStore {
+        SqliteStore::new(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn name(s: &str) -> device::Name {
+        s.parse::<device::Name>().unwrap()
+    }
+
+    #[test]
+    fn test_values() {
+        let values = [
+            device::Value::Bool(false),
+            device::Value::Bool(true),
+            device::Value::Int(i32::MIN),
+            device::Value::Int(i32::MAX),
+            device::Value::Flt(2.0),
+            device::Value::Flt(-1.5),
+            device::Value::Str("hello".into()),
+            device::Value::Color(palette::LinSrgba::new(1, 2, 3, 4)),
+        ];
+
+        for v in values {
+            let (kind, sv) = to_sql(&v);
+
+            assert_eq!(from_sql(kind, sv), Ok(v));
+        }
+
+        assert!(from_sql("I", types::Value::Integer(1 << 40)).is_err());
+        assert!(from_sql("C", types::Value::Blob(vec![1, 2])).is_err());
+        assert!(from_sql("B", types::Value::Text("true".into())).is_err());
+        assert!(from_sql("X", types::Value::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let ts = time::UNIX_EPOCH + time::Duration::from_micros(1_234_567);
+
+        assert_eq!(ts_to_us(ts), 1_234_567);
+        assert_eq!(us_to_ts(1_234_567), ts);
+        assert_eq!(us_to_ts(-5), time::UNIX_EPOCH);
+    }
+
+    #[tokio::test]
+    async fn test_read_live_stream() {
+        stream_tests::read_live_stream(&mut store()).await
+    }
+
+    #[tokio::test]
+    async fn test_read_start_stream() {
+        stream_tests::read_start_stream(&mut store()).await
+    }
+
+    #[tokio::test]
+    async fn test_read_end_stream() {
+        stream_tests::read_end_stream(&mut store()).await
+    }
+
+    #[tokio::test]
+    async fn test_read_start_end_stream() {
+        stream_tests::read_start_end_stream(&mut store()).await
+    }
+
+    #[tokio::test]
+    async fn test_registration() {
+        let mut db = store();
+        let dev = name("misc:junk");
+        let units = String::from("V");
+
+        assert!(db.monitor_device(dev.clone(), None, None).await.is_err());
+
+        let f = db
+            .register_read_only_device("test", &dev, Some(&units), None, None)
+            .await
+            .unwrap();
+
+        // Another driver can't register the device, but the same
+        // driver can.
+
+        assert_eq!(
+            db.register_read_only_device("test2", &dev, None, None, None)
+                .await
+                .err(),
+            Some(Error::InUse)
+        );
+        assert!(db
+            .register_read_only_device("test", &dev, Some(&units), None, None)
+            .await
+            .is_ok());
+
+        f(device::Value::Int(1)).await;
+        f(device::Value::Int(2)).await;
+
+        let info = db.get_device_info(Some("misc:*")).await.unwrap();
+
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].name, dev);
+        assert_eq!(info[0].units, Some(units.clone()));
+        assert_eq!(info[0].driver.as_ref(), "test");
+        assert!(!info[0].settable);
+        assert_eq!(info[0].total_points, 2);
+        assert_eq!(
+            info[0].first_point.as_ref().map(|v| &v.value),
+            Some(&device::Value::Int(1))
+        );
+        assert_eq!(
+            info[0].last_point.as_ref().map(|v| &v.value),
+            Some(&device::Value::Int(2))
+        );
+        assert!(db
+            .get_device_info(Some("other:*"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // A read-write device gets a setting channel and its last
+        // value.
+
+        let rw = name("misc:rw");
+
+        match db
+            .register_read_write_device("test", &rw, None, None, None)
+            .await
+        {
+            Ok((f, _, None)) => f(device::Value::Bool(true)).await,
+            _ => panic!("couldn't register read-write device"),
+        }
+        assert!(db.get_setting_chan(rw.clone(), false).await.is_ok());
+        assert!(db.get_setting_chan(dev.clone(), false).await.is_err());
+        assert!(matches!(
+            db.register_read_write_device("test", &rw, None, None, None)
+                .await,
+            Ok((_, _, Some(device::Value::Bool(true))))
+        ));
+        assert_eq!(db.get_device_info(None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_history() {
+        let mut db = store();
+        let dev = name("misc:junk");
+        let f = db
+            .register_read_only_device("test", &dev, None, Some(3), None)
+            .await
+            .unwrap();
+
+        for ii in 1..=5 {
+            f(device::Value::Int(ii)).await;
+        }
+
+        let info = db.get_device_info(None).await.unwrap();
+
+        assert_eq!(info[0].total_points, 3);
+        assert_eq!(
+            info[0].first_point.as_ref().map(|v| &v.value),
+            Some(&device::Value::Int(3))
+        );
+
+        // All the remaining history is available to a monitor.
+
+        let s = db
+            .monitor_device(dev, Some(time::UNIX_EPOCH.into()), None)
+            .await
+            .unwrap()
+            .timeout(time::Duration::from_millis(100));
+
+        tokio::pin!(s);
+
+        for ii in 3..=5 {
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(ii)
+            );
+        }
+        assert!(s.try_next().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_max_age() {
+        let mut db = store();
+        let dev = name("misc:junk");
+        let age = time::Duration::from_secs(60);
+        let f = db
+            .register_read_only_device("test", &dev, None, None, Some(age))
+            .await
+            .unwrap();
+        let now = time::SystemTime::now();
+
+        // Add readings from the past. Only the last one is young
+        // enough to be kept once a new reading arrives.
+
+        for (secs, v) in [(120, 1), (90, 2), (30, 3)] {
+            db.db
+                .lock()
+                .unwrap()
+                .insert(
+                    &dev,
+                    now - time::Duration::from_secs(secs),
+                    device::Value::Int(v),
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(db.get_device_info(None).await.unwrap()[0].total_points, 3);
+
+        f(device::Value::Int(4)).await;
+
+        let info = db.get_device_info(None).await.unwrap();
+
+        assert_eq!(info[0].total_points, 2);
+        assert_eq!(
+            info[0].first_point.as_ref().map(|v| &v.value),
+            Some(&device::Value::Int(3))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import() {
+        let mut db = store();
+        let dev = name("misc:junk");
+        let rdg = |us, v| device::Reading {
+            ts: time::UNIX_EPOCH + time::Duration::from_micros(us),
+            value: device::Value::Int(v),
+        };
+        let _ = db
+            .register_read_only_device("test", &dev, None, None, None)
+            .await
+            .unwrap();
+
+        db.import_readings(&dev, vec![rdg(10, 1), rdg(20, 2)])
+            .await
+            .unwrap();
+
+        // A reading with the timestamp of a saved reading replaces it.
+
+        db.import_readings(&dev, vec![rdg(20, 3), rdg(30, 4)])
+            .await
+            .unwrap();
+
+        let info = db.get_device_info(None).await.unwrap();
+
+        assert_eq!(info[0].total_points, 3);
+        assert_eq!(info[0].first_point, Some(rdg(10, 1)));
+        assert_eq!(info[0].last_point, Some(rdg(30, 4)));
+
+        let s = db
+            .monitor_device(dev, Some(time::UNIX_EPOCH.into()), None)
+            .await
+            .unwrap()
+            .timeout(time::Duration::from_millis(100));
+
+        tokio::pin!(s);
+
+        for (us, v) in [(10, 1), (20, 3), (30, 4)] {
+            assert_eq!(s.try_next().await.unwrap(), Some(rdg(us, v)));
+        }
+        assert!(s.try_next().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_paged_history() {
+        let mut db = store();
+        let dev = name("misc:junk");
+        let rdg = |us: u64| device::Reading {
+            ts: time::UNIX_EPOCH + time::Duration::from_micros(us),
+            value: device::Value::Int(us as i32),
+        };
+        let _ = db
+            .register_read_only_device("test", &dev, None, None, None)
+            .await
+            .unwrap();
+
+        db.import_readings(&dev, (1..=2500).map(rdg).collect())
+            .await
+            .unwrap();
+
+        // The history spans several pages. Since the range ends
+        // before the last reading, the stream ends with it.
+
+        let s = db
+            .monitor_device(
+                dev,
+                Some(rdg(100).ts.into()),
+                Some(rdg(2300).ts.into()),
+            )
+            .await
+            .unwrap()
+            .timeout(time::Duration::from_millis(100))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            s.into_iter().map(|v| v.unwrap()).collect::<Vec<_>>(),
+            (100..=2300).map(rdg).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persistence() {
+        let file = std::env::temp_dir()
+            .join(format!("drmem-test-{}.db", std::process::id()));
+        let cfg = config::Config {
+            file: Some(file.to_string_lossy().into()),
+        };
+        let dev = name("misc:junk");
+
+        {
+            let mut db = open(&cfg).await.unwrap();
+
+            match db
+                .register_read_write_device("test", &dev, None, None, None)
+                .await
+            {
+                Ok((f, _, None)) => f(device::Value::Flt(1.5)).await,
+                _ => panic!("couldn't register device"),
+            }
+        }
+
+        // A new instance sees the device and its history.
+
+        {
+            let mut db = open(&cfg).await.unwrap();
+            let info = db.get_device_info(None).await.unwrap();
+
+            assert_eq!(info.len(), 1);
+            assert_eq!(info[0].total_points, 1);
+            assert!(matches!(
+                db.register_read_write_device("test", &dev, None, None, None)
+                    .await,
+                Ok((_, _, Some(device::Value::Flt(v)))) if v == 1.5
+            ));
+        }
+
+        for ext in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", file.display(), ext));
+        }
+    }
+}
@@ -0,0 +1,416 @@
+// Tests of the `monitor_device` semantics which every back-end has
+// to share. Each back-end runs them against a new, empty instance of
+// its store.
+//
+// - With no start time, the stream begins with the latest reading of
+//   the device, if there is one, followed by all new readings.
+// - With a start time, the stream only holds readings whose
+//   timestamps are at or after it.
+// - With an end time, the stream ends at the first reading after it.
+
+use crate::backends::Store;
+use drmem_api::device;
+use std::time;
+use tokio::time::interval;
+use tokio_stream::StreamExt;
+
+// Verifies streams which have no start or end time.
+
+pub async fn read_live_stream(db: &mut impl Store) {
+    let name = "test:device".parse::<device::Name>().unwrap();
+
+    if let Ok(f) = db
+        .register_read_only_device("test", &name, None, None, None)
+        .await
+    {
+        // Test that priming the history with one value returns
+        // the entire sequence.
+
+        {
+            let data = vec![1, 2, 3];
+
+            f(device::Value::Int(data[0])).await;
+
+            let s = db
+                .monitor_device(name.clone(), None, None)
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in &data[1..] {
+                f(device::Value::Int(*ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(1)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(2)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(3)
+            );
+            assert!(s.try_next().await.is_err());
+        }
+
+        // Test that priming the history with two values only
+        // returns the latest and all remaining.
+
+        {
+            let data = vec![1, 2, 3, 4];
+
+            f(device::Value::Int(data[0])).await;
+            f(device::Value::Int(data[1])).await;
+
+            let s = db
+                .monitor_device(name.clone(), None, None)
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in &data[2..] {
+                f(device::Value::Int(*ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(2)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(3)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(4)
+            );
+            assert!(s.try_next().await.is_err());
+        }
+    } else {
+        panic!("error registering read-only device on empty database")
+    }
+}
+
+// Verifies streams which have a start time.
+
+pub async fn read_start_stream(db: &mut impl Store) {
+    let name = "test:device".parse::<device::Name>().unwrap();
+
+    if let Ok(f) = db
+        .register_read_only_device("test", &name, None, None, None)
+        .await
+    {
+        // Verify that monitoring device, starting now, picks up
+        // all future inserted data.
+
+        {
+            let data = vec![1, 2, 3];
+
+            let s = db
+                .monitor_device(
+                    name.clone(),
+                    Some(time::SystemTime::now().into()),
+                    None,
+                )
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            assert!(s.try_next().await.is_err());
+
+            for ii in data {
+                f(device::Value::Int(ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(1)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(2)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(3)
+            );
+            assert!(s.try_next().await.is_err());
+        }
+
+        // Verify that, if the latest point is before the starting
+        // timestamp, it doesn't get returned.
+
+        {
+            let data = vec![1, 2, 3];
+
+            f(device::Value::Int(data[0])).await;
+
+            let s = db
+                .monitor_device(
+                    name.clone(),
+                    Some(time::SystemTime::now().into()),
+                    None,
+                )
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in &data[1..] {
+                f(device::Value::Int(*ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(2)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(3)
+            );
+            assert!(s.try_next().await.is_err());
+        }
+    } else {
+        panic!("error registering read-only device on empty database")
+    }
+}
+
+// Verifies streams which have an end time.
+
+pub async fn read_end_stream(db: &mut impl Store) {
+    let name = "test:device".parse::<device::Name>().unwrap();
+
+    if let Ok(f) = db
+        .register_read_only_device("test", &name, None, None, None)
+        .await
+    {
+        // Verify that, if the latest point is before the starting
+        // timestamp, it doesn't get returned.
+
+        {
+            let data = vec![1, 2, 3];
+
+            f(device::Value::Int(data[0])).await;
+
+            let s = db
+                .monitor_device(
+                    name.clone(),
+                    None,
+                    Some(time::SystemTime::now().into()),
+                )
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in &data[1..] {
+                f(device::Value::Int(*ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(1)
+            );
+            assert_eq!(s.try_next().await.unwrap(), None);
+        }
+    } else {
+        panic!("error registering read-only device on empty database")
+    }
+}
+
+// Verifies streams which have a start and an end time.
+
+pub async fn read_start_end_stream(db: &mut impl Store) {
+    let name = "test:device".parse::<device::Name>().unwrap();
+
+    if let Ok(f) = db
+        .register_read_only_device("test", &name, None, None, None)
+        .await
+    {
+        // Verify that, if both times are before the data, nothing
+        // is returned.
+
+        {
+            let data = vec![1, 2, 3, 4, 5];
+
+            let mut interval = interval(time::Duration::from_millis(100));
+
+            let now = time::SystemTime::now();
+            let s = db
+                .monitor_device(
+                    name.clone(),
+                    Some(
+                        now.checked_sub(time::Duration::from_millis(500))
+                            .unwrap()
+                            .into(),
+                    ),
+                    Some(
+                        now.checked_sub(time::Duration::from_millis(250))
+                            .unwrap()
+                            .into(),
+                    ),
+                )
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in data {
+                interval.tick().await;
+                f(device::Value::Int(ii)).await;
+            }
+
+            assert_eq!(s.try_next().await.unwrap(), None);
+        }
+
+        // Verify that, if the latest point is before the starting
+        // timestamp, it doesn't get returned.
+
+        {
+            let data = vec![1, 2, 3, 4, 5];
+
+            f(device::Value::Int(data[0])).await;
+            let mut interval = interval(time::Duration::from_millis(100));
+
+            let now = time::SystemTime::now();
+            let s = db
+                .monitor_device(
+                    name.clone(),
+                    Some(now.into()),
+                    Some(
+                        now.checked_add(time::Duration::from_millis(250))
+                            .unwrap()
+                            .into(),
+                    ),
+                )
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in &data[1..] {
+                interval.tick().await;
+                f(device::Value::Int(*ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(2)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(3)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(4)
+            );
+            assert_eq!(s.try_next().await.unwrap(), None);
+        }
+
+        // Verify that, if the latest point is after the starting
+        // timestamp, it isn't part of the results.
+
+        {
+            let data = vec![1, 2, 3, 4, 5];
+
+            let mut interval = interval(time::Duration::from_millis(100));
+
+            let now = time::SystemTime::now();
+            let s = db
+                .monitor_device(
+                    name.clone(),
+                    Some(now.into()),
+                    Some(
+                        now.checked_add(time::Duration::from_millis(250))
+                            .unwrap()
+                            .into(),
+                    ),
+                )
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in data {
+                interval.tick().await;
+                f(device::Value::Int(ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(1)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(2)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(3)
+            );
+            assert_eq!(s.try_next().await.unwrap(), None);
+        }
+
+        // Verify that, if the latest point is after the starting
+        // timestamp, it isn't part of the results.
+
+        {
+            let data = vec![1, 2, 3, 4, 5];
+
+            let mut interval = interval(time::Duration::from_millis(100));
+
+            let now = time::SystemTime::now();
+            let s = db
+                .monitor_device(
+                    name.clone(),
+                    Some(
+                        now.checked_add(time::Duration::from_millis(150))
+                            .unwrap()
+                            .into(),
+                    ),
+                    Some(
+                        now.checked_add(time::Duration::from_millis(350))
+                            .unwrap()
+                            .into(),
+                    ),
+                )
+                .await
+                .unwrap()
+                .timeout(time::Duration::from_millis(100));
+
+            tokio::pin!(s);
+
+            for ii in data {
+                interval.tick().await;
+                f(device::Value::Int(ii)).await;
+            }
+
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(3)
+            );
+            assert_eq!(
+                s.try_next().await.unwrap().unwrap().value,
+                device::Value::Int(4)
+            );
+            assert_eq!(s.try_next().await.unwrap(), None);
+        }
+    } else {
+        panic!("error registering read-only device on empty database")
+    }
+}
@@ -26,6 +26,30 @@ pub struct Config {
     pub driver: Vec<Driver>,
     #[serde(default)]
     pub logic: Vec<Logic>,
+    #[serde(default)]
+    pub calendar: Vec<Calendar>,
+
+    // The file the configuration was read from, if any.
+    #[serde(skip)]
+    pub file: Option<String>,
+
+    // The logic block and trace file given to the `simulate`
+    // subcommand, if it was used.
+    #[serde(skip)]
+    pub simulate: Option<(String, String)>,
+
+    // The archive given to the `export` or `import` subcommand, if
+    // one was used.
+    #[serde(skip)]
+    pub archive: Option<Archive>,
+}
+
+// Selects whether the back-end is exported to, or imported from, an
+// archive file.
+
+pub enum Archive {
+    Export(String),
+    Import(String),
 }
 
 impl<'a> Config {
@@ -64,6 +88,10 @@ impl Default for Config {
             backend: Some(store::config::Config::new()),
             driver: vec![],
             logic: vec![],
+            calendar: vec![],
+            file: None,
+            simulate: None,
+            archive: None,
         }
     }
 }
@@ -73,10 +101,20 @@ pub struct Driver {
     pub name: String,
     pub prefix: device::Path,
     pub max_history: Option<usize>,
+    pub max_age: Option<u64>,
     pub cfg: Option<DriverConfig>,
 }
 
-#[derive(Deserialize)]
+impl Driver {
+    // Returns how long the readings of the driver's devices should
+    // be kept. The configuration specifies it in seconds.
+
+    pub fn get_max_age(&self) -> Option<std::time::Duration> {
+        self.max_age.map(std::time::Duration::from_secs)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct Logic {
     pub name: String,
     pub summary: Option<String>,
@@ -86,6 +124,26 @@ pub struct Logic {
     #[serde(default)]
     pub inputs: HashMap<String, device::Name>,
     pub outputs: HashMap<String, device::Name>,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub enable_device: bool,
+    #[serde(default)]
+    pub publish: Vec<String>,
+}
+
+// A calendar that logic expressions can use. Its dates come from the
+// `dates` list, which holds "YYYY-MM-DD" dates and
+// "YYYY-MM-DD..YYYY-MM-DD" ranges, and from the events in the
+// iCalendar `file`. If `holiday` is true, its dates are holidays.
+
+#[derive(Clone, Deserialize)]
+pub struct Calendar {
+    pub name: String,
+    #[serde(default)]
+    pub holiday: bool,
+    pub file: Option<String>,
+    #[serde(default)]
+    pub dates: Vec<String>,
 }
 
 fn from_cmdline(mut cfg: Config) -> (bool, Config) {
@@ -117,6 +175,42 @@ fn from_cmdline(mut cfg: Config) -> (bool, Config) {
                 .action(ArgAction::SetTrue)
                 .help("Displays the configuration and exits"),
         )
+        .subcommand(
+            Command::new("simulate")
+                .about("Replays a trace of readings through a logic block")
+                .arg(
+                    Arg::new("logic")
+                        .value_name("LOGIC")
+                        .required(true)
+                        .help("The name of the logic block"),
+                )
+                .arg(
+                    Arg::new("trace")
+                        .value_name("TRACE")
+                        .required(true)
+                        .help("A CSV file of TIME,DEVICE,VALUE readings"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Saves every device's history in an archive")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("The archive to create"),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Adds the devices and history in an archive")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("The archive to read"),
+                ),
+        )
         .get_matches();
 
     // The number of '-v' options determines the log level.
@@ -128,6 +222,31 @@ fn from_cmdline(mut cfg: Config) -> (bool, Config) {
         _ => cfg.log_level = String::from("trace"),
     };
 
+    // The `simulate` subcommand runs a logic block against a trace
+    // instead of starting the control system.
+
+    if let Some(sim) = matches.subcommand_matches("simulate") {
+        cfg.simulate = sim
+            .get_one::<String>("logic")
+            .cloned()
+            .zip(sim.get_one::<String>("trace").cloned());
+    }
+
+    // The `export` and `import` subcommands move the back-end's
+    // history to, or from, an archive.
+
+    match matches.subcommand() {
+        Some(("export", sub)) => {
+            cfg.archive =
+                sub.get_one::<String>("file").cloned().map(Archive::Export)
+        }
+        Some(("import", sub)) => {
+            cfg.archive =
+                sub.get_one::<String>("file").cloned().map(Archive::Import)
+        }
+        _ => (),
+    }
+
     // Return the config built from the command line and a flag
     // indicating the user wants the final configuration displayed.
 
@@ -153,6 +272,20 @@ fn parse_config(contents: &str) -> Result<Config> {
                     "'longitude' is out of range".into(),
                 ));
             }
+
+            // Logic nodes are identified by their names, so they
+            // have to be unique.
+
+            let mut names = std::collections::HashSet::new();
+
+            if let Some(logic) =
+                cfg.logic.iter().find(|l| !names.insert(l.name.as_str()))
+            {
+                return Err(Error::ConfigError(format!(
+                    "logic name '{}' is used more than once",
+                    &logic.name
+                )));
+            }
             Ok(cfg)
         })
 }
@@ -163,12 +296,24 @@ async fn from_file(path: &str) -> Option<Result<Config>> {
     if let Ok(contents) = fs::read(path).await {
         let contents = String::from_utf8_lossy(&contents);
 
-        Some(parse_config(&contents))
+        Some(parse_config(&contents).map(|mut cfg| {
+            cfg.file = Some(path.into());
+            cfg
+        }))
     } else {
         None
     }
 }
 
+// Reads the configuration file again. This is used to reload parts
+// of the configuration while `drmemd` is running.
+
+pub async fn read(path: &str) -> Result<Config> {
+    from_file(path).await.unwrap_or_else(|| {
+        Err(Error::ConfigError(format!("couldn't read '{}'", path)))
+    })
+}
+
 async fn find_cfg() -> Result<Config> {
     const CFG_FILE: &str = "drmem.toml";
 
@@ -212,7 +357,16 @@ fn dump_config(cfg: &Config) {
 
     #[cfg(feature = "simple-backend")]
     {
-        println!("Using SIMPLE backend -- no configuration for it.\n");
+        println!("Using SIMPLE backend:");
+        if let Some(file) = &cfg.get_backend().snapshot {
+            println!("    snapshot: {}", file);
+            println!(
+                "    snapshot interval: {:?}\n",
+                cfg.get_backend().get_snapshot_interval()
+            );
+        } else {
+            println!("    snapshot: none\n");
+        }
     }
 
     #[cfg(feature = "redis-backend")]
@@ -222,6 +376,12 @@ fn dump_config(cfg: &Config) {
         println!("    db #: {}\n", cfg.get_backend().get_dbn());
     }
 
+    #[cfg(feature = "sqlite-backend")]
+    {
+        println!("Using SQLITE for storage:");
+        println!("    file: {}\n", cfg.get_backend().get_file());
+    }
+
     #[cfg(feature = "graphql")]
     {
         println!("Using GraphQL:");
@@ -242,6 +402,19 @@ fn dump_config(cfg: &Config) {
     } else {
         println!("    No drivers specified.");
     }
+
+    if !cfg.calendar.is_empty() {
+        println!("\nCalendars:");
+        for ii in &cfg.calendar {
+            println!(
+                "    name: {}{}\n    file: {}\n    dates: {:?}\n",
+                &ii.name,
+                if ii.holiday { " (holidays)" } else { "" },
+                ii.file.as_deref().unwrap_or("none"),
+                &ii.dates
+            )
+        }
+    }
 }
 
 #[tracing::instrument(name = "loading config")]
@@ -551,6 +724,22 @@ max_history = false
             "TOML parser accepted [[driver]] section with bad max_history"
         );
 
+        assert!(
+            toml::from_str::<Config>(
+                r#"
+latitude = -45.0
+longitude = 45.0
+
+[[driver]]
+name = "none"
+prefix = "null"
+max_age = -1
+"#,
+            )
+            .is_err(),
+            "TOML parser accepted [[driver]] section with bad max_age"
+        );
+
         match toml::from_str::<Config>(
             r#"
 latitude = -45.0
@@ -570,6 +759,7 @@ prefix = "null"
                     "null".parse::<device::Path>().unwrap()
                 );
                 assert_eq!(cfg.driver[0].max_history, None);
+                assert_eq!(cfg.driver[0].get_max_age(), None);
             }
             Err(e) => panic!("TOML parse error: {}", e),
         }
@@ -583,6 +773,7 @@ longitude = 45.0
 name = "none"
 prefix = "null"
 max_history = 10000
+max_age = 86400
 "#,
         ) {
             Ok(cfg) => {
@@ -594,6 +785,10 @@ max_history = 10000
                     "null".parse::<device::Path>().unwrap()
                 );
                 assert_eq!(cfg.driver[0].max_history, Some(10000));
+                assert_eq!(
+                    cfg.driver[0].get_max_age(),
+                    Some(std::time::Duration::from_secs(86400))
+                );
             }
             Err(e) => panic!("TOML parse error: {}", e),
         }
@@ -752,6 +947,58 @@ outputs = {}
                 assert!(cfg.logic[0].exprs.is_empty());
                 assert!(cfg.logic[0].inputs.is_empty());
                 assert!(cfg.logic[0].outputs.is_empty());
+                assert!(cfg.logic[0].timezone.is_none());
+                assert!(!cfg.logic[0].enable_device);
+                assert!(cfg.logic[0].publish.is_empty());
+            }
+            Err(e) => panic!("TOML parse error: {}", e),
+        }
+
+        // Logic names have to be unique.
+
+        assert!(
+            parse_config(
+                r#"
+latitude = -45.0
+longitude = 45.0
+
+[[logic]]
+name = "none"
+exprs = []
+outputs = {}
+
+[[logic]]
+name = "none"
+exprs = []
+outputs = {}
+"#
+            )
+            .is_err(),
+            "accepted [[logic]] sections with the same name"
+        );
+
+        match toml::from_str::<Config>(
+            r#"
+latitude = -45.0
+longitude = 45.0
+
+[[logic]]
+name = "none"
+exprs = []
+outputs = {}
+timezone = "Europe/Berlin"
+enable_device = true
+publish = ["dewpoint"]
+"#,
+        ) {
+            Ok(cfg) => {
+                assert_eq!(cfg.logic.len(), 1);
+                assert_eq!(
+                    cfg.logic[0].timezone.as_deref(),
+                    Some("Europe/Berlin")
+                );
+                assert!(cfg.logic[0].enable_device);
+                assert_eq!(cfg.logic[0].publish, vec!["dewpoint"]);
             }
             Err(e) => panic!("TOML parse error: {}", e),
         }
@@ -808,9 +1055,141 @@ outputs = {}
         }
     }
 
+    #[test]
+    fn test_calendar_section() {
+        // A calendar needs a name.
+
+        assert!(
+            toml::from_str::<Config>(
+                r#"
+latitude = -45.0
+longitude = 45.0
+
+[[calendar]]
+dates = ["2024-12-25"]
+"#
+            )
+            .is_err(),
+            "TOML parser accepted [[calendar]] section without a name"
+        );
+
+        match toml::from_str::<Config>(
+            r#"
+latitude = -45.0
+longitude = 45.0
+
+[[calendar]]
+name = "holidays"
+holiday = true
+file = "/etc/holidays.ics"
+
+[[calendar]]
+name = "vacation"
+dates = ["2024-07-01..2024-07-14", "2024-12-27"]
+"#,
+        ) {
+            Ok(cfg) => {
+                assert_eq!(cfg.calendar.len(), 2);
+                assert_eq!(cfg.calendar[0].name, "holidays");
+                assert!(cfg.calendar[0].holiday);
+                assert_eq!(
+                    cfg.calendar[0].file.as_deref(),
+                    Some("/etc/holidays.ics")
+                );
+                assert!(cfg.calendar[0].dates.is_empty());
+                assert_eq!(cfg.calendar[1].name, "vacation");
+                assert!(!cfg.calendar[1].holiday);
+                assert_eq!(cfg.calendar[1].file, None);
+                assert_eq!(
+                    cfg.calendar[1].dates,
+                    &["2024-07-01..2024-07-14", "2024-12-27"]
+                );
+            }
+            Err(e) => panic!("TOML parse error: {}", e),
+        }
+    }
+
     #[cfg(feature = "simple-backend")]
     #[test]
-    fn test_simple_config() {}
+    fn test_simple_config() {
+        // Verify a missing [backend] results in no snapshot.
+
+        match toml::from_str::<Config>(
+            r#"
+latitude = -45.0
+longitude = 45.0
+"#,
+        ) {
+            Ok(cfg) => {
+                assert_eq!(cfg.get_backend().snapshot, None);
+                assert_eq!(
+                    cfg.get_backend().get_snapshot_interval(),
+                    std::time::Duration::from_secs(60)
+                );
+            }
+            Err(e) => panic!("TOML parse error: {}", e),
+        }
+
+        match toml::from_str::<Config>(
+            r#"
+latitude = -45.0
+longitude = 45.0
+
+[backend]
+snapshot = "/var/db/drmem.snapshot"
+snapshot_interval = 300
+"#,
+        ) {
+            Ok(cfg) => {
+                assert_eq!(
+                    cfg.get_backend().snapshot.as_deref(),
+                    Some("/var/db/drmem.snapshot")
+                );
+                assert_eq!(
+                    cfg.get_backend().get_snapshot_interval(),
+                    std::time::Duration::from_secs(300)
+                );
+            }
+            Err(e) => panic!("TOML parse error: {}", e),
+        }
+    }
+
+    #[cfg(feature = "sqlite-backend")]
+    #[test]
+    fn test_sqlite_config() {
+        // Verify a missing [backend] results in a properly defined
+        // default.
+
+        match toml::from_str::<Config>(
+            r#"
+latitude = -45.0
+longitude = 45.0
+"#,
+        ) {
+            Ok(cfg) => assert_eq!(
+                cfg.get_backend().get_file(),
+                Config::default().get_backend().get_file()
+            ),
+            Err(e) => panic!("TOML parse error: {}", e),
+        }
+
+        // Verify the file can be specified.
+
+        match toml::from_str::<Config>(
+            r#"
+latitude = -45.0
+longitude = 45.0
+
+[backend]
+file = "/var/db/drmem.db"
+"#,
+        ) {
+            Ok(cfg) => {
+                assert_eq!(cfg.get_backend().get_file(), "/var/db/drmem.db")
+            }
+            Err(e) => panic!("TOML parse error: {}", e),
+        }
+    }
 
     #[cfg(feature = "redis-backend")]
     #[test]
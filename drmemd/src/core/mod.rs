@@ -30,6 +30,7 @@ impl State {
                 ref dev_name,
                 ref dev_units,
                 max_history,
+                max_age,
                 rpy_chan,
             } => {
                 let result = self
@@ -39,6 +40,7 @@ impl State {
                         dev_name,
                         dev_units.as_ref(),
                         max_history,
+                        max_age,
                     )
                     .await
                     .map_err(|_| Error::DeviceDefined(format!("{}", dev_name)));
@@ -53,6 +55,7 @@ impl State {
                 ref dev_name,
                 ref dev_units,
                 max_history,
+                max_age,
                 rpy_chan,
             } => {
                 let result = self
@@ -62,6 +65,7 @@ impl State {
                         dev_name,
                         dev_units.as_ref(),
                         max_history,
+                        max_age,
                     )
                     .await
                     .map_err(|_| Error::DeviceDefined(format!("{}", dev_name)));
@@ -117,10 +121,15 @@ impl State {
                 rpy_chan,
                 start,
                 end,
+                aggregate,
             } => {
-                let fut = self.backend.monitor_device(name, start, end);
+                let result = if let Some(agg) = aggregate {
+                    self.backend.monitor_aggregate(name, start, end, agg).await
+                } else {
+                    self.backend.monitor_device(name, start, end).await
+                };
 
-                if rpy_chan.send(fut.await).is_err() {
+                if rpy_chan.send(result).is_err() {
                     warn!("client exited before a reply could be sent")
                 }
             }
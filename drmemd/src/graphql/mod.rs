@@ -3,7 +3,8 @@ use drmem_api::{client, device, driver, Error};
 use futures::Future;
 use juniper::{
     executor::FieldError, graphql_object, graphql_subscription, graphql_value,
-    FieldResult, GraphQLInputObject, GraphQLObject, RootNode, Value,
+    FieldResult, GraphQLEnum, GraphQLInputObject, GraphQLObject, RootNode,
+    Value,
 };
 use juniper_graphql_ws::ConnectionConfig;
 use juniper_warp::subscriptions::serve_graphql_ws;
@@ -23,7 +24,11 @@ impl reject::Reject for NoAuthorization {}
 // The Context parameter for Queries.
 
 #[derive(Clone)]
-struct ConfigDb(crate::driver::DriverDb, client::RequestChan);
+struct ConfigDb(
+    crate::driver::DriverDb,
+    client::RequestChan,
+    crate::logic::status::Db,
+);
 
 impl juniper::Context for ConfigDb {}
 
@@ -161,6 +166,151 @@ impl DeviceInfo {
     }
 }
 
+// Holds a value computed, or used, by a logic node. Unlike a
+// `Reading`, it doesn't have a timestamp.
+
+#[derive(GraphQLObject)]
+#[graphql(description = "Represents a value in a logic node. Only the \
+			 field matching the value's type is set.")]
+struct LogicValue {
+    #[graphql(description = "Placeholder for integer values.")]
+    int_value: Option<i32>,
+    #[graphql(description = "Placeholder for float values.")]
+    float_value: Option<f64>,
+    #[graphql(description = "Placeholder for boolean values.")]
+    bool_value: Option<bool>,
+    #[graphql(description = "Placeholder for string values.")]
+    string_value: Option<Arc<str>>,
+    #[graphql(
+        description = "Placeholder for color values. Values are a 3-element \
+		       array holding red, green, and blue values or a \
+		       4-element array holding red, green, blue, and alpha \
+		       values. Each value ranges from 0 - 255."
+    )]
+    color_value: Option<Vec<i32>>,
+}
+
+// Stores a value in the placeholder that matches its type. `Reading`
+// uses this, too.
+
+impl From<&device::Value> for LogicValue {
+    fn from(value: &device::Value) -> Self {
+        let mut result = LogicValue {
+            int_value: None,
+            float_value: None,
+            bool_value: None,
+            string_value: None,
+            color_value: None,
+        };
+
+        match value {
+            device::Value::Bool(v) => result.bool_value = Some(*v),
+            device::Value::Int(v) => result.int_value = Some(*v),
+            device::Value::Flt(v) => result.float_value = Some(*v),
+            device::Value::Str(v) => result.string_value = Some(v.clone()),
+            device::Value::Color(v) if v.alpha == 255 => {
+                result.color_value =
+                    Some(vec![v.red as i32, v.green as i32, v.blue as i32])
+            }
+            device::Value::Color(v) => {
+                result.color_value = Some(vec![
+                    v.red as i32,
+                    v.green as i32,
+                    v.blue as i32,
+                    v.alpha as i32,
+                ])
+            }
+        }
+        result
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A variable of a logic node.")]
+struct LogicVariable {
+    #[graphql(description = "The name used in the node's expressions.")]
+    name: String,
+    #[graphql(description = "The device bound to the variable. Variables \
+			     defined in the `defs` section aren't bound \
+			     to a device so this field is `null`.")]
+    device: Option<String>,
+    #[graphql(description = "The current value of the variable. It is \
+			     `null` until its device reports a value or \
+			     its definition can be computed.")]
+    value: Option<LogicValue>,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "An output of a logic node and the expression \
+			 which computes its value.")]
+struct LogicOutput {
+    #[graphql(description = "The name used in the node's expressions.")]
+    name: String,
+    #[graphql(description = "The device controlled by the output.")]
+    device: String,
+    #[graphql(description = "The optimized form of the expression. Input \
+			     variables are shown as `inp[n]` and outputs \
+			     as `out[n]`.")]
+    program: String,
+    #[graphql(description = "The last value that the device accepted.")]
+    last_value: Option<LogicValue>,
+    #[graphql(description = "The number of times the expression failed \
+			     to compute a value after every input device \
+			     reported a value. The log contains the \
+			     reason for each failure.")]
+    errors: i32,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "Information about a running logic node. This \
+			 information is a snapshot from when it was \
+			 obtained.")]
+struct LogicInfo {
+    #[graphql(description = "The name of the logic node.")]
+    name: String,
+    #[graphql(description = "The summary from the node's configuration.")]
+    summary: Option<String>,
+    #[graphql(description = "The input variables and definitions of the \
+			     node.")]
+    inputs: Vec<LogicVariable>,
+    #[graphql(description = "The optimized programs which compute the \
+			     definitions, `let` variables and shared \
+			     subexpressions of the node.")]
+    defs: Vec<String>,
+    #[graphql(description = "The outputs of the node.")]
+    outputs: Vec<LogicOutput>,
+}
+
+impl From<crate::logic::status::Status> for LogicInfo {
+    fn from(status: crate::logic::status::Status) -> Self {
+        LogicInfo {
+            name: status.name,
+            summary: status.summary,
+            inputs: status
+                .variables
+                .into_iter()
+                .map(|v| LogicVariable {
+                    name: v.name,
+                    device: v.device.map(|d| d.to_string()),
+                    value: v.value.as_ref().map(LogicValue::from),
+                })
+                .collect(),
+            defs: status.defs,
+            outputs: status
+                .exprs
+                .into_iter()
+                .map(|e| LogicOutput {
+                    name: e.output,
+                    device: e.device.to_string(),
+                    program: e.program,
+                    last_value: e.last.as_ref().map(LogicValue::from),
+                    errors: e.errors.min(i32::MAX as u64) as i32,
+                })
+                .collect(),
+        }
+    }
+}
+
 // This defines the top-level Query API.
 
 struct Config;
@@ -305,6 +455,33 @@ impl Config {
                 FieldError::new("error looking-up device", Value::null())
             })
     }
+
+    #[graphql(description = "Returns the state of the running logic nodes. \
+			     If `name` isn't provided, an array with every \
+			     node is returned. If `name` is specified and a \
+			     node with that name is running, a single \
+			     element array is returned. Otherwise an error \
+			     is returned.")]
+    fn logic_info(
+        #[graphql(context)] db: &ConfigDb,
+        #[graphql(description = "An optional argument which, when provided, \
+				 only returns the logic node with the \
+				 matching name.")]
+        name: Option<String>,
+    ) -> result::Result<Vec<LogicInfo>, FieldError> {
+        if let Some(name) = name {
+            if let Some(status) = db.2.get(&name) {
+                Ok(vec![status.into()])
+            } else {
+                Err(FieldError::new(
+                    "logic node not found",
+                    graphql_value!({ "missing_node": name }),
+                ))
+            }
+        } else {
+            Ok(db.2.get_all().into_iter().map(LogicInfo::from).collect())
+        }
+    }
 }
 
 // The `Control` mutation is used to group queries that attempt to
@@ -569,6 +746,55 @@ struct DateRange {
     end: Option<DateTime<Utc>>,
 }
 
+#[derive(GraphQLEnum, Clone, Copy)]
+#[graphql(description = "The function used to summarize the readings in \
+			 an interval.")]
+enum AggregateFunction {
+    #[graphql(description = "The smallest value in the interval.")]
+    Min,
+    #[graphql(description = "The largest value in the interval.")]
+    Max,
+    #[graphql(description = "The average of the values in the interval.")]
+    Mean,
+    #[graphql(description = "The last value in the interval.")]
+    Last,
+    #[graphql(description = "The number of readings in the interval.")]
+    Count,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Downsamples a device's readings. Time is divided \
+			 into intervals and each interval which holds \
+			 readings is reported as a single reading, \
+			 timestamped at the start of the interval.")]
+struct Aggregation {
+    #[graphql(description = "The width of each interval, in seconds.")]
+    interval: f64,
+    #[graphql(description = "How the readings in an interval are \
+			     combined.")]
+    function: AggregateFunction,
+}
+
+impl Aggregation {
+    // Converts the GraphQL parameters into the form used by the
+    // back-ends.
+
+    fn to_aggregate(&self) -> Option<device::Aggregate> {
+        let interval = Duration::try_from_secs_f64(self.interval)
+            .ok()
+            .filter(|v| !v.is_zero())?;
+        let function = match self.function {
+            AggregateFunction::Min => device::AggregateFn::Min,
+            AggregateFunction::Max => device::AggregateFn::Max,
+            AggregateFunction::Mean => device::AggregateFn::Mean,
+            AggregateFunction::Last => device::AggregateFn::Last,
+            AggregateFunction::Count => device::AggregateFn::Count,
+        };
+
+        Some(device::Aggregate { interval, function })
+    }
+}
+
 #[derive(GraphQLObject)]
 #[graphql(
     description = "Represents a value of a device at an instant of time."
@@ -587,79 +813,44 @@ struct Reading {
     #[graphql(
         description = "Placeholder for color values. Values are a 3-element \
 		       array holding red, green, and blue values or a \
-		       4-element array holding red, gree, blue, and alpha \
+		       4-element array holding red, green, blue, and alpha \
 		       values. Each value ranges from 0 - 255."
     )]
     color_value: Option<Vec<i32>>,
 }
 
+impl Reading {
+    // Builds the reading of a device. Like a `LogicValue`, the value
+    // is stored in the placeholder that matches its type.
+
+    fn new(
+        device: String,
+        ts: std::time::SystemTime,
+        value: &device::Value,
+    ) -> Self {
+        let LogicValue {
+            int_value,
+            float_value,
+            bool_value,
+            string_value,
+            color_value,
+        } = LogicValue::from(value);
+
+        Reading {
+            device,
+            stamp: DateTime::<Utc>::from(ts),
+            int_value,
+            float_value,
+            bool_value,
+            string_value,
+            color_value,
+        }
+    }
+}
+
 impl From<&device::Reading> for Reading {
     fn from(value: &device::Reading) -> Self {
-        match &value.value {
-            device::Value::Bool(v) => Reading {
-                device: "".into(),
-                stamp: DateTime::<Utc>::from(value.ts),
-                int_value: None,
-                float_value: None,
-                bool_value: Some(*v),
-                string_value: None,
-                color_value: None,
-            },
-            device::Value::Int(v) => Reading {
-                device: "".into(),
-                stamp: DateTime::<Utc>::from(value.ts),
-                int_value: Some(*v),
-                float_value: None,
-                bool_value: None,
-                string_value: None,
-                color_value: None,
-            },
-            device::Value::Flt(v) => Reading {
-                device: "".into(),
-                stamp: DateTime::<Utc>::from(value.ts),
-                int_value: None,
-                float_value: Some(*v),
-                bool_value: None,
-                string_value: None,
-                color_value: None,
-            },
-            device::Value::Str(v) => Reading {
-                device: "".into(),
-                stamp: DateTime::<Utc>::from(value.ts),
-                int_value: None,
-                float_value: None,
-                bool_value: None,
-                string_value: Some(v.clone()),
-                color_value: None,
-            },
-            device::Value::Color(v) if v.alpha == 255 => Reading {
-                device: "".into(),
-                stamp: DateTime::<Utc>::from(value.ts),
-                int_value: None,
-                float_value: None,
-                bool_value: None,
-                string_value: None,
-                color_value: Some(vec![
-                    v.red as i32,
-                    v.green as i32,
-                    v.blue as i32,
-                ]),
-            },
-            device::Value::Color(v) => Reading {
-                device: "".into(),
-                stamp: DateTime::<Utc>::from(value.ts),
-                int_value: None,
-                float_value: None,
-                bool_value: None,
-                string_value: None,
-                color_value: Some(vec![
-                    v.red as i32,
-                    v.green as i32,
-                    v.blue as i32,
-                    v.alpha as i32,
-                ]),
-            },
-        }
+        Reading::new("".into(), value.ts, &value.value)
     }
 }
 
@@ -667,38 +858,7 @@ struct Subscription;
 
 impl Subscription {
     fn xlat(name: String) -> impl Fn(device::Reading) -> FieldResult<Reading> {
-        move |e: device::Reading| {
-            let mut reading = Reading {
-                device: name.clone(),
-                stamp: DateTime::<Utc>::from(e.ts),
-                bool_value: None,
-                int_value: None,
-                float_value: None,
-                string_value: None,
-                color_value: None,
-            };
-
-            match e.value {
-                device::Value::Bool(v) => reading.bool_value = Some(v),
-                device::Value::Int(v) => reading.int_value = Some(v),
-                device::Value::Flt(v) => reading.float_value = Some(v),
-                device::Value::Str(v) => reading.string_value = Some(v.clone()),
-                device::Value::Color(v) if v.alpha == 255 => {
-                    reading.color_value =
-                        Some(vec![v.red as i32, v.green as i32, v.blue as i32])
-                }
-                device::Value::Color(v) => {
-                    reading.color_value = Some(vec![
-                        v.red as i32,
-                        v.green as i32,
-                        v.blue as i32,
-                        v.alpha as i32,
-                    ])
-                }
-            }
-
-            Ok(reading)
-        }
+        move |e: device::Reading| Ok(Reading::new(name.clone(), e.ts, &e.value))
     }
 }
 
@@ -708,11 +868,13 @@ impl Subscription {
 			     a device. The GraphQL request must provide the \
 			     name of a device. This method returns a stream \
 			     which generates a reply each time a device's \
-			     value changes.")]
+			     value changes. If `aggregate` is provided, the \
+			     readings are downsampled by the server.")]
     async fn monitor_device(
         #[graphql(context)] db: &ConfigDb,
         device: String,
         range: Option<DateRange>,
+        aggregate: Option<Aggregation>,
     ) -> device::DataStream<FieldResult<Reading>> {
         use tokio_stream::StreamExt;
 
@@ -722,8 +884,24 @@ impl Subscription {
             let start = range.as_ref().and_then(|v| v.start);
             let end = range.as_ref().and_then(|v| v.end);
 
-            if let Ok(rx) = db.1.monitor_device(name.clone(), start, end).await
+            let result = match aggregate.as_ref().map(Aggregation::to_aggregate)
             {
+                None => db.1.monitor_device(name.clone(), start, end).await,
+                Some(Some(agg)) => {
+                    db.1.monitor_aggregate(name.clone(), start, end, agg).await
+                }
+                Some(None) => {
+                    let stream = tokio_stream::once(Err(FieldError::new(
+                        "invalid aggregation interval",
+                        Value::null(),
+                    )));
+
+                    return Box::pin(stream)
+                        as device::DataStream<FieldResult<Reading>>;
+                }
+            };
+
+            if let Ok(rx) = result {
                 let stream = StreamExt::map(rx, Subscription::xlat(device));
 
                 Box::pin(stream) as device::DataStream<FieldResult<Reading>>
@@ -774,8 +952,9 @@ mod paths {
 fn build_base_site(
     db: crate::driver::DriverDb,
     cchan: client::RequestChan,
+    logic: crate::logic::status::Db,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    let context = ConfigDb(db, cchan);
+    let context = ConfigDb(db, cchan, logic);
     let ctxt = context.clone();
 
     // Create filter that handles GraphQL queries and mutations.
@@ -854,15 +1033,17 @@ fn build_base_site(
 fn build_site(
     db: crate::driver::DriverDb,
     cchan: client::RequestChan,
+    logic: crate::logic::status::Db,
 ) -> impl Filter<Extract = (impl Reply,), Error = std::convert::Infallible> + Clone
 {
-    build_base_site(db, cchan).recover(handle_rejection)
+    build_base_site(db, cchan, logic).recover(handle_rejection)
 }
 
 fn build_secure_site(
     cfg: &config::Security,
     db: crate::driver::DriverDb,
     cchan: client::RequestChan,
+    logic: crate::logic::status::Db,
 ) -> impl Filter<Extract = (impl Reply,), Error = std::convert::Infallible> + Clone
 {
     // Clone the table of clients that are allowed in to the system.
@@ -889,7 +1070,7 @@ fn build_secure_site(
     warp::header::<String>("X-DrMem-Client-Id")
         .and_then(check_client)
         .untuple_one()
-        .and(build_base_site(db, cchan))
+        .and(build_base_site(db, cchan, logic))
         .recover(handle_rejection)
 }
 
@@ -927,17 +1108,18 @@ fn build_server(
     cfg: &config::Config,
     db: crate::driver::DriverDb,
     cchan: client::RequestChan,
+    logic: crate::logic::status::Db,
 ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
     if let Some(security) = &cfg.security {
         Box::pin(
-            warp::serve(build_secure_site(security, db, cchan))
+            warp::serve(build_secure_site(security, db, cchan, logic))
                 .tls()
                 .key_path(security.key_file.clone())
                 .cert_path(security.cert_file.clone())
                 .bind(cfg.addr),
         ) as Pin<Box<dyn Future<Output = ()> + Send>>
     } else {
-        Box::pin(warp::serve(build_site(db, cchan)).bind(cfg.addr))
+        Box::pin(warp::serve(build_site(db, cchan, logic)).bind(cfg.addr))
             as Pin<Box<dyn Future<Output = ()> + Send>>
     }
 }
@@ -964,6 +1146,7 @@ pub fn server(
     cfg: &config::Config,
     db: crate::driver::DriverDb,
     cchan: client::RequestChan,
+    logic: crate::logic::status::Db,
 ) -> impl Future<Output = ()> {
     // Create the background mDNS task.
 
@@ -971,7 +1154,7 @@ pub fn server(
 
     // Create the http task.
 
-    let http_task = build_server(cfg, db, cchan);
+    let http_task = build_server(cfg, db, cchan, logic);
 
     // Get the boot-time and store it in the mDNS payload.
 
@@ -1058,12 +1241,13 @@ mod test {
     #[tokio::test]
     async fn test_base_site() {
         use super::build_site;
-        use crate::driver::DriverDb;
+        use crate::{driver::DriverDb, logic::status::Db};
         use drmem_api::client::RequestChan;
         use tokio::sync::mpsc;
 
         let (tx, _) = mpsc::channel(100);
-        let filter = build_site(DriverDb::create(), RequestChan::new(tx));
+        let filter =
+            build_site(DriverDb::create(), RequestChan::new(tx), Db::default());
 
         #[cfg(not(feature = "graphiql"))]
         {
@@ -1124,7 +1308,11 @@ mod test {
 
         {
             let (tx, _) = mpsc::channel(100);
-            let filter = build_site(DriverDb::create(), RequestChan::new(tx));
+            let filter = build_site(
+                DriverDb::create(),
+                RequestChan::new(tx),
+                Db::default(),
+            );
             let client =
                 warp::test::ws().path("/drmem/s").handshake(filter).await;
 
@@ -1135,7 +1323,7 @@ mod test {
     #[tokio::test]
     async fn test_site_security() {
         use super::{build_secure_site, config::Security};
-        use crate::driver::DriverDb;
+        use crate::{driver::DriverDb, logic::status::Db};
         use drmem_api::client::RequestChan;
         use std::{path::Path, sync::Arc};
         use tokio::sync::mpsc;
@@ -1149,8 +1337,12 @@ mod test {
             cert_file: Path::new("").into(),
             key_file: Path::new("").into(),
         };
-        let filter =
-            build_secure_site(&cfg, DriverDb::create(), RequestChan::new(tx));
+        let filter = build_secure_site(
+            &cfg,
+            DriverDb::create(),
+            RequestChan::new(tx),
+            Db::default(),
+        );
 
         // Test a client that didn't define the Client ID
         // header. Should generate a FORBIDDEN status.
@@ -1249,6 +1441,7 @@ mod test {
                 &cfg,
                 DriverDb::create(),
                 RequestChan::new(tx),
+                Db::default(),
             );
             let client =
                 warp::test::ws().path("/drmem/s").handshake(filter).await;
@@ -1262,6 +1455,7 @@ mod test {
                 &cfg,
                 DriverDb::create(),
                 RequestChan::new(tx),
+                Db::default(),
             );
             let client = warp::test::ws()
                 .header("X-DrMem-Client-Id", "77:66:55:44:33:22:11:00")
@@ -1278,6 +1472,7 @@ mod test {
                 &cfg,
                 DriverDb::create(),
                 RequestChan::new(tx),
+                Db::default(),
             );
             let client = warp::test::ws()
                 .header("X-DrMem-Client-Id", "00:11:22:33:44:55:66:77")
@@ -0,0 +1,644 @@
+// Calendars let logic expressions check whether the current day is a
+// holiday or part of an event. They're loaded, when `drmemd` starts,
+// from the `[[calendar]]` sections of the configuration. Each
+// calendar can list dates explicitly and can load the events of an
+// iCalendar (.ics) file.
+//
+// Only the dates of the events are used. The iCalendar support
+// covers what holiday and vacation calendars typically use: all-day
+// and timed events, multi-day events, and events that repeat yearly
+// on the same date or on the nth weekday of a month (e.g. the fourth
+// Thursday of November.) Events with other recurrence rules are
+// skipped, with a warning, so the rest of the calendar can be used.
+
+use crate::config;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use drmem_api::{Error, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+// The nth weekday of a month, as used by holidays like Thanksgiving
+// (the 4th Thursday of November.) A negative `nth` counts from the
+// end of the month, so -1 is the last one.
+
+#[derive(Debug, Clone, PartialEq)]
+struct NthWeekday {
+    month: u32,
+    nth: i8,
+    day: Weekday,
+}
+
+impl NthWeekday {
+    fn in_year(&self, year: i32) -> Option<NaiveDate> {
+        if self.nth > 0 {
+            NaiveDate::from_weekday_of_month_opt(
+                year,
+                self.month,
+                self.day,
+                self.nth as u8,
+            )
+        } else {
+            // Find the last day of the month and back up to the
+            // weekday.
+
+            let last = if self.month == 12 {
+                NaiveDate::from_ymd_opt(year, 12, 31)?
+            } else {
+                NaiveDate::from_ymd_opt(year, self.month + 1, 1)?.pred_opt()?
+            };
+            let back = (7 + last.weekday().num_days_from_monday()
+                - self.day.num_days_from_monday())
+                % 7
+                + 7 * (u32::from(self.nth.unsigned_abs()) - 1);
+
+            last.checked_sub_days(Days::new(back.into()))
+                .filter(|d| d.month() == self.month)
+        }
+    }
+}
+
+// A span of days, including both ends. If `until` is `Some`, the
+// span repeats every year through the year it holds. A span that
+// repeats starts on the same date each year or, if `weekday` is
+// `Some`, on the weekday it describes.
+
+#[derive(Debug, Clone, PartialEq)]
+struct Span {
+    first: NaiveDate,
+    last: NaiveDate,
+    until: Option<i32>,
+    weekday: Option<NthWeekday>,
+}
+
+impl Span {
+    // Returns the first day of the occurrence that starts in `year`.
+
+    fn start_in(&self, year: i32) -> Option<NaiveDate> {
+        match &self.weekday {
+            None => self.first.with_year(year),
+            Some(wd) => wd.in_year(year),
+        }
+    }
+
+    fn contains(&self, date: NaiveDate) -> bool {
+        match self.until {
+            None => self.first <= date && date <= self.last,
+
+            // An occurrence that includes `date` started in the same
+            // year or, if it spans New Year's Day, the year before.
+            // Occurrences of spans starting on Feb 29th only happen
+            // in leap years.
+            Some(until) => {
+                let len = self.last - self.first;
+
+                [date.year(), date.year() - 1]
+                    .into_iter()
+                    .filter(|yr| *yr >= self.first.year() && *yr <= until)
+                    .filter_map(|yr| self.start_in(yr))
+                    .any(|first| first <= date && date <= first + len)
+            }
+        }
+    }
+}
+
+// The set of days on which a holiday or event occurs. Expressions
+// that use a calendar hold a reference to one of these.
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Dates(Vec<Span>);
+
+impl Dates {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.0.iter().any(|s| s.contains(date))
+    }
+}
+
+// Holds the dates of the holidays and of each named event. An event
+// can be looked up by the name of its calendar, which includes all
+// the calendar's dates, or by the summary of an iCalendar event.
+
+#[derive(Default)]
+pub struct Calendar {
+    holidays: Arc<Dates>,
+    events: HashMap<String, Arc<Dates>>,
+}
+
+pub type Info = Arc<Calendar>;
+
+impl Calendar {
+    pub fn holidays(&self) -> Arc<Dates> {
+        self.holidays.clone()
+    }
+
+    pub fn event(&self, name: &str) -> Option<Arc<Dates>> {
+        self.events.get(name).cloned()
+    }
+}
+
+// Parses an entry of a calendar's `dates` list. It's either a date,
+// "YYYY-MM-DD", or an inclusive range of dates,
+// "YYYY-MM-DD..YYYY-MM-DD".
+
+fn parse_date_entry(s: &str) -> Option<Span> {
+    let parse = |v: &str| NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d").ok();
+
+    let (first, last) = match s.split_once("..") {
+        Some((a, b)) => (parse(a)?, parse(b)?),
+        None => (parse(s)?, parse(s)?),
+    };
+
+    if first <= last {
+        Some(Span {
+            first,
+            last,
+            until: None,
+            weekday: None,
+        })
+    } else {
+        None
+    }
+}
+
+// Parses the value of an iCalendar DATE or DATE-TIME property. The
+// returned flag is `true` if the value has a time that isn't
+// midnight. The date is used as written; a UTC time isn't converted
+// to the local time zone.
+
+fn parse_ics_date(s: &str) -> Option<(NaiveDate, bool)> {
+    let date = NaiveDate::parse_from_str(s.get(..8)?, "%Y%m%d").ok()?;
+    let timed = s
+        .get(8..)
+        .and_then(|t| t.strip_prefix('T'))
+        .is_some_and(|t| !t.starts_with("000000"));
+
+    Some((date, timed))
+}
+
+// Removes the escapes from an iCalendar TEXT value.
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(ch) = chars.next() {
+        match (ch, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                chars.next();
+                result.push('\n')
+            }
+            ('\\', Some(c)) => {
+                chars.next();
+                result.push(c)
+            }
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+// Parses a BYDAY value that picks one weekday of the month, like
+// "4TH" or "-1MO". The weekday is returned with its position.
+
+fn parse_by_day(s: &str) -> Option<(i8, Weekday)> {
+    let split = s.len().checked_sub(2)?;
+    let (nth, day) = (s.get(..split)?, s.get(split..)?);
+    let nth = nth.strip_prefix('+').unwrap_or(nth).parse::<i8>().ok()?;
+    let day = match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+
+    Some((nth, day)).filter(|_| (1..=5).contains(&nth.abs()))
+}
+
+// Converts an event's recurrence rule into the last year in which it
+// occurs and, if it doesn't repeat on the same date, the weekday on
+// which it starts. Only rules that repeat every year, on the same
+// date or on the nth weekday of a month, are supported.
+
+fn parse_rrule(
+    rule: &str,
+    first: NaiveDate,
+) -> Option<(i32, Option<NthWeekday>)> {
+    let mut until = i32::MAX;
+    let mut yearly = false;
+    let mut month = None;
+    let mut by_day = None;
+
+    for part in rule.split(';') {
+        match part.split_once('=')? {
+            ("FREQ", "YEARLY") => yearly = true,
+            ("INTERVAL", "1") | ("WKST", _) => (),
+            ("UNTIL", v) => until = parse_ics_date(v)?.0.year(),
+            ("COUNT", v) => {
+                until = first.year() + v.parse::<i32>().ok()?.max(1) - 1
+            }
+            ("BYMONTH", v) => {
+                month = Some(v.parse::<u32>().ok().filter(|m| *m <= 12)?)
+            }
+            ("BYDAY", v) => by_day = Some(parse_by_day(v)?),
+            _ => return None,
+        }
+    }
+
+    let weekday = match (month, by_day) {
+        (None, None) => None,
+        (Some(m), None) if m == first.month() => None,
+        (Some(month), Some((nth, day))) => Some(NthWeekday { month, nth, day }),
+        _ => return None,
+    };
+
+    Some((until, weekday)).filter(|_| yearly)
+}
+
+// The properties of a VEVENT that are used.
+
+#[derive(Default)]
+struct Event {
+    summary: String,
+    start: Option<NaiveDate>,
+    end: Option<(NaiveDate, bool)>,
+    rrule: Option<String>,
+}
+
+impl Event {
+    // Converts the event into a span of days. `None` is returned if
+    // the event has a recurrence rule that isn't supported.
+
+    fn into_span(self) -> Result<Option<(String, Span)>> {
+        let first = self.start.ok_or_else(|| {
+            Error::ConfigError(format!(
+                "event '{}' doesn't have a start date",
+                &self.summary
+            ))
+        })?;
+
+        // The end of an event is exclusive so, unless the event ends
+        // part way through a day, its last day is the one before.
+
+        let last = match self.end {
+            Some((end, true)) => end,
+            Some((end, false)) => end.pred_opt().unwrap_or(end),
+            None => first,
+        }
+        .max(first);
+
+        let (until, weekday) = match self.rrule {
+            Some(rule) => match parse_rrule(&rule, first) {
+                Some((until, weekday)) => (Some(until), weekday),
+                None => {
+                    warn!(
+                        "skipping event '{}' -- unsupported recurrence rule '{}'",
+                        &self.summary, rule
+                    );
+                    return Ok(None);
+                }
+            },
+            None => (None, None),
+        };
+
+        Ok(Some((
+            self.summary,
+            Span {
+                first,
+                last,
+                until,
+                weekday,
+            },
+        )))
+    }
+}
+
+// Extracts the events from the contents of an iCalendar file. Each
+// event is returned with its summary.
+
+fn parse_ics(text: &str) -> Result<Vec<(String, Span)>> {
+    // Long lines are "folded" by breaking them and starting the
+    // continuation with a space or tab. Join them back together.
+
+    let mut lines: Vec<String> = vec![];
+
+    for line in text.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => {
+                lines.last_mut().unwrap().push_str(rest)
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = vec![];
+    let mut event: Option<Event> = None;
+
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap_or("").to_ascii_uppercase();
+        let bad_date =
+            || Error::ConfigError(format!("bad date in line '{}'", line));
+
+        match (name.as_str(), event.as_mut()) {
+            ("BEGIN", _) if value == "VEVENT" => event = Some(Event::default()),
+            ("END", Some(_)) if value == "VEVENT" => {
+                events.extend(event.take().unwrap().into_span()?)
+            }
+            ("SUMMARY", Some(ev)) => ev.summary = unescape(value),
+            ("DTSTART", Some(ev)) => {
+                ev.start = Some(parse_ics_date(value).ok_or_else(bad_date)?.0)
+            }
+            ("DTEND", Some(ev)) => {
+                ev.end = Some(parse_ics_date(value).ok_or_else(bad_date)?)
+            }
+            ("RRULE", Some(ev)) => ev.rrule = Some(value.to_string()),
+            _ => (),
+        }
+    }
+    Ok(events)
+}
+
+// Builds the calendar from the `[[calendar]]` sections of the
+// configuration. Any iCalendar files are read at this time.
+
+pub async fn load(cfg: &[config::Calendar]) -> Result<Info> {
+    let mut holidays = vec![];
+    let mut events: HashMap<String, Vec<Span>> = HashMap::new();
+
+    for cal in cfg {
+        if events.contains_key(&cal.name) {
+            return Err(Error::ConfigError(format!(
+                "calendar '{}' is defined more than once",
+                &cal.name
+            )));
+        }
+
+        let mut spans = vec![];
+
+        for entry in &cal.dates {
+            spans.push(parse_date_entry(entry).ok_or_else(|| {
+                Error::ConfigError(format!(
+                    "calendar '{}' has a bad date : '{}'",
+                    &cal.name, entry
+                ))
+            })?)
+        }
+
+        if let Some(file) = &cal.file {
+            let text = tokio::fs::read_to_string(file).await.map_err(|e| {
+                Error::ConfigError(format!(
+                    "calendar '{}' can't read '{}' : {}",
+                    &cal.name, file, e
+                ))
+            })?;
+            let ics = parse_ics(&text).map_err(|e| {
+                Error::ConfigError(format!(
+                    "calendar '{}', file '{}' : {}",
+                    &cal.name, file, e
+                ))
+            })?;
+
+            info!("calendar '{}' has {} event(s)", &cal.name, ics.len());
+
+            for (summary, span) in ics {
+                if !summary.is_empty() && summary != cal.name {
+                    events.entry(summary).or_default().push(span.clone())
+                }
+                spans.push(span)
+            }
+        }
+
+        if cal.holiday {
+            holidays.extend(spans.iter().cloned())
+        }
+        events.entry(cal.name.clone()).or_default().extend(spans)
+    }
+
+    Ok(Arc::new(Calendar {
+        holidays: Arc::new(Dates(holidays)),
+        events: events
+            .into_iter()
+            .map(|(k, v)| (k, Arc::new(Dates(v))))
+            .collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_date_entries() {
+        assert!(parse_date_entry("2024-12-25").is_some());
+        assert!(parse_date_entry("2024-07-01..2024-07-14").is_some());
+        assert!(parse_date_entry("2024-07-01 .. 2024-07-14").is_some());
+        assert!(parse_date_entry("2024-07-14..2024-07-01").is_none());
+        assert!(parse_date_entry("2024-13-01").is_none());
+        assert!(parse_date_entry("12/25/2024").is_none());
+        assert!(parse_date_entry("").is_none());
+
+        let span = parse_date_entry("2024-07-01..2024-07-14").unwrap();
+
+        assert!(!span.contains(date("2024-06-30")));
+        assert!(span.contains(date("2024-07-01")));
+        assert!(span.contains(date("2024-07-14")));
+        assert!(!span.contains(date("2024-07-15")));
+        assert!(!span.contains(date("2025-07-01")));
+    }
+
+    #[test]
+    fn test_yearly_spans() {
+        let span = Span {
+            first: date("2020-12-31"),
+            last: date("2021-01-01"),
+            until: Some(2022),
+            weekday: None,
+        };
+
+        assert!(!span.contains(date("2019-12-31")));
+        assert!(!span.contains(date("2020-12-30")));
+        assert!(span.contains(date("2020-12-31")));
+        assert!(span.contains(date("2021-01-01")));
+        assert!(!span.contains(date("2021-01-02")));
+        assert!(span.contains(date("2021-12-31")));
+        assert!(span.contains(date("2023-01-01")));
+        assert!(!span.contains(date("2023-12-31")));
+
+        let span = Span {
+            first: date("2024-02-29"),
+            last: date("2024-02-29"),
+            until: Some(i32::MAX),
+            weekday: None,
+        };
+
+        assert!(span.contains(date("2024-02-29")));
+        assert!(!span.contains(date("2025-02-28")));
+        assert!(!span.contains(date("2025-03-01")));
+        assert!(span.contains(date("2028-02-29")));
+    }
+
+    #[test]
+    fn test_ics() {
+        const ICS: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20241225\r
+DTEND;VALUE=DATE:20241226\r
+SUMMARY:Christmas Day\r
+RRULE:FREQ=YEARLY\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20240701\r
+DTEND;VALUE=DATE:20240715\r
+SUMMARY:Summer vaca\r
+ tion\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART:20240910T140000Z\r
+DTEND:20240911T100000Z\r
+SUMMARY:Dentist\\, then lunch\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20200101\r
+RRULE:FREQ=YEARLY;COUNT=3\r
+SUMMARY:New Year\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+        let events = parse_ics(ICS).unwrap();
+
+        assert_eq!(events.len(), 4);
+
+        assert_eq!(events[0].0, "Christmas Day");
+        assert!(events[0].1.contains(date("2024-12-25")));
+        assert!(!events[0].1.contains(date("2024-12-26")));
+        assert!(events[0].1.contains(date("2031-12-25")));
+        assert!(!events[0].1.contains(date("2023-12-25")));
+
+        assert_eq!(events[1].0, "Summer vacation");
+        assert!(events[1].1.contains(date("2024-07-14")));
+        assert!(!events[1].1.contains(date("2024-07-15")));
+
+        assert_eq!(events[2].0, "Dentist, then lunch");
+        assert!(events[2].1.contains(date("2024-09-10")));
+        assert!(events[2].1.contains(date("2024-09-11")));
+
+        assert_eq!(events[3].0, "New Year");
+        assert!(events[3].1.contains(date("2022-01-01")));
+        assert!(!events[3].1.contains(date("2023-01-01")));
+
+        // Events can repeat on the nth weekday of a month, counting
+        // from either end.
+
+        let events = parse_ics(
+            "BEGIN:VEVENT
+DTSTART;VALUE=DATE:20241128
+DTEND;VALUE=DATE:20241130
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH
+SUMMARY:Thanksgiving
+END:VEVENT
+BEGIN:VEVENT
+DTSTART;VALUE=DATE:20240527
+RRULE:FREQ=YEARLY;BYDAY=-1MO;BYMONTH=5
+SUMMARY:Memorial Day
+END:VEVENT
+BEGIN:VEVENT
+DTSTART;VALUE=DATE:20240101
+RRULE:FREQ=MONTHLY
+SUMMARY:Rent
+END:VEVENT
+",
+        )
+        .unwrap();
+
+        // Events with unsupported rules are skipped.
+
+        assert_eq!(events.len(), 2);
+
+        assert_eq!(events[0].0, "Thanksgiving");
+        assert!(events[0].1.contains(date("2024-11-29")));
+        assert!(events[0].1.contains(date("2025-11-27")));
+        assert!(events[0].1.contains(date("2025-11-28")));
+        assert!(!events[0].1.contains(date("2025-11-29")));
+        assert!(!events[0].1.contains(date("2026-11-25")));
+        assert!(events[0].1.contains(date("2026-11-26")));
+        assert!(!events[0].1.contains(date("2023-11-23")));
+
+        assert_eq!(events[1].0, "Memorial Day");
+        assert!(events[1].1.contains(date("2025-05-26")));
+        assert!(!events[1].1.contains(date("2025-05-19")));
+        assert!(events[1].1.contains(date("2026-05-25")));
+        assert!(events[1].1.contains(date("2027-05-31")));
+
+        assert_eq!(parse_by_day("1MO"), Some((1, Weekday::Mon)));
+        assert_eq!(parse_by_day("+3MO"), Some((3, Weekday::Mon)));
+        assert_eq!(parse_by_day("-2SU"), Some((-2, Weekday::Sun)));
+        assert_eq!(parse_by_day("MO"), None);
+        assert_eq!(parse_by_day("6MO"), None);
+        assert_eq!(parse_by_day("1XX"), None);
+        assert_eq!(parse_by_day("1MO,2MO"), None);
+
+        assert!(parse_ics("BEGIN:VEVENT\nSUMMARY:x\nEND:VEVENT\n").is_err());
+        assert!(parse_ics(
+            "BEGIN:VEVENT\nDTSTART:2024\nSUMMARY:x\nEND:VEVENT\n"
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load() {
+        let cfg = vec![
+            config::Calendar {
+                name: "holidays".into(),
+                holiday: true,
+                file: None,
+                dates: vec![
+                    "2024-12-25".into(),
+                    "2024-12-31..2025-01-01".into(),
+                ],
+            },
+            config::Calendar {
+                name: "vacation".into(),
+                holiday: false,
+                file: None,
+                dates: vec!["2024-07-01..2024-07-14".into()],
+            },
+        ];
+        let cal = load(&cfg).await.unwrap();
+
+        assert!(cal.holidays().contains(date("2024-12-25")));
+        assert!(cal.holidays().contains(date("2025-01-01")));
+        assert!(!cal.holidays().contains(date("2024-07-01")));
+        assert!(cal.event("vacation").unwrap().contains(date("2024-07-01")));
+        assert!(cal.event("holidays").unwrap().contains(date("2024-12-25")));
+        assert!(cal.event("birthday").is_none());
+
+        // Calendar names must be unique, dates must be valid, and
+        // files must exist.
+
+        let dup = vec![cfg[0].clone(), cfg[0].clone()];
+
+        assert!(load(&dup).await.is_err());
+
+        let mut bad = cfg[1].clone();
+
+        bad.dates.push("2024-02-30".into());
+        assert!(load(&[bad]).await.is_err());
+
+        let mut missing = cfg[1].clone();
+
+        missing.file = Some("/nonexistent/calendar.ics".into());
+        assert!(load(&[missing]).await.is_err());
+    }
+}
@@ -23,6 +23,9 @@
 //     {utc:year}
 //     {utc:DOW}	day of week (Monday = 0, Sunday = 6)
 //     {utc:DOY}	day of year from 0 to 365
+//     {utc:week_of_month}
+//		week of the month the day is in (days 1-7 are
+//		week 1, days 8-14 are week 2, etc.)
 //
 //     {local:second}
 //     {local:minute}
@@ -32,6 +35,18 @@
 //     {local:year}
 //     {local:DOW}	day of week (Monday = 0, Sunday = 6)
 //     {local:DOY}	day of year from 0 to 365
+//     {local:week_of_month}
+//
+// The local time uses the node's `timezone` parameter, if it has
+// one (e.g. "America/Chicago"), or the system's time zone.
+//
+// The "cal" type uses the calendars in the configuration to check
+// the local date. Its values are booleans.
+//
+//     {cal:holiday}	true on the dates of holiday calendars
+//     {cal:event("NAME")}
+//		true on the dates of the calendar, or the
+//		iCalendar events, named NAME
 //
 // There is a built-in type, "solar", that provides solar position in
 // the sky.
@@ -41,10 +56,38 @@
 //     {solar:ra}	right ascension of sun
 //     {solar:dec}	declination of sun
 //
-// The token "->" represents assignment. The only item that can be on
-// the right hand side of the arrow is a variable referring to a
-// settable device (for logic blocks, output devices are specified in
-// the `output` map of the configuration).
+// It also provides the times of the day's sunrise, sunset and
+// twilights, in minutes after local midnight, and the minutes until
+// some of them (negative once they've passed). Events that don't
+// occur on the day have no value.
+//
+//     {solar:sunrise}, {solar:sunset}
+//     {solar:civil_dawn}, {solar:civil_dusk}
+//     {solar:nautical_dawn}, {solar:nautical_dusk}
+//     {solar:astro_dawn}, {solar:astro_dusk}
+//     {solar:minutes_to_sunrise}, {solar:minutes_to_sunset}
+//     {solar:minutes_to_civil_dawn}, {solar:minutes_to_civil_dusk}
+//     {solar:is_day}	true between sunrise and sunset
+//
+// The "lunar" type provides the moon's position in the sky and its
+// phase.
+//
+//     {lunar:alt}	altitude of moon (< 0 is below horizon)
+//     {lunar:az}	azimuth of moon
+//     {lunar:illum}	illuminated fraction of the disk, 0.0 to 1.0
+//     {lunar:phase}	name of the phase ("new", "waxing crescent",
+//     		"first quarter", ..., "full", ..., "waning crescent")
+//
+// The token "->" represents assignment. The only items that can be on
+// the right hand side of the arrow are variables referring to
+// settable devices (for logic blocks, output devices are specified in
+// the `output` map of the configuration). Separate them with commas
+// to send the value to several devices.
+//
+// An expression can start with local definitions. Each one is
+// computed before the expression and can use the ones before it:
+//
+//     let NAME = EXPR;  Makes {NAME} hold the value of EXPR
 //
 // Parentheses can be used to group subexpressions.
 //
@@ -56,13 +99,90 @@
 //
 //     +,-,*,/,%         Perform addition, subtraction, multiplication,
 //                       division, and modulo operations
-
+//
+//     if EXPR then EXPR else EXPR
+//                       Evaluates the boolean condition and returns the
+//                       value of the "then" or the "else" expression
+//
+// There are also built-in functions which take numeric arguments.
+// Integers and floating point values can be mixed; if any argument is
+// a float, the result is a float.
+//
+//     min(a, b)         Returns the smaller of two values
+//     max(a, b)         Returns the larger of two values
+//     abs(a)            Returns the absolute value
+//     clamp(a, lo, hi)  Limits a value to the range lo through hi
+//     round(a)          Rounds to the nearest integer
+//     floor(a)          Rounds down to an integer
+//     ceil(a)           Rounds up to an integer
+//
+// Colors can be built, mixed and taken apart with these functions.
+// Components are in the range 0 through 255, like the #rrggbb
+// literals.
+//
+//     rgb(r, g, b)      Builds a color from red, green and blue
+//     hsv(h, s, v)      Builds a color from a hue (in degrees) and a
+//                       saturation and value (from 0.0 to 1.0)
+//     blend(c1, c2, t)  Mixes two colors. `t` goes from 0.0 (all `c1`)
+//                       to 1.0 (all `c2`)
+//     brightness(c, k)  Scales the intensity of a color by `k`
+//     red(c), green(c), blue(c), alpha(c)
+//                       Returns a component of the color
+//
+// Strings can be inspected and built. Adding two strings concatenates
+// them. Strings that are built can't be longer than MAX_STR_LEN
+// bytes.
+//
+//     len(s)            Returns the number of characters in `s`
+//     contains(s, t)    True if `t` appears in `s`
+//     starts_with(s, t) True if `s` starts with `t`
+//     ends_with(s, t)   True if `s` ends with `t`
+//     format("TEXT", a, ...)
+//                       Replaces each "{}" in the literal string with
+//                       the value of the next argument. Use "{{" and
+//                       "}}" for braces.
+//
+// Some built-in functions remember their state between evaluations.
+// Each use of one of these functions in an expression has its own
+// state, which is cleared when the logic node is (re)started.
+//
+//     rising(a)         True when the boolean changed from false to true
+//                       since the previous evaluation
+//     falling(a)        True when the boolean changed from true to false
+//                       since the previous evaluation
+//     latch(set, reset) Becomes true when `set` is true and stays true
+//                       until `reset` is true (`reset` has priority)
+//     hysteresis(a, lo, hi)
+//                       Becomes true when `a` rises above `hi` and
+//                       false when it drops below `lo`. In between, it
+//                       keeps its previous value.
+//
+// Durations are written as a number followed by a unit: "ms"
+// (milliseconds), "s" (seconds), "m" (minutes), or "h" (hours). For
+// instance, 500ms, 30s, 1.5m, 2h. They evaluate to a floating point
+// number of seconds. The timer functions take a boolean and a
+// constant duration. They use the time-of-day and the logic node
+// wakes itself up when their results need to change.
+//
+//     held(a, DUR)      True once `a` has been true for the duration
+//     delay_off(a, DUR) True while `a` is true and for the duration
+//                       after it becomes false
+//
+// `schedule("SPEC")` is true during the minutes, of the local time,
+// given by SPEC. It's written like a crontab entry, for instance
+// "30 7 * * MON-FRI". Like the timer functions, the node wakes
+// itself up when its result changes.
+
+use super::calendar;
+use super::lunar;
+use super::schedule;
 use super::solar;
 use super::tod;
 use drmem_api::{device, Error, Result};
 use lrlex::lrlex_mod;
 use lrpar::lrpar_mod;
-use std::fmt;
+use palette::{FromColor, Hsv, LinSrgba, Mix, Srgb, WithAlpha};
+use std::{cell::RefCell, fmt, sync::Arc};
 use tracing::error;
 
 // Pull in the lexer and parser for the Logic Node language.
@@ -70,6 +190,10 @@ use tracing::error;
 lrlex_mod!("logic/logic.l");
 lrpar_mod!("logic/logic.y");
 
+mod bytecode;
+
+pub use bytecode::Code;
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum TimeField {
     Second,
@@ -78,6 +202,7 @@ pub enum TimeField {
     Day,
     DoW,
     DoY,
+    WoM,
     Month,
     Year,
 }
@@ -96,6 +221,7 @@ impl std::fmt::Display for TimeField {
             TimeField::Month => write!(f, "month"),
             TimeField::Year => write!(f, "year"),
             TimeField::DoY => write!(f, "DOY"),
+            TimeField::WoM => write!(f, "week_of_month"),
         }
     }
 }
@@ -106,6 +232,30 @@ pub enum SolarField {
     Azimuth,
     RightAscension,
     Declination,
+    Sunrise,
+    Sunset,
+    CivilDawn,
+    CivilDusk,
+    NauticalDawn,
+    NauticalDusk,
+    AstroDawn,
+    AstroDusk,
+    IsDay,
+    MinutesToSunrise,
+    MinutesToSunset,
+    MinutesToCivilDawn,
+    MinutesToCivilDusk,
+}
+
+impl SolarField {
+    // Returns the type of the field's value.
+
+    pub fn ty(&self) -> Type {
+        match self {
+            SolarField::IsDay => Type::Bool,
+            _ => Type::Flt,
+        }
+    }
 }
 
 impl std::fmt::Display for SolarField {
@@ -118,16 +268,538 @@ impl std::fmt::Display for SolarField {
             SolarField::Azimuth => write!(f, "az"),
             SolarField::RightAscension => write!(f, "ra"),
             SolarField::Declination => write!(f, "dec"),
+            SolarField::Sunrise => write!(f, "sunrise"),
+            SolarField::Sunset => write!(f, "sunset"),
+            SolarField::CivilDawn => write!(f, "civil_dawn"),
+            SolarField::CivilDusk => write!(f, "civil_dusk"),
+            SolarField::NauticalDawn => write!(f, "nautical_dawn"),
+            SolarField::NauticalDusk => write!(f, "nautical_dusk"),
+            SolarField::AstroDawn => write!(f, "astro_dawn"),
+            SolarField::AstroDusk => write!(f, "astro_dusk"),
+            SolarField::IsDay => write!(f, "is_day"),
+            SolarField::MinutesToSunrise => write!(f, "minutes_to_sunrise"),
+            SolarField::MinutesToSunset => write!(f, "minutes_to_sunset"),
+            SolarField::MinutesToCivilDawn => {
+                write!(f, "minutes_to_civil_dawn")
+            }
+            SolarField::MinutesToCivilDusk => {
+                write!(f, "minutes_to_civil_dusk")
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum LunarField {
+    Elevation,
+    Azimuth,
+    Illumination,
+    Phase,
+}
+
+impl LunarField {
+    // Returns the type of the field's value.
+
+    pub fn ty(&self) -> Type {
+        match self {
+            LunarField::Phase => Type::Str,
+            _ => Type::Flt,
+        }
+    }
+
+    // Returns the field's value from the lunar information.
+
+    pub fn eval(&self, info: &lunar::Info) -> device::Value {
+        match self {
+            LunarField::Elevation => device::Value::Flt(info.elevation),
+            LunarField::Azimuth => device::Value::Flt(info.azimuth),
+            LunarField::Illumination => device::Value::Flt(info.illumination),
+            LunarField::Phase => device::Value::Str(info.phase.into()),
+        }
+    }
+}
+
+impl std::fmt::Display for LunarField {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            LunarField::Elevation => write!(f, "alt"),
+            LunarField::Azimuth => write!(f, "az"),
+            LunarField::Illumination => write!(f, "illum"),
+            LunarField::Phase => write!(f, "phase"),
+        }
+    }
+}
+
+// The values provided by the calendars. `Event` holds the name of
+// the event.
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CalField {
+    Holiday,
+    Event(String),
+}
+
+impl std::fmt::Display for CalField {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            CalField::Holiday => write!(f, "holiday"),
+            CalField::Event(name) => write!(f, "event(\"{}\")", name),
+        }
+    }
+}
+
+// The most recent information from the solar and lunar tasks. Each
+// is `None` until the logic node receives its first update.
+
+#[derive(Default)]
+pub struct Sky {
+    pub solar: Option<solar::Info>,
+    pub lunar: Option<lunar::Info>,
+}
+
+// The kinds of arguments that built-in functions accept.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgType {
+    Bool,
+    Num,
+    Str,
+    Color,
+    Duration,
+}
+
+impl ArgType {
+    // Returns `false` if the expression is known to never produce a
+    // value of this type. Since the values of devices aren't known
+    // until the node runs, those expressions are always accepted
+    // here and type errors get reported when evaluated.
+
+    pub fn accepts(&self, e: &Expr) -> bool {
+        // Built-in functions declare the type of their result.
+
+        if let Expr::Call(func, _) = e {
+            return func.ret == *self;
+        }
+
+        match self {
+            ArgType::Bool => !matches!(
+                e,
+                Expr::Lit(device::Value::Int(_))
+                    | Expr::Lit(device::Value::Flt(_))
+                    | Expr::Lit(device::Value::Str(_))
+                    | Expr::Lit(device::Value::Color(_))
+                    | Expr::Add(..)
+                    | Expr::Sub(..)
+                    | Expr::Mul(..)
+                    | Expr::Div(..)
+                    | Expr::Rem(..)
+                    | Expr::Format(..)
+            ),
+            ArgType::Color => !matches!(
+                e,
+                Expr::Lit(device::Value::Bool(_))
+                    | Expr::Lit(device::Value::Int(_))
+                    | Expr::Lit(device::Value::Flt(_))
+                    | Expr::Lit(device::Value::Str(_))
+                    | Expr::Not(_)
+                    | Expr::And(..)
+                    | Expr::Or(..)
+                    | Expr::Eq(..)
+                    | Expr::Lt(..)
+                    | Expr::LtEq(..)
+                    | Expr::Add(..)
+                    | Expr::Sub(..)
+                    | Expr::Mul(..)
+                    | Expr::Div(..)
+                    | Expr::Rem(..)
+                    | Expr::StatefulCall(..)
+                    | Expr::Timer(..)
+                    | Expr::Format(..)
+            ),
+            ArgType::Str => !matches!(
+                e,
+                Expr::Lit(device::Value::Bool(_))
+                    | Expr::Lit(device::Value::Int(_))
+                    | Expr::Lit(device::Value::Flt(_))
+                    | Expr::Lit(device::Value::Color(_))
+                    | Expr::Not(_)
+                    | Expr::And(..)
+                    | Expr::Or(..)
+                    | Expr::Eq(..)
+                    | Expr::Lt(..)
+                    | Expr::LtEq(..)
+                    | Expr::Sub(..)
+                    | Expr::Mul(..)
+                    | Expr::Div(..)
+                    | Expr::Rem(..)
+                    | Expr::StatefulCall(..)
+                    | Expr::Timer(..)
+            ),
+            ArgType::Duration => matches!(
+                e,
+                Expr::Lit(device::Value::Int(_))
+                    | Expr::Lit(device::Value::Flt(_))
+            ),
+            ArgType::Num => !matches!(
+                e,
+                Expr::Lit(device::Value::Bool(_))
+                    | Expr::Lit(device::Value::Str(_))
+                    | Expr::Lit(device::Value::Color(_))
+                    | Expr::Not(_)
+                    | Expr::And(..)
+                    | Expr::Or(..)
+                    | Expr::Eq(..)
+                    | Expr::Lt(..)
+                    | Expr::LtEq(..)
+                    | Expr::StatefulCall(..)
+                    | Expr::Timer(..)
+                    | Expr::Format(..)
+            ),
+        }
+    }
+}
+
+impl ArgType {
+    // Returns `true` if a value of type `t` can be used for this
+    // argument.
+
+    fn allows(&self, t: Type) -> bool {
+        match self {
+            ArgType::Bool => t == Type::Bool,
+            ArgType::Num | ArgType::Duration => t.is_numeric(),
+            ArgType::Str => t == Type::Str,
+            ArgType::Color => t == Type::Color,
+        }
+    }
+}
+
+impl fmt::Display for ArgType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgType::Bool => write!(f, "boolean"),
+            ArgType::Num => write!(f, "numeric"),
+            ArgType::Str => write!(f, "string"),
+            ArgType::Color => write!(f, "color"),
+            ArgType::Duration => write!(f, "a constant duration"),
+        }
+    }
+}
+
+// The type of an expression's result, as far as it can be determined
+// before the node runs. `Num` is used when the result is known to be
+// numeric, but could be an integer or a float.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    Bool,
+    Int,
+    Flt,
+    Num,
+    Str,
+    Color,
+}
+
+impl Type {
+    pub fn of(v: &device::Value) -> Self {
+        match v {
+            device::Value::Bool(_) => Type::Bool,
+            device::Value::Int(_) => Type::Int,
+            device::Value::Flt(_) => Type::Flt,
+            device::Value::Str(_) => Type::Str,
+            device::Value::Color(_) => Type::Color,
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(self, Type::Int | Type::Flt | Type::Num)
+    }
+
+    // Returns the type of an arithmetic result, following the same
+    // promotion rules the evaluator uses. `with_bool` indicates
+    // whether booleans are treated as 0 or 1. Returns `None` if the
+    // types can't be used together.
+
+    fn arith(a: Type, b: Type, with_bool: bool) -> Option<Type> {
+        let num = |t: Type| t.is_numeric() || (with_bool && t == Type::Bool);
+
+        match (a, b) {
+            (Type::Bool, Type::Bool) => None,
+            (a, b) if !num(a) || !num(b) => None,
+            (Type::Flt, _) | (_, Type::Flt) => Some(Type::Flt),
+            (Type::Num, _) | (_, Type::Num) => Some(Type::Num),
+            _ => Some(Type::Int),
+        }
+    }
+
+    // Returns `true` if values of the two types can be compared for
+    // equality (`ordered` is `false`) or for order.
+
+    fn comparable(a: Type, b: Type, ordered: bool) -> bool {
+        match (a, b) {
+            (a, b) if a.is_numeric() && b.is_numeric() => true,
+            (Type::Str, Type::Str) => true,
+            (Type::Bool, Type::Bool) | (Type::Color, Type::Color) => !ordered,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Bool => write!(f, "boolean"),
+            Type::Int => write!(f, "integer"),
+            Type::Flt => write!(f, "float"),
+            Type::Num => write!(f, "numeric"),
+            Type::Str => write!(f, "string"),
+            Type::Color => write!(f, "color"),
+        }
+    }
+}
+
+// Describes a built-in function. The parser uses `name` to find the
+// entry and `args` to verify the arguments. `ret` is the type of the
+// result, so calls can be checked when used as arguments. `eval` is
+// called with the values of the arguments, which are guaranteed to
+// match the length of `args`.
+
+#[derive(Debug)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub args: &'static [ArgType],
+    pub ret: ArgType,
+    pub eval: fn(&[device::Value]) -> Option<device::Value>,
+}
+
+// Two built-ins are the same if they have the same name. This avoids
+// comparing function pointers, which isn't reliable.
+
+impl PartialEq for Builtin {
+    fn eq(&self, o: &Self) -> bool {
+        self.name == o.name
+    }
+}
+
+pub static BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "min",
+        args: &[ArgType::Num, ArgType::Num],
+        ret: ArgType::Num,
+        eval: eval_min,
+    },
+    Builtin {
+        name: "max",
+        args: &[ArgType::Num, ArgType::Num],
+        ret: ArgType::Num,
+        eval: eval_max,
+    },
+    Builtin {
+        name: "abs",
+        args: &[ArgType::Num],
+        ret: ArgType::Num,
+        eval: eval_abs,
+    },
+    Builtin {
+        name: "clamp",
+        args: &[ArgType::Num, ArgType::Num, ArgType::Num],
+        ret: ArgType::Num,
+        eval: eval_clamp,
+    },
+    Builtin {
+        name: "round",
+        args: &[ArgType::Num],
+        ret: ArgType::Num,
+        eval: eval_round,
+    },
+    Builtin {
+        name: "floor",
+        args: &[ArgType::Num],
+        ret: ArgType::Num,
+        eval: eval_floor,
+    },
+    Builtin {
+        name: "ceil",
+        args: &[ArgType::Num],
+        ret: ArgType::Num,
+        eval: eval_ceil,
+    },
+    Builtin {
+        name: "rgb",
+        args: &[ArgType::Num, ArgType::Num, ArgType::Num],
+        ret: ArgType::Color,
+        eval: eval_rgb,
+    },
+    Builtin {
+        name: "hsv",
+        args: &[ArgType::Num, ArgType::Num, ArgType::Num],
+        ret: ArgType::Color,
+        eval: eval_hsv,
+    },
+    Builtin {
+        name: "blend",
+        args: &[ArgType::Color, ArgType::Color, ArgType::Num],
+        ret: ArgType::Color,
+        eval: eval_blend,
+    },
+    Builtin {
+        name: "brightness",
+        args: &[ArgType::Color, ArgType::Num],
+        ret: ArgType::Color,
+        eval: eval_brightness,
+    },
+    Builtin {
+        name: "red",
+        args: &[ArgType::Color],
+        ret: ArgType::Num,
+        eval: eval_red,
+    },
+    Builtin {
+        name: "green",
+        args: &[ArgType::Color],
+        ret: ArgType::Num,
+        eval: eval_green,
+    },
+    Builtin {
+        name: "blue",
+        args: &[ArgType::Color],
+        ret: ArgType::Num,
+        eval: eval_blue,
+    },
+    Builtin {
+        name: "alpha",
+        args: &[ArgType::Color],
+        ret: ArgType::Num,
+        eval: eval_alpha,
+    },
+    Builtin {
+        name: "len",
+        args: &[ArgType::Str],
+        ret: ArgType::Num,
+        eval: eval_len,
+    },
+    Builtin {
+        name: "contains",
+        args: &[ArgType::Str, ArgType::Str],
+        ret: ArgType::Bool,
+        eval: eval_contains,
+    },
+    Builtin {
+        name: "starts_with",
+        args: &[ArgType::Str, ArgType::Str],
+        ret: ArgType::Bool,
+        eval: eval_starts_with,
+    },
+    Builtin {
+        name: "ends_with",
+        args: &[ArgType::Str, ArgType::Str],
+        ret: ArgType::Bool,
+        eval: eval_ends_with,
+    },
+];
+
+// The longest string, in bytes, that an expression may build. Strings
+// are copied to every client monitoring a device so, as the notes on
+// `device::Value::Str` explain, they should be kept short.
+
+pub const MAX_STR_LEN: usize = 1024;
+
+// Describes a built-in function whose result depends on earlier
+// evaluations. It's like `Builtin` except `eval` is also given the
+// state saved by the previous evaluation, which it should update.
+
+#[derive(Debug)]
+pub struct StatefulBuiltin {
+    pub name: &'static str,
+    pub args: &'static [ArgType],
+    pub eval: fn(&[device::Value], &mut Option<bool>) -> Option<device::Value>,
+}
+
+impl PartialEq for StatefulBuiltin {
+    fn eq(&self, o: &Self) -> bool {
+        self.name == o.name
+    }
+}
+
+pub static STATEFUL_BUILTINS: &[StatefulBuiltin] = &[
+    StatefulBuiltin {
+        name: "rising",
+        args: &[ArgType::Bool],
+        eval: eval_rising,
+    },
+    StatefulBuiltin {
+        name: "falling",
+        args: &[ArgType::Bool],
+        eval: eval_falling,
+    },
+    StatefulBuiltin {
+        name: "latch",
+        args: &[ArgType::Bool, ArgType::Bool],
+        eval: eval_latch,
+    },
+    StatefulBuiltin {
+        name: "hysteresis",
+        args: &[ArgType::Num, ArgType::Num, ArgType::Num],
+        eval: eval_hysteresis,
+    },
+];
+
+// The timer functions.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimerKind {
+    Held,
+    DelayOff,
+}
+
+impl TimerKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "held" => Some(TimerKind::Held),
+            "delay_off" => Some(TimerKind::DelayOff),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimerKind::Held => write!(f, "held"),
+            TimerKind::DelayOff => write!(f, "delay_off"),
         }
     }
 }
 
+// The state of a timer function. `Active` means the input is true
+// and `Since` holds the time the input last changed (to true, for
+// `held()`, and to false, for `delay_off()`.)
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TimerState {
+    #[default]
+    Idle,
+    Active,
+    Since(chrono::DateTime<chrono::Utc>),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Lit(device::Value),
     Var(usize),
     TimeVal(&'static str, TimeField, fn(&tod::Info) -> device::Value),
-    SolarVal(SolarField, fn(&solar::Info) -> device::Value),
+    SolarVal(
+        SolarField,
+        fn(&solar::Info, &tod::Info) -> Option<device::Value>,
+    ),
+    LunarVal(LunarField),
+    CalVal(CalField, Arc<calendar::Dates>),
+    Schedule(Arc<schedule::Schedule>),
 
     Not(Box<Expr>),
     And(Box<Expr>, Box<Expr>),
@@ -146,6 +818,22 @@ pub enum Expr {
     Mul(Box<Expr>, Box<Expr>),
     Div(Box<Expr>, Box<Expr>),
     Rem(Box<Expr>, Box<Expr>),
+
+    // Holds the condition, the "then" expression, and the "else"
+    // expression, in that order.
+    IfElse(Box<Expr>, Box<Expr>, Box<Expr>),
+
+    Call(&'static Builtin, Vec<Expr>),
+
+    // The state of stateful calls and timers isn't kept in the
+    // expression; `Code` holds it for each one.
+    StatefulCall(&'static StatefulBuiltin, Vec<Expr>),
+
+    Timer(TimerKind, Box<Expr>, chrono::Duration),
+
+    // The literal text is split at each "{}". There's always one
+    // more piece of text than arguments.
+    Format(Vec<String>, Vec<Expr>),
 }
 
 impl Expr {
@@ -154,13 +842,21 @@ impl Expr {
             Expr::Lit(_)
             | Expr::Var(_)
             | Expr::TimeVal(..)
-            | Expr::SolarVal(..) => 10,
+            | Expr::SolarVal(..)
+            | Expr::LunarVal(..)
+            | Expr::CalVal(..)
+            | Expr::Schedule(..)
+            | Expr::Call(..)
+            | Expr::StatefulCall(..)
+            | Expr::Timer(..)
+            | Expr::Format(..) => 10,
             Expr::Not(_) => 9,
             Expr::Mul(_, _) | Expr::Div(_, _) | Expr::Rem(_, _) => 5,
             Expr::Add(_, _) | Expr::Sub(_, _) => 4,
             Expr::Lt(_, _) | Expr::LtEq(_, _) | Expr::Eq(_, _) => 3,
             Expr::And(_, _) => 2,
             Expr::Or(_, _) => 1,
+            Expr::IfElse(..) => 0,
         }
     }
 
@@ -178,13 +874,27 @@ impl Expr {
             Expr::TimeVal(_, TimeField::Hour, _) => Some(tod::TimeField::Hour),
             Expr::TimeVal(_, TimeField::Day, _)
             | Expr::TimeVal(_, TimeField::DoW, _)
-            | Expr::TimeVal(_, TimeField::DoY, _) => Some(tod::TimeField::Day),
+            | Expr::TimeVal(_, TimeField::DoY, _)
+            | Expr::TimeVal(_, TimeField::WoM, _)
+            | Expr::CalVal(..) => Some(tod::TimeField::Day),
             Expr::TimeVal(_, TimeField::Month, _) => {
                 Some(tod::TimeField::Month)
             }
             Expr::TimeVal(_, TimeField::Year, _) => Some(tod::TimeField::Year),
-            Expr::SolarVal(..) | Expr::Lit(_) | Expr::Var(_) => None,
-            Expr::Not(e) => e.uses_time(),
+            Expr::SolarVal(..)
+            | Expr::LunarVal(..)
+            | Expr::Schedule(..)
+            | Expr::Lit(_)
+            | Expr::Var(_) => None,
+            Expr::Not(e) | Expr::Timer(_, e, _) => e.uses_time(),
+            Expr::IfElse(c, a, b) => {
+                [c, a, b].iter().filter_map(|e| e.uses_time()).min()
+            }
+            Expr::Call(_, args)
+            | Expr::StatefulCall(_, args)
+            | Expr::Format(_, args) => {
+                args.iter().filter_map(|e| e.uses_time()).min()
+            }
             Expr::Mul(a, b)
             | Expr::Div(a, b)
             | Expr::Rem(a, b)
@@ -209,8 +919,19 @@ impl Expr {
     pub fn uses_solar(&self) -> bool {
         match self {
             Expr::SolarVal(..) => true,
-            Expr::TimeVal(..) | Expr::Lit(_) | Expr::Var(_) => false,
-            Expr::Not(e) => e.uses_solar(),
+            Expr::TimeVal(..)
+            | Expr::LunarVal(..)
+            | Expr::CalVal(..)
+            | Expr::Schedule(..)
+            | Expr::Lit(_)
+            | Expr::Var(_) => false,
+            Expr::Not(e) | Expr::Timer(_, e, _) => e.uses_solar(),
+            Expr::IfElse(c, a, b) => {
+                c.uses_solar() || a.uses_solar() || b.uses_solar()
+            }
+            Expr::Call(_, args)
+            | Expr::StatefulCall(_, args)
+            | Expr::Format(_, args) => args.iter().any(|e| e.uses_solar()),
             Expr::Mul(a, b)
             | Expr::Div(a, b)
             | Expr::Rem(a, b)
@@ -224,63 +945,341 @@ impl Expr {
         }
     }
 
-    fn fmt_subexpr(&self, e: &Expr, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let my_prec = self.precedence();
+    // Traverses an expression and returns `true` if it uses any
+    // `LunarVal()` variants.
 
-        if my_prec > e.precedence() {
-            write!(f, "({})", &e)
-        } else {
-            write!(f, "{}", &e)
+    pub fn uses_lunar(&self) -> bool {
+        matches!(self, Expr::LunarVal(..))
+            || self.subexprs().into_iter().any(|e| e.uses_lunar())
+    }
+
+    // Traverses an expression and returns `true` if it uses any timer
+    // functions.
+
+    pub fn uses_timers(&self) -> bool {
+        match self {
+            Expr::Timer(..) | Expr::Schedule(..) => true,
+            Expr::TimeVal(..)
+            | Expr::SolarVal(..)
+            | Expr::LunarVal(..)
+            | Expr::CalVal(..)
+            | Expr::Lit(_)
+            | Expr::Var(_) => false,
+            Expr::Not(e) => e.uses_timers(),
+            Expr::IfElse(c, a, b) => {
+                c.uses_timers() || a.uses_timers() || b.uses_timers()
+            }
+            Expr::Call(_, args)
+            | Expr::StatefulCall(_, args)
+            | Expr::Format(_, args) => args.iter().any(|e| e.uses_timers()),
+            Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Rem(a, b)
+            | Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Lt(a, b)
+            | Expr::LtEq(a, b)
+            | Expr::Eq(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b) => a.uses_timers() || b.uses_timers(),
         }
     }
-}
 
-impl fmt::Display for Expr {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    // Returns the immediate subexpressions of the expression.
+
+    fn subexprs(&self) -> Vec<&Expr> {
         match self {
-            Expr::Lit(v) => write!(f, "{}", &v),
-            Expr::Var(v) => write!(f, "inp[{}]", &v),
+            Expr::Lit(_)
+            | Expr::Var(_)
+            | Expr::TimeVal(..)
+            | Expr::SolarVal(..)
+            | Expr::LunarVal(..)
+            | Expr::CalVal(..)
+            | Expr::Schedule(..) => vec![],
+            Expr::Not(e) | Expr::Timer(_, e, _) => vec![e.as_ref()],
+            Expr::IfElse(c, a, b) => vec![c.as_ref(), a.as_ref(), b.as_ref()],
+            Expr::Call(_, args)
+            | Expr::StatefulCall(_, args)
+            | Expr::Format(_, args) => args.iter().collect(),
+            Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Rem(a, b)
+            | Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Lt(a, b)
+            | Expr::LtEq(a, b)
+            | Expr::Eq(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b) => vec![a.as_ref(), b.as_ref()],
+        }
+    }
 
-            Expr::TimeVal(cat, fld, _) => write!(f, "{{{}:{}}}", cat, fld),
+    fn subexprs_mut(&mut self) -> Vec<&mut Expr> {
+        match self {
+            Expr::Lit(_)
+            | Expr::Var(_)
+            | Expr::TimeVal(..)
+            | Expr::SolarVal(..)
+            | Expr::LunarVal(..)
+            | Expr::CalVal(..)
+            | Expr::Schedule(..) => vec![],
+            Expr::Not(e) | Expr::Timer(_, e, _) => vec![e.as_mut()],
+            Expr::IfElse(c, a, b) => vec![c.as_mut(), a.as_mut(), b.as_mut()],
+            Expr::Call(_, args)
+            | Expr::StatefulCall(_, args)
+            | Expr::Format(_, args) => args.iter_mut().collect(),
+            Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Rem(a, b)
+            | Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Lt(a, b)
+            | Expr::LtEq(a, b)
+            | Expr::Eq(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b) => vec![a.as_mut(), b.as_mut()],
+        }
+    }
 
-            Expr::SolarVal(fld, _) => write!(f, "{{solar:{}}}", fld),
+    // Returns the number of nodes in the expression tree.
 
-            Expr::Not(e) => {
-                write!(f, "not ")?;
-                self.fmt_subexpr(e, f)
-            }
+    fn size(&self) -> usize {
+        1 + self.subexprs().into_iter().map(Expr::size).sum::<usize>()
+    }
 
-            Expr::And(a, b) => {
-                self.fmt_subexpr(a, f)?;
-                write!(f, " and ")?;
-                self.fmt_subexpr(b, f)
-            }
+    // Determines the type of the expression's result. `inp` holds the
+    // types of the input variables; a `None` entry means its type
+    // isn't known. If the type of an expression can't be determined
+    // (because it depends on an unknown input, for instance), `None`
+    // is returned and any problem will be reported when the
+    // expression is evaluated. An `Err` is returned when the
+    // expression can never be evaluated successfully.
 
-            Expr::Or(a, b) => {
-                self.fmt_subexpr(a, f)?;
-                write!(f, " or ")?;
-                self.fmt_subexpr(b, f)
+    pub fn infer_type(
+        &self,
+        inp: &[Option<Type>],
+    ) -> std::result::Result<Option<Type>, String> {
+        // Reports a subexpression that has the wrong type.
+
+        fn expected(
+            e: &Expr,
+            what: &str,
+            t: Type,
+        ) -> std::result::Result<Option<Type>, String> {
+            Err(format!("'{}' should be {} but is {}", e, what, t))
+        }
+
+        // Makes sure a subexpression, if its type is known, is a
+        // boolean.
+
+        fn boolean(
+            e: &Expr,
+            inp: &[Option<Type>],
+        ) -> std::result::Result<(), String> {
+            match e.infer_type(inp)? {
+                Some(t) if t != Type::Bool => {
+                    expected(e, "boolean", t).map(|_| ())
+                }
+                _ => Ok(()),
             }
+        }
 
-            Expr::Eq(a, b) => {
-                self.fmt_subexpr(a, f)?;
-                write!(f, " = ")?;
-                self.fmt_subexpr(b, f)
+        // Checks the arguments of a function.
+
+        fn check_args(
+            name: &str,
+            types: &[ArgType],
+            args: &[Expr],
+            inp: &[Option<Type>],
+        ) -> std::result::Result<(), String> {
+            for (ty, arg) in types.iter().zip(args) {
+                match arg.infer_type(inp)? {
+                    Some(t) if !ty.allows(t) => {
+                        return Err(format!(
+                            "argument '{}' of {}() should be {} but is {}",
+                            arg, name, ty, t
+                        ))
+                    }
+                    _ => (),
+                }
             }
+            Ok(())
+        }
 
-            Expr::Lt(a, b) => {
-                self.fmt_subexpr(a, f)?;
-                write!(f, " < ")?;
-                self.fmt_subexpr(b, f)
+        match self {
+            Expr::Lit(v) => Ok(Some(Type::of(v))),
+            Expr::Var(n) => Ok(inp.get(*n).copied().flatten()),
+            Expr::TimeVal(..) => Ok(Some(Type::Int)),
+            Expr::SolarVal(fld, _) => Ok(Some(fld.ty())),
+            Expr::LunarVal(fld) => Ok(Some(fld.ty())),
+            Expr::CalVal(..) | Expr::Schedule(..) => Ok(Some(Type::Bool)),
+
+            Expr::Not(e) => {
+                boolean(e, inp)?;
+                Ok(Some(Type::Bool))
             }
 
-            Expr::LtEq(a, b) => {
-                self.fmt_subexpr(a, f)?;
-                write!(f, " <= ")?;
-                self.fmt_subexpr(b, f)
+            Expr::And(a, b) | Expr::Or(a, b) => {
+                boolean(a, inp)?;
+                boolean(b, inp)?;
+                Ok(Some(Type::Bool))
             }
 
-            Expr::Add(a, b) => {
+            Expr::Eq(a, b) | Expr::Lt(a, b) | Expr::LtEq(a, b) => {
+                let ordered = !matches!(self, Expr::Eq(..));
+
+                match (a.infer_type(inp)?, b.infer_type(inp)?) {
+                    (Some(ta), Some(tb))
+                        if !Type::comparable(ta, tb, ordered) =>
+                    {
+                        Err(format!(
+                            "can't compare '{}' ({}) with '{}' ({})",
+                            a, ta, b, tb
+                        ))
+                    }
+                    _ => Ok(Some(Type::Bool)),
+                }
+            }
+
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Rem(a, b) => {
+                let with_bool = matches!(
+                    self,
+                    Expr::Add(..) | Expr::Sub(..) | Expr::Mul(..)
+                );
+
+                match (a.infer_type(inp)?, b.infer_type(inp)?) {
+                    (Some(Type::Str), Some(Type::Str))
+                        if matches!(self, Expr::Add(..)) =>
+                    {
+                        Ok(Some(Type::Str))
+                    }
+                    (Some(ta), Some(tb)) => Type::arith(ta, tb, with_bool)
+                        .map(Some)
+                        .ok_or_else(|| {
+                            format!(
+                                "can't use '{}' ({}) and '{}' ({}) in '{}'",
+                                a, ta, b, tb, self
+                            )
+                        }),
+                    _ => Ok(None),
+                }
+            }
+
+            Expr::IfElse(c, a, b) => {
+                boolean(c, inp)?;
+
+                match (a.infer_type(inp)?, b.infer_type(inp)?) {
+                    (Some(ta), Some(tb)) if ta == tb => Ok(Some(ta)),
+                    (Some(ta), Some(tb))
+                        if ta.is_numeric() && tb.is_numeric() =>
+                    {
+                        Ok(Some(Type::Num))
+                    }
+                    _ => Ok(None),
+                }
+            }
+
+            Expr::Call(func, args) => {
+                check_args(func.name, func.args, args, inp)?;
+                Ok(Some(match func.ret {
+                    ArgType::Bool => Type::Bool,
+                    ArgType::Str => Type::Str,
+                    ArgType::Color => Type::Color,
+                    ArgType::Num | ArgType::Duration => Type::Num,
+                }))
+            }
+
+            Expr::StatefulCall(func, args) => {
+                check_args(func.name, func.args, args, inp)?;
+                Ok(Some(Type::Bool))
+            }
+
+            Expr::Timer(kind, e, _) => {
+                check_args(
+                    &kind.to_string(),
+                    &[ArgType::Bool],
+                    std::slice::from_ref(e.as_ref()),
+                    inp,
+                )?;
+                Ok(Some(Type::Bool))
+            }
+
+            Expr::Format(_, args) => {
+                for arg in args {
+                    arg.infer_type(inp)?;
+                }
+                Ok(Some(Type::Str))
+            }
+        }
+    }
+
+    fn fmt_subexpr(&self, e: &Expr, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let my_prec = self.precedence();
+
+        if my_prec > e.precedence() {
+            write!(f, "({})", &e)
+        } else {
+            write!(f, "{}", &e)
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Lit(v) => write!(f, "{}", &v),
+            Expr::Var(v) => write!(f, "inp[{}]", &v),
+
+            Expr::TimeVal(cat, fld, _) => write!(f, "{{{}:{}}}", cat, fld),
+
+            Expr::SolarVal(fld, _) => write!(f, "{{solar:{}}}", fld),
+
+            Expr::LunarVal(fld) => write!(f, "{{lunar:{}}}", fld),
+
+            Expr::CalVal(fld, _) => write!(f, "{{cal:{}}}", fld),
+
+            Expr::Schedule(s) => write!(f, "schedule({:?})", s.to_string()),
+
+            Expr::Not(e) => {
+                write!(f, "not ")?;
+                self.fmt_subexpr(e, f)
+            }
+
+            Expr::And(a, b) => {
+                self.fmt_subexpr(a, f)?;
+                write!(f, " and ")?;
+                self.fmt_subexpr(b, f)
+            }
+
+            Expr::Or(a, b) => {
+                self.fmt_subexpr(a, f)?;
+                write!(f, " or ")?;
+                self.fmt_subexpr(b, f)
+            }
+
+            Expr::Eq(a, b) => {
+                self.fmt_subexpr(a, f)?;
+                write!(f, " = ")?;
+                self.fmt_subexpr(b, f)
+            }
+
+            Expr::Lt(a, b) => {
+                self.fmt_subexpr(a, f)?;
+                write!(f, " < ")?;
+                self.fmt_subexpr(b, f)
+            }
+
+            Expr::LtEq(a, b) => {
+                self.fmt_subexpr(a, f)?;
+                write!(f, " <= ")?;
+                self.fmt_subexpr(b, f)
+            }
+
+            Expr::Add(a, b) => {
                 self.fmt_subexpr(a, f)?;
                 write!(f, " + ")?;
                 self.fmt_subexpr(b, f)
@@ -309,10 +1308,78 @@ impl fmt::Display for Expr {
                 write!(f, " % ")?;
                 self.fmt_subexpr(b, f)
             }
+
+            Expr::IfElse(c, a, b) => {
+                write!(f, "if ")?;
+                self.fmt_subexpr(c, f)?;
+                write!(f, " then ")?;
+                self.fmt_subexpr(a, f)?;
+                write!(f, " else ")?;
+                self.fmt_subexpr(b, f)
+            }
+
+            Expr::Call(func, args) => fmt_call(func.name, args, f),
+
+            Expr::StatefulCall(func, args) => fmt_call(func.name, args, f),
+
+            Expr::Timer(kind, e, delay) => {
+                write!(f, "{}({}, ", kind, e)?;
+                fmt_duration(delay, f)?;
+                write!(f, ")")
+            }
+
+            Expr::Format(text, args) => {
+                let text = text
+                    .iter()
+                    .map(|s| s.replace('{', "{{").replace('}', "}}"))
+                    .collect::<Vec<_>>()
+                    .join("{}");
+
+                write!(f, "format(\"{}\"", text)?;
+                for arg in args {
+                    write!(f, ", {}", arg)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
 
+// Writes a duration using the largest unit that represents it
+// exactly.
+
+fn fmt_duration(
+    d: &chrono::Duration,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    let ms = d.num_milliseconds();
+
+    if ms % 3_600_000 == 0 {
+        write!(f, "{}h", ms / 3_600_000)
+    } else if ms % 60_000 == 0 {
+        write!(f, "{}m", ms / 60_000)
+    } else if ms % 1_000 == 0 {
+        write!(f, "{}s", ms / 1_000)
+    } else {
+        write!(f, "{}ms", ms)
+    }
+}
+
+fn fmt_call(
+    name: &str,
+    args: &[Expr],
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write!(f, "{}(", name)?;
+    for (idx, arg) in args.iter().enumerate() {
+        if idx > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", arg)?;
+    }
+    write!(f, ")")
+}
+
 // This is the "environment" of a compile. The first element is a list
 // of names associated with devices that are to be read. The second
 // element is a list of names associated with devices to be set. The
@@ -321,7 +1388,43 @@ impl fmt::Display for Expr {
 
 type Env<'a> = (&'a [String], &'a [String]);
 
-#[derive(Debug, PartialEq)]
+// Holds the names defined by `let` statements while an expression is
+// being parsed. Local definitions are stored in the inputs numbered
+// from `base` and can only be used by the expression that defines
+// them. `{cal:...}` values are looked up in `cal`.
+
+struct Scope<'a> {
+    env: &'a Env<'a>,
+    cal: &'a calendar::Calendar,
+    base: usize,
+    locals: RefCell<Vec<String>>,
+}
+
+impl Scope<'_> {
+    // Returns the input index of a local definition or an entry of
+    // the environment's inputs.
+
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.locals
+            .borrow()
+            .iter()
+            .position(|v| v == name)
+            .map(|idx| self.base + idx)
+            .or_else(|| self.env.0.iter().position(|v| v == name))
+    }
+
+    // Reserves an input for a local definition and returns its
+    // index.
+
+    fn add_local(&self, name: String) -> usize {
+        let mut locals = self.locals.borrow_mut();
+
+        locals.push(name);
+        self.base + locals.len() - 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Program(pub Expr, pub usize);
 
 impl Program {
@@ -329,10 +1432,69 @@ impl Program {
         Program(optimize(self.0), self.1)
     }
 
+    // Compiles an expression that sets one output and doesn't
+    // have any `let` statements.
+
     pub fn compile(s: &str, env: &Env) -> Result<Program> {
+        Program::compile_with(s, env, &calendar::Calendar::default())
+    }
+
+    // Like `compile()` but the expression can use the events of the
+    // calendar.
+
+    pub fn compile_with(
+        s: &str,
+        env: &Env,
+        cal: &calendar::Calendar,
+    ) -> Result<Program> {
+        match Block::compile(s, env, cal, env.0.len())? {
+            Block {
+                lets,
+                expr,
+                outputs,
+            } if lets.is_empty() => match outputs[..] {
+                [idx] => Ok(Program(expr, idx)),
+                _ => Err(Error::ParseError(format!(
+                    "{}\n    only one output can be set",
+                    s
+                ))),
+            },
+            _ => Err(Error::ParseError(format!(
+                "{}\n    'let' can't be used here",
+                s
+            ))),
+        }
+    }
+}
+
+// The compiled form of an entry in a logic node's `exprs`. The
+// programs in `lets` compute the `let` definitions, in order, into
+// inputs numbered from the `base` given to `Block::compile`. The
+// value of `expr` is sent to each output in `outputs`.
+
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    pub lets: Vec<Program>,
+    pub expr: Expr,
+    pub outputs: Vec<usize>,
+}
+
+impl Block {
+    pub fn compile(
+        s: &str,
+        env: &Env,
+        cal: &calendar::Calendar,
+        base: usize,
+    ) -> Result<Block> {
         let lexerdef = logic_l::lexerdef();
         let lexer = lexerdef.lexer(s);
-        let (res, errs) = logic_y::parse(&lexer, env);
+        let scope = Scope {
+            env,
+            cal,
+            base,
+            locals: RefCell::new(vec![]),
+        };
+        let (res, errs) = logic_y::parse(&lexer, &scope);
 
         res.unwrap_or_else(|| {
             let res = errs.iter().fold(s.to_owned(), |mut acc, e| {
@@ -351,70 +1513,27 @@ impl fmt::Display for Program {
     }
 }
 
-// Evaluates an expression and returns the computed value. If the
-// function returns `None`, there was an error in the expression and
-// it won't get computed ever again. The log will have a message
-// indicating what the error was.
+// Returns whether the local date is one of the calendar's dates.
 
-pub fn eval(
-    e: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
-) -> Option<device::Value> {
-    match e {
-        // Literals hold actual `device::Values`, so simply return it.
-        Expr::Lit(v) => Some(v.clone()),
-
-        Expr::Var(n) => eval_as_var(*n, inp),
-
-        Expr::TimeVal(_, _, f) => Some(f(time)),
-
-        Expr::SolarVal(_, f) => solar.map(f),
-
-        Expr::Not(ref e) => eval_as_not_expr(e, inp, time, solar),
-
-        Expr::Or(ref a, ref b) => eval_as_or_expr(a, b, inp, time, solar),
-
-        Expr::And(ref a, ref b) => eval_as_and_expr(a, b, inp, time, solar),
-
-        Expr::Eq(ref a, ref b) => eval_as_eq_expr(a, b, inp, time, solar),
-
-        Expr::Lt(ref a, ref b) => eval_as_lt_expr(a, b, inp, time, solar),
-
-        Expr::LtEq(ref a, ref b) => eval_as_lteq_expr(a, b, inp, time, solar),
-
-        Expr::Add(ref a, ref b) => eval_as_add_expr(a, b, inp, time, solar),
-
-        Expr::Sub(ref a, ref b) => eval_as_sub_expr(a, b, inp, time, solar),
-
-        Expr::Mul(ref a, ref b) => eval_as_mul_expr(a, b, inp, time, solar),
-
-        Expr::Div(ref a, ref b) => eval_as_div_expr(a, b, inp, time, solar),
-
-        Expr::Rem(ref a, ref b) => eval_as_rem_expr(a, b, inp, time, solar),
-    }
+pub fn eval_cal(dates: &calendar::Dates, time: &tod::Info) -> device::Value {
+    device::Value::Bool(dates.contains(time.1.date_naive()))
 }
 
-// Returns the latest value of the variable.
+// Returns whether the local time is in a minute given by the
+// schedule.
 
-fn eval_as_var(
-    idx: usize,
-    inp: &[Option<device::Value>],
-) -> Option<device::Value> {
-    inp[idx].clone()
+pub fn eval_schedule(
+    sched: &schedule::Schedule,
+    time: &tod::Info,
+) -> device::Value {
+    device::Value::Bool(sched.matches(&time.1.naive_local()))
 }
 
 // Evaluates the subexpression of a NOT expression. It only accepts
 // booleans as values and simply complements the value.
 
-fn eval_as_not_expr(
-    e: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
-) -> Option<device::Value> {
-    match eval(e, inp, time, solar) {
+fn eval_not(v: Option<device::Value>) -> Option<device::Value> {
+    match v {
         Some(device::Value::Bool(v)) => Some(device::Value::Bool(!v)),
         Some(v) => {
             error!("NOT expression contains non-boolean value : {}", &v);
@@ -424,83 +1543,81 @@ fn eval_as_not_expr(
     }
 }
 
-// OR expressions. If the first subexpression is `true`, the second
-// subexpression isn't evaluated.
-fn eval_as_or_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
-) -> Option<device::Value> {
-    match eval(a, inp, time, solar) {
-        v @ Some(device::Value::Bool(true)) => v,
-        Some(device::Value::Bool(false)) => match eval(b, inp, time, solar) {
-            v @ Some(device::Value::Bool(_)) => v,
-            Some(v) => {
-                error!("OR expression contains non-boolean argument: {}", &v);
-                None
-            }
-            None => None,
-        },
+// Looks at the first operand of an OR expression. If it determines
+// the result, the result is returned and the second operand doesn't
+// need to be evaluated.
+
+fn or_shortcut(a: &Option<device::Value>) -> Option<Option<device::Value>> {
+    match a {
+        Some(device::Value::Bool(true)) => Some(a.clone()),
+        Some(device::Value::Bool(false)) | None => None,
         Some(v) => {
+            error!("OR expression contains non-boolean argument: {}", v);
+            Some(None)
+        }
+    }
+}
+
+// Computes the result of an OR expression whose first operand didn't
+// determine the result. If the first operand doesn't have a value,
+// the result is only known when the second operand is `true`.
+
+fn eval_or(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
+) -> Option<device::Value> {
+    match (a, b) {
+        (Some(_), v @ Some(device::Value::Bool(_))) => v,
+        (None, v @ Some(device::Value::Bool(true))) => v,
+        (_, Some(device::Value::Bool(false)) | None) => None,
+        (_, Some(v)) => {
             error!("OR expression contains non-boolean argument: {}", &v);
             None
         }
-        None => match eval(b, inp, time, solar) {
-            v @ Some(device::Value::Bool(true)) => v,
-            Some(device::Value::Bool(false)) | None => None,
-            Some(v) => {
-                error!("OR expression contains non-boolean argument: {}", &v);
-                None
-            }
-        },
     }
 }
 
-// AND expressions. If the first subexpression is `false`, the second
-// subexpression isn't evaluated.
-fn eval_as_and_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
-) -> Option<device::Value> {
-    match eval(a, inp, time, solar) {
-        v @ Some(device::Value::Bool(false)) => v,
-        Some(device::Value::Bool(true)) => match eval(b, inp, time, solar) {
-            v @ Some(device::Value::Bool(_)) => v,
-            Some(v) => {
-                error!("AND expression contains non-boolean argument: {}", &v);
-                None
-            }
-            None => None,
-        },
+// Looks at the first operand of an AND expression. If it determines
+// the result, the result is returned and the second operand doesn't
+// need to be evaluated.
+
+fn and_shortcut(a: &Option<device::Value>) -> Option<Option<device::Value>> {
+    match a {
+        Some(device::Value::Bool(false)) => Some(a.clone()),
+        Some(device::Value::Bool(true)) | None => None,
         Some(v) => {
+            error!("AND expression contains non-boolean argument: {}", v);
+            Some(None)
+        }
+    }
+}
+
+// Computes the result of an AND expression whose first operand
+// didn't determine the result. If the first operand doesn't have a
+// value, the result is only known when the second operand is
+// `false`.
+
+fn eval_and(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
+) -> Option<device::Value> {
+    match (a, b) {
+        (Some(_), v @ Some(device::Value::Bool(_))) => v,
+        (None, v @ Some(device::Value::Bool(false))) => v,
+        (_, Some(device::Value::Bool(true)) | None) => None,
+        (_, Some(v)) => {
             error!("AND expression contains non-boolean argument: {}", &v);
             None
         }
-        None => match eval(b, inp, time, solar) {
-            v @ Some(device::Value::Bool(false)) => v,
-            Some(device::Value::Bool(true)) | None => None,
-            Some(v) => {
-                error!("AND expression contains non-boolean argument: {}", &v);
-                None
-            }
-        },
     }
 }
 
 // EQ expressions. Both expressions must be of the same type.
-fn eval_as_eq_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_eq(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Bool(a)), Some(device::Value::Bool(b))) => {
             Some(device::Value::Bool(a == b))
         }
@@ -519,6 +1636,9 @@ fn eval_as_eq_expr(
         (Some(device::Value::Str(a)), Some(device::Value::Str(b))) => {
             Some(device::Value::Bool(a == b))
         }
+        (Some(device::Value::Color(a)), Some(device::Value::Color(b))) => {
+            Some(device::Value::Bool(a == b))
+        }
         (Some(a), Some(b)) => {
             error!("cannot compare {} and {} for equality", &a, &b);
             None
@@ -528,14 +1648,11 @@ fn eval_as_eq_expr(
 }
 
 // LT expressions. Both expressions must be of the same type.
-fn eval_as_lt_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_lt(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Int(a)), Some(device::Value::Int(b))) => {
             Some(device::Value::Bool(a < b))
         }
@@ -560,14 +1677,11 @@ fn eval_as_lt_expr(
 }
 
 // LT_EQ expressions. Both expressions must be of the same type.
-fn eval_as_lteq_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_lteq(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Int(a)), Some(device::Value::Int(b))) => {
             Some(device::Value::Bool(a <= b))
         }
@@ -592,14 +1706,11 @@ fn eval_as_lteq_expr(
 }
 
 // ADD expressions.
-fn eval_as_add_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_add(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Int(a)), Some(device::Value::Int(b))) => {
             Some(device::Value::Int(a + b))
         }
@@ -624,6 +1735,9 @@ fn eval_as_add_expr(
         (Some(device::Value::Flt(a)), Some(device::Value::Int(b))) => {
             Some(device::Value::Flt(a + b as f64))
         }
+        (Some(device::Value::Str(a)), Some(device::Value::Str(b))) => {
+            to_str_value(format!("{}{}", a, b))
+        }
         (Some(a), Some(b)) => {
             error!("cannot add {} and {} types together", &a, &b);
             None
@@ -633,14 +1747,11 @@ fn eval_as_add_expr(
 }
 
 // SUB expressions.
-fn eval_as_sub_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_sub(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Int(a)), Some(device::Value::Int(b))) => {
             Some(device::Value::Int(a - b))
         }
@@ -674,14 +1785,11 @@ fn eval_as_sub_expr(
 }
 
 // MUL expressions.
-fn eval_as_mul_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_mul(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Int(a)), Some(device::Value::Int(b))) => {
             Some(device::Value::Int(a * b))
         }
@@ -715,14 +1823,11 @@ fn eval_as_mul_expr(
 }
 
 // DIV expressions.
-fn eval_as_div_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_div(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Int(a)), Some(device::Value::Int(b)))
             if b != 0 =>
         {
@@ -752,14 +1857,11 @@ fn eval_as_div_expr(
 }
 
 // REM expressions.
-fn eval_as_rem_expr(
-    a: &Expr,
-    b: &Expr,
-    inp: &[Option<device::Value>],
-    time: &tod::Info,
-    solar: Option<&solar::Info>,
+fn eval_rem(
+    a: Option<device::Value>,
+    b: Option<device::Value>,
 ) -> Option<device::Value> {
-    match (eval(a, inp, time, solar), eval(b, inp, time, solar)) {
+    match (a, b) {
         (Some(device::Value::Int(a)), Some(device::Value::Int(b))) if b > 0 => {
             Some(device::Value::Int(a % b))
         }
@@ -784,569 +1886,2489 @@ fn eval_as_rem_expr(
     }
 }
 
-// This function takes an expression and tries to reduce it.
-
-pub fn optimize(e: Expr) -> Expr {
-    match e {
-        // Look for optimizations with expressions starting with NOT.
-        Expr::Not(ref ne) => match **ne {
-            // If the sub-expression is also a NOT expression. If so,
-            // we throw them both away.
-            Expr::Not(ref e) => optimize(*e.clone()),
-
-            // If the subexpression is either `true` or `false`,
-            // return the complement.
-            Expr::Lit(ref v) => match v {
-                device::Value::Bool(false) => {
-                    Expr::Lit(device::Value::Bool(true))
-                }
-                device::Value::Bool(true) => {
-                    Expr::Lit(device::Value::Bool(false))
-                }
-                _ => e,
-            },
-            _ => e,
-        },
+// Checks the condition of an IF expression. Returns `None` if it
+// doesn't have a boolean value.
 
-        Expr::And(ref a, ref b) => {
-            match (optimize(*a.clone()), optimize(*b.clone())) {
-                (v @ Expr::Lit(device::Value::Bool(false)), _)
-                | (_, v @ Expr::Lit(device::Value::Bool(false))) => v,
-                (
-                    v @ Expr::Lit(device::Value::Bool(true)),
-                    Expr::Lit(device::Value::Bool(true)),
-                ) => v,
-                (Expr::Lit(device::Value::Bool(true)), e)
-                | (e, Expr::Lit(device::Value::Bool(true))) => e,
-                _ => e,
-            }
+fn condition(v: Option<device::Value>) -> Option<bool> {
+    match v {
+        Some(device::Value::Bool(v)) => Some(v),
+        Some(v) => {
+            error!("IF expression contains non-boolean condition: {}", &v);
+            None
         }
+        None => None,
+    }
+}
 
-        Expr::Or(ref a, ref b) => {
-            match (optimize(*a.clone()), optimize(*b.clone())) {
-                (v @ Expr::Lit(device::Value::Bool(true)), _)
-                | (_, v @ Expr::Lit(device::Value::Bool(true))) => v,
-                (
-                    v @ Expr::Lit(device::Value::Bool(false)),
-                    Expr::Lit(device::Value::Bool(false)),
-                ) => v,
-                (Expr::Lit(device::Value::Bool(false)), e)
-                | (e, Expr::Lit(device::Value::Bool(false))) => e,
-                _ => e,
-            }
-        }
+// Makes sure a string that was built by an expression isn't too
+// long.
 
-        _ => e,
+fn to_str_value(s: String) -> Option<device::Value> {
+    if s.len() <= MAX_STR_LEN {
+        Some(device::Value::Str(s.into()))
+    } else {
+        error!(
+            "string result is {} bytes, which is longer than {}",
+            s.len(),
+            MAX_STR_LEN
+        );
+        None
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use drmem_api::device;
-    use palette::LinSrgba;
-    use std::sync::Arc;
+// Builds the result of a format expression from the text and the
+// values of the arguments.
 
-    fn to_expr(expr: &str) -> Expr {
-        let env: Env = (
-            &[String::from("a"), String::from("b")],
-            &[String::from("c")],
-        );
+fn format_values(
+    text: &[String],
+    args: &[device::Value],
+) -> Option<device::Value> {
+    let mut result = text[0].clone();
+
+    for (arg, s) in args.iter().zip(&text[1..]) {
+        match arg {
+            device::Value::Str(v) => result.push_str(v),
+            device::Value::Color(v) => result.push_str(&format!(
+                "#{:02x}{:02x}{:02x}",
+                v.red, v.green, v.blue
+            )),
+            v => result.push_str(&v.to_string()),
+        }
+        result.push_str(s);
 
-        match Program::compile(&format!("{} -> {{c}}", expr), &env) {
-            Ok(Program(expr, _)) => expr,
-            Err(_) => panic!("couldn't parse {}", expr),
+        if result.len() > MAX_STR_LEN {
+            break;
         }
     }
+    to_str_value(result)
+}
 
-    #[test]
-    fn test_parser() {
-        let env: Env = (
-            &[String::from("switch"), String::from("on_time")],
-            &[String::from("bulb")],
-        );
+// Computes the result of a timer function given the value of its
+// argument and the current time. The state is updated.
 
-        assert!(Program::compile("", &env).is_err());
-        assert!(Program::compile("{switch -> {bulb}", &env).is_err());
-        assert!(Program::compile("switch} -> {bulb}", &env).is_err());
+fn timer_step(
+    kind: TimerKind,
+    v: Option<device::Value>,
+    delay: &chrono::Duration,
+    state: &mut TimerState,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> Option<device::Value> {
+    let now = *now;
 
-        // Test for defined categories and fields.
+    match v {
+        Some(device::Value::Bool(v)) => {
+            let (next, result) = match (kind, v, *state) {
+                (TimerKind::Held, true, TimerState::Since(t)) => {
+                    (TimerState::Since(t), now - t >= *delay)
+                }
+                (TimerKind::Held, true, _) => (TimerState::Since(now), false),
+                (TimerKind::Held, false, _) => (TimerState::Idle, false),
+                (TimerKind::DelayOff, true, _) => (TimerState::Active, true),
+                (TimerKind::DelayOff, false, TimerState::Active) => {
+                    (TimerState::Since(now), true)
+                }
+                (TimerKind::DelayOff, false, TimerState::Since(t))
+                    if now - t < *delay =>
+                {
+                    (TimerState::Since(t), true)
+                }
+                (TimerKind::DelayOff, false, _) => (TimerState::Idle, false),
+            };
 
-        assert!(Program::compile("{utc:second} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{utc:minute} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{utc:hour} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{utc:day} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{utc:month} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{utc:year} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{utc:DOW} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{utc:DOY} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{local:second} -> {bulb}", &env).is_ok());
-        assert!(Program::compile("{local:minute} -> {bulb}", &env).is_ok());
+            *state = next;
+            Some(device::Value::Bool(result))
+        }
+        Some(v) => {
+            error!("{}() requires a boolean, got {}", kind, &v);
+            None
+        }
+        None => None,
+    }
+}
+
+// The result of applying the Int/Flt promotion rules, used by the
+// arithmetic operators, to a pair of values. If both are integers,
+// they stay integers. If either is a float, both become floats.
+
+enum NumPair {
+    Int(i32, i32),
+    Flt(f64, f64),
+}
+
+fn promote(a: &device::Value, b: &device::Value) -> Option<NumPair> {
+    match (a, b) {
+        (device::Value::Int(a), device::Value::Int(b)) => {
+            Some(NumPair::Int(*a, *b))
+        }
+        (device::Value::Flt(a), device::Value::Flt(b)) => {
+            Some(NumPair::Flt(*a, *b))
+        }
+        (device::Value::Int(a), device::Value::Flt(b)) => {
+            Some(NumPair::Flt(*a as f64, *b))
+        }
+        (device::Value::Flt(a), device::Value::Int(b)) => {
+            Some(NumPair::Flt(*a, *b as f64))
+        }
+        _ => None,
+    }
+}
+
+fn eval_min(args: &[device::Value]) -> Option<device::Value> {
+    match promote(&args[0], &args[1]) {
+        Some(NumPair::Int(a, b)) => Some(device::Value::Int(a.min(b))),
+        Some(NumPair::Flt(a, b)) => Some(device::Value::Flt(a.min(b))),
+        None => {
+            error!("cannot compute min of {} and {}", &args[0], &args[1]);
+            None
+        }
+    }
+}
+
+fn eval_max(args: &[device::Value]) -> Option<device::Value> {
+    match promote(&args[0], &args[1]) {
+        Some(NumPair::Int(a, b)) => Some(device::Value::Int(a.max(b))),
+        Some(NumPair::Flt(a, b)) => Some(device::Value::Flt(a.max(b))),
+        None => {
+            error!("cannot compute max of {} and {}", &args[0], &args[1]);
+            None
+        }
+    }
+}
+
+fn eval_abs(args: &[device::Value]) -> Option<device::Value> {
+    match args[0] {
+        device::Value::Int(v) => v.checked_abs().map(device::Value::Int),
+        device::Value::Flt(v) => Some(device::Value::Flt(v.abs())),
+        ref v => {
+            error!("cannot compute absolute value of {}", v);
+            None
+        }
+    }
+}
+
+// Limits the first argument to the range specified by the other two.
+// If the lower limit is greater than the upper limit, there is no
+// valid result.
+
+fn eval_clamp(args: &[device::Value]) -> Option<device::Value> {
+    let valid = match promote(&args[1], &args[2]) {
+        Some(NumPair::Int(lo, hi)) => lo <= hi,
+        Some(NumPair::Flt(lo, hi)) => lo <= hi,
+        None => false,
+    };
+
+    if valid {
+        let v = eval_min(&[args[0].clone(), args[2].clone()])?;
+
+        eval_max(&[v, args[1].clone()])
+    } else {
+        error!("cannot clamp to the range {} to {}", &args[1], &args[2]);
+        None
+    }
+}
+
+// Converts a rounded, floating point value to an integer. If it can't
+// be represented as an `i32`, there is no result.
+
+fn flt_to_int(v: f64) -> Option<device::Value> {
+    if v >= i32::MIN as f64 && v <= i32::MAX as f64 {
+        Some(device::Value::Int(v as i32))
+    } else {
+        error!("{} cannot be represented as an integer", v);
+        None
+    }
+}
+
+fn eval_round(args: &[device::Value]) -> Option<device::Value> {
+    match args[0] {
+        device::Value::Int(v) => Some(device::Value::Int(v)),
+        device::Value::Flt(v) => flt_to_int(v.round()),
+        ref v => {
+            error!("cannot round {}", v);
+            None
+        }
+    }
+}
+
+fn eval_floor(args: &[device::Value]) -> Option<device::Value> {
+    match args[0] {
+        device::Value::Int(v) => Some(device::Value::Int(v)),
+        device::Value::Flt(v) => flt_to_int(v.floor()),
+        ref v => {
+            error!("cannot compute floor of {}", v);
+            None
+        }
+    }
+}
+
+fn eval_ceil(args: &[device::Value]) -> Option<device::Value> {
+    match args[0] {
+        device::Value::Int(v) => Some(device::Value::Int(v)),
+        device::Value::Flt(v) => flt_to_int(v.ceil()),
+        ref v => {
+            error!("cannot compute ceiling of {}", v);
+            None
+        }
+    }
+}
+
+// Converts a numeric value into a color component. Floating point
+// values are rounded.
+
+fn to_component(v: &device::Value) -> Option<u8> {
+    match as_flt(v) {
+        Some(c) if (0.0..=255.0).contains(&c.round()) => Some(c.round() as u8),
+        Some(_) => {
+            error!("color component {} is out of range (0 - 255)", v);
+            None
+        }
+        None => {
+            error!("color component must be numeric, got {}", v);
+            None
+        }
+    }
+}
+
+fn eval_rgb(args: &[device::Value]) -> Option<device::Value> {
+    Some(device::Value::Color(LinSrgba::new(
+        to_component(&args[0])?,
+        to_component(&args[1])?,
+        to_component(&args[2])?,
+        255,
+    )))
+}
+
+// Builds a color from the HSV color space. Like named colors, the
+// HSV values are in the sRGB space, so they're converted to linear
+// RGB.
+
+fn eval_hsv(args: &[device::Value]) -> Option<device::Value> {
+    match (as_flt(&args[0]), as_flt(&args[1]), as_flt(&args[2])) {
+        (Some(h), Some(s), Some(v))
+            if (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&v) =>
+        {
+            let hsv = Hsv::new(h as f32, s as f32, v as f32);
+
+            Some(device::Value::Color(
+                Srgb::from_color(hsv)
+                    .into_linear()
+                    .into_format::<u8>()
+                    .with_alpha(255u8),
+            ))
+        }
+        (Some(_), Some(_), Some(_)) => {
+            error!("hsv() saturation and value must be from 0.0 to 1.0");
+            None
+        }
+        _ => {
+            error!(
+                "hsv() requires numeric arguments, got {}, {}, {}",
+                &args[0], &args[1], &args[2]
+            );
+            None
+        }
+    }
+}
+
+// Mixes two colors (and their alpha channels.) The factor is limited
+// to the range 0.0 to 1.0.
+
+fn eval_blend(args: &[device::Value]) -> Option<device::Value> {
+    match (&args[0], &args[1], as_flt(&args[2])) {
+        (device::Value::Color(a), device::Value::Color(b), Some(t)) => {
+            let a: LinSrgba<f32> = a.into_format();
+            let b: LinSrgba<f32> = b.into_format();
+
+            Some(device::Value::Color(
+                a.mix(b, t.clamp(0.0, 1.0) as f32).into_format(),
+            ))
+        }
+        _ => {
+            error!(
+                "blend() requires two colors and a number, got {}, {}, {}",
+                &args[0], &args[1], &args[2]
+            );
+            None
+        }
+    }
+}
+
+// Scales the red, green and blue components of a color. The
+// components are linear, so this scales the light output. The alpha
+// channel isn't affected.
+
+fn eval_brightness(args: &[device::Value]) -> Option<device::Value> {
+    match (&args[0], as_flt(&args[1])) {
+        (device::Value::Color(c), Some(k)) if k >= 0.0 => {
+            let c: LinSrgba<f32> = c.into_format();
+            let k = k as f32;
+
+            Some(device::Value::Color(
+                LinSrgba::new(
+                    (c.red * k).min(1.0),
+                    (c.green * k).min(1.0),
+                    (c.blue * k).min(1.0),
+                    c.alpha,
+                )
+                .into_format(),
+            ))
+        }
+        (device::Value::Color(_), Some(k)) => {
+            error!("brightness() requires a non-negative factor, got {}", k);
+            None
+        }
+        _ => {
+            error!(
+                "brightness() requires a color and a number, got {}, {}",
+                &args[0], &args[1]
+            );
+            None
+        }
+    }
+}
+
+// Extracts a component from a color.
+
+fn color_component(
+    v: &device::Value,
+    f: fn(&LinSrgba<u8>) -> u8,
+) -> Option<device::Value> {
+    if let device::Value::Color(c) = v {
+        Some(device::Value::Int(f(c) as i32))
+    } else {
+        error!("cannot get a color component from {}", v);
+        None
+    }
+}
+
+fn eval_red(args: &[device::Value]) -> Option<device::Value> {
+    color_component(&args[0], |c| c.red)
+}
+
+fn eval_green(args: &[device::Value]) -> Option<device::Value> {
+    color_component(&args[0], |c| c.green)
+}
+
+fn eval_blue(args: &[device::Value]) -> Option<device::Value> {
+    color_component(&args[0], |c| c.blue)
+}
+
+fn eval_alpha(args: &[device::Value]) -> Option<device::Value> {
+    color_component(&args[0], |c| c.alpha)
+}
+
+// String functions.
+
+fn eval_len(args: &[device::Value]) -> Option<device::Value> {
+    if let device::Value::Str(s) = &args[0] {
+        i32::try_from(s.chars().count())
+            .ok()
+            .map(device::Value::Int)
+    } else {
+        error!("len() requires a string, got {}", &args[0]);
+        None
+    }
+}
+
+// Applies a test to two string arguments.
+
+fn str_test(
+    name: &str,
+    args: &[device::Value],
+    f: fn(&str, &str) -> bool,
+) -> Option<device::Value> {
+    if let (device::Value::Str(a), device::Value::Str(b)) = (&args[0], &args[1])
+    {
+        Some(device::Value::Bool(f(a, b)))
+    } else {
+        error!(
+            "{}() requires two strings, got {} and {}",
+            name, &args[0], &args[1]
+        );
+        None
+    }
+}
+
+fn eval_contains(args: &[device::Value]) -> Option<device::Value> {
+    str_test("contains", args, |a, b| a.contains(b))
+}
+
+fn eval_starts_with(args: &[device::Value]) -> Option<device::Value> {
+    str_test("starts_with", args, |a, b| a.starts_with(b))
+}
+
+fn eval_ends_with(args: &[device::Value]) -> Option<device::Value> {
+    str_test("ends_with", args, |a, b| a.ends_with(b))
+}
+
+// Returns `true` for the one evaluation where the argument changes
+// from `false` to `true`. The first evaluation always returns `false`
+// since there's no previous value to compare against.
+
+fn eval_rising(
+    args: &[device::Value],
+    prev: &mut Option<bool>,
+) -> Option<device::Value> {
+    match args[0] {
+        device::Value::Bool(v) => {
+            let result = *prev == Some(false) && v;
+
+            *prev = Some(v);
+            Some(device::Value::Bool(result))
+        }
+        ref v => {
+            error!("rising() requires a boolean, got {}", v);
+            None
+        }
+    }
+}
+
+// Returns `true` for the one evaluation where the argument changes
+// from `true` to `false`.
+
+fn eval_falling(
+    args: &[device::Value],
+    prev: &mut Option<bool>,
+) -> Option<device::Value> {
+    match args[0] {
+        device::Value::Bool(v) => {
+            let result = *prev == Some(true) && !v;
+
+            *prev = Some(v);
+            Some(device::Value::Bool(result))
+        }
+        ref v => {
+            error!("falling() requires a boolean, got {}", v);
+            None
+        }
+    }
+}
+
+// A set/reset latch. The reset input has priority. The latch starts
+// out cleared.
+
+fn eval_latch(
+    args: &[device::Value],
+    state: &mut Option<bool>,
+) -> Option<device::Value> {
+    match (&args[0], &args[1]) {
+        (device::Value::Bool(set), device::Value::Bool(reset)) => {
+            let v = !*reset && (*set || state.unwrap_or(false));
+
+            *state = Some(v);
+            Some(device::Value::Bool(v))
+        }
+        (a, b) => {
+            error!("latch() requires booleans, got {} and {}", a, b);
+            None
+        }
+    }
+}
+
+fn as_flt(v: &device::Value) -> Option<f64> {
+    match v {
+        device::Value::Int(v) => Some(*v as f64),
+        device::Value::Flt(v) => Some(*v),
+        _ => None,
+    }
+}
+
+// Becomes `true` when the value goes above the upper limit and `false`
+// when it goes below the lower limit. While the value is between the
+// limits, the previous result is returned (`false`, if the value
+// starts out between the limits.)
+
+fn eval_hysteresis(
+    args: &[device::Value],
+    state: &mut Option<bool>,
+) -> Option<device::Value> {
+    match (as_flt(&args[0]), as_flt(&args[1]), as_flt(&args[2])) {
+        (Some(v), Some(lo), Some(hi)) if lo <= hi => {
+            let result = if v > hi {
+                true
+            } else if v < lo {
+                false
+            } else {
+                state.unwrap_or(false)
+            };
+
+            *state = Some(result);
+            Some(device::Value::Bool(result))
+        }
+        _ => {
+            error!(
+                "hysteresis() requires a value and a range, got {}, {}, {}",
+                &args[0], &args[1], &args[2]
+            );
+            None
+        }
+    }
+}
+
+// This function takes an expression and tries to reduce it.
+// Operations whose operands are all literals are computed here,
+// once, instead of every time the expression is evaluated.
+// Identities that only hold for some types, like `x * 1` (which
+// turns a boolean `x` into an integer), are only applied when the
+// type of `x` is known. The types of the inputs aren't used since a
+// device can report values of a different type later.
+
+pub fn optimize(e: Expr) -> Expr {
+    match e {
+        // Look for optimizations with expressions starting with NOT.
+        Expr::Not(e) => match optimize(*e) {
+            // If the sub-expression is also a NOT expression of a
+            // boolean, we throw them both away.
+            Expr::Not(e) if is_bool(&e) => *e,
+
+            // If the subexpression is a literal, return the
+            // complement.
+            e => {
+                let v = literal(&e).and_then(|v| eval_not(Some(v)));
+
+                fold(Expr::Not(Box::new(e)), v)
+            }
+        },
+
+        // The second operand isn't evaluated when the first one
+        // determines the result, but the first one is always
+        // evaluated. Unless it's a boolean, it's an error, so it's
+        // only removed when it's known to be a boolean.
+        Expr::And(a, b) => match (optimize(*a), optimize(*b)) {
+            (v @ Expr::Lit(device::Value::Bool(false)), _) => v,
+            (a, v @ Expr::Lit(device::Value::Bool(false))) if is_bool(&a) => v,
+            (
+                v @ Expr::Lit(device::Value::Bool(true)),
+                Expr::Lit(device::Value::Bool(true)),
+            ) => v,
+            (Expr::Lit(device::Value::Bool(true)), e)
+            | (e, Expr::Lit(device::Value::Bool(true)))
+                if is_bool(&e) =>
+            {
+                e
+            }
+            (a, b) => Expr::And(Box::new(a), Box::new(b)),
+        },
+
+        Expr::Or(a, b) => match (optimize(*a), optimize(*b)) {
+            (v @ Expr::Lit(device::Value::Bool(true)), _) => v,
+            (a, v @ Expr::Lit(device::Value::Bool(true))) if is_bool(&a) => v,
+            (
+                v @ Expr::Lit(device::Value::Bool(false)),
+                Expr::Lit(device::Value::Bool(false)),
+            ) => v,
+            (Expr::Lit(device::Value::Bool(false)), e)
+            | (e, Expr::Lit(device::Value::Bool(false)))
+                if is_bool(&e) =>
+            {
+                e
+            }
+            (a, b) => Expr::Or(Box::new(a), Box::new(b)),
+        },
+
+        Expr::Eq(a, b) => optimize_binary(*a, *b, Expr::Eq, eval_eq),
+        Expr::Lt(a, b) => optimize_binary(*a, *b, Expr::Lt, eval_lt),
+        Expr::LtEq(a, b) => optimize_binary(*a, *b, Expr::LtEq, eval_lteq),
+        Expr::Add(a, b) => optimize_binary(*a, *b, Expr::Add, eval_add),
+        Expr::Sub(a, b) => optimize_binary(*a, *b, Expr::Sub, eval_sub),
+        Expr::Mul(a, b) => optimize_binary(*a, *b, Expr::Mul, eval_mul),
+        Expr::Div(a, b) => optimize_binary(*a, *b, Expr::Div, eval_div),
+        Expr::Rem(a, b) => optimize_binary(*a, *b, Expr::Rem, eval_rem),
+
+        // If the condition reduces to a literal boolean, the IF
+        // expression can be replaced with the selected
+        // subexpression. Otherwise all three subexpressions get
+        // optimized. A NOT in the condition is removed by swapping
+        // the other two subexpressions.
+        Expr::IfElse(c, a, b) => match optimize(*c) {
+            Expr::Lit(device::Value::Bool(true)) => optimize(*a),
+            Expr::Lit(device::Value::Bool(false)) => optimize(*b),
+            Expr::Not(c) => optimize_if(*c, optimize(*b), optimize(*a)),
+            c => optimize_if(c, optimize(*a), optimize(*b)),
+        },
+
+        // Calls to built-in functions with literal arguments are
+        // computed now. Stateful functions and timers have to be
+        // called each time, so only their arguments are optimized.
+        Expr::Call(func, args) => {
+            let args: Vec<Expr> = args.into_iter().map(optimize).collect();
+            let v = literals(&args).and_then(|v| (func.eval)(&v));
+
+            fold(Expr::Call(func, args), v)
+        }
+
+        Expr::StatefulCall(func, args) => {
+            Expr::StatefulCall(func, args.into_iter().map(optimize).collect())
+        }
+
+        Expr::Timer(kind, e, delay) => {
+            Expr::Timer(kind, Box::new(optimize(*e)), delay)
+        }
+
+        Expr::Format(text, args) => {
+            let args: Vec<Expr> = args.into_iter().map(optimize).collect();
+            let v = literals(&args).and_then(|v| format_values(&text, &v));
+
+            fold(Expr::Format(text, args), v)
+        }
+
+        _ => e,
+    }
+}
+
+// Returns the value of a literal expression.
+
+fn literal(e: &Expr) -> Option<device::Value> {
+    if let Expr::Lit(v) = e {
+        Some(v.clone())
+    } else {
+        None
+    }
+}
+
+// Returns the values of a list of expressions, if they're all
+// literals.
+
+fn literals(args: &[Expr]) -> Option<Vec<device::Value>> {
+    args.iter().map(literal).collect()
+}
+
+// Replaces an expression with the value computed from its literal
+// operands. If the value couldn't be computed (dividing by zero, for
+// instance), the expression is kept so the error gets reported when
+// it's evaluated.
+
+fn fold(e: Expr, v: Option<device::Value>) -> Expr {
+    v.map_or(e, Expr::Lit)
+}
+
+// Returns the type of an expression, if it's known without knowing
+// the types of the inputs.
+
+fn known_type(e: &Expr) -> Option<Type> {
+    e.infer_type(&[]).ok().flatten()
+}
+
+// Returns `true` if the expression is always a boolean (or has no
+// value.)
+
+fn is_bool(e: &Expr) -> bool {
+    known_type(e) == Some(Type::Bool)
+}
+
+// Returns `true` if the expression is a numeric literal with the
+// value `v`.
+
+fn is_num_lit(e: &Expr, v: i32) -> bool {
+    match e {
+        Expr::Lit(device::Value::Int(n)) => *n == v,
+        Expr::Lit(device::Value::Flt(n)) => *n == v as f64,
+        _ => false,
+    }
+}
+
+// Returns `true` if using `x` in place of an arithmetic operation on
+// `x` and the literal `lit` results in the same type. An integer
+// operand, for instance, gets promoted when used with a float.
+
+fn keeps_type(x: &Expr, lit: &Expr) -> bool {
+    match (known_type(x), known_type(lit)) {
+        (Some(t), Some(lt)) => {
+            t.is_numeric() && Type::arith(t, lt, false) == Some(t)
+        }
+        _ => false,
+    }
+}
+
+// Complements a boolean expression.
+
+fn negate(e: Expr) -> Expr {
+    match e {
+        Expr::Not(e) if is_bool(&e) => *e,
+        e => Expr::Not(Box::new(e)),
+    }
+}
+
+// Optimizes the operands of a binary operation. If they're both
+// literals, the result is computed. Otherwise identities that
+// remove the operation are applied.
+
+fn optimize_binary(
+    a: Expr,
+    b: Expr,
+    op: fn(Box<Expr>, Box<Expr>) -> Expr,
+    f: fn(
+        Option<device::Value>,
+        Option<device::Value>,
+    ) -> Option<device::Value>,
+) -> Expr {
+    let a = optimize(a);
+    let b = optimize(b);
+
+    match literal(&a)
+        .zip(literal(&b))
+        .and_then(|(a, b)| f(Some(a), Some(b)))
+    {
+        Some(v) => Expr::Lit(v),
+        None => apply_identities(op(Box::new(a), Box::new(b))),
+    }
+}
+
+// Applies identities to a binary operation whose operands have
+// already been optimized.
+
+fn apply_identities(e: Expr) -> Expr {
+    match e {
+        // Adding or subtracting zero, or multiplying or dividing by
+        // one, doesn't change a number.
+        Expr::Add(a, b) | Expr::Sub(a, b)
+            if is_num_lit(&b, 0) && keeps_type(&a, &b) =>
+        {
+            *a
+        }
+        Expr::Add(a, b) if is_num_lit(&a, 0) && keeps_type(&b, &a) => *b,
+        Expr::Mul(a, b) | Expr::Div(a, b)
+            if is_num_lit(&b, 1) && keeps_type(&a, &b) =>
+        {
+            *a
+        }
+        Expr::Mul(a, b) if is_num_lit(&a, 1) && keeps_type(&b, &a) => *b,
+
+        // Comparing a boolean with `true` is the boolean itself.
+        // Comparing it with `false` is its complement.
+        Expr::Eq(a, b) if is_bool(&a) && literal(&b).is_some() => match *b {
+            Expr::Lit(device::Value::Bool(true)) => *a,
+            Expr::Lit(device::Value::Bool(false)) => negate(*a),
+            b => Expr::Eq(a, Box::new(b)),
+        },
+        Expr::Eq(a, b) if is_bool(&b) && literal(&a).is_some() => match *a {
+            Expr::Lit(device::Value::Bool(true)) => *b,
+            Expr::Lit(device::Value::Bool(false)) => negate(*b),
+            a => Expr::Eq(Box::new(a), b),
+        },
+        e => e,
+    }
+}
+
+// Builds an IF expression from optimized subexpressions. An IF that
+// selects `true` or `false` is its boolean condition, or its
+// complement.
+
+fn optimize_if(c: Expr, a: Expr, b: Expr) -> Expr {
+    match (a, b) {
+        (
+            Expr::Lit(device::Value::Bool(true)),
+            Expr::Lit(device::Value::Bool(false)),
+        ) if is_bool(&c) => c,
+        (
+            Expr::Lit(device::Value::Bool(false)),
+            Expr::Lit(device::Value::Bool(true)),
+        ) if is_bool(&c) => negate(c),
+        (a, b) => Expr::IfElse(Box::new(c), Box::new(a), Box::new(b)),
+    }
+}
+
+// Subexpressions that can be computed once and shared. Inputs,
+// literals and time values are already cheap to get. Stateful
+// functions and timers can't be shared because each use keeps its
+// own state.
+
+fn is_shareable(e: &Expr) -> bool {
+    fn is_pure(e: &Expr) -> bool {
+        !matches!(e, Expr::StatefulCall(..) | Expr::Timer(..))
+            && e.subexprs().into_iter().all(is_pure)
+    }
+
+    !matches!(
+        e,
+        Expr::Lit(_)
+            | Expr::Var(_)
+            | Expr::TimeVal(..)
+            | Expr::SolarVal(..)
+            | Expr::LunarVal(..)
+            | Expr::CalVal(..)
+            | Expr::Schedule(..)
+    ) && is_pure(e)
+}
+
+// Adds every shareable subexpression of `e`, including `e` itself,
+// to `out`.
+
+fn shareable_subexprs<'a>(e: &'a Expr, out: &mut Vec<&'a Expr>) {
+    if is_shareable(e) {
+        out.push(e)
+    }
+    for s in e.subexprs() {
+        shareable_subexprs(s, out)
+    }
+}
+
+// Adds the shareable subexpressions of `e` that are evaluated every
+// time `e` is, including `e` itself, to `out`. The second operand of
+// `and` and `or`, and the branches of `if`, may be skipped so they're
+// left out.
+
+fn unconditional_subexprs<'a>(e: &'a Expr, out: &mut Vec<&'a Expr>) {
+    if is_shareable(e) {
+        out.push(e)
+    }
+    match e {
+        Expr::And(a, _) | Expr::Or(a, _) | Expr::IfElse(a, _, _) => {
+            unconditional_subexprs(a, out)
+        }
+        e => {
+            for s in e.subexprs() {
+                unconditional_subexprs(s, out)
+            }
+        }
+    }
+}
+
+// Replaces every occurrence of `target` in `e` with the input `var`.
+
+fn replace_subexpr(e: &mut Expr, target: &Expr, var: usize) {
+    if e == target {
+        *e = Expr::Var(var)
+    } else {
+        for s in e.subexprs_mut() {
+            replace_subexpr(s, target, var)
+        }
+    }
+}
+
+// Adds the index of every input used by `e` to `out`.
+
+fn inputs_used(e: &Expr, out: &mut Vec<usize>) {
+    match e {
+        Expr::Var(n) => out.push(*n),
+        e => e.subexprs().into_iter().for_each(|s| inputs_used(s, out)),
+    }
+}
+
+// Adds `progs[idx]` to `order`, after the programs that compute the
+// inputs it uses.
+
+fn order_program(
+    idx: usize,
+    progs: &mut [Option<Program>],
+    order: &mut Vec<Program>,
+) {
+    if let Some(prog) = progs[idx].take() {
+        let mut used = vec![];
+
+        inputs_used(&prog.0, &mut used);
+        for n in used {
+            if let Some(dep) = progs
+                .iter()
+                .position(|p| p.as_ref().is_some_and(|p| p.1 == n))
+            {
+                order_program(dep, progs, order)
+            }
+        }
+        order.push(prog)
+    }
+}
+
+// Finds subexpressions that appear more than once in a logic node's
+// definitions and expressions and arranges for each one to be
+// computed once per update. A shared subexpression is stored in a new
+// input, numbered from `n_slots`, and its uses are replaced with that
+// input. An expression that contains a whole definition uses the
+// definition's input instead.
+//
+// Since shared subexpressions are always computed, only the ones
+// that are always evaluated somewhere are shared. Otherwise, a
+// subexpression that only appears in branches that are skipped (like
+// a division guarded by an `if`) would be computed anyway.
+//
+// The inputs below `n_slots` that aren't computed by `defs` come
+// from devices. A definition may use the ones before it. The
+// definitions, with the shared subexpressions added, are returned in
+// the order they need to be evaluated along with the new number of
+// inputs.
+
+pub fn share_subexprs(
+    mut defs: Vec<Program>,
+    exprs: &mut [Program],
+    n_slots: usize,
+) -> (Vec<Program>, usize) {
+    let mut shared: Vec<Program> = vec![];
+
+    for def in &defs {
+        if is_shareable(&def.0) {
+            for Program(e, _) in exprs.iter_mut() {
+                replace_subexpr(e, &def.0, def.1)
+            }
+        }
+    }
+
+    // Repeatedly share the largest subexpression that's used more
+    // than once. Sharing the larger ones first means a shared
+    // subexpression can only use ones that get shared after it.
+
+    loop {
+        let mut all = vec![];
+        let mut always = vec![];
+
+        for Program(e, _) in defs.iter().chain(exprs.iter()).chain(&shared) {
+            shareable_subexprs(e, &mut all);
+            unconditional_subexprs(e, &mut always)
+        }
+
+        let target = always
+            .iter()
+            .filter(|e| all.iter().filter(|o| o == e).count() > 1)
+            .max_by_key(|e| e.size())
+            .map(|e| (*e).clone());
+
+        match target {
+            Some(target) => {
+                let var = n_slots + shared.len();
+
+                for Program(e, _) in defs
+                    .iter_mut()
+                    .chain(exprs.iter_mut())
+                    .chain(shared.iter_mut())
+                {
+                    replace_subexpr(e, &target, var)
+                }
+                shared.push(Program(target, var))
+            }
+            None => break,
+        }
+    }
+
+    // Evaluate every definition and shared subexpression after the
+    // ones it uses. Device inputs are always ready.
+
+    let total = n_slots + shared.len();
+    let mut progs: Vec<Option<Program>> =
+        shared.into_iter().rev().chain(defs).map(Some).collect();
+    let mut order = Vec::with_capacity(progs.len());
+
+    for idx in 0..progs.len() {
+        order_program(idx, &mut progs, &mut order)
+    }
+    (order, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drmem_api::device;
+    use palette::LinSrgba;
+    use std::sync::Arc;
+
+    // Expressions, and how they're displayed, when compiled with
+    // inputs `a` and `b` and outputs `b` and `c`. These are also
+    // used by the `bytecode` tests.
+
+    pub(super) const EXPRESSIONS: &[(&str, &str)] = &[
+        ("{a} -> {b}", "inp[0] -> out[0]"),
+        ("true -> {b}", "true -> out[0]"),
+        ("not true -> {b}", "not true -> out[0]"),
+        ("{a} and {b} -> {c}", "inp[0] and inp[1] -> out[1]"),
+        ("{a} or {b} -> {c}", "inp[0] or inp[1] -> out[1]"),
+        (
+            "{a} and {b} or true -> {c}",
+            "inp[0] and inp[1] or true -> out[1]",
+        ),
+        (
+            "{a} and ({b} or true) -> {c}",
+            "inp[0] and (inp[1] or true) -> out[1]",
+        ),
+        ("{a} = {b} -> {c}", "inp[0] = inp[1] -> out[1]"),
+        ("{a} < {b} -> {c}", "inp[0] < inp[1] -> out[1]"),
+        ("{a} <= {b} -> {c}", "inp[0] <= inp[1] -> out[1]"),
+        ("{a} + {b} -> {c}", "inp[0] + inp[1] -> out[1]"),
+        (
+            "{a} + {b} + {b} -> {c}",
+            "inp[0] + inp[1] + inp[1] -> out[1]",
+        ),
+        ("{a} - {b} -> {c}", "inp[0] - inp[1] -> out[1]"),
+        ("{a} * {b} -> {c}", "inp[0] * inp[1] -> out[1]"),
+        ("{a} / {b} -> {c}", "inp[0] / inp[1] -> out[1]"),
+        ("{a} % {b} -> {c}", "inp[0] % inp[1] -> out[1]"),
+        (
+            "{a} * 3 + {b} > 4 -> {c}",
+            "4 < inp[0] * 3 + inp[1] -> out[1]",
+        ),
+        (
+            "{a} * (3 + {b}) > 4 -> {c}",
+            "4 < inp[0] * (3 + inp[1]) -> out[1]",
+        ),
+        ("{utc:second} -> {c}", "{utc:second} -> out[1]"),
+        ("{utc:minute} -> {c}", "{utc:minute} -> out[1]"),
+        ("{utc:hour} -> {c}", "{utc:hour} -> out[1]"),
+        ("{utc:day} -> {c}", "{utc:day} -> out[1]"),
+        ("{utc:month} -> {c}", "{utc:month} -> out[1]"),
+        ("{utc:year} -> {c}", "{utc:year} -> out[1]"),
+        ("{utc:DOW} -> {c}", "{utc:DOW} -> out[1]"),
+        ("{utc:DOY} -> {c}", "{utc:DOY} -> out[1]"),
+        ("{local:second} -> {c}", "{local:second} -> out[1]"),
+        ("{local:minute} -> {c}", "{local:minute} -> out[1]"),
+        ("{local:hour} -> {c}", "{local:hour} -> out[1]"),
+        ("{local:day} -> {c}", "{local:day} -> out[1]"),
+        ("{local:month} -> {c}", "{local:month} -> out[1]"),
+        ("{local:year} -> {c}", "{local:year} -> out[1]"),
+        ("{local:DOW} -> {c}", "{local:DOW} -> out[1]"),
+        ("{local:DOY} -> {c}", "{local:DOY} -> out[1]"),
+        (
+            "{local:week_of_month} -> {c}",
+            "{local:week_of_month} -> out[1]",
+        ),
+        ("{cal:holiday} -> {c}", "{cal:holiday} -> out[1]"),
+        (
+            "if {a} then 1 else 2 -> {c}",
+            "if inp[0] then 1 else 2 -> out[1]",
+        ),
+        (
+            "if {a} and {b} then 1 + 2 else 3 * 4 -> {c}",
+            "if inp[0] and inp[1] then 1 + 2 else 3 * 4 -> out[1]",
+        ),
+        (
+            "if {a} then 1 else if {b} then 2 else 3 -> {c}",
+            "if inp[0] then 1 else if inp[1] then 2 else 3 -> out[1]",
+        ),
+        (
+            "(if {a} then 1 else 2) + 3 -> {c}",
+            "(if inp[0] then 1 else 2) + 3 -> out[1]",
+        ),
+        ("abs({a}) -> {c}", "abs(inp[0]) -> out[1]"),
+        (
+            "latch(rising({a}), {b} > 5) -> {c}",
+            "latch(rising(inp[0]), 5 < inp[1]) -> out[1]",
+        ),
+        (
+            "hysteresis({a}, 68, 72.5) -> {c}",
+            "hysteresis(inp[0], 68, 72.5) -> out[1]",
+        ),
+        (
+            "clamp({a} + 1, 0, min({b}, 10)) * 2 -> {c}",
+            "clamp(inp[0] + 1, 0, min(inp[1], 10)) * 2 -> out[1]",
+        ),
+        (
+            "blend(#red, rgb({a}, 0, 0), 0.5) -> {c}",
+            "blend(\"#ff0000\", rgb(inp[0], 0, 0), 0.5) -> out[1]",
+        ),
+        (
+            "format(\"{} is {{{}}}\", {a}, len({b})) -> {c}",
+            "format(\"{} is {{{}}}\", inp[0], len(inp[1])) -> out[1]",
+        ),
+        (
+            "contains({a} + \"x\", \"y\") -> {c}",
+            "contains(inp[0] + \"x\", \"y\") -> out[1]",
+        ),
+        ("held({a}, 5m) -> {c}", "held(inp[0], 5m) -> out[1]"),
+        ("held({a}, 90s) -> {c}", "held(inp[0], 90s) -> out[1]"),
+        ("held({a}, 1.5s) -> {c}", "held(inp[0], 1500ms) -> out[1]"),
+        (
+            "delay_off({a} and {b}, 2h) -> {c}",
+            "delay_off(inp[0] and inp[1], 2h) -> out[1]",
+        ),
+        ("{a} + 30s -> {c}", "inp[0] + 30 -> out[1]"),
+        (
+            "schedule(\"30 7 * * MON-FRI\") -> {c}",
+            "schedule(\"30 7 * * MON-FRI\") -> out[1]",
+        ),
+        (
+            "schedule(\"@hourly\") and {a} -> {c}",
+            "schedule(\"@hourly\") and inp[0] -> out[1]",
+        ),
+    ];
+
+    // Evaluates an expression once. Expressions are run by compiling
+    // them (see `Code`) so, for stateful functions and timers, each
+    // call starts with a new state.
+
+    fn eval(
+        e: &Expr,
+        inp: &[Option<device::Value>],
+        time: &tod::Info,
+        sky: Option<&Sky>,
+    ) -> Option<device::Value> {
+        Code::new(e).eval(inp, time, sky)
+    }
+
+    fn to_expr(expr: &str) -> Expr {
+        let env: Env = (
+            &[String::from("a"), String::from("b")],
+            &[String::from("c")],
+        );
+
+        match Program::compile(&format!("{} -> {{c}}", expr), &env) {
+            Ok(Program(expr, _)) => expr,
+            Err(_) => panic!("couldn't parse {}", expr),
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        let env: Env = (
+            &[String::from("switch"), String::from("on_time")],
+            &[String::from("bulb")],
+        );
+
+        assert!(Program::compile("", &env).is_err());
+        assert!(Program::compile("{switch -> {bulb}", &env).is_err());
+        assert!(Program::compile("switch} -> {bulb}", &env).is_err());
+
+        // Test for defined categories and fields.
+
+        assert!(Program::compile("{utc:second} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{utc:minute} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{utc:hour} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{utc:day} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{utc:month} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{utc:year} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{utc:DOW} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{utc:DOY} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{local:second} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{local:minute} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{local:hour} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{local:day} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{local:month} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{local:year} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{local:DOW} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{local:DOY} -> {bulb}", &env).is_ok());
+        assert!(
+            Program::compile("{local:week_of_month} -> {bulb}", &env).is_ok()
+        );
+        assert!(Program::compile("{utc:week_of_month} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{cal:holiday} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{solar:alt} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{solar:az} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{solar:ra} -> {bulb}", &env).is_ok());
         assert!(Program::compile("{solar:dec} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{solar:sunrise} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{solar:sunset} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{solar:civil_dawn} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{solar:civil_dusk} -> {bulb}", &env).is_ok());
+        assert!(
+            Program::compile("{solar:nautical_dawn} -> {bulb}", &env).is_ok()
+        );
+        assert!(
+            Program::compile("{solar:nautical_dusk} -> {bulb}", &env).is_ok()
+        );
+        assert!(Program::compile("{solar:astro_dawn} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{solar:astro_dusk} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{solar:is_day} -> {bulb}", &env).is_ok());
+        assert!(
+            Program::compile("{solar:minutes_to_sunrise} -> {bulb}", &env)
+                .is_ok()
+        );
+        assert!(
+            Program::compile("{solar:minutes_to_sunset} -> {bulb}", &env)
+                .is_ok()
+        );
+        assert!(Program::compile(
+            "{solar:minutes_to_civil_dawn} -> {bulb}",
+            &env
+        )
+        .is_ok());
+        assert!(Program::compile(
+            "{solar:minutes_to_civil_dusk} -> {bulb}",
+            &env
+        )
+        .is_ok());
+        assert!(Program::compile("{lunar:alt} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{lunar:az} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{lunar:illum} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{lunar:phase} -> {bulb}", &env).is_ok());
+        assert!(Program::compile("{lunar:ra} -> {bulb}", &env).is_err());
+
+        // Don't allow bad categories or fields.
+
+        assert!(Program::compile("{bad:second} -> {bulb}", &env).is_err());
+        assert!(Program::compile("{utc:bad} -> {bulb}", &env).is_err());
+        assert!(Program::compile("{local:bad} -> {bulb}", &env).is_err());
+
+        // Don't allow whitespace.
+
+        assert!(Program::compile("{ switch} -> {bulb}", &env).is_err());
+        assert!(Program::compile("{switch } -> {bulb}", &env).is_err());
+        assert!(Program::compile("{ utc:second} -> {bulb}", &env).is_err());
+        assert!(Program::compile("{utc :second} -> {bulb}", &env).is_err());
+        assert!(Program::compile("{utc: second} -> {bulb}", &env).is_err());
+        assert!(Program::compile("{utc:second } -> {bulb}", &env).is_err());
+
+        // Test proper compilations.
+
+        assert_eq!(
+            Program::compile("{switch} -> {bulb}", &env),
+            Ok(Program(Expr::Var(0), 0))
+        );
+
+        assert_eq!(
+            Program::compile("true -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Bool(true)), 0))
+        );
+        assert_eq!(
+            Program::compile("false -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Bool(false)), 0))
+        );
+
+        assert_eq!(
+            Program::compile("1 -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Int(1)), 0))
+        );
+        assert_eq!(
+            Program::compile("1. -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(1.0)), 0))
+        );
+        assert_eq!(
+            Program::compile("1.0 -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(1.0)), 0))
+        );
+        assert_eq!(
+            Program::compile("-1.0 -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(-1.0)), 0))
+        );
+        assert_eq!(
+            Program::compile("1.5 -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(1.5)), 0))
+        );
+        assert_eq!(
+            Program::compile("1.0e10 -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(1.0e10)), 0))
+        );
+        assert_eq!(
+            Program::compile("2.75e-10 -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(2.75e-10)), 0))
+        );
+        assert_eq!(
+            Program::compile("(((10))) -> {bulb}", &env),
+            Ok(Program(Expr::Lit(device::Value::Int(10)), 0))
+        );
+
+        assert!(Program::compile("#1 -> {bulb}", &env).is_err());
+        assert!(Program::compile("#12 -> {bulb}", &env).is_err());
+        assert!(Program::compile("#12345 -> {bulb}", &env).is_err());
+        assert_eq!(
+            Program::compile("#123 -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lit(device::Value::Color(LinSrgba::new(
+                    0x11, 0x22, 0x33, 255
+                ))),
+                0
+            ))
+        );
+        assert_eq!(
+            Program::compile("#1234 -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lit(device::Value::Color(LinSrgba::new(
+                    0x11, 0x22, 0x33, 0x44
+                ))),
+                0
+            ))
+        );
+        assert_eq!(
+            Program::compile("#7f8081 -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lit(device::Value::Color(LinSrgba::new(
+                    127, 128, 129, 255
+                ))),
+                0
+            ))
+        );
+        assert_eq!(
+            Program::compile("#7f808182 -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lit(device::Value::Color(LinSrgba::new(
+                    127, 128, 129, 130
+                ))),
+                0
+            ))
+        );
+        assert_eq!(
+            Program::compile("#7F80A0 -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lit(device::Value::Color(LinSrgba::new(
+                    127, 128, 160, 255
+                ))),
+                0
+            ))
+        );
+        assert_eq!(
+            Program::compile("#black -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lit(device::Value::Color(LinSrgba::new(0, 0, 0, 255))),
+                0
+            ))
+        );
+
+        assert_eq!(
+            Program::compile("{on_time} > 10.0 -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lt(
+                    Box::new(Expr::Lit(device::Value::Flt(10.0))),
+                    Box::new(Expr::Var(1))
+                ),
+                0
+            ))
+        );
+
+        assert_eq!(
+            Program::compile(
+                "4 + ({on_time} + 5) * 10 > 10.0 % 3 -> {bulb}",
+                &env
+            ),
+            Ok(Program(
+                Expr::Lt(
+                    Box::new(Expr::Rem(
+                        Box::new(Expr::Lit(device::Value::Flt(10.0))),
+                        Box::new(Expr::Lit(device::Value::Int(3)))
+                    )),
+                    Box::new(Expr::Add(
+                        Box::new(Expr::Lit(device::Value::Int(4))),
+                        Box::new(Expr::Mul(
+                            Box::new(Expr::Add(
+                                Box::new(Expr::Var(1)),
+                                Box::new(Expr::Lit(device::Value::Int(5)))
+                            )),
+                            Box::new(Expr::Lit(device::Value::Int(10)))
+                        ))
+                    ))
+                ),
+                0
+            ))
+        );
+
+        assert_eq!(
+            Program::compile(
+                "true and false or false and true -> {bulb}",
+                &env
+            ),
+            Ok(Program(
+                Expr::Or(
+                    Box::new(Expr::And(
+                        Box::new(Expr::Lit(device::Value::Bool(true))),
+                        Box::new(Expr::Lit(device::Value::Bool(false)))
+                    )),
+                    Box::new(Expr::And(
+                        Box::new(Expr::Lit(device::Value::Bool(false))),
+                        Box::new(Expr::Lit(device::Value::Bool(true)))
+                    ))
+                ),
+                0
+            ))
+        );
+
+        assert_eq!(
+            Program::compile("true and (5 < 7 or true) -> {bulb}", &env),
+            Ok(Program(
+                Expr::And(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Or(
+                        Box::new(Expr::Lt(
+                            Box::new(Expr::Lit(device::Value::Int(5))),
+                            Box::new(Expr::Lit(device::Value::Int(7)))
+                        )),
+                        Box::new(Expr::Lit(device::Value::Bool(true)))
+                    ))
+                ),
+                0
+            ))
+        );
+
+        assert_eq!(
+            Program::compile("\"Hello, world!\" -> {bulb}", &env),
+            Ok(Program(
+                Expr::Lit(device::Value::Str("Hello, world!".into())),
+                0
+            ))
+        );
+
+        assert!(Program::compile("if {switch} then 1 -> {bulb}", &env).is_err());
+        assert!(Program::compile("if {switch} else 1 -> {bulb}", &env).is_err());
+        assert!(Program::compile("if then 1 else 2 -> {bulb}", &env).is_err());
+
+        assert_eq!(
+            Program::compile(
+                "if {switch} then {on_time} else 2 + 3 -> {bulb}",
+                &env
+            ),
+            Ok(Program(
+                Expr::IfElse(
+                    Box::new(Expr::Var(0)),
+                    Box::new(Expr::Var(1)),
+                    Box::new(Expr::Add(
+                        Box::new(Expr::Lit(device::Value::Int(2))),
+                        Box::new(Expr::Lit(device::Value::Int(3)))
+                    ))
+                ),
+                0
+            ))
+        );
+
+        assert_eq!(
+            Program::compile(
+                "if {switch} then 1 else if {on_time} > 5 then 2 else 3 \
+                 -> {bulb}",
+                &env
+            ),
+            Ok(Program(
+                Expr::IfElse(
+                    Box::new(Expr::Var(0)),
+                    Box::new(Expr::Lit(device::Value::Int(1))),
+                    Box::new(Expr::IfElse(
+                        Box::new(Expr::Lt(
+                            Box::new(Expr::Lit(device::Value::Int(5))),
+                            Box::new(Expr::Var(1))
+                        )),
+                        Box::new(Expr::Lit(device::Value::Int(2))),
+                        Box::new(Expr::Lit(device::Value::Int(3)))
+                    ))
+                ),
+                0
+            ))
+        );
+
+        assert_eq!(
+            Program::compile(
+                "(if {switch} then 1 else 2) * 10 -> {bulb}",
+                &env
+            ),
+            Ok(Program(
+                Expr::Mul(
+                    Box::new(Expr::IfElse(
+                        Box::new(Expr::Var(0)),
+                        Box::new(Expr::Lit(device::Value::Int(1))),
+                        Box::new(Expr::Lit(device::Value::Int(2)))
+                    )),
+                    Box::new(Expr::Lit(device::Value::Int(10)))
+                ),
+                0
+            ))
+        );
+    }
+
+    #[test]
+    fn test_eval_not_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
+        // Test for uninitialized and initialized variables.
+
+        assert_eq!(
+            eval(&Expr::Not(Box::new(Expr::Var(0))), &[None], &time, None),
+            None
+        );
+        assert_eq!(
+            eval(
+                &Expr::Not(Box::new(Expr::Var(0))),
+                &[Some(device::Value::Bool(true))],
+                &time,
+                None
+            ),
+            Some(device::Value::Bool(false))
+        );
+
+        // Test literal values.
+
+        assert_eq!(
+            eval(&Expr::Not(Box::new(Expr::Lit(FALSE))), &[], &time, None),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(&Expr::Not(Box::new(Expr::Lit(TRUE))), &[], &time, None),
+            Some(FALSE)
+        );
+
+        // Test incorrect types.
+
+        assert_eq!(
+            eval(
+                &Expr::Not(Box::new(Expr::Lit(device::Value::Int(1)))),
+                &[],
+                &time,
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_eval_or_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ONE: device::Value = device::Value::Int(1);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
+        // Test uninitialized and initialized variables.
+
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(FALSE), Some(FALSE)],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(FALSE), Some(TRUE)],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(TRUE), Some(FALSE)],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(TRUE), None],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(FALSE), None],
+                &time,
+                None
+            ),
+            None
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[None, Some(TRUE)],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[None, Some(FALSE)],
+                &time,
+                None
+            ),
+            None
+        );
 
-        // Don't allow bad categories or fields.
+        // Test literal values.
 
-        assert!(Program::compile("{bad:second} -> {bulb}", &env).is_err());
-        assert!(Program::compile("{utc:bad} -> {bulb}", &env).is_err());
-        assert!(Program::compile("{local:bad} -> {bulb}", &env).is_err());
+        assert_eq!(
+            eval(
+                &Expr::Or(
+                    Box::new(Expr::Lit(FALSE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(
+                    Box::new(Expr::Lit(FALSE)),
+                    Box::new(Expr::Lit(TRUE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(TRUE))),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
 
-        // Don't allow whitespace.
+        // Test invalid types.
 
-        assert!(Program::compile("{ switch} -> {bulb}", &env).is_err());
-        assert!(Program::compile("{switch } -> {bulb}", &env).is_err());
-        assert!(Program::compile("{ utc:second} -> {bulb}", &env).is_err());
-        assert!(Program::compile("{utc :second} -> {bulb}", &env).is_err());
-        assert!(Program::compile("{utc: second} -> {bulb}", &env).is_err());
-        assert!(Program::compile("{utc:second } -> {bulb}", &env).is_err());
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TRUE))),
+                &[],
+                &time,
+                None
+            ),
+            None
+        );
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Lit(FALSE)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            None
+        );
+        // This is a loophole for expression errors. If the first
+        // subexpression is `true`, we don't evaluate the second so
+        // we won't catch type errors until the first subexpression is
+        // `false`.
+        assert_eq!(
+            eval(
+                &Expr::Or(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+    }
+
+    #[test]
+    fn test_eval_and_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ONE: device::Value = device::Value::Int(1);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
+        // Test uninitialized and initialized variables.
+
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(FALSE), Some(FALSE)],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(FALSE), Some(TRUE)],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(TRUE), Some(FALSE)],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(TRUE), Some(TRUE)],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(TRUE), None],
+                &time,
+                None
+            ),
+            None
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[Some(FALSE), None],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[None, Some(TRUE)],
+                &time,
+                None
+            ),
+            None
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
+                &[None, Some(FALSE)],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+
+        // Test literal values.
+
+        assert_eq!(
+            eval(
+                &Expr::And(
+                    Box::new(Expr::Lit(FALSE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(
+                    Box::new(Expr::Lit(FALSE)),
+                    Box::new(Expr::Lit(TRUE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(TRUE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TRUE))),
+                &[],
+                &time,
+                None
+            ),
+            None
+        );
+        // This is a loophole for expression errors. If the first
+        // subexpression is `false`, we don't evaluate the second so
+        // we won't catch type errors until the first subexpression is
+        // `true`.
+        assert_eq!(
+            eval(
+                &Expr::And(
+                    Box::new(Expr::Lit(FALSE)),
+                    Box::new(Expr::Lit(ONE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::And(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            None
+        );
+    }
 
-        // Test proper compilations.
+    #[test]
+    fn test_eval_eq_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ONE: device::Value = device::Value::Int(1);
+        const TWO: device::Value = device::Value::Int(2);
+        const FP_ONE: device::Value = device::Value::Flt(1.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
 
         assert_eq!(
-            Program::compile("{switch} -> {bulb}", &env),
-            Ok(Program(Expr::Var(0), 0))
+            eval(
+                &Expr::Eq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
-
         assert_eq!(
-            Program::compile("true -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Bool(true)), 0))
+            eval(
+                &Expr::Eq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
         assert_eq!(
-            Program::compile("false -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Bool(false)), 0))
+            eval(
+                &Expr::Eq(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
-
         assert_eq!(
-            Program::compile("1 -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Int(1)), 0))
+            eval(
+                &Expr::Eq(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(ONE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
         assert_eq!(
-            Program::compile("1. -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Flt(1.0)), 0))
+            eval(
+                &Expr::Eq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(FALSE))),
+                &[],
+                &time,
+                None
+            ),
+            None
         );
         assert_eq!(
-            Program::compile("1.0 -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Flt(1.0)), 0))
+            eval(
+                &Expr::Eq(
+                    Box::new(Expr::Lit(device::Value::Str("same".into()))),
+                    Box::new(Expr::Lit(device::Value::Str("same".into())))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
         assert_eq!(
-            Program::compile("-1.0 -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Flt(-1.0)), 0))
+            eval(
+                &Expr::Eq(
+                    Box::new(Expr::Lit(device::Value::Str("same".into()))),
+                    Box::new(Expr::Lit(device::Value::Str("not same".into())))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
+    }
+
+    #[test]
+    fn test_eval_lt_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ONE: device::Value = device::Value::Int(1);
+        const TWO: device::Value = device::Value::Int(2);
+        const FP_ONE: device::Value = device::Value::Flt(1.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
         assert_eq!(
-            Program::compile("1.5 -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Flt(1.5)), 0))
+            eval(
+                &Expr::Lt(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
         assert_eq!(
-            Program::compile("1.0e10 -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Flt(1.0e10)), 0))
+            eval(
+                &Expr::Lt(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(TWO))),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
         assert_eq!(
-            Program::compile("2.75e-10 -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Flt(2.75e-10)), 0))
+            eval(
+                &Expr::Lt(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
         assert_eq!(
-            Program::compile("(((10))) -> {bulb}", &env),
-            Ok(Program(Expr::Lit(device::Value::Int(10)), 0))
+            eval(
+                &Expr::Lt(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(FALSE))),
+                &[],
+                &time,
+                None
+            ),
+            None
         );
-
-        assert!(Program::compile("#1 -> {bulb}", &env).is_err());
-        assert!(Program::compile("#12 -> {bulb}", &env).is_err());
-        assert!(Program::compile("#12345 -> {bulb}", &env).is_err());
         assert_eq!(
-            Program::compile("#123 -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lit(device::Value::Color(LinSrgba::new(
-                    0x11, 0x22, 0x33, 255
-                ))),
-                0
-            ))
+            eval(
+                &Expr::Lt(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
         assert_eq!(
-            Program::compile("#1234 -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lit(device::Value::Color(LinSrgba::new(
-                    0x11, 0x22, 0x33, 0x44
-                ))),
-                0
-            ))
+            eval(
+                &Expr::Lt(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Lt(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(TWO))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
         assert_eq!(
-            Program::compile("#7f8081 -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lit(device::Value::Color(LinSrgba::new(
-                    127, 128, 129, 255
-                ))),
-                0
-            ))
+            eval(
+                &Expr::Lt(
+                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
+                    Box::new(Expr::Lit(device::Value::Str("abc".into())))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::Lt(
+                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
+                    Box::new(Expr::Lit(device::Value::Str("abcd".into())))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
+        );
+    }
+
+    #[test]
+    fn test_eval_lteq_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ONE: device::Value = device::Value::Int(1);
+        const TWO: device::Value = device::Value::Int(2);
+        const FP_ONE: device::Value = device::Value::Flt(1.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
+        assert_eq!(
+            eval(
+                &Expr::LtEq(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
         assert_eq!(
-            Program::compile("#7f808182 -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lit(device::Value::Color(LinSrgba::new(
-                    127, 128, 129, 130
-                ))),
-                0
-            ))
+            eval(
+                &Expr::LtEq(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(TWO))),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
         assert_eq!(
-            Program::compile("#7F80A0 -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lit(device::Value::Color(LinSrgba::new(
-                    127, 128, 160, 255
-                ))),
-                0
-            ))
+            eval(
+                &Expr::LtEq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
         assert_eq!(
-            Program::compile("#black -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lit(device::Value::Color(LinSrgba::new(0, 0, 0, 255))),
-                0
-            ))
+            eval(
+                &Expr::LtEq(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            None
         );
-
         assert_eq!(
-            Program::compile("{on_time} > 10.0 -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lt(
-                    Box::new(Expr::Lit(device::Value::Flt(10.0))),
-                    Box::new(Expr::Var(1))
+            eval(
+                &Expr::LtEq(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FP_ONE))
                 ),
-                0
-            ))
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
-
         assert_eq!(
-            Program::compile(
-                "4 + ({on_time} + 5) * 10 > 10.0 % 3 -> {bulb}",
-                &env
-            ),
-            Ok(Program(
-                Expr::Lt(
-                    Box::new(Expr::Rem(
-                        Box::new(Expr::Lit(device::Value::Flt(10.0))),
-                        Box::new(Expr::Lit(device::Value::Int(3)))
-                    )),
-                    Box::new(Expr::Add(
-                        Box::new(Expr::Lit(device::Value::Int(4))),
-                        Box::new(Expr::Mul(
-                            Box::new(Expr::Add(
-                                Box::new(Expr::Var(1)),
-                                Box::new(Expr::Lit(device::Value::Int(5)))
-                            )),
-                            Box::new(Expr::Lit(device::Value::Int(10)))
-                        ))
-                    ))
+            eval(
+                &Expr::LtEq(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
                 ),
-                0
-            ))
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
-
         assert_eq!(
-            Program::compile(
-                "true and false or false and true -> {bulb}",
-                &env
+            eval(
+                &Expr::LtEq(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(TWO))
+                ),
+                &[],
+                &time,
+                None
             ),
-            Ok(Program(
-                Expr::Or(
-                    Box::new(Expr::And(
-                        Box::new(Expr::Lit(device::Value::Bool(true))),
-                        Box::new(Expr::Lit(device::Value::Bool(false)))
-                    )),
-                    Box::new(Expr::And(
-                        Box::new(Expr::Lit(device::Value::Bool(false))),
-                        Box::new(Expr::Lit(device::Value::Bool(true)))
-                    ))
+            Some(TRUE)
+        );
+        assert_eq!(
+            eval(
+                &Expr::LtEq(
+                    Box::new(Expr::Lit(device::Value::Str("abcd".into()))),
+                    Box::new(Expr::Lit(device::Value::Str("abc".into())))
                 ),
-                0
-            ))
+                &[],
+                &time,
+                None
+            ),
+            Some(FALSE)
         );
-
         assert_eq!(
-            Program::compile("true and (5 < 7 or true) -> {bulb}", &env),
-            Ok(Program(
-                Expr::And(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Or(
-                        Box::new(Expr::Lt(
-                            Box::new(Expr::Lit(device::Value::Int(5))),
-                            Box::new(Expr::Lit(device::Value::Int(7)))
-                        )),
-                        Box::new(Expr::Lit(device::Value::Bool(true)))
-                    ))
+            eval(
+                &Expr::LtEq(
+                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
+                    Box::new(Expr::Lit(device::Value::Str("abc".into())))
                 ),
-                0
-            ))
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
-
         assert_eq!(
-            Program::compile("\"Hello, world!\" -> {bulb}", &env),
-            Ok(Program(
-                Expr::Lit(device::Value::Str("Hello, world!".into())),
-                0
-            ))
+            eval(
+                &Expr::LtEq(
+                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
+                    Box::new(Expr::Lit(device::Value::Str("abcd".into())))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(TRUE)
         );
     }
 
     #[test]
-    fn test_eval_not_expr() {
+    fn test_eval_add_expr() {
         const TRUE: device::Value = device::Value::Bool(true);
         const FALSE: device::Value = device::Value::Bool(false);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-
-        // Test for uninitialized and initialized variables.
+        const ONE: device::Value = device::Value::Int(1);
+        const TWO: device::Value = device::Value::Int(2);
+        const FP_ONE: device::Value = device::Value::Flt(1.0);
+        const FP_TWO: device::Value = device::Value::Flt(2.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
 
         assert_eq!(
-            eval(&Expr::Not(Box::new(Expr::Var(0))), &[None], &time, None),
-            None
+            eval(
+                &Expr::Add(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            Some(device::Value::Int(3))
+        );
+        assert_eq!(
+            eval(
+                &Expr::Add(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
+                &[],
+                &time,
+                None
+            ),
+            Some(device::Value::Int(2))
+        );
+        assert_eq!(
+            eval(
+                &Expr::Add(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(device::Value::Int(1))
+        );
+        assert_eq!(
+            eval(
+                &Expr::Add(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(device::Value::Flt(3.0))
+        );
+        assert_eq!(
+            eval(
+                &Expr::Add(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(device::Value::Flt(2.0))
+        );
+        assert_eq!(
+            eval(
+                &Expr::Add(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
+                &time,
+                None
+            ),
+            Some(device::Value::Flt(1.0))
         );
         assert_eq!(
             eval(
-                &Expr::Not(Box::new(Expr::Var(0))),
-                &[Some(device::Value::Bool(true))],
+                &Expr::Add(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(ONE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(device::Value::Bool(false))
-        );
-
-        // Test literal values.
-
-        assert_eq!(
-            eval(&Expr::Not(Box::new(Expr::Lit(FALSE))), &[], &time, None),
-            Some(TRUE)
-        );
-        assert_eq!(
-            eval(&Expr::Not(Box::new(Expr::Lit(TRUE))), &[], &time, None),
-            Some(FALSE)
+            Some(device::Value::Flt(3.0))
         );
-
-        // Test incorrect types.
-
         assert_eq!(
             eval(
-                &Expr::Not(Box::new(Expr::Lit(device::Value::Int(1)))),
+                &Expr::Add(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
                 &[],
                 &time,
                 None
             ),
-            None
+            Some(device::Value::Flt(3.0))
         );
     }
 
     #[test]
-    fn test_eval_or_expr() {
+    fn test_eval_sub_expr() {
         const TRUE: device::Value = device::Value::Bool(true);
         const FALSE: device::Value = device::Value::Bool(false);
         const ONE: device::Value = device::Value::Int(1);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-
-        // Test uninitialized and initialized variables.
+        const TWO: device::Value = device::Value::Int(2);
+        const FP_ONE: device::Value = device::Value::Flt(1.0);
+        const FP_TWO: device::Value = device::Value::Flt(2.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
 
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(FALSE), Some(FALSE)],
-                &time,
-                None
-            ),
-            Some(FALSE)
-        );
-        assert_eq!(
-            eval(
-                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(FALSE), Some(TRUE)],
+                &Expr::Sub(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
+                &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Int(1))
         );
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(TRUE), Some(FALSE)],
+                &Expr::Sub(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
+                &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Int(0))
         );
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(TRUE), None],
+                &Expr::Sub(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Int(1))
         );
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(FALSE), None],
+                &Expr::Sub(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            None
+            Some(device::Value::Flt(1.0))
         );
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[None, Some(TRUE)],
+                &Expr::Sub(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Flt(0.0))
         );
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[None, Some(FALSE)],
+                &Expr::Sub(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            None
+            Some(device::Value::Flt(1.0))
         );
-
-        // Test literal values.
-
         assert_eq!(
             eval(
-                &Expr::Or(
-                    Box::new(Expr::Lit(FALSE)),
-                    Box::new(Expr::Lit(FALSE))
+                &Expr::Sub(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(ONE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(1.0))
         );
         assert_eq!(
             eval(
-                &Expr::Or(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(FALSE))
+                &Expr::Sub(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Flt(1.0))
         );
+    }
+
+    #[test]
+    fn test_eval_mul_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ONE: device::Value = device::Value::Int(1);
+        const TWO: device::Value = device::Value::Int(2);
+        const FP_ONE: device::Value = device::Value::Flt(1.0);
+        const FP_TWO: device::Value = device::Value::Flt(2.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
         assert_eq!(
             eval(
-                &Expr::Or(
-                    Box::new(Expr::Lit(FALSE)),
-                    Box::new(Expr::Lit(TRUE))
-                ),
+                &Expr::Mul(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Int(2))
         );
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(TRUE))),
+                &Expr::Mul(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Int(1))
         );
-
-        // Test invalid types.
-
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TRUE))),
+                &Expr::Mul(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
                 &[],
                 &time,
                 None
             ),
-            None
+            Some(device::Value::Int(0))
         );
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Lit(FALSE)), Box::new(Expr::Lit(ONE))),
+                &Expr::Mul(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
                 &[],
                 &time,
                 None
             ),
-            None
+            Some(device::Value::Flt(2.0))
         );
-        // This is a loophole for expression errors. If the first
-        // subexpression is `true`, we don't evaluate the second so
-        // we won't catch type errors until the first subexpression is
-        // `false`.
         assert_eq!(
             eval(
-                &Expr::Or(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
+                &Expr::Mul(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Flt(1.0))
         );
-    }
-
-    #[test]
-    fn test_eval_and_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
-        const FALSE: device::Value = device::Value::Bool(false);
-        const ONE: device::Value = device::Value::Int(1);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-
-        // Test uninitialized and initialized variables.
-
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(FALSE), Some(FALSE)],
+                &Expr::Mul(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(0.0))
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(FALSE), Some(TRUE)],
+                &Expr::Mul(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(ONE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(2.0))
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(TRUE), Some(FALSE)],
+                &Expr::Mul(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(2.0))
         );
+    }
+
+    #[test]
+    fn test_eval_div_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ZERO: device::Value = device::Value::Int(0);
+        const ONE: device::Value = device::Value::Int(1);
+        const TWO: device::Value = device::Value::Int(2);
+        const FP_ZERO: device::Value = device::Value::Flt(0.0);
+        const FP_ONE: device::Value = device::Value::Flt(1.0);
+        const FP_TWO: device::Value = device::Value::Flt(2.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(TRUE), Some(TRUE)],
+                &Expr::Div(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
+                &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Int(2))
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(TRUE), None],
+                &Expr::Div(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
+                &[],
                 &time,
                 None
             ),
@@ -1354,110 +4376,115 @@ mod tests {
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[Some(FALSE), None],
+                &Expr::Div(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FALSE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[None, Some(TRUE)],
+                &Expr::Div(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            None
+            Some(device::Value::Flt(2.0))
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Var(0)), Box::new(Expr::Var(1))),
-                &[None, Some(FALSE)],
+                &Expr::Div(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
+                &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
         );
-
-        // Test literal values.
-
         assert_eq!(
             eval(
-                &Expr::And(
-                    Box::new(Expr::Lit(FALSE)),
+                &Expr::Div(
+                    Box::new(Expr::Lit(FP_ONE)),
                     Box::new(Expr::Lit(FALSE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::And(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(FALSE))
+                &Expr::Div(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(ONE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(2.0))
         );
         assert_eq!(
             eval(
-                &Expr::And(
-                    Box::new(Expr::Lit(FALSE)),
-                    Box::new(Expr::Lit(TRUE))
+                &Expr::Div(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ONE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(2.0))
         );
         assert_eq!(
             eval(
-                &Expr::And(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(TRUE))
-                ),
+                &Expr::Div(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ZERO))),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TRUE))),
+                &Expr::Div(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ZERO))
+                ),
                 &[],
                 &time,
                 None
             ),
             None
         );
-        // This is a loophole for expression errors. If the first
-        // subexpression is `false`, we don't evaluate the second so
-        // we won't catch type errors until the first subexpression is
-        // `true`.
         assert_eq!(
             eval(
-                &Expr::And(
-                    Box::new(Expr::Lit(FALSE)),
-                    Box::new(Expr::Lit(ONE))
+                &Expr::Div(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(ZERO))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::And(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
+                &Expr::Div(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(FP_ZERO))
+                ),
                 &[],
                 &time,
                 None
@@ -1467,59 +4494,67 @@ mod tests {
     }
 
     #[test]
-    fn test_eval_eq_expr() {
+    fn test_eval_rem_expr() {
         const TRUE: device::Value = device::Value::Bool(true);
         const FALSE: device::Value = device::Value::Bool(false);
+        const ZERO: device::Value = device::Value::Int(0);
+        const NEG_ONE: device::Value = device::Value::Int(-1);
         const ONE: device::Value = device::Value::Int(1);
         const TWO: device::Value = device::Value::Int(2);
+        const FP_ZERO: device::Value = device::Value::Flt(0.0);
         const FP_ONE: device::Value = device::Value::Flt(1.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
+        const FP_TWO: device::Value = device::Value::Flt(2.0);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
 
         assert_eq!(
             eval(
-                &Expr::Eq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(ONE))),
+                &Expr::Rem(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Int(1))
         );
         assert_eq!(
             eval(
-                &Expr::Eq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
+                &Expr::Rem(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::Eq(
+                &Expr::Rem(
                     Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FP_ONE))
+                    Box::new(Expr::Lit(FALSE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::Eq(
+                &Expr::Rem(
                     Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(ONE))
+                    Box::new(Expr::Lit(FP_TWO))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(device::Value::Flt(1.0))
         );
         assert_eq!(
             eval(
-                &Expr::Eq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(FALSE))),
+                &Expr::Rem(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(FP_ONE))
+                ),
                 &[],
                 &time,
                 None
@@ -1528,69 +4563,67 @@ mod tests {
         );
         assert_eq!(
             eval(
-                &Expr::Eq(
-                    Box::new(Expr::Lit(device::Value::Str("same".into()))),
-                    Box::new(Expr::Lit(device::Value::Str("same".into())))
+                &Expr::Rem(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(FALSE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::Eq(
-                    Box::new(Expr::Lit(device::Value::Str("same".into()))),
-                    Box::new(Expr::Lit(device::Value::Str("not same".into())))
+                &Expr::Rem(
+                    Box::new(Expr::Lit(FP_ONE)),
+                    Box::new(Expr::Lit(TWO))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(1.0))
         );
-    }
-
-    #[test]
-    fn test_eval_lt_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
-        const FALSE: device::Value = device::Value::Bool(false);
-        const ONE: device::Value = device::Value::Int(1);
-        const TWO: device::Value = device::Value::Int(2);
-        const FP_ONE: device::Value = device::Value::Flt(1.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-
         assert_eq!(
             eval(
-                &Expr::Lt(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
+                &Expr::Rem(
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Lit(FP_TWO))
+                ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            Some(device::Value::Flt(1.0))
         );
         assert_eq!(
             eval(
-                &Expr::Lt(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(TWO))),
+                &Expr::Rem(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ZERO))),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::Lt(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
+                &Expr::Rem(
+                    Box::new(Expr::Lit(TWO)),
+                    Box::new(Expr::Lit(FP_ZERO))
+                ),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::Lt(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(FALSE))),
+                &Expr::Rem(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(ZERO))
+                ),
                 &[],
                 &time,
                 None
@@ -1599,1262 +4632,2206 @@ mod tests {
         );
         assert_eq!(
             eval(
-                &Expr::Lt(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FP_ONE))
+                &Expr::Rem(
+                    Box::new(Expr::Lit(FP_TWO)),
+                    Box::new(Expr::Lit(FP_ZERO))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
         );
         assert_eq!(
             eval(
-                &Expr::Lt(
+                &Expr::Rem(
                     Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
+                    Box::new(Expr::Lit(NEG_ONE))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(FALSE)
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_calls() {
+        let env: Env = (
+            &[String::from("level"), String::from("flag")],
+            &[String::from("dimmer")],
         );
+
         assert_eq!(
-            eval(
-                &Expr::Lt(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(TWO))
+            Program::compile("abs({level}) -> {dimmer}", &env),
+            Ok(Program(Expr::Call(&BUILTINS[2], vec![Expr::Var(0)]), 0))
+        );
+        assert_eq!(
+            Program::compile("clamp({level} * 2, 0, 100) -> {dimmer}", &env),
+            Ok(Program(
+                Expr::Call(
+                    &BUILTINS[3],
+                    vec![
+                        Expr::Mul(
+                            Box::new(Expr::Var(0)),
+                            Box::new(Expr::Lit(device::Value::Int(2)))
+                        ),
+                        Expr::Lit(device::Value::Int(0)),
+                        Expr::Lit(device::Value::Int(100))
+                    ]
                 ),
-                &[],
-                &time,
-                None
-            ),
-            Some(TRUE)
+                0
+            ))
         );
         assert_eq!(
-            eval(
-                &Expr::Lt(
-                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
-                    Box::new(Expr::Lit(device::Value::Str("abc".into())))
+            Program::compile("max(min({level}, 10), 1) + 1 -> {dimmer}", &env),
+            Ok(Program(
+                Expr::Add(
+                    Box::new(Expr::Call(
+                        &BUILTINS[1],
+                        vec![
+                            Expr::Call(
+                                &BUILTINS[0],
+                                vec![
+                                    Expr::Var(0),
+                                    Expr::Lit(device::Value::Int(10))
+                                ]
+                            ),
+                            Expr::Lit(device::Value::Int(1))
+                        ]
+                    )),
+                    Box::new(Expr::Lit(device::Value::Int(1)))
                 ),
-                &[],
-                &time,
-                None
-            ),
-            Some(FALSE)
+                0
+            ))
+        );
+
+        // Unknown functions and missing parentheses are errors.
+
+        assert!(Program::compile("sqrt({level}) -> {dimmer}", &env).is_err());
+        assert!(Program::compile("abs -> {dimmer}", &env).is_err());
+        assert!(Program::compile("abs() -> {dimmer}", &env).is_err());
+        assert!(Program::compile("abs({level} -> {dimmer}", &env).is_err());
+
+        // Bad arity is reported when compiling.
+
+        assert!(Program::compile("abs(1, 2) -> {dimmer}", &env).is_err());
+        assert!(Program::compile("min(1) -> {dimmer}", &env).is_err());
+        assert!(Program::compile("max(1, 2, 3) -> {dimmer}", &env).is_err());
+        assert!(Program::compile("clamp(1, 2) -> {dimmer}", &env).is_err());
+
+        // Arguments that can never be numeric are reported when
+        // compiling.
+
+        assert!(Program::compile("abs(true) -> {dimmer}", &env).is_err());
+        assert!(Program::compile("abs(\"1\") -> {dimmer}", &env).is_err());
+        assert!(Program::compile("round(#red) -> {dimmer}", &env).is_err());
+        assert!(
+            Program::compile("min({level} > 1, 2) -> {dimmer}", &env).is_err()
+        );
+        assert!(
+            Program::compile("min(2, not {flag}) -> {dimmer}", &env).is_err()
+        );
+        assert!(Program::compile("abs({flag}) -> {dimmer}", &env).is_ok());
+
+        // Color functions check for color arguments and their
+        // results are checked, too.
+
+        assert!(Program::compile("red(1) -> {dimmer}", &env).is_err());
+        assert!(Program::compile("red({flag} > 1) -> {dimmer}", &env).is_err());
+        assert!(Program::compile("rgb(#red, 1, 2) -> {dimmer}", &env).is_err());
+        assert!(Program::compile(
+            "blend(#red, red(#red), 1) -> {dimmer}",
+            &env
+        )
+        .is_err());
+        assert!(
+            Program::compile("min(rgb(1, 2, 3), 1) -> {dimmer}", &env).is_err()
+        );
+        assert!(Program::compile(
+            "brightness(#red, {level}) -> {dimmer}",
+            &env
+        )
+        .is_ok());
+        assert!(Program::compile(
+            "red(blend({flag}, #red, 0.5)) -> {dimmer}",
+            &env
+        )
+        .is_ok());
+        assert!(Program::compile("rgb(red({flag}), 0, 0) -> {dimmer}", &env)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_eval_if_expr() {
+        const TRUE: device::Value = device::Value::Bool(true);
+        const FALSE: device::Value = device::Value::Bool(false);
+        const ONE: device::Value = device::Value::Int(1);
+        const TWO: device::Value = device::Value::Int(2);
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
+        let expr = Expr::IfElse(
+            Box::new(Expr::Var(0)),
+            Box::new(Expr::Var(1)),
+            Box::new(Expr::Var(2)),
+        );
+
+        assert_eq!(
+            eval(&expr, &[Some(TRUE), Some(ONE), Some(TWO)], &time, None),
+            Some(ONE)
+        );
+        assert_eq!(
+            eval(&expr, &[Some(FALSE), Some(ONE), Some(TWO)], &time, None),
+            Some(TWO)
+        );
+
+        // An uninitialized or non-boolean condition doesn't produce a
+        // value.
+
+        assert_eq!(
+            eval(&expr, &[None, Some(ONE), Some(TWO)], &time, None),
+            None
+        );
+        assert_eq!(
+            eval(&expr, &[Some(ONE), Some(ONE), Some(TWO)], &time, None),
+            None
+        );
+
+        // Only the selected subexpression is evaluated so the other
+        // one can be uninitialized (or have an error.)
+
+        assert_eq!(
+            eval(&expr, &[Some(TRUE), Some(ONE), None], &time, None),
+            Some(ONE)
+        );
+        assert_eq!(
+            eval(&expr, &[Some(FALSE), None, Some(TWO)], &time, None),
+            Some(TWO)
+        );
+        assert_eq!(
+            eval(&expr, &[Some(TRUE), None, Some(TWO)], &time, None),
+            None
         );
         assert_eq!(
             eval(
-                &Expr::Lt(
-                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
-                    Box::new(Expr::Lit(device::Value::Str("abcd".into())))
+                &Expr::IfElse(
+                    Box::new(Expr::Lit(TRUE)),
+                    Box::new(Expr::Lit(ONE)),
+                    Box::new(Expr::Div(
+                        Box::new(Expr::Lit(ONE)),
+                        Box::new(Expr::Lit(device::Value::Int(0)))
+                    ))
                 ),
                 &[],
                 &time,
                 None
             ),
-            Some(TRUE)
+            Some(ONE)
         );
     }
 
     #[test]
-    fn test_eval_lteq_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
+    fn test_eval() {
         const FALSE: device::Value = device::Value::Bool(false);
-        const ONE: device::Value = device::Value::Int(1);
-        const TWO: device::Value = device::Value::Int(2);
-        const FP_ONE: device::Value = device::Value::Flt(1.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+
+        assert_eq!(eval(&Expr::Lit(FALSE), &[], &time, None), Some(FALSE));
+    }
+
+    // This function tests the optimizations that can be done on an
+    // expression.
+
+    #[test]
+    fn test_not_optimizer() {
+        assert_eq!(
+            optimize(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(true))))),
+            Expr::Lit(device::Value::Bool(false))
+        );
+        assert_eq!(
+            optimize(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(
+                false
+            ))))),
+            Expr::Lit(device::Value::Bool(true))
+        );
+        assert_eq!(
+            optimize(Expr::Not(Box::new(Expr::Not(Box::new(Expr::Lit(
+                device::Value::Bool(true)
+            )))))),
+            Expr::Lit(device::Value::Bool(true))
+        );
+        assert_eq!(
+            optimize(Expr::Not(Box::new(Expr::Not(Box::new(Expr::Not(
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )))))),
+            Expr::Lit(device::Value::Bool(false))
+        );
+        assert_eq!(
+            optimize(Expr::Not(Box::new(Expr::Not(Box::new(Expr::Not(
+                Box::new(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(
+                    true
+                )))))
+            )))))),
+            Expr::Lit(device::Value::Bool(true))
+        );
+    }
 
+    #[test]
+    fn test_and_optimizer() {
         assert_eq!(
-            eval(
-                &Expr::LtEq(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
-            ),
-            Some(FALSE)
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Lit(device::Value::Bool(false)))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
         assert_eq!(
-            eval(
-                &Expr::LtEq(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(TWO))),
-                &[],
-                &time,
-                None
-            ),
-            Some(TRUE)
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
         assert_eq!(
-            eval(
-                &Expr::LtEq(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
-                &[],
-                &time,
-                None
-            ),
-            Some(TRUE)
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Lit(device::Value::Str("test".into())))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
         assert_eq!(
-            eval(
-                &Expr::LtEq(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Bool(false)))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
         assert_eq!(
-            eval(
-                &Expr::LtEq(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(TRUE)
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
+
+        // A non-boolean operand is an error, which is left for the
+        // evaluator to report.
+
         assert_eq!(
-            eval(
-                &Expr::LtEq(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(FALSE)
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Str("test".into())))
+            )),
+            Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Str("test".into())))
+            )
         );
+
         assert_eq!(
-            eval(
-                &Expr::LtEq(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(TWO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(TRUE)
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::And(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(true)))
+                ))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::LtEq(
-                    Box::new(Expr::Lit(device::Value::Str("abcd".into()))),
-                    Box::new(Expr::Lit(device::Value::Str("abc".into())))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(FALSE)
+            optimize(Expr::And(
+                Box::new(Expr::And(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(true)))
+                )),
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
+
         assert_eq!(
-            eval(
-                &Expr::LtEq(
-                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
-                    Box::new(Expr::Lit(device::Value::Str("abc".into())))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(TRUE)
+            optimize(Expr::And(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::And(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(false)))
+                ))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
         assert_eq!(
-            eval(
-                &Expr::LtEq(
-                    Box::new(Expr::Lit(device::Value::Str("abc".into()))),
-                    Box::new(Expr::Lit(device::Value::Str("abcd".into())))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(TRUE)
+            optimize(Expr::And(
+                Box::new(Expr::And(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(true)))
+                )),
+                Box::new(Expr::Lit(device::Value::Bool(false)))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
     }
 
     #[test]
-    fn test_eval_add_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
-        const FALSE: device::Value = device::Value::Bool(false);
-        const ONE: device::Value = device::Value::Int(1);
-        const TWO: device::Value = device::Value::Int(2);
-        const FP_ONE: device::Value = device::Value::Flt(1.0);
-        const FP_TWO: device::Value = device::Value::Flt(2.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-
+    fn test_or_optimizer() {
         assert_eq!(
-            eval(
-                &Expr::Add(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Int(3))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Lit(device::Value::Bool(false)))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
         assert_eq!(
-            eval(
-                &Expr::Add(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Int(2))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::Add(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Int(1))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Lit(device::Value::Str("test".into())))
+            )),
+            Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Lit(device::Value::Str("test".into())))
+            )
         );
         assert_eq!(
-            eval(
-                &Expr::Add(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(3.0))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Bool(false)))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::Add(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(2.0))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::Add(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Str("test".into())))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
+
         assert_eq!(
-            eval(
-                &Expr::Add(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(3.0))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Or(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(true)))
+                ))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::Add(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(3.0))
+            optimize(Expr::Or(
+                Box::new(Expr::Or(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(true)))
+                )),
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
-    }
-
-    #[test]
-    fn test_eval_sub_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
-        const FALSE: device::Value = device::Value::Bool(false);
-        const ONE: device::Value = device::Value::Int(1);
-        const TWO: device::Value = device::Value::Int(2);
-        const FP_ONE: device::Value = device::Value::Flt(1.0);
-        const FP_TWO: device::Value = device::Value::Flt(2.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
 
         assert_eq!(
-            eval(
-                &Expr::Sub(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Int(1))
+            optimize(Expr::Or(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Or(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(false)))
+                ))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::Sub(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Int(0))
+            optimize(Expr::Or(
+                Box::new(Expr::Or(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Lit(device::Value::Bool(true)))
+                )),
+                Box::new(Expr::Lit(device::Value::Bool(false)))
+            )),
+            Expr::Lit(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::Sub(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Int(1))
+            optimize(Expr::Or(
+                Box::new(Expr::Or(
+                    Box::new(Expr::Lit(device::Value::Bool(false))),
+                    Box::new(Expr::Lit(device::Value::Bool(false)))
+                )),
+                Box::new(Expr::Lit(device::Value::Bool(false)))
+            )),
+            Expr::Lit(device::Value::Bool(false))
         );
+    }
+
+    #[test]
+    fn test_if_optimizer() {
         assert_eq!(
-            eval(
-                &Expr::Sub(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            optimize(Expr::IfElse(
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Var(0)),
+                Box::new(Expr::Var(1))
+            )),
+            Expr::Var(0)
         );
         assert_eq!(
-            eval(
-                &Expr::Sub(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(0.0))
+            optimize(Expr::IfElse(
+                Box::new(Expr::Lit(device::Value::Bool(false))),
+                Box::new(Expr::Var(0)),
+                Box::new(Expr::Var(1))
+            )),
+            Expr::Var(1)
         );
         assert_eq!(
-            eval(
-                &Expr::Sub(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            optimize(Expr::IfElse(
+                Box::new(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(
+                    true
+                ))))),
+                Box::new(Expr::Var(0)),
+                Box::new(Expr::Var(1))
+            )),
+            Expr::Var(1)
         );
+
+        // A condition that can't be reduced to a literal keeps the IF
+        // expression, but its subexpressions are still optimized.
+
         assert_eq!(
-            eval(
-                &Expr::Sub(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            optimize(Expr::IfElse(
+                Box::new(Expr::Var(0)),
+                Box::new(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(
+                    false
+                ))))),
+                Box::new(Expr::Or(
+                    Box::new(Expr::Lit(device::Value::Bool(true))),
+                    Box::new(Expr::Var(1))
+                ))
+            )),
+            Expr::IfElse(
+                Box::new(Expr::Var(0)),
+                Box::new(Expr::Lit(device::Value::Bool(true))),
+                Box::new(Expr::Lit(device::Value::Bool(true)))
+            )
         );
-        assert_eq!(
-            eval(
-                &Expr::Sub(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+    }
+
+    // Compiles an expression, using inputs `a` and `b` and outputs
+    // `b` and `c`, optimizes it and returns how it's displayed.
+
+    fn optimized(expr: &str) -> String {
+        let env: Env = (
+            &[String::from("a"), String::from("b")],
+            &[String::from("b"), String::from("c")],
         );
+
+        Program::compile(expr, &env).unwrap().optimize().to_string()
     }
 
     #[test]
-    fn test_eval_mul_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
-        const FALSE: device::Value = device::Value::Bool(false);
-        const ONE: device::Value = device::Value::Int(1);
-        const TWO: device::Value = device::Value::Int(2);
-        const FP_ONE: device::Value = device::Value::Flt(1.0);
-        const FP_TWO: device::Value = device::Value::Flt(2.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
+    fn test_constant_folding() {
+        const TESTS: &[(&str, &str)] = &[
+            ("1 + 2 * 3 -> {c}", "7 -> out[1]"),
+            (
+                "{a} * (9.0 / 5.0) + 32 -> {c}",
+                "inp[0] * 1.8 + 32 -> out[1]",
+            ),
+            ("(2 < 3) and {a} > 1 -> {c}", "1 < inp[0] -> out[1]"),
+            ("\"ab\" + \"cd\" = \"abcd\" -> {c}", "true -> out[1]"),
+            ("min(3, 4) + len(\"abc\") -> {c}", "6 -> out[1]"),
+            (
+                "format(\"{} of {}\", 1 + 1, \"x\") -> {c}",
+                "\"2 of x\" -> out[1]",
+            ),
+            ("rgb(255, 0, 0) = #red -> {c}", "true -> out[1]"),
+            ("if 1 < 2 then {a} else {b} -> {c}", "inp[0] -> out[1]"),
+            ("not (2 = 2) -> {c}", "false -> out[1]"),
+            ("{utc:hour} + 0 -> {c}", "{utc:hour} -> out[1]"),
+            // Errors are left for the evaluator to report.
+            ("1 / 0 -> {c}", "1 / 0 -> out[1]"),
+            // Stateful functions are still called each time.
+            ("rising(1 < 2) -> {c}", "rising(true) -> out[1]"),
+        ];
 
-        assert_eq!(
-            eval(
-                &Expr::Mul(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
+        for (expr, result) in TESTS {
+            assert_eq!(optimized(expr), *result, "failed on: {}", expr);
+        }
+    }
+
+    #[test]
+    fn test_identities() {
+        const TESTS: &[(&str, &str)] = &[
+            ("{utc:hour} * 1 + 0 -> {c}", "{utc:hour} -> out[1]"),
+            ("1 * {utc:hour} - 0 -> {c}", "{utc:hour} -> out[1]"),
+            ("0 + {utc:hour} / 1 -> {c}", "{utc:hour} -> out[1]"),
+            // These would change the type of the result.
+            ("{utc:hour} * 1.0 -> {c}", "{utc:hour} * 1 -> out[1]"),
+            ("({a} > 0) + 0 -> {c}", "(0 < inp[0]) + 0 -> out[1]"),
+            ("({a} > 0) = true -> {c}", "0 < inp[0] -> out[1]"),
+            ("false = ({a} > 0) -> {c}", "not (0 < inp[0]) -> out[1]"),
+            ("({a} > 0) <> false -> {c}", "0 < inp[0] -> out[1]"),
+            ("{a} > 0 and true -> {c}", "0 < inp[0] -> out[1]"),
+            ("false or {a} > 0 -> {c}", "0 < inp[0] -> out[1]"),
+            ("not not ({a} > 0) -> {c}", "0 < inp[0] -> out[1]"),
+            (
+                "if {a} > 0 then false else true -> {c}",
+                "not (0 < inp[0]) -> out[1]",
             ),
-            Some(device::Value::Int(2))
-        );
-        assert_eq!(
-            eval(
-                &Expr::Mul(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
+            (
+                "if {a} > 0 then true else false -> {c}",
+                "0 < inp[0] -> out[1]",
             ),
-            Some(device::Value::Int(1))
+            (
+                "if not {b} then 1 else 2 -> {c}",
+                "if inp[1] then 2 else 1 -> out[1]",
+            ),
+        ];
+
+        for (expr, result) in TESTS {
+            assert_eq!(optimized(expr), *result, "failed on: {}", expr);
+        }
+
+        // The type of an input isn't used since its device can
+        // report a value of another type, so the identities can't be
+        // used on inputs.
+
+        assert_eq!(optimized("{a} * 1 -> {c}"), "inp[0] * 1 -> out[1]");
+        assert_eq!(optimized("{b} = true -> {c}"), "inp[1] = true -> out[1]");
+        assert_eq!(
+            optimized("{b} and true -> {c}"),
+            "inp[1] and true -> out[1]"
         );
+        assert_eq!(optimized("not not {b} -> {c}"), "not not inp[1] -> out[1]");
         assert_eq!(
-            eval(
-                &Expr::Mul(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Int(0))
+            optimized("if {b} then true else false -> {c}"),
+            "if inp[1] then true else false -> out[1]"
         );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let inputs = [String::from("a"), String::from("b")];
+        let outputs = [String::from("b"), String::from("c")];
+        let env: Env = (&inputs, &outputs);
+        let cal = calendar::Calendar::default();
+        let compile = |s: &str| {
+            Block::compile(s, &env, &cal, 2).map(|b| {
+                (
+                    b.lets.iter().map(Program::to_string).collect::<Vec<_>>(),
+                    b.expr.to_string(),
+                    b.outputs,
+                )
+            })
+        };
+
+        // An expression can set several outputs. Unless the value is
+        // trivial, it's computed once into a local definition.
+
         assert_eq!(
-            eval(
-                &Expr::Mul(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(2.0))
+            compile("{a} -> {c}, {b}"),
+            Ok((vec![], String::from("inp[0]"), vec![1, 0]))
         );
         assert_eq!(
-            eval(
-                &Expr::Mul(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            compile("{a} and {b} -> {b}, {c}"),
+            Ok((
+                vec![String::from("inp[0] and inp[1] -> out[2]")],
+                String::from("inp[2]"),
+                vec![0, 1]
+            ))
         );
+        assert!(compile("{a} -> {b}, {b}").is_err());
+        assert!(compile("{a} -> {b},").is_err());
+        assert!(compile("{a} -> {b}, {a}").is_err());
+
+        // Local definitions are stored after the inputs and can use
+        // the ones before them.
+
         assert_eq!(
-            eval(
-                &Expr::Mul(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(0.0))
+            compile("let on = {a} and {b}; let x = {on} or {a}; {x} -> {c}"),
+            Ok((
+                vec![
+                    String::from("inp[0] and inp[1] -> out[2]"),
+                    String::from("inp[2] or inp[0] -> out[3]")
+                ],
+                String::from("inp[3]"),
+                vec![1]
+            ))
         );
         assert_eq!(
-            eval(
-                &Expr::Mul(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(2.0))
+            compile("let on = {a} > 3; {on} -> {b}, {c}"),
+            Ok((
+                vec![String::from("3 < inp[0] -> out[2]")],
+                String::from("inp[2]"),
+                vec![0, 1]
+            ))
         );
+        assert!(compile("let x = {x}; {x} -> {c}").is_err());
+        assert!(compile("let x = {y}; let y = 1; {x} -> {c}").is_err());
+        assert!(compile("let a = 1; {a} -> {c}").is_err());
+        assert!(compile("let x = 1; let x = 2; {x} -> {c}").is_err());
+        assert!(compile("let x = 1 {x} -> {c}").is_err());
+        assert!(compile("let x = 1;").is_err());
+
+        // Local definitions can't be used by other expressions.
+
+        assert!(compile("let x = 1; {x} -> {c}").is_ok());
+        assert!(compile("{x} -> {c}").is_err());
+
+        // `Program::compile` only accepts one output and no `let`
+        // statements.
+
+        assert!(Program::compile("{a} -> {b}, {c}", &env).is_err());
+        assert!(Program::compile("let x = 1; {x} -> {c}", &env).is_err());
+
+        // A shared subexpression that uses a local definition is
+        // computed before the local definitions that use it.
+
+        let b = Block::compile(
+            "let x = {a} + 1; let y = ({x} * 2) > 0; \
+             if {y} then {x} * 2 else 0 -> {c}",
+            &env,
+            &cal,
+            2,
+        )
+        .unwrap();
+        let mut exprs = vec![Program(b.expr, 1)];
+        let (defs, total) = share_subexprs(b.lets, &mut exprs, 4);
+
         assert_eq!(
-            eval(
-                &Expr::Mul(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(2.0))
+            defs.iter().map(Program::to_string).collect::<Vec<_>>(),
+            vec![
+                String::from("inp[0] + 1 -> out[2]"),
+                String::from("inp[2] * 2 -> out[4]"),
+                String::from("0 < inp[4] -> out[3]")
+            ]
+        );
+        assert_eq!(
+            exprs[0].to_string(),
+            "if inp[3] then inp[4] else 0 -> out[1]"
         );
+        assert_eq!(total, 5);
     }
 
     #[test]
-    fn test_eval_div_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
-        const FALSE: device::Value = device::Value::Bool(false);
-        const ZERO: device::Value = device::Value::Int(0);
-        const ONE: device::Value = device::Value::Int(1);
-        const TWO: device::Value = device::Value::Int(2);
-        const FP_ZERO: device::Value = device::Value::Flt(0.0);
-        const FP_ONE: device::Value = device::Value::Flt(1.0);
-        const FP_TWO: device::Value = device::Value::Flt(2.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
+    fn test_share_subexprs() {
+        let inputs = [String::from("a"), String::from("b"), String::from("d")];
+        let outputs = [String::from("b"), String::from("c")];
+        let env: Env = (&inputs, &outputs);
+        let share = |def: &str, exprs: &[&str]| {
+            let def =
+                Program::compile(def, &(&inputs[..2], &inputs[..])).unwrap();
+            let mut exprs: Vec<Program> = exprs
+                .iter()
+                .map(|e| Program::compile(e, &env).unwrap())
+                .collect();
+            let (defs, total) = share_subexprs(vec![def], &mut exprs, 3);
 
-        assert_eq!(
-            eval(
-                &Expr::Div(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
+            (
+                defs.iter().map(Program::to_string).collect::<Vec<_>>(),
+                exprs.iter().map(Program::to_string).collect::<Vec<_>>(),
+                total,
+            )
+        };
+
+        // The definition is used by both expressions. The sum is also
+        // used by the second expression so it gets computed first.
+
+        assert_eq!(
+            share(
+                "({a} + {b}) * 2 -> {d}",
+                &[
+                    "({a} + {b}) * 2 > 3 -> {b}",
+                    "({a} + {b}) * 2 + ({a} + {b}) -> {c}"
+                ]
             ),
-            Some(device::Value::Int(2))
+            (
+                vec![
+                    String::from("inp[0] + inp[1] -> out[3]"),
+                    String::from("inp[3] * 2 -> out[2]")
+                ],
+                vec![
+                    String::from("3 < inp[2] -> out[0]"),
+                    String::from("inp[2] + inp[3] -> out[1]")
+                ],
+                4
+            )
         );
+
+        // A shared subexpression that uses a definition is computed
+        // after the definitions.
+
         assert_eq!(
-            eval(
-                &Expr::Div(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
+            share(
+                "not {a} -> {d}",
+                &["{d} * 3 - 1 > 0 -> {b}", "{d} * 3 - 1 -> {c}"]
             ),
-            None
+            (
+                vec![
+                    String::from("not inp[0] -> out[2]"),
+                    String::from("inp[2] * 3 - 1 -> out[3]")
+                ],
+                vec![
+                    String::from("0 < inp[3] -> out[0]"),
+                    String::from("inp[3] -> out[1]")
+                ],
+                4
+            )
         );
+
+        // Each stateful function has its own state so they aren't
+        // shared.
+
         assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
+            share(
+                "not {a} -> {d}",
+                &["rising({a}) and {b} -> {b}", "rising({a}) -> {c}"]
             ),
-            None
+            (
+                vec![String::from("not inp[0] -> out[2]")],
+                vec![
+                    String::from("rising(inp[0]) and inp[1] -> out[0]"),
+                    String::from("rising(inp[0]) -> out[1]")
+                ],
+                3
+            )
         );
+
+        // Subexpressions that are only in branches which may be
+        // skipped aren't shared, since they'd always be computed.
+
         assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
+            share(
+                "not {a} -> {d}",
+                &[
+                    "if {b} = 0 then 0 else {a} / {b} -> {b}",
+                    "if {b} = 0 then 1 else {a} / {b} + 1 -> {c}"
+                ]
             ),
-            Some(device::Value::Flt(2.0))
+            (
+                vec![
+                    String::from("inp[1] = 0 -> out[3]"),
+                    String::from("not inp[0] -> out[2]")
+                ],
+                vec![
+                    String::from(
+                        "if inp[3] then 0 else inp[0] / inp[1] -> out[0]"
+                    ),
+                    String::from(
+                        "if inp[3] then 1 else inp[0] / inp[1] + 1 -> out[1]"
+                    )
+                ],
+                4
+            )
+        );
+
+        // They're shared if they're also used where they're always
+        // computed.
+
+        assert_eq!(
+            share(
+                "not {a} -> {d}",
+                &["{a} / {b} > 1 -> {b}", "{a} > 0 and {a} / {b} < 3 -> {c}"]
+            ),
+            (
+                vec![
+                    String::from("inp[0] / inp[1] -> out[3]"),
+                    String::from("not inp[0] -> out[2]")
+                ],
+                vec![
+                    String::from("1 < inp[3] -> out[0]"),
+                    String::from("0 < inp[0] and inp[3] < 3 -> out[1]")
+                ],
+                4
+            )
         );
-        assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
+    }
+
+    // A small pseudo-random number generator (xorshift) so the
+    // property tests are repeatable.
+
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+    }
+
+    // The devices used by the generated expressions: two booleans,
+    // two integers, a float and a string.
+
+    const GEN_TYPES: &[Option<Type>] = &[
+        Some(Type::Bool),
+        Some(Type::Bool),
+        Some(Type::Int),
+        Some(Type::Int),
+        Some(Type::Flt),
+        Some(Type::Str),
+    ];
+
+    // Generates a random value of the given type.
+
+    fn gen_value(rng: &mut Rng, t: Type) -> device::Value {
+        match t {
+            Type::Bool => device::Value::Bool(rng.below(2) == 1),
+            Type::Int => device::Value::Int(rng.below(7) as i32 - 3),
+            Type::Flt => device::Value::Flt((rng.below(13) as f64 - 6.0) / 2.0),
+            _ => device::Value::Str(
+                ["", "a", "ab", "ba"][rng.below(4) as usize].into(),
             ),
-            None
+        }
+    }
+
+    // Generates values for the inputs. Most of them have the type in
+    // `GEN_TYPES` but, since a device can report a different type
+    // than the one it had when the node started, some have a random
+    // type.
+
+    fn gen_inputs(rng: &mut Rng) -> Vec<Option<device::Value>> {
+        const ANY: [Type; 4] = [Type::Bool, Type::Int, Type::Flt, Type::Str];
+
+        GEN_TYPES
+            .iter()
+            .map(|t| match rng.below(8) {
+                0 => None,
+                1 => {
+                    let t = ANY[rng.below(4) as usize];
+
+                    Some(gen_value(rng, t))
+                }
+                _ => Some(gen_value(rng, t.unwrap())),
+            })
+            .collect()
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Kind {
+        Bool,
+        Num,
+        Str,
+    }
+
+    // Generates random expressions that are correctly typed. The
+    // fields hold the inputs that can be used for each kind of
+    // value. If `pool` is `Some`, generated subexpressions are saved
+    // in it and get reused, so some expressions have subexpressions
+    // in common.
+
+    struct Gen {
+        bools: &'static [usize],
+        nums: &'static [usize],
+        strs: &'static [usize],
+        pool: Option<Vec<(Kind, Expr)>>,
+    }
+
+    impl Gen {
+        fn expr(&mut self, rng: &mut Rng, kind: Kind, depth: u32) -> Expr {
+            fn pick(rng: &mut Rng, v: &[usize]) -> Expr {
+                Expr::Var(v[rng.below(v.len() as u64) as usize])
+            }
+
+            fn call(name: &str, args: Vec<Expr>) -> Expr {
+                Expr::Call(
+                    BUILTINS.iter().find(|f| f.name == name).unwrap(),
+                    args,
+                )
+            }
+
+            if let Some(pool) = &self.pool {
+                let found: Vec<&Expr> = pool
+                    .iter()
+                    .filter(|(k, _)| *k == kind)
+                    .map(|(_, e)| e)
+                    .collect();
+
+                if !found.is_empty() && rng.below(4) == 0 {
+                    return found[rng.below(found.len() as u64) as usize]
+                        .clone();
+                }
+            }
+
+            let leaf = depth == 0 || rng.below(4) == 0;
+            let choice = rng.below(10);
+
+            let e = if leaf {
+                match kind {
+                    Kind::Bool if choice < 3 => {
+                        Expr::Lit(device::Value::Bool(choice == 1))
+                    }
+                    Kind::Bool => pick(rng, self.bools),
+                    Kind::Num if choice < 2 => {
+                        Expr::Lit(device::Value::Int(rng.below(5) as i32 - 2))
+                    }
+                    Kind::Num if choice < 4 => {
+                        Expr::Lit(device::Value::Flt(rng.below(3) as f64))
+                    }
+                    Kind::Num => pick(rng, self.nums),
+                    Kind::Str if choice < 3 => {
+                        Expr::Lit(device::Value::Str("b".into()))
+                    }
+                    Kind::Str => pick(rng, self.strs),
+                }
+            } else {
+                let d = depth - 1;
+                let mut sub = |k| Box::new(self.expr(rng, k, d));
+
+                match (kind, choice) {
+                    (Kind::Bool, 0) => Expr::Not(sub(Kind::Bool)),
+                    (Kind::Bool, 1) => {
+                        Expr::And(sub(Kind::Bool), sub(Kind::Bool))
+                    }
+                    (Kind::Bool, 2) => {
+                        Expr::Or(sub(Kind::Bool), sub(Kind::Bool))
+                    }
+                    (Kind::Bool, 3) => Expr::Eq(sub(Kind::Num), sub(Kind::Num)),
+                    (Kind::Bool, 4) => Expr::Lt(sub(Kind::Num), sub(Kind::Num)),
+                    (Kind::Bool, 5) => {
+                        Expr::LtEq(sub(Kind::Num), sub(Kind::Num))
+                    }
+                    (Kind::Bool, 6) => {
+                        Expr::Eq(sub(Kind::Bool), sub(Kind::Bool))
+                    }
+                    (Kind::Bool, 7) => Expr::IfElse(
+                        sub(Kind::Bool),
+                        sub(Kind::Bool),
+                        sub(Kind::Bool),
+                    ),
+                    (Kind::Bool, 8) => Expr::StatefulCall(
+                        &STATEFUL_BUILTINS[0],
+                        vec![*sub(Kind::Bool)],
+                    ),
+                    (Kind::Bool, _) => call(
+                        "starts_with",
+                        vec![*sub(Kind::Str), *sub(Kind::Str)],
+                    ),
+                    (Kind::Num, 0) => Expr::Add(sub(Kind::Num), sub(Kind::Num)),
+                    (Kind::Num, 1) => Expr::Sub(sub(Kind::Num), sub(Kind::Num)),
+                    (Kind::Num, 2) => Expr::Mul(sub(Kind::Num), sub(Kind::Num)),
+                    (Kind::Num, 3) => Expr::Div(sub(Kind::Num), sub(Kind::Num)),
+                    (Kind::Num, 4) => Expr::Rem(sub(Kind::Num), sub(Kind::Num)),
+                    (Kind::Num, 5) => Expr::IfElse(
+                        sub(Kind::Bool),
+                        sub(Kind::Num),
+                        sub(Kind::Num),
+                    ),
+                    (Kind::Num, 6) => {
+                        call("min", vec![*sub(Kind::Num), *sub(Kind::Num)])
+                    }
+                    (Kind::Num, 7) => call("abs", vec![*sub(Kind::Num)]),
+                    (Kind::Num, _) => call("len", vec![*sub(Kind::Str)]),
+                    (Kind::Str, 0..=3) => {
+                        Expr::Add(sub(Kind::Str), sub(Kind::Str))
+                    }
+                    (Kind::Str, 4..=6) => Expr::IfElse(
+                        sub(Kind::Bool),
+                        sub(Kind::Str),
+                        sub(Kind::Str),
+                    ),
+                    (Kind::Str, _) => Expr::Format(
+                        vec![String::new(), String::from("-"), String::new()],
+                        vec![*sub(Kind::Num), *sub(Kind::Str)],
+                    ),
+                }
+            };
+
+            if let Some(pool) = &mut self.pool {
+                if !leaf {
+                    pool.push((kind, e.clone()))
+                }
+            }
+            e
+        }
+    }
+
+    const KINDS: [Kind; 3] = [Kind::Bool, Kind::Num, Kind::Str];
+
+    // Optimized expressions have to produce the same results as the
+    // original ones, even when an input has a different type than
+    // the one the expression was optimized for. Since some
+    // expressions have state, the inputs are run, in order, through
+    // both.
+
+    #[test]
+    fn test_optimizer_properties() {
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        let mut gen = Gen {
+            bools: &[0, 1],
+            nums: &[2, 3, 4],
+            strs: &[5],
+            pool: None,
+        };
+
+        for n in 0..3000 {
+            let e = gen.expr(&mut rng, KINDS[n % 3], 4);
+            let opt = optimize(e.clone());
+            let mut opt_code = Code::new(&opt);
+            let mut code = Code::new(&e);
+
+            for _ in 0..8 {
+                let inp = gen_inputs(&mut rng);
+
+                assert_eq!(
+                    opt_code.eval(&inp, &time, None),
+                    code.eval(&inp, &time, None),
+                    "'{}' optimized to '{}' with {:?}",
+                    e,
+                    opt,
+                    inp
+                );
+            }
+        }
+    }
+
+    // Replaces the inputs of `e` that are computed by `progs` with
+    // their expressions.
+
+    fn inline(e: &Expr, progs: &[Program]) -> Expr {
+        match e {
+            Expr::Var(n) => match progs.iter().find(|prog| prog.1 == *n) {
+                Some(prog) => inline(&prog.0, progs),
+                None => e.clone(),
+            },
+            e => {
+                let mut e = e.clone();
+
+                for s in e.subexprs_mut() {
+                    *s = inline(s, progs)
+                }
+                e
+            }
+        }
+    }
+
+    // Adds every subexpression of `e` that's evaluated whenever `e`
+    // is to `out`.
+
+    fn always_evaluated<'a>(e: &'a Expr, out: &mut Vec<&'a Expr>) {
+        out.push(e);
+        match e {
+            Expr::And(a, _) | Expr::Or(a, _) | Expr::IfElse(a, _, _) => {
+                always_evaluated(a, out)
+            }
+            e => {
+                for s in e.subexprs() {
+                    always_evaluated(s, out)
+                }
+            }
+        }
+    }
+
+    // Sharing subexpressions mustn't change the results of a node's
+    // expressions. The generated nodes use the devices as inputs 0
+    // through 5 and have two definitions, stored in inputs 6 and 7.
+
+    #[test]
+    fn test_sharing_properties() {
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        let mut total_shared = 0;
+
+        for _ in 0..500 {
+            let mut gen = Gen {
+                bools: &[0, 1],
+                nums: &[2, 3, 4],
+                strs: &[5],
+                pool: Some(vec![]),
+            };
+            let defs = vec![
+                Program(gen.expr(&mut rng, Kind::Bool, 3), 6),
+                Program(gen.expr(&mut rng, Kind::Num, 3), 7),
+            ];
+
+            gen.bools = &[0, 1, 6];
+            gen.nums = &[2, 3, 4, 7];
+
+            let exprs: Vec<Program> = (0..4)
+                .map(|n| Program(gen.expr(&mut rng, KINDS[n % 3], 3), n))
+                .collect();
+            let mut s_exprs = exprs.clone();
+            let (s_defs, total) = share_subexprs(defs.clone(), &mut s_exprs, 8);
+
+            total_shared += total - 8;
+
+            // Every shared subexpression must be one that the node
+            // always computed. Otherwise, sharing it would compute
+            // (and report errors from) operands that `and`, `or` and
+            // `if` skip.
+
+            let mut always = vec![];
+            let originals: Vec<Expr> = defs
+                .iter()
+                .chain(&exprs)
+                .map(|prog| inline(&prog.0, &defs))
+                .collect();
+
+            for e in &originals {
+                always_evaluated(e, &mut always)
+            }
+            for prog in s_defs.iter().filter(|prog| prog.1 >= 8) {
+                let e = inline(&prog.0, &s_defs);
+
+                assert!(
+                    always.contains(&&e),
+                    "'{}' isn't always evaluated in {:?}",
+                    e,
+                    exprs
+                );
+            }
+
+            // Every definition may only read the devices and the slots
+            // computed before it.
+
+            let mut ready: Vec<usize> = (0..6).collect();
+
+            for prog in &s_defs {
+                let mut used = vec![];
+
+                inputs_used(&prog.0, &mut used);
+                assert!(
+                    used.iter().all(|n| ready.contains(n)),
+                    "'{}' evaluated too early in {:?}",
+                    prog,
+                    s_defs
+                );
+                ready.push(prog.1)
+            }
+
+            // The programs keep their state through the sequence of
+            // inputs, like they do in a node.
+
+            let code = |progs: &[Program]| -> Vec<(Code, usize)> {
+                progs.iter().map(|p| (Code::new(&p.0), p.1)).collect()
+            };
+            let (mut a_defs, mut b_defs) = (code(&defs), code(&s_defs));
+            let (mut a_exprs, mut b_exprs) = (code(&exprs), code(&s_exprs));
+
+            for _ in 0..8 {
+                let mut a = gen_inputs(&mut rng);
+                let mut b = a.clone();
+
+                a.resize(8, None);
+                b.resize(total, None);
+
+                for (code, idx) in &mut a_defs {
+                    a[*idx] = code.eval(&a, &time, None)
+                }
+                for (code, idx) in &mut b_defs {
+                    b[*idx] = code.eval(&b, &time, None)
+                }
+                for ((x, y), (a_code, b_code)) in exprs
+                    .iter()
+                    .zip(&s_exprs)
+                    .zip(a_exprs.iter_mut().zip(&mut b_exprs))
+                {
+                    assert_eq!(
+                        b_code.0.eval(&b, &time, None),
+                        a_code.0.eval(&a, &time, None),
+                        "'{}' shared as '{}' with {:?}",
+                        x,
+                        y,
+                        b
+                    );
+                }
+            }
+        }
+
+        assert!(total_shared > 0);
+    }
+
+    #[test]
+    fn test_to_string() {
+        let env: Env = (
+            &[String::from("a"), String::from("b")],
+            &[String::from("b"), String::from("c")],
         );
-        assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(FALSE))
+
+        for (in_val, out_val) in EXPRESSIONS {
+            match Program::compile(in_val, &env) {
+                Ok(prog) => assert_eq!(
+                    prog.to_string(),
+                    *out_val,
+                    "failed on: {}",
+                    in_val
                 ),
-                &[],
-                &time,
-                None
-            ),
-            None
+                Err(e) => panic!("{}", &e),
+            }
+        }
+    }
+
+    fn evaluate(
+        expr: &str,
+        time: &tod::Info,
+        sky: Option<&Sky>,
+    ) -> Option<device::Value> {
+        let env: Env = (&[], &[String::from("a")]);
+        let expr = format!("{} -> {{a}}", expr);
+        let prog = Program::compile(&expr, &env).unwrap();
+
+        eval(&prog.0, &[], time, sky)
+    }
+
+    #[test]
+    fn test_evaluations() {
+        use chrono::{TimeZone, Timelike};
+
+        let time = Arc::new((
+            chrono::Utc
+                .with_ymd_and_hms(2000, 1, 2, 3, 4, 5)
+                .single()
+                .unwrap(),
+            chrono::Local
+                .with_ymd_and_hms(2001, 6, 7, 8, 9, 10)
+                .single()
+                .unwrap()
+                .fixed_offset(),
+        ));
+
+        let at = |h, m, s| {
+            chrono::Utc.with_ymd_and_hms(2000, 1, 2, h, m, s).single()
+        };
+        let solar = Arc::new(solar::SolarInfo {
+            time: at(12, 0, 0).unwrap(),
+            elevation: 1.0,
+            azimuth: 2.0,
+            right_ascension: 3.0,
+            declination: 4.0,
+            sunrise: at(7, 30, 30),
+            sunset: at(16, 45, 0),
+            civil_dawn: at(7, 0, 0),
+            civil_dusk: at(17, 15, 0),
+            ..Default::default()
+        });
+        let sky = Sky {
+            solar: Some(solar.clone()),
+            lunar: Some(Arc::new(lunar::LunarInfo {
+                elevation: 20.0,
+                azimuth: 120.0,
+                illumination: 0.95,
+                phase: "full",
+            })),
+        };
+
+        assert_eq!(evaluate("1 / 0", &time, None), None);
+        assert_eq!(evaluate("5 > true", &time, None), None);
+
+        assert_eq!(
+            evaluate("1 + 2 * 3", &time, None),
+            Some(device::Value::Int(7))
+        );
+        assert_eq!(
+            evaluate("(1 + 2) * 3", &time, None),
+            Some(device::Value::Int(9))
+        );
+        assert_eq!(
+            evaluate("1 + (2 * 3)", &time, None),
+            Some(device::Value::Int(7))
         );
+
         assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(2.0))
+            evaluate("1 + 2 < 1 + 3", &time, None),
+            Some(device::Value::Bool(true))
         );
         assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(2.0))
+            evaluate("1 + 2 < 1 + 1", &time, None),
+            Some(device::Value::Bool(false))
         );
+
         assert_eq!(
-            eval(
-                &Expr::Div(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ZERO))),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("1 > 2 or 5 < 3", &time, None),
+            Some(device::Value::Bool(false))
         );
         assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ZERO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("1 > 2 or 5 >= 3", &time, None),
+            Some(device::Value::Bool(true))
         );
+
         assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(ZERO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{utc:second}", &time, None),
+            Some(device::Value::Int(5))
         );
         assert_eq!(
-            eval(
-                &Expr::Div(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(FP_ZERO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{utc:minute}", &time, None),
+            Some(device::Value::Int(4))
         );
-    }
-
-    #[test]
-    fn test_eval_rem_expr() {
-        const TRUE: device::Value = device::Value::Bool(true);
-        const FALSE: device::Value = device::Value::Bool(false);
-        const ZERO: device::Value = device::Value::Int(0);
-        const NEG_ONE: device::Value = device::Value::Int(-1);
-        const ONE: device::Value = device::Value::Int(1);
-        const TWO: device::Value = device::Value::Int(2);
-        const FP_ZERO: device::Value = device::Value::Flt(0.0);
-        const FP_ONE: device::Value = device::Value::Flt(1.0);
-        const FP_TWO: device::Value = device::Value::Flt(2.0);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-
         assert_eq!(
-            eval(
-                &Expr::Rem(Box::new(Expr::Lit(ONE)), Box::new(Expr::Lit(TWO))),
-                &[],
-                &time,
-                None
-            ),
+            evaluate("{utc:hour}", &time, None),
+            Some(device::Value::Int(3))
+        );
+        assert_eq!(
+            evaluate("{utc:day}", &time, None),
+            Some(device::Value::Int(2))
+        );
+        assert_eq!(
+            evaluate("{utc:month}", &time, None),
             Some(device::Value::Int(1))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(Box::new(Expr::Lit(TRUE)), Box::new(Expr::Lit(ONE))),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{utc:year}", &time, None),
+            Some(device::Value::Int(2000))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{utc:DOW}", &time, None),
+            Some(device::Value::Int(6))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(FP_TWO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            evaluate("{utc:DOY}", &time, None),
+            Some(device::Value::Int(1))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(TRUE)),
-                    Box::new(Expr::Lit(FP_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{local:second}", &time, None),
+            Some(device::Value::Int(10))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(FALSE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{local:minute}", &time, None),
+            Some(device::Value::Int(9))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(FP_ONE)),
-                    Box::new(Expr::Lit(TWO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            evaluate("{local:hour}", &time, None),
+            Some(device::Value::Int(8))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(ONE)),
-                    Box::new(Expr::Lit(FP_TWO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            Some(device::Value::Flt(1.0))
+            evaluate("{local:day}", &time, None),
+            Some(device::Value::Int(7))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(Box::new(Expr::Lit(TWO)), Box::new(Expr::Lit(ZERO))),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{local:month}", &time, None),
+            Some(device::Value::Int(6))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(FP_ZERO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{local:year}", &time, None),
+            Some(device::Value::Int(2001))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(ZERO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{local:DOW}", &time, None),
+            Some(device::Value::Int(3))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(FP_TWO)),
-                    Box::new(Expr::Lit(FP_ZERO))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{local:DOY}", &time, None),
+            Some(device::Value::Int(157))
         );
         assert_eq!(
-            eval(
-                &Expr::Rem(
-                    Box::new(Expr::Lit(TWO)),
-                    Box::new(Expr::Lit(NEG_ONE))
-                ),
-                &[],
-                &time,
-                None
-            ),
-            None
+            evaluate("{utc:week_of_month}", &time, None),
+            Some(device::Value::Int(1))
         );
-    }
-
-    #[test]
-    fn test_eval() {
-        const FALSE: device::Value = device::Value::Bool(false);
-        let time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-
-        assert_eq!(eval(&Expr::Lit(FALSE), &[], &time, None), Some(FALSE));
-    }
-
-    // This function tests the optimizations that can be done on an
-    // expression.
-
-    #[test]
-    fn test_not_optimizer() {
         assert_eq!(
-            optimize(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(true))))),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{local:week_of_month}", &time, None),
+            Some(device::Value::Int(1))
         );
+
+        // Verify the solar variable are working correctly.
+
+        assert_eq!(evaluate("{solar:alt}", &time, None), None);
+        assert_eq!(evaluate("{solar:az}", &time, None), None);
+        assert_eq!(evaluate("{solar:ra}", &time, None), None);
+        assert_eq!(evaluate("{solar:dec}", &time, None), None);
         assert_eq!(
-            optimize(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(
-                false
-            ))))),
-            Expr::Lit(device::Value::Bool(true))
+            evaluate("{solar:alt}", &time, Some(&sky)),
+            Some(device::Value::Flt(1.0))
         );
         assert_eq!(
-            optimize(Expr::Not(Box::new(Expr::Not(Box::new(Expr::Lit(
-                device::Value::Bool(true)
-            )))))),
-            Expr::Lit(device::Value::Bool(true))
+            evaluate("{solar:az}", &time, Some(&sky)),
+            Some(device::Value::Flt(2.0))
         );
         assert_eq!(
-            optimize(Expr::Not(Box::new(Expr::Not(Box::new(Expr::Not(
-                Box::new(Expr::Lit(device::Value::Bool(true)))
-            )))))),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{solar:ra}", &time, Some(&sky)),
+            Some(device::Value::Flt(3.0))
         );
         assert_eq!(
-            optimize(Expr::Not(Box::new(Expr::Not(Box::new(Expr::Not(
-                Box::new(Expr::Not(Box::new(Expr::Lit(device::Value::Bool(
-                    true
-                )))))
-            )))))),
-            Expr::Lit(device::Value::Bool(true))
+            evaluate("{solar:dec}", &time, Some(&sky)),
+            Some(device::Value::Flt(4.0))
         );
-    }
 
-    #[test]
-    fn test_and_optimizer() {
+        // The times of events are in minutes after local midnight.
+        // Events that don't happen don't have a value.
+
+        let sunrise = solar.sunrise.unwrap().with_timezone(time.1.offset());
+
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(false))),
-                Box::new(Expr::Lit(device::Value::Bool(false)))
-            )),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{solar:sunrise}", &time, Some(&sky)),
+            Some(device::Value::Flt(
+                (sunrise.hour() * 60 + sunrise.minute()) as f64 + 0.5
+            ))
         );
+        assert_eq!(evaluate("{solar:nautical_dawn}", &time, Some(&sky)), None);
+        assert_eq!(evaluate("{solar:astro_dusk}", &time, Some(&sky)), None);
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(false))),
-                Box::new(Expr::Lit(device::Value::Bool(true)))
-            )),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{solar:is_day}", &time, Some(&sky)),
+            Some(device::Value::Bool(true))
         );
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(false))),
-                Box::new(Expr::Lit(device::Value::Str("test".into())))
-            )),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{solar:minutes_to_sunrise}", &time, Some(&sky)),
+            Some(device::Value::Flt(-269.5))
         );
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Lit(device::Value::Bool(false)))
-            )),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{solar:minutes_to_sunset}", &time, Some(&sky)),
+            Some(device::Value::Flt(285.0))
         );
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Lit(device::Value::Bool(true)))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            evaluate("{solar:minutes_to_civil_dawn}", &time, Some(&sky)),
+            Some(device::Value::Flt(-300.0))
         );
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Lit(device::Value::Str("test".into())))
-            )),
-            Expr::Lit(device::Value::Str("test".into()))
+            evaluate("{solar:minutes_to_civil_dusk}", &time, Some(&sky)),
+            Some(device::Value::Flt(315.0))
+        );
+        assert_eq!(
+            evaluate("{solar:minutes_to_sunset} < 300", &time, Some(&sky)),
+            Some(device::Value::Bool(true))
         );
 
+        // Verify the lunar values. A sky without lunar information
+        // doesn't produce values.
+
+        let no_moon = Sky {
+            solar: Some(solar.clone()),
+            lunar: None,
+        };
+
+        assert_eq!(evaluate("{lunar:alt}", &time, None), None);
+        assert_eq!(evaluate("{lunar:phase}", &time, Some(&no_moon)), None);
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::And(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(true)))
-                ))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            evaluate("{lunar:alt}", &time, Some(&sky)),
+            Some(device::Value::Flt(20.0))
         );
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::And(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(true)))
-                )),
-                Box::new(Expr::Lit(device::Value::Bool(true)))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            evaluate("{lunar:az}", &time, Some(&sky)),
+            Some(device::Value::Flt(120.0))
         );
-
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::And(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(false)))
-                ))
-            )),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{lunar:illum}", &time, Some(&sky)),
+            Some(device::Value::Flt(0.95))
         );
         assert_eq!(
-            optimize(Expr::And(
-                Box::new(Expr::And(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(true)))
-                )),
-                Box::new(Expr::Lit(device::Value::Bool(false)))
-            )),
-            Expr::Lit(device::Value::Bool(false))
+            evaluate("{lunar:phase}", &time, Some(&sky)),
+            Some(device::Value::Str("full".into()))
+        );
+        assert_eq!(
+            evaluate(
+                "if {lunar:illum} > 0.9 and not {solar:is_day} then 30 else 100",
+                &time,
+                Some(&sky)
+            ),
+            Some(device::Value::Int(100))
         );
     }
 
     #[test]
-    fn test_or_optimizer() {
-        assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(false))),
-                Box::new(Expr::Lit(device::Value::Bool(false)))
-            )),
-            Expr::Lit(device::Value::Bool(false))
-        );
+    fn test_eval_calls() {
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+        const DATA: &[(&str, Option<device::Value>)] = &[
+            ("min(1, 2)", Some(device::Value::Int(1))),
+            ("min(2, 1)", Some(device::Value::Int(1))),
+            ("min(1, 2.5)", Some(device::Value::Flt(1.0))),
+            ("min(2.5, 1)", Some(device::Value::Flt(1.0))),
+            ("min(-1.5, 2.5)", Some(device::Value::Flt(-1.5))),
+            ("max(1, 2)", Some(device::Value::Int(2))),
+            ("max(2, 1)", Some(device::Value::Int(2))),
+            ("max(1, 2.5)", Some(device::Value::Flt(2.5))),
+            ("max(2.5, 1)", Some(device::Value::Flt(2.5))),
+            ("abs(-5)", Some(device::Value::Int(5))),
+            ("abs(5)", Some(device::Value::Int(5))),
+            ("abs(-5.5)", Some(device::Value::Flt(5.5))),
+            ("abs(-2147483647 - 1)", None),
+            ("clamp(5, 0, 10)", Some(device::Value::Int(5))),
+            ("clamp(-5, 0, 10)", Some(device::Value::Int(0))),
+            ("clamp(15, 0, 10)", Some(device::Value::Int(10))),
+            ("clamp(15.5, 0, 10)", Some(device::Value::Flt(10.0))),
+            ("clamp(5, 0.5, 10)", Some(device::Value::Flt(5.0))),
+            ("clamp(5, 10, 0)", None),
+            ("round(2.5)", Some(device::Value::Int(3))),
+            ("round(2.4)", Some(device::Value::Int(2))),
+            ("round(-2.5)", Some(device::Value::Int(-3))),
+            ("round(7)", Some(device::Value::Int(7))),
+            ("round(1.0e20)", None),
+            ("floor(2.9)", Some(device::Value::Int(2))),
+            ("floor(-2.1)", Some(device::Value::Int(-3))),
+            ("floor(7)", Some(device::Value::Int(7))),
+            ("ceil(2.1)", Some(device::Value::Int(3))),
+            ("ceil(-2.9)", Some(device::Value::Int(-2))),
+            ("ceil(7)", Some(device::Value::Int(7))),
+            (
+                "rgb(127, 128, 129) = #7f8081",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "rgb(127.6, 0, 0) = #800000",
+                Some(device::Value::Bool(true)),
+            ),
+            ("rgb(256, 0, 0)", None),
+            ("rgb(-1, 0, 0)", None),
+            ("hsv(0, 1, 1) = #red", Some(device::Value::Bool(true))),
+            ("hsv(120, 1, 1) = #lime", Some(device::Value::Bool(true))),
+            ("hsv(240, 1, 0) = #black", Some(device::Value::Bool(true))),
+            ("hsv(0, 0, 0.5) = #373737", Some(device::Value::Bool(true))),
+            ("hsv(0, 1.5, 1)", None),
+            (
+                "blend(#000, #fff, 0) = #000",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "blend(#000, #fff, 1) = #fff",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "blend(#000, #fff, 0.5) = #808080",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "blend(#000, #fff, 2) = #fff",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "blend(#00000000, #20406080, 0.5) = #10203040",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "brightness(#ff8040, 0.5) = #804020",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "brightness(#ff8040, 2) = #ffff80",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "brightness(#ff804080, 0) = #00000080",
+                Some(device::Value::Bool(true)),
+            ),
+            ("brightness(#ff8040, -1)", None),
+            ("red(#7f808182)", Some(device::Value::Int(127))),
+            ("green(#7f808182)", Some(device::Value::Int(128))),
+            ("blue(#7f808182)", Some(device::Value::Int(129))),
+            ("alpha(#7f808182)", Some(device::Value::Int(130))),
+            ("alpha(rgb(1, 2, 3))", Some(device::Value::Int(255))),
+            ("round(1.8 * 10.0) + 32", Some(device::Value::Int(50))),
+            ("min(1, 1 / 0)", None),
+        ];
+
+        for (expr, result) in DATA {
+            assert_eq!(
+                &evaluate(expr, &time, None),
+                result,
+                "error using {}",
+                expr
+            );
+        }
+
+        // Values that aren't known at compile-time get checked when
+        // evaluated.
+
+        let expr = to_expr("min({a}, {b})");
+
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(false))),
-                Box::new(Expr::Lit(device::Value::Bool(true)))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            eval(
+                &expr,
+                &[Some(device::Value::Int(3)), Some(device::Value::Flt(2.0))],
+                &time,
+                None
+            ),
+            Some(device::Value::Flt(2.0))
         );
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(false))),
-                Box::new(Expr::Lit(device::Value::Str("test".into())))
-            )),
-            Expr::Lit(device::Value::Str("test".into()))
+            eval(
+                &expr,
+                &[Some(device::Value::Int(3)), Some(device::Value::Bool(true))],
+                &time,
+                None
+            ),
+            None
         );
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Lit(device::Value::Bool(false)))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            eval(&expr, &[Some(device::Value::Int(3)), None], &time, None),
+            None
         );
+    }
+
+    // Evaluates an expression using a sequence of input values. The
+    // expression keeps its state between evaluations so this is used
+    // to test the stateful functions.
+
+    fn eval_sequence(
+        expr: &str,
+        inputs: &[&[Option<device::Value>]],
+    ) -> Vec<Option<device::Value>> {
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+        let mut code = Code::new(&to_expr(expr));
+
+        inputs
+            .iter()
+            .map(|inp| code.eval(inp, &time, None))
+            .collect()
+    }
+
+    #[test]
+    fn test_eval_stateful_calls() {
+        const T: Option<device::Value> = Some(device::Value::Bool(true));
+        const F: Option<device::Value> = Some(device::Value::Bool(false));
+
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Lit(device::Value::Bool(true)))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            eval_sequence(
+                "rising({a})",
+                &[&[F], &[T], &[T], &[F], &[None], &[T], &[T]]
+            ),
+            vec![F, T, F, F, None, T, F]
         );
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Lit(device::Value::Str("test".into())))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            eval_sequence("rising({a})", &[&[T], &[T], &[F], &[T]]),
+            vec![F, F, F, T]
         );
-
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Or(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(true)))
-                ))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            eval_sequence(
+                "falling({a})",
+                &[&[T], &[F], &[F], &[T], &[F], &[F]]
+            ),
+            vec![F, T, F, F, T, F]
         );
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Or(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(true)))
-                )),
-                Box::new(Expr::Lit(device::Value::Bool(true)))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            eval_sequence("falling({a})", &[&[Some(device::Value::Int(1))]]),
+            vec![None]
         );
 
+        // The latch starts cleared, is set by the first argument and
+        // cleared by the second. Clearing has priority.
+
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Lit(device::Value::Bool(true))),
-                Box::new(Expr::Or(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(false)))
-                ))
-            )),
-            Expr::Lit(device::Value::Bool(true))
-        );
-        assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Or(
-                    Box::new(Expr::Lit(device::Value::Bool(true))),
-                    Box::new(Expr::Lit(device::Value::Bool(true)))
-                )),
-                Box::new(Expr::Lit(device::Value::Bool(false)))
-            )),
-            Expr::Lit(device::Value::Bool(true))
+            eval_sequence(
+                "latch({a}, {b})",
+                &[
+                    &[F, F],
+                    &[T, F],
+                    &[F, F],
+                    &[T, T],
+                    &[F, F],
+                    &[T, F],
+                    &[F, T],
+                    &[None, F],
+                    &[F, F]
+                ]
+            ),
+            vec![F, T, T, F, F, T, F, None, F]
         );
+
+        // Hysteresis turns on above the upper limit and off below
+        // the lower limit.
+
+        {
+            let v = |x: f64| Some(device::Value::Flt(x));
+
+            assert_eq!(
+                eval_sequence(
+                    "hysteresis({a}, 68, 72)",
+                    &[
+                        &[v(70.0)],
+                        &[v(72.0)],
+                        &[v(72.5)],
+                        &[v(70.0)],
+                        &[v(68.0)],
+                        &[v(67.9)],
+                        &[v(71.0)],
+                        &[Some(device::Value::Int(73))],
+                    ]
+                ),
+                vec![F, F, T, T, T, F, F, T]
+            );
+            assert_eq!(
+                eval_sequence("hysteresis({a}, 72, 68)", &[&[v(70.0)]]),
+                vec![None]
+            );
+        }
+
+        // Each use of a stateful function has its own state.
+
         assert_eq!(
-            optimize(Expr::Or(
-                Box::new(Expr::Or(
-                    Box::new(Expr::Lit(device::Value::Bool(false))),
-                    Box::new(Expr::Lit(device::Value::Bool(false)))
-                )),
-                Box::new(Expr::Lit(device::Value::Bool(false)))
-            )),
-            Expr::Lit(device::Value::Bool(false))
+            eval_sequence(
+                "rising({a}) or rising({b})",
+                &[&[F, F], &[T, F], &[T, T], &[T, T]]
+            ),
+            vec![F, T, T, F]
         );
-    }
 
-    #[test]
-    fn test_to_string() {
-        let env: Env = (
-            &[String::from("a"), String::from("b")],
-            &[String::from("b"), String::from("c")],
+        // Stateful functions only take values of the correct type.
+
+        let env: Env = (&[String::from("a")], &[String::from("b")]);
+
+        assert!(Program::compile("rising(1) -> {b}", &env).is_err());
+        assert!(Program::compile("rising({a} + 1) -> {b}", &env).is_err());
+        assert!(Program::compile("rising({a}, {a}) -> {b}", &env).is_err());
+        assert!(Program::compile("latch(true) -> {b}", &env).is_err());
+        assert!(Program::compile("latch({a}, 5) -> {b}", &env).is_err());
+        assert!(
+            Program::compile("hysteresis({a} > 1, 1, 2) -> {b}", &env).is_err()
         );
+        assert!(
+            Program::compile("hysteresis(rising({a}), 1, 2) -> {b}", &env)
+                .is_err()
+        );
+        assert!(Program::compile("rising({a} > 1) -> {b}", &env).is_ok());
+    }
 
-        const TESTS: &[(&str, &str)] = &[
-            ("{a} -> {b}", "inp[0] -> out[0]"),
-            ("true -> {b}", "true -> out[0]"),
-            ("not true -> {b}", "not true -> out[0]"),
-            ("{a} and {b} -> {c}", "inp[0] and inp[1] -> out[1]"),
-            ("{a} or {b} -> {c}", "inp[0] or inp[1] -> out[1]"),
+    #[test]
+    fn test_eval_strings() {
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+        const DATA: &[(&str, Option<device::Value>)] = &[
+            ("len(\"\")", Some(device::Value::Int(0))),
+            ("len(\"hello\")", Some(device::Value::Int(5))),
+            ("len(\"déjà\")", Some(device::Value::Int(4))),
+            (
+                "contains(\"light rain\", \"rain\")",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "contains(\"light rain\", \"snow\")",
+                Some(device::Value::Bool(false)),
+            ),
+            (
+                "starts_with(\"light rain\", \"light\")",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "starts_with(\"light rain\", \"rain\")",
+                Some(device::Value::Bool(false)),
+            ),
+            (
+                "ends_with(\"light rain\", \"rain\")",
+                Some(device::Value::Bool(true)),
+            ),
             (
-                "{a} and {b} or true -> {c}",
-                "inp[0] and inp[1] or true -> out[1]",
+                "\"ab\" + \"cd\" = \"abcd\"",
+                Some(device::Value::Bool(true)),
             ),
+            ("\"ab\" + \"\" = \"ab\"", Some(device::Value::Bool(true))),
+            ("\"ab\" + 1", None),
             (
-                "{a} and ({b} or true) -> {c}",
-                "inp[0] and (inp[1] or true) -> out[1]",
+                "format(\"temp is {} F\", 72.5) = \"temp is 72.5 F\"",
+                Some(device::Value::Bool(true)),
             ),
-            ("{a} = {b} -> {c}", "inp[0] = inp[1] -> out[1]"),
-            ("{a} < {b} -> {c}", "inp[0] < inp[1] -> out[1]"),
-            ("{a} <= {b} -> {c}", "inp[0] <= inp[1] -> out[1]"),
-            ("{a} + {b} -> {c}", "inp[0] + inp[1] -> out[1]"),
             (
-                "{a} + {b} + {b} -> {c}",
-                "inp[0] + inp[1] + inp[1] -> out[1]",
+                "format(\"{}{}{}\", \"a\", 1, true) = \"a1true\"",
+                Some(device::Value::Bool(true)),
             ),
-            ("{a} - {b} -> {c}", "inp[0] - inp[1] -> out[1]"),
-            ("{a} * {b} -> {c}", "inp[0] * inp[1] -> out[1]"),
-            ("{a} / {b} -> {c}", "inp[0] / inp[1] -> out[1]"),
-            ("{a} % {b} -> {c}", "inp[0] % inp[1] -> out[1]"),
             (
-                "{a} * 3 + {b} > 4 -> {c}",
-                "4 < inp[0] * 3 + inp[1] -> out[1]",
+                "format(\"{{{}}}\", #red) = \"{#ff0000}\"",
+                Some(device::Value::Bool(true)),
             ),
             (
-                "{a} * (3 + {b}) > 4 -> {c}",
-                "4 < inp[0] * (3 + inp[1]) -> out[1]",
-            ),
-            ("{utc:second} -> {c}", "{utc:second} -> out[1]"),
-            ("{utc:minute} -> {c}", "{utc:minute} -> out[1]"),
-            ("{utc:hour} -> {c}", "{utc:hour} -> out[1]"),
-            ("{utc:day} -> {c}", "{utc:day} -> out[1]"),
-            ("{utc:month} -> {c}", "{utc:month} -> out[1]"),
-            ("{utc:year} -> {c}", "{utc:year} -> out[1]"),
-            ("{utc:DOW} -> {c}", "{utc:DOW} -> out[1]"),
-            ("{utc:DOY} -> {c}", "{utc:DOY} -> out[1]"),
-            ("{local:second} -> {c}", "{local:second} -> out[1]"),
-            ("{local:minute} -> {c}", "{local:minute} -> out[1]"),
-            ("{local:hour} -> {c}", "{local:hour} -> out[1]"),
-            ("{local:day} -> {c}", "{local:day} -> out[1]"),
-            ("{local:month} -> {c}", "{local:month} -> out[1]"),
-            ("{local:year} -> {c}", "{local:year} -> out[1]"),
-            ("{local:DOW} -> {c}", "{local:DOW} -> out[1]"),
-            ("{local:DOY} -> {c}", "{local:DOY} -> out[1]"),
+                "format(\"no args\") = \"no args\"",
+                Some(device::Value::Bool(true)),
+            ),
+            (
+                "len(format(\"{} {}\", \"a\", \"b\"))",
+                Some(device::Value::Int(3)),
+            ),
         ];
 
-        for (in_val, out_val) in TESTS {
-            match Program::compile(in_val, &env) {
-                Ok(prog) => assert_eq!(
-                    prog.to_string(),
-                    *out_val,
-                    "failed on: {}",
-                    in_val
-                ),
-                Err(e) => panic!("{}", &e),
-            }
+        for (expr, result) in DATA {
+            assert_eq!(
+                &evaluate(expr, &time, None),
+                result,
+                "error using {}",
+                expr
+            );
         }
-    }
-
-    fn evaluate(
-        expr: &str,
-        time: &tod::Info,
-        solar: Option<&solar::Info>,
-    ) -> Option<device::Value> {
-        let env: Env = (&[], &[String::from("a")]);
-        let expr = format!("{} -> {{a}}", expr);
-        let prog = Program::compile(&expr, &env).unwrap();
-
-        eval(&prog.0, &[], &time, solar)
-    }
-
-    #[test]
-    fn test_evaluations() {
-        use chrono::TimeZone;
-
-        let time = Arc::new((
-            chrono::Utc
-                .with_ymd_and_hms(2000, 1, 2, 3, 4, 5)
-                .single()
-                .unwrap(),
-            chrono::Local
-                .with_ymd_and_hms(2001, 6, 7, 8, 9, 10)
-                .single()
-                .unwrap(),
-        ));
 
-        let solar = Arc::new(solar::SolarInfo {
-            elevation: 1.0,
-            azimuth: 2.0,
-            right_ascension: 3.0,
-            declination: 4.0,
-        });
+        // Built strings are limited in length.
 
-        assert_eq!(evaluate("1 / 0", &time, None), None);
-        assert_eq!(evaluate("5 > true", &time, None), None);
+        let expr = to_expr("{a} + {b}");
+        let half: device::Value = "x".repeat(MAX_STR_LEN / 2).as_str().into();
+        let more: device::Value =
+            "x".repeat(MAX_STR_LEN / 2 + 1).as_str().into();
 
         assert_eq!(
-            evaluate("1 + 2 * 3", &time, None),
-            Some(device::Value::Int(7))
-        );
-        assert_eq!(
-            evaluate("(1 + 2) * 3", &time, None),
-            Some(device::Value::Int(9))
+            eval(
+                &expr,
+                &[Some(half.clone()), Some(half.clone())],
+                &time,
+                None
+            ),
+            Some("x".repeat(MAX_STR_LEN).as_str().into())
         );
         assert_eq!(
-            evaluate("1 + (2 * 3)", &time, None),
-            Some(device::Value::Int(7))
+            eval(&expr, &[Some(half.clone()), Some(more)], &time, None),
+            None
         );
 
-        assert_eq!(
-            evaluate("1 + 2 < 1 + 3", &time, None),
-            Some(device::Value::Bool(true))
-        );
-        assert_eq!(
-            evaluate("1 + 2 < 1 + 1", &time, None),
-            Some(device::Value::Bool(false))
-        );
+        let expr = to_expr("format(\"{}{}{}\", {a}, {b}, {a})");
 
         assert_eq!(
-            evaluate("1 > 2 or 5 < 3", &time, None),
-            Some(device::Value::Bool(false))
-        );
-        assert_eq!(
-            evaluate("1 > 2 or 5 >= 3", &time, None),
-            Some(device::Value::Bool(true))
+            eval(&expr, &[Some(half.clone()), Some(half)], &time, None),
+            None
         );
 
-        assert_eq!(
-            evaluate("{utc:second}", &time, None),
-            Some(device::Value::Int(5))
-        );
-        assert_eq!(
-            evaluate("{utc:minute}", &time, None),
-            Some(device::Value::Int(4))
+        // Check the arguments when compiling.
+
+        let env: Env = (&[String::from("a")], &[String::from("b")]);
+
+        assert!(Program::compile("len(1) -> {b}", &env).is_err());
+        assert!(Program::compile("len(#red) -> {b}", &env).is_err());
+        assert!(Program::compile("len({a} - 1) -> {b}", &env).is_err());
+        assert!(Program::compile("len(len({a})) -> {b}", &env).is_err());
+        assert!(
+            Program::compile("contains({a}, \"x\") + 1 -> {b}", &env).is_ok()
+        );
+        assert!(
+            Program::compile("abs(contains({a}, \"x\")) -> {b}", &env).is_err()
+        );
+        assert!(Program::compile(
+            "if contains({a}, \"x\") then 1 else 0 -> {b}",
+            &env
+        )
+        .is_ok());
+        assert!(Program::compile(
+            "rising(starts_with({a}, \"x\")) -> {b}",
+            &env
+        )
+        .is_ok());
+        assert!(Program::compile("format({a}, 1) -> {b}", &env).is_err());
+        assert!(Program::compile("format(\"{}\") -> {b}", &env).is_err());
+        assert!(Program::compile("format(\"x\", 1) -> {b}", &env).is_err());
+        assert!(Program::compile("format(\"{x}\", 1) -> {b}", &env).is_err());
+        assert!(Program::compile("format(\"}\") -> {b}", &env).is_err());
+        assert!(Program::compile("len(format(\"{}\", 1)) -> {b}", &env).is_ok());
+        assert!(
+            Program::compile("abs(format(\"{}\", 1)) -> {b}", &env).is_err()
         );
+    }
+
+    #[test]
+    fn test_type_inference() {
+        // `{a}` is a float and the type of `{b}` is unknown.
+
+        const TYPES: &[Option<Type>] = &[Some(Type::Flt), None];
+        const GOOD: &[(&str, Option<Type>)] = &[
+            ("{a}", Some(Type::Flt)),
+            ("{b}", None),
+            ("1", Some(Type::Int)),
+            ("\"x\"", Some(Type::Str)),
+            ("#red", Some(Type::Color)),
+            ("{utc:hour}", Some(Type::Int)),
+            ("{solar:alt}", Some(Type::Flt)),
+            ("{solar:sunset}", Some(Type::Flt)),
+            ("{solar:is_day}", Some(Type::Bool)),
+            ("{lunar:illum}", Some(Type::Flt)),
+            ("{lunar:phase}", Some(Type::Str)),
+            ("{local:week_of_month}", Some(Type::Int)),
+            ("{cal:holiday}", Some(Type::Bool)),
+            ("{a} > 70", Some(Type::Bool)),
+            ("{b} and true", Some(Type::Bool)),
+            ("not {b}", Some(Type::Bool)),
+            ("{a} + 1", Some(Type::Flt)),
+            ("1 + 2", Some(Type::Int)),
+            ("true + 2", Some(Type::Int)),
+            ("{b} + 1", None),
+            ("\"a\" + \"b\"", Some(Type::Str)),
+            ("7 % 2", Some(Type::Int)),
+            ("min(1, 2) * 2", Some(Type::Num)),
+            ("min(1, 2) * 2.0", Some(Type::Flt)),
+            ("if {b} then 1 else 2", Some(Type::Int)),
+            ("if {b} then 1 else 2.0", Some(Type::Num)),
+            ("if {b} then 1 else \"x\"", None),
+            ("#red = #blue", Some(Type::Bool)),
+            ("\"a\" < \"b\"", Some(Type::Bool)),
+            ("contains({b}, \"x\")", Some(Type::Bool)),
+            ("len(\"x\")", Some(Type::Num)),
+            ("rgb(1, 2, 3)", Some(Type::Color)),
+            ("format(\"{}\", {a})", Some(Type::Str)),
+            ("rising({a} > 1)", Some(Type::Bool)),
+            ("held({b}, 5s)", Some(Type::Bool)),
+            ("schedule(\"@daily\")", Some(Type::Bool)),
+        ];
+        const BAD: &[&str] = &[
+            "{a} and true",
+            "not {a}",
+            "{a} or {b}",
+            "if {a} then 1 else 2",
+            "{a} + \"F\"",
+            "true + false",
+            "true / 2",
+            "{a} = true",
+            "true < false",
+            "#red < #blue",
+            "len({a})",
+            "abs(len({a}))",
+            "red({a})",
+            "rising({a})",
+            "held({a}, 1s)",
+            "format(\"{}\", not {a})",
+            "({a} > 1) + \"x\"",
+        ];
+
+        for (expr, result) in GOOD {
+            assert_eq!(
+                to_expr(expr).infer_type(TYPES),
+                Ok(*result),
+                "error using {}",
+                expr
+            );
+        }
+
+        for expr in BAD {
+            assert!(
+                to_expr(expr).infer_type(TYPES).is_err(),
+                "{} should have failed",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_durations() {
+        let env: Env = (&[String::from("a")], &[String::from("b")]);
+
         assert_eq!(
-            evaluate("{utc:hour}", &time, None),
-            Some(device::Value::Int(3))
+            Program::compile("500ms -> {b}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(0.5)), 0))
         );
         assert_eq!(
-            evaluate("{utc:day}", &time, None),
-            Some(device::Value::Int(2))
+            Program::compile("30s -> {b}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(30.0)), 0))
         );
         assert_eq!(
-            evaluate("{utc:month}", &time, None),
-            Some(device::Value::Int(1))
+            Program::compile("1.5m -> {b}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(90.0)), 0))
         );
         assert_eq!(
-            evaluate("{utc:year}", &time, None),
-            Some(device::Value::Int(2000))
+            Program::compile("2h -> {b}", &env),
+            Ok(Program(Expr::Lit(device::Value::Flt(7200.0)), 0))
         );
+        assert!(Program::compile("5x -> {b}", &env).is_err());
+        assert!(Program::compile("5 m -> {b}", &env).is_err());
+
         assert_eq!(
-            evaluate("{utc:DOW}", &time, None),
-            Some(device::Value::Int(6))
+            Program::compile("held({a}, 5m) -> {b}", &env),
+            Ok(Program(
+                Expr::Timer(
+                    TimerKind::Held,
+                    Box::new(Expr::Var(0)),
+                    chrono::Duration::try_minutes(5).unwrap()
+                ),
+                0
+            ))
         );
         assert_eq!(
-            evaluate("{utc:DOY}", &time, None),
-            Some(device::Value::Int(1))
+            Program::compile("delay_off({a}, 250) -> {b}", &env),
+            Ok(Program(
+                Expr::Timer(
+                    TimerKind::DelayOff,
+                    Box::new(Expr::Var(0)),
+                    chrono::Duration::try_seconds(250).unwrap()
+                ),
+                0
+            ))
         );
+
+        // The duration has to be a positive constant and the first
+        // argument has to be a boolean.
+
+        assert!(Program::compile("held({a}, {a}) -> {b}", &env).is_err());
+        assert!(Program::compile("held({a}, 0s) -> {b}", &env).is_err());
+        assert!(Program::compile("held({a}, -5) -> {b}", &env).is_err());
+        assert!(Program::compile("held({a}, 1 + 1) -> {b}", &env).is_err());
+        assert!(Program::compile("held(1, 5s) -> {b}", &env).is_err());
+        assert!(Program::compile("held({a}) -> {b}", &env).is_err());
+    }
+
+    #[test]
+    fn test_eval_timers() {
+        use chrono::TimeZone;
+
+        const T: Option<device::Value> = Some(device::Value::Bool(true));
+        const F: Option<device::Value> = Some(device::Value::Bool(false));
+
+        // Evaluates an expression with a sequence of inputs. Each
+        // input is paired with the number of seconds since the start
+        // of the test. The result of the expression and the next
+        // deadline (in seconds) are returned.
+
+        fn eval_timed(
+            expr: &str,
+            inputs: &[(i64, Option<device::Value>)],
+        ) -> Vec<(Option<device::Value>, Option<i64>)> {
+            let start =
+                chrono::Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap();
+            let mut code = Code::new(&to_expr(expr));
+
+            inputs
+                .iter()
+                .map(|(secs, inp)| {
+                    let now =
+                        start + chrono::Duration::try_seconds(*secs).unwrap();
+                    let time = Arc::new((
+                        now,
+                        now.with_timezone(&chrono::Local).fixed_offset(),
+                    ));
+                    let result =
+                        code.eval(std::slice::from_ref(inp), &time, None);
+
+                    (
+                        result,
+                        code.next_deadline(&now, None)
+                            .map(|t| (t - start).num_seconds()),
+                    )
+                })
+                .collect()
+        }
+
         assert_eq!(
-            evaluate("{local:second}", &time, None),
-            Some(device::Value::Int(10))
+            eval_timed(
+                "held({a}, 10s)",
+                &[
+                    (0, F),
+                    (1, T),
+                    (5, T),
+                    (11, T),
+                    (12, T),
+                    (13, F),
+                    (14, T),
+                    (15, None),
+                    (24, T)
+                ]
+            ),
+            vec![
+                (F, None),
+                (F, Some(11)),
+                (F, Some(11)),
+                (T, None),
+                (T, None),
+                (F, None),
+                (F, Some(24)),
+                (None, Some(24)),
+                (T, None)
+            ]
+        );
+
+        assert_eq!(
+            eval_timed(
+                "delay_off({a}, 10s)",
+                &[
+                    (0, F),
+                    (1, T),
+                    (5, F),
+                    (10, F),
+                    (12, T),
+                    (13, F),
+                    (23, F),
+                    (24, F)
+                ]
+            ),
+            vec![
+                (F, None),
+                (T, None),
+                (T, Some(15)),
+                (T, Some(15)),
+                (T, None),
+                (T, Some(23)),
+                (F, None),
+                (F, None)
+            ]
         );
+
         assert_eq!(
-            evaluate("{local:minute}", &time, None),
-            Some(device::Value::Int(9))
+            eval_timed("held({a}, 10s)", &[(0, Some(device::Value::Int(1)))]),
+            vec![(None, None)]
         );
-        assert_eq!(
-            evaluate("{local:hour}", &time, None),
-            Some(device::Value::Int(8))
+
+        assert!(to_expr("held({a}, 1s)").uses_timers());
+        assert!(to_expr("not delay_off({a}, 1s) and true").uses_timers());
+        assert!(!to_expr("rising({a})").uses_timers());
+    }
+
+    #[test]
+    fn test_schedule() {
+        use chrono::TimeZone;
+
+        let env: Env = (&[String::from("a")], &[String::from("b")]);
+
+        // The argument has to be a single, literal string holding a
+        // valid schedule.
+
+        assert!(Program::compile("schedule() -> {b}", &env).is_err());
+        assert!(Program::compile("schedule({a}) -> {b}", &env).is_err());
+        assert!(Program::compile("schedule(5) -> {b}", &env).is_err());
+        assert!(Program::compile("schedule(\"\") -> {b}", &env).is_err());
+        assert!(
+            Program::compile("schedule(\"61 * * * *\") -> {b}", &env).is_err()
         );
+        assert!(Program::compile(
+            "schedule(\"* * * * *\", \"* * * * *\") -> {b}",
+            &env
+        )
+        .is_err());
+
+        let expr = to_expr("schedule(\"30 7 * * MON-FRI\")");
+
+        assert!(expr.uses_timers());
+
+        // The schedule is true during the minute it gives, using the
+        // local time. 2024-06-03 is a Monday.
+
+        let at = |d, h, m, s| {
+            let utc = chrono::Utc
+                .with_ymd_and_hms(2024, 6, d, h, m, s)
+                .single()
+                .unwrap();
+
+            Arc::new((utc, utc.fixed_offset()))
+        };
+
         assert_eq!(
-            evaluate("{local:day}", &time, None),
-            Some(device::Value::Int(7))
+            eval(&expr, &[], &at(3, 7, 29, 59), None),
+            Some(device::Value::Bool(false))
         );
         assert_eq!(
-            evaluate("{local:month}", &time, None),
-            Some(device::Value::Int(6))
+            eval(&expr, &[], &at(3, 7, 30, 0), None),
+            Some(device::Value::Bool(true))
         );
         assert_eq!(
-            evaluate("{local:year}", &time, None),
-            Some(device::Value::Int(2001))
+            eval(&expr, &[], &at(3, 7, 30, 59), None),
+            Some(device::Value::Bool(true))
         );
         assert_eq!(
-            evaluate("{local:DOW}", &time, None),
-            Some(device::Value::Int(3))
+            eval(&expr, &[], &at(3, 7, 31, 0), None),
+            Some(device::Value::Bool(false))
         );
         assert_eq!(
-            evaluate("{local:DOY}", &time, None),
-            Some(device::Value::Int(157))
+            eval(&expr, &[], &at(8, 7, 30, 0), None),
+            Some(device::Value::Bool(false))
         );
 
-        // Verify the solar variable are working correctly.
+        // The node is woken up when the result changes.
+
+        let tz = Some(chrono_tz::UTC);
+
+        let code = Code::new(&expr);
 
-        assert_eq!(evaluate("{solar:alt}", &time, None), None);
-        assert_eq!(evaluate("{solar:az}", &time, None), None);
-        assert_eq!(evaluate("{solar:ra}", &time, None), None);
-        assert_eq!(evaluate("{solar:dec}", &time, None), None);
-        assert_eq!(
-            evaluate("{solar:alt}", &time, Some(&solar)),
-            Some(device::Value::Flt(1.0))
-        );
         assert_eq!(
-            evaluate("{solar:az}", &time, Some(&solar)),
-            Some(device::Value::Flt(2.0))
+            code.next_deadline(&at(1, 12, 0, 0).0, tz),
+            Some(at(3, 7, 30, 0).0)
         );
         assert_eq!(
-            evaluate("{solar:ra}", &time, Some(&solar)),
-            Some(device::Value::Flt(3.0))
+            code.next_deadline(&at(3, 7, 30, 10).0, tz),
+            Some(at(3, 7, 31, 0).0)
         );
         assert_eq!(
-            evaluate("{solar:dec}", &time, Some(&solar)),
-            Some(device::Value::Flt(4.0))
+            Code::new(&to_expr("schedule(\"@daily\") or held({a}, 1s)"))
+                .next_deadline(&at(3, 7, 30, 10).0, tz),
+            Some(at(4, 0, 0, 0).0)
         );
     }
 
@@ -2870,6 +6847,8 @@ mod tests {
             ("#green", None),
             ("\"test\"", None),
             ("{solar:alt}", None),
+            // Schedules wake the node up themselves.
+            ("schedule(\"* * * * *\")", None),
             // Make sure the time values return the proper field.
             ("{utc:second}", Some(tod::TimeField::Second)),
             ("{utc:minute}", Some(tod::TimeField::Minute)),
@@ -2887,6 +6866,12 @@ mod tests {
             ("{local:DOY}", Some(tod::TimeField::Day)),
             ("{local:month}", Some(tod::TimeField::Month)),
             ("{local:year}", Some(tod::TimeField::Year)),
+            ("{local:week_of_month}", Some(tod::TimeField::Day)),
+            ("{cal:holiday}", Some(tod::TimeField::Day)),
+            (
+                "{cal:holiday} or {utc:hour} > 5",
+                Some(tod::TimeField::Hour),
+            ),
             // Now test more complicated expressions to make sure each
             // subtree is correctly compared.
             ("not (2 > 3)", None),
@@ -2895,6 +6880,13 @@ mod tests {
             ("2 + {utc:second}", Some(tod::TimeField::Second)),
             ("{local:hour} + {utc:minute}", Some(tod::TimeField::Minute)),
             ("{local:minute} + {utc:day}", Some(tod::TimeField::Minute)),
+            ("if {a} then {utc:hour} else 2", Some(tod::TimeField::Hour)),
+            (
+                "if {utc:day} = 1 then {a} else {utc:minute}",
+                Some(tod::TimeField::Minute),
+            ),
+            ("max({a}, {utc:hour})", Some(tod::TimeField::Hour)),
+            ("rising({utc:hour} > 5)", Some(tod::TimeField::Hour)),
         ];
 
         for (expr, result) in DATA {
@@ -2931,6 +6923,12 @@ mod tests {
             ("{solar:alt} + 2", true),
             ("2 + {solar:az}", true),
             ("{solar:dec} + {solar:az}", true),
+            ("if {a} then 1 else 2", false),
+            ("if {solar:alt} > 0 then 1 else 2", true),
+            ("if {a} then 1 else {solar:az}", true),
+            ("abs({a})", false),
+            ("max({a}, {solar:alt})", true),
+            ("hysteresis({solar:alt}, -1, 1)", true),
         ];
 
         for (expr, result) in DATA {
@@ -2942,4 +6940,150 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn test_calendar() {
+        use chrono::TimeZone;
+
+        let cal = calendar::load(&[
+            crate::config::Calendar {
+                name: String::from("holidays"),
+                holiday: true,
+                file: None,
+                dates: vec![String::from("2024-12-25")],
+            },
+            crate::config::Calendar {
+                name: String::from("vacation"),
+                holiday: false,
+                file: None,
+                dates: vec![String::from("2024-07-01..2024-07-14")],
+            },
+        ])
+        .await
+        .unwrap();
+        let env: Env = (&[], &[String::from("a")]);
+        let compile = |s: &str| {
+            Program::compile_with(&format!("{} -> {{a}}", s), &env, &cal)
+                .map(|p| p.0)
+        };
+
+        // Events have to be in the calendar.
+
+        assert!(compile("{cal:holiday}").is_ok());
+        assert!(compile("{cal:event(\"vacation\")}").is_ok());
+        assert!(compile("{cal:event(\"holidays\")}").is_ok());
+        assert!(compile("{cal:event(\"birthday\")}").is_err());
+        assert!(compile("{cal:event}").is_err());
+        assert!(compile("{cal:holiday(\"x\")}").is_err());
+        assert!(compile("{cal:weekday}").is_err());
+        assert!(compile("{utc:hour(\"x\")}").is_err());
+        assert!(
+            Program::compile("{cal:event(\"vacation\")} -> {a}", &env).is_err()
+        );
+
+        assert_eq!(
+            compile("{cal:event(\"vacation\")}").unwrap().to_string(),
+            "{cal:event(\"vacation\")}"
+        );
+
+        // The calendar values use the local date.
+
+        let at = |m, d| {
+            Arc::new((
+                chrono::Utc::now(),
+                chrono::Local
+                    .with_ymd_and_hms(2024, m, d, 12, 0, 0)
+                    .single()
+                    .unwrap()
+                    .fixed_offset(),
+            ))
+        };
+        let holiday = compile("{cal:holiday}").unwrap();
+        let vacation = compile("{cal:event(\"vacation\")}").unwrap();
+        let off = compile(
+            "{cal:holiday} or {cal:event(\"vacation\")} or {local:DOW} >= 5",
+        )
+        .unwrap();
+
+        const DATA: &[(u32, u32, bool, bool, bool)] = &[
+            (6, 28, false, false, false),
+            (6, 30, false, false, true),
+            (7, 1, false, true, true),
+            (7, 14, false, true, true),
+            (7, 15, false, false, false),
+            (12, 25, true, false, true),
+            (12, 26, false, false, false),
+        ];
+
+        for (m, d, is_holiday, is_vacation, is_off) in DATA {
+            let time = at(*m, *d);
+
+            assert_eq!(
+                eval(&holiday, &[], &time, None),
+                Some(device::Value::Bool(*is_holiday)),
+                "holiday on {}/{}",
+                m,
+                d
+            );
+            assert_eq!(
+                eval(&vacation, &[], &time, None),
+                Some(device::Value::Bool(*is_vacation)),
+                "vacation on {}/{}",
+                m,
+                d
+            );
+            assert_eq!(
+                eval(&off, &[], &time, None),
+                Some(device::Value::Bool(*is_off)),
+                "day off on {}/{}",
+                m,
+                d
+            );
+        }
+
+        // The third Sunday of the month.
+
+        let third_sunday =
+            compile("{local:DOW} = 6 and {local:week_of_month} = 3").unwrap();
+
+        for (m, d, result) in [
+            (9, 8, false),
+            (9, 15, true),
+            (9, 21, false),
+            (9, 22, false),
+            (12, 15, true),
+        ] {
+            assert_eq!(
+                eval(&third_sunday, &[], &at(m, d), None),
+                Some(device::Value::Bool(result)),
+                "third Sunday on {}/{}",
+                m,
+                d
+            );
+        }
+    }
+
+    #[test]
+    fn test_lunar_usage() {
+        const DATA: &[(&str, bool)] = &[
+            ("{a}", false),
+            ("{solar:alt}", false),
+            ("{utc:second}", false),
+            ("{lunar:alt}", true),
+            ("{lunar:phase}", true),
+            ("2 + {lunar:az}", true),
+            ("if {a} then 1 else {lunar:illum}", true),
+            ("if {lunar:phase} = \"full\" then 1 else 2", true),
+            ("max({a}, {solar:alt})", false),
+        ];
+
+        for (expr, result) in DATA {
+            assert_eq!(
+                &to_expr(expr).uses_lunar(),
+                result,
+                "error using {}",
+                expr
+            );
+        }
+    }
 }
@@ -0,0 +1,633 @@
+// This module converts an expression tree into a flat list of
+// instructions for a small stack machine. Evaluating the instructions
+// doesn't recurse through boxed subexpressions. The value stack is
+// sized when the expression is compiled so, once the argument buffer
+// has grown to the size the expression needs, evaluating doesn't
+// allocate memory (except when building strings.)
+
+use super::{
+    and_shortcut, calendar, condition, eval_add, eval_and, eval_cal, eval_div,
+    eval_eq, eval_lt, eval_lteq, eval_mul, eval_not, eval_or, eval_rem,
+    eval_schedule, eval_sub, format_values, or_shortcut, schedule, solar,
+    timer_step, tod, Builtin, Expr, LunarField, Sky, StatefulBuiltin,
+    TimerKind, TimerState,
+};
+use drmem_api::device;
+use std::sync::Arc;
+
+// An operand of a binary operation. Inputs and literals are read
+// directly by the instruction that uses them, which saves pushing
+// them onto the stack first. Other operands have already been
+// evaluated and are on the stack.
+
+#[derive(Debug)]
+enum Arg {
+    Stack,
+    Var(usize),
+    Lit(device::Value),
+}
+
+// The instructions of the stack machine. Jump targets are indexes
+// into the list of instructions.
+
+#[derive(Debug)]
+enum Op {
+    // Push a value onto the stack.
+    Lit(device::Value),
+    Var(usize),
+    Time(fn(&tod::Info) -> device::Value),
+    Solar(fn(&solar::Info, &tod::Info) -> Option<device::Value>),
+    Lunar(LunarField),
+    Cal(Arc<calendar::Dates>),
+    Schedule(Arc<schedule::Schedule>),
+
+    // Replace the top of the stack with its complement.
+    Not,
+
+    // Get the two operands and push the result of the operation.
+    Eq(Arg, Arg),
+    Lt(Arg, Arg),
+    LtEq(Arg, Arg),
+    Add(Arg, Arg),
+    Sub(Arg, Arg),
+    Mul(Arg, Arg),
+    Div(Arg, Arg),
+    Rem(Arg, Arg),
+
+    // Look at the first operand of an AND or OR. If it determines the
+    // result, replace it with the result and jump to the target.
+    AndTest(usize),
+    OrTest(usize),
+
+    // Pop the two operands of an AND or OR and push the result.
+    AndCombine,
+    OrCombine,
+
+    // Pop the condition of an IF expression. If it's `false`, jump to
+    // the first target (the "else" expression.) If it isn't a
+    // boolean, push `None` and jump to the second target (the end of
+    // the expression.)
+    Branch(usize, usize),
+    Jump(usize),
+
+    // Pop the arguments and push the result of the function. The
+    // stateful functions and timers also hold the index of their
+    // state.
+    Call(&'static Builtin, usize),
+    StatefulCall(&'static StatefulBuiltin, usize, usize),
+    Timer(TimerKind, chrono::Duration, usize),
+    Format(Vec<String>),
+}
+
+// The value stack. It holds the most values the expression can push
+// so pushing never has to grow it. Popping takes the value out of
+// its slot, leaving `None` behind.
+
+#[derive(Debug)]
+struct Stack {
+    vals: Box<[Option<device::Value>]>,
+    sp: usize,
+}
+
+impl Stack {
+    #[inline(always)]
+    fn push(&mut self, v: Option<device::Value>) {
+        self.vals[self.sp] = v;
+        self.sp += 1
+    }
+
+    #[inline(always)]
+    fn pop(&mut self) -> Option<device::Value> {
+        self.sp -= 1;
+        self.vals[self.sp].take()
+    }
+
+    #[inline(always)]
+    fn top(&mut self) -> &mut Option<device::Value> {
+        &mut self.vals[self.sp - 1]
+    }
+
+    // Returns the value of an operand.
+
+    #[inline(always)]
+    fn get(
+        &mut self,
+        arg: &Arg,
+        inp: &[Option<device::Value>],
+    ) -> Option<device::Value> {
+        match arg {
+            Arg::Stack => self.pop(),
+            Arg::Var(n) => inp[*n].clone(),
+            Arg::Lit(v) => Some(v.clone()),
+        }
+    }
+
+    // Pushes the result of a binary operation. If both operands are
+    // on the stack, the second one is on top.
+
+    #[inline(always)]
+    fn binary(
+        &mut self,
+        a: &Arg,
+        b: &Arg,
+        inp: &[Option<device::Value>],
+        f: impl FnOnce(
+            Option<device::Value>,
+            Option<device::Value>,
+        ) -> Option<device::Value>,
+    ) {
+        let b = self.get(b, inp);
+        let a = self.get(a, inp);
+
+        self.push(f(a, b))
+    }
+
+    // Moves the top `n` values into the argument buffer. Returns
+    // `false` if any of them doesn't have a value.
+
+    fn pop_args(&mut self, args: &mut Vec<device::Value>, n: usize) -> bool {
+        let mut ok = true;
+
+        self.sp -= n;
+        args.clear();
+
+        for v in &mut self.vals[self.sp..self.sp + n] {
+            match v.take() {
+                Some(v) => args.push(v),
+                None => ok = false,
+            }
+        }
+        ok
+    }
+}
+
+// Returns the most values the stack can hold while evaluating the
+// expression. Operands that are read directly by a binary operation
+// aren't pushed, so this may be more than are actually used.
+
+fn stack_size(e: &Expr) -> usize {
+    fn args_size(args: &[Expr]) -> usize {
+        args.iter()
+            .enumerate()
+            .map(|(idx, e)| idx + stack_size(e))
+            .max()
+            .unwrap_or(0)
+            .max(1)
+    }
+
+    match e {
+        Expr::Lit(_)
+        | Expr::Var(_)
+        | Expr::TimeVal(..)
+        | Expr::SolarVal(..)
+        | Expr::LunarVal(..)
+        | Expr::CalVal(..)
+        | Expr::Schedule(..) => 1,
+        Expr::Not(e) | Expr::Timer(_, e, _) => stack_size(e),
+        Expr::And(a, b)
+        | Expr::Or(a, b)
+        | Expr::Eq(a, b)
+        | Expr::Lt(a, b)
+        | Expr::LtEq(a, b)
+        | Expr::Add(a, b)
+        | Expr::Sub(a, b)
+        | Expr::Mul(a, b)
+        | Expr::Div(a, b)
+        | Expr::Rem(a, b) => stack_size(a).max(1 + stack_size(b)),
+        Expr::IfElse(c, a, b) => {
+            stack_size(c).max(stack_size(a)).max(stack_size(b))
+        }
+        Expr::Call(_, args)
+        | Expr::StatefulCall(_, args)
+        | Expr::Format(_, args) => args_size(args),
+    }
+}
+
+// A compiled expression. Along with the instructions, it holds the
+// state of any stateful functions and timers, so each `Code` needs
+// to be evaluated with `&mut self`. It also keeps the schedules it
+// uses so it can report when they change.
+
+#[derive(Debug)]
+pub struct Code {
+    ops: Vec<Op>,
+    stack: Stack,
+    args: Vec<device::Value>,
+    states: Vec<Option<bool>>,
+    timers: Vec<(chrono::Duration, TimerState)>,
+    schedules: Vec<Arc<schedule::Schedule>>,
+}
+
+impl Code {
+    pub fn new(e: &Expr) -> Self {
+        let mut code = Code {
+            ops: vec![],
+            stack: Stack {
+                vals: vec![None; stack_size(e)].into_boxed_slice(),
+                sp: 0,
+            },
+            args: vec![],
+            states: vec![],
+            timers: vec![],
+            schedules: vec![],
+        };
+
+        code.emit(e);
+        code
+    }
+
+    // Appends the instructions for an expression. When the
+    // instructions are run, they leave the result on the stack.
+
+    fn emit(&mut self, e: &Expr) {
+        match e {
+            Expr::Lit(v) => self.ops.push(Op::Lit(v.clone())),
+            Expr::Var(n) => self.ops.push(Op::Var(*n)),
+            Expr::TimeVal(_, _, f) => self.ops.push(Op::Time(*f)),
+            Expr::SolarVal(_, f) => self.ops.push(Op::Solar(*f)),
+            Expr::LunarVal(fld) => self.ops.push(Op::Lunar(fld.clone())),
+            Expr::CalVal(_, dates) => self.ops.push(Op::Cal(dates.clone())),
+            Expr::Schedule(s) => {
+                self.schedules.push(s.clone());
+                self.ops.push(Op::Schedule(s.clone()))
+            }
+
+            Expr::Not(e) => {
+                self.emit(e);
+                self.ops.push(Op::Not)
+            }
+
+            Expr::And(a, b) => {
+                self.emit_logic(a, b, Op::AndTest, Op::AndCombine)
+            }
+            Expr::Or(a, b) => self.emit_logic(a, b, Op::OrTest, Op::OrCombine),
+
+            Expr::Eq(a, b) => self.emit_binary(a, b, Op::Eq),
+            Expr::Lt(a, b) => self.emit_binary(a, b, Op::Lt),
+            Expr::LtEq(a, b) => self.emit_binary(a, b, Op::LtEq),
+            Expr::Add(a, b) => self.emit_binary(a, b, Op::Add),
+            Expr::Sub(a, b) => self.emit_binary(a, b, Op::Sub),
+            Expr::Mul(a, b) => self.emit_binary(a, b, Op::Mul),
+            Expr::Div(a, b) => self.emit_binary(a, b, Op::Div),
+            Expr::Rem(a, b) => self.emit_binary(a, b, Op::Rem),
+
+            Expr::IfElse(c, a, b) => {
+                self.emit(c);
+
+                let branch = self.ops.len();
+
+                self.ops.push(Op::Branch(0, 0));
+                self.emit(a);
+
+                let jump = self.ops.len();
+
+                self.ops.push(Op::Jump(0));
+
+                let else_target = self.ops.len();
+
+                self.emit(b);
+
+                let end = self.ops.len();
+
+                self.ops[branch] = Op::Branch(else_target, end);
+                self.ops[jump] = Op::Jump(end)
+            }
+
+            Expr::Call(func, args) => {
+                args.iter().for_each(|e| self.emit(e));
+                self.ops.push(Op::Call(func, args.len()))
+            }
+
+            Expr::StatefulCall(func, args) => {
+                args.iter().for_each(|e| self.emit(e));
+                self.states.push(None);
+                self.ops.push(Op::StatefulCall(
+                    func,
+                    args.len(),
+                    self.states.len() - 1,
+                ))
+            }
+
+            Expr::Timer(kind, e, delay) => {
+                self.emit(e);
+                self.timers.push((*delay, TimerState::Idle));
+                self.ops
+                    .push(Op::Timer(*kind, *delay, self.timers.len() - 1))
+            }
+
+            Expr::Format(text, args) => {
+                args.iter().for_each(|e| self.emit(e));
+                self.ops.push(Op::Format(text.clone()))
+            }
+        }
+    }
+
+    fn emit_binary(&mut self, a: &Expr, b: &Expr, op: fn(Arg, Arg) -> Op) {
+        let a = self.emit_arg(a);
+        let b = self.emit_arg(b);
+
+        self.ops.push(op(a, b))
+    }
+
+    // Returns the operand for an expression. If the expression isn't
+    // an input or a literal, the instructions to compute it are
+    // appended.
+
+    fn emit_arg(&mut self, e: &Expr) -> Arg {
+        match e {
+            Expr::Var(n) => Arg::Var(*n),
+            Expr::Lit(v) => Arg::Lit(v.clone()),
+            _ => {
+                self.emit(e);
+                Arg::Stack
+            }
+        }
+    }
+
+    // AND and OR expressions only evaluate their second operand when
+    // the first one doesn't determine the result.
+
+    fn emit_logic(
+        &mut self,
+        a: &Expr,
+        b: &Expr,
+        test: fn(usize) -> Op,
+        combine: Op,
+    ) {
+        self.emit(a);
+
+        let idx = self.ops.len();
+
+        self.ops.push(test(0));
+        self.emit(b);
+        self.ops.push(combine);
+        self.ops[idx] = test(self.ops.len())
+    }
+
+    // Runs the instructions and returns the result.
+
+    pub fn eval(
+        &mut self,
+        inp: &[Option<device::Value>],
+        time: &tod::Info,
+        sky: Option<&Sky>,
+    ) -> Option<device::Value> {
+        let Code {
+            ops,
+            stack,
+            args,
+            states,
+            timers,
+            ..
+        } = self;
+        let mut pc = 0;
+
+        while let Some(op) = ops.get(pc) {
+            pc += 1;
+
+            match op {
+                Op::Lit(v) => stack.push(Some(v.clone())),
+                Op::Var(n) => stack.push(inp[*n].clone()),
+                Op::Time(f) => stack.push(Some(f(time))),
+                Op::Solar(f) => stack.push(
+                    sky.and_then(|s| s.solar.as_ref())
+                        .and_then(|info| f(info, time)),
+                ),
+                Op::Lunar(fld) => stack.push(
+                    sky.and_then(|s| s.lunar.as_ref())
+                        .map(|info| fld.eval(info)),
+                ),
+                Op::Cal(dates) => stack.push(Some(eval_cal(dates, time))),
+                Op::Schedule(s) => stack.push(Some(eval_schedule(s, time))),
+
+                Op::Not => {
+                    let v = stack.pop();
+
+                    stack.push(eval_not(v))
+                }
+
+                Op::Eq(a, b) => stack.binary(a, b, inp, eval_eq),
+                Op::Lt(a, b) => stack.binary(a, b, inp, eval_lt),
+                Op::LtEq(a, b) => stack.binary(a, b, inp, eval_lteq),
+                Op::Add(a, b) => stack.binary(a, b, inp, eval_add),
+                Op::Sub(a, b) => stack.binary(a, b, inp, eval_sub),
+                Op::Mul(a, b) => stack.binary(a, b, inp, eval_mul),
+                Op::Div(a, b) => stack.binary(a, b, inp, eval_div),
+                Op::Rem(a, b) => stack.binary(a, b, inp, eval_rem),
+
+                Op::AndTest(target) | Op::OrTest(target) => {
+                    let top = stack.top();
+                    let result = if let Op::AndTest(_) = op {
+                        and_shortcut(top)
+                    } else {
+                        or_shortcut(top)
+                    };
+
+                    if let Some(v) = result {
+                        *top = v;
+                        pc = *target
+                    }
+                }
+
+                Op::AndCombine | Op::OrCombine => {
+                    let b = stack.pop();
+                    let a = stack.pop();
+
+                    stack.push(if let Op::AndCombine = op {
+                        eval_and(a, b)
+                    } else {
+                        eval_or(a, b)
+                    })
+                }
+
+                Op::Branch(else_target, end) => match condition(stack.pop()) {
+                    Some(true) => (),
+                    Some(false) => pc = *else_target,
+                    None => {
+                        stack.push(None);
+                        pc = *end
+                    }
+                },
+
+                Op::Jump(target) => pc = *target,
+
+                Op::Call(func, n) => {
+                    let result = if stack.pop_args(args, *n) {
+                        (func.eval)(args)
+                    } else {
+                        None
+                    };
+
+                    stack.push(result)
+                }
+
+                Op::StatefulCall(func, n, idx) => {
+                    let result = if stack.pop_args(args, *n) {
+                        (func.eval)(args, &mut states[*idx])
+                    } else {
+                        None
+                    };
+
+                    stack.push(result)
+                }
+
+                Op::Timer(kind, delay, idx) => {
+                    let v = stack.pop();
+
+                    stack.push(timer_step(
+                        *kind,
+                        v,
+                        delay,
+                        &mut timers[*idx].1,
+                        &time.0,
+                    ))
+                }
+
+                Op::Format(text) => {
+                    let result = if stack.pop_args(args, text.len() - 1) {
+                        format_values(text, args)
+                    } else {
+                        None
+                    };
+
+                    stack.push(result)
+                }
+            }
+        }
+        stack.pop()
+    }
+
+    // Returns the earliest time, after `now`, at which a timer or a
+    // schedule will change its result. Schedules use the local time
+    // in the time zone `tz`.
+
+    pub fn next_deadline(
+        &self,
+        now: &chrono::DateTime<chrono::Utc>,
+        tz: tod::Zone,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.timers
+            .iter()
+            .filter_map(|(delay, state)| match state {
+                TimerState::Since(t) if *t + *delay > *now => Some(*t + *delay),
+                _ => None,
+            })
+            .chain(self.schedules.iter().filter_map(|s| s.next_change(now, tz)))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::compile::{tests::EXPRESSIONS, Env, Program};
+    use std::sync::Arc;
+
+    // Values used as inputs. Each expression gets evaluated with
+    // every pair.
+
+    fn inputs() -> Vec<[Option<device::Value>; 2]> {
+        let vals = [
+            None,
+            Some(device::Value::Bool(false)),
+            Some(device::Value::Bool(true)),
+            Some(device::Value::Int(-3)),
+            Some(device::Value::Int(7)),
+            Some(device::Value::Flt(2.5)),
+            Some(device::Value::Str("hello".into())),
+            Some(device::Value::Color(palette::LinSrgba::new(1, 2, 3, 255))),
+        ];
+
+        vals.iter()
+            .flat_map(|a| vals.iter().map(|b| [a.clone(), b.clone()]))
+            .collect()
+    }
+
+    fn compile(expr: &str) -> Expr {
+        let env: Env = (
+            &[String::from("a"), String::from("b")],
+            &[String::from("b"), String::from("c")],
+        );
+
+        Program::compile(expr, &env).unwrap().0
+    }
+
+    #[test]
+    fn test_short_circuit() {
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+        let t = Some(device::Value::Bool(true));
+        let f = Some(device::Value::Bool(false));
+
+        // The second operand of `and` and `or` isn't evaluated when
+        // the first determines the result, so the stateful function
+        // doesn't see the change. If it had, the last evaluation of
+        // each sequence would return `true`.
+
+        let mut code = Code::new(&compile("{a} and rising({b}) -> {c}"));
+
+        assert_eq!(code.eval(&[t.clone(), t.clone()], &time, None), f);
+        assert_eq!(code.eval(&[f.clone(), f.clone()], &time, None), f);
+        assert_eq!(code.eval(&[t.clone(), t.clone()], &time, None), f);
+
+        let mut code = Code::new(&compile("{a} or rising({b}) -> {c}"));
+
+        assert_eq!(code.eval(&[f.clone(), t.clone()], &time, None), f);
+        assert_eq!(code.eval(&[t.clone(), f.clone()], &time, None), t);
+        assert_eq!(code.eval(&[f.clone(), t.clone()], &time, None), f);
+
+        // Only one branch of an `if` is evaluated.
+
+        let mut code = Code::new(&compile(
+            "if {a} then rising({b}) else falling({b}) -> {c}",
+        ));
+
+        assert_eq!(code.eval(&[t.clone(), t.clone()], &time, None), f);
+        assert_eq!(code.eval(&[f.clone(), t.clone()], &time, None), f);
+        assert_eq!(code.eval(&[t.clone(), f.clone()], &time, None), f);
+        assert_eq!(code.eval(&[f.clone(), f.clone()], &time, None), t);
+        assert_eq!(
+            code.eval(&[Some(device::Value::Int(1)), t.clone()], &time, None),
+            None
+        );
+    }
+
+    // Measures how long the compiled code takes to evaluate an
+    // expression. It's ignored by default; run it with
+    //
+    //   cargo test --release -- --ignored --nocapture bench_
+    //
+    // with the features used to build drmemd.
+
+    #[test]
+    #[ignore]
+    fn bench_evaluation() {
+        use std::time::Instant;
+
+        const LOOPS: usize = 2_000;
+
+        let inputs = inputs();
+        let time =
+            Arc::new((chrono::Utc::now(), chrono::Local::now().fixed_offset()));
+        let mut codes: Vec<Code> = EXPRESSIONS
+            .iter()
+            .map(|(src, _)| Code::new(&compile(src)))
+            .collect();
+        let start = Instant::now();
+
+        for _ in 0..LOOPS {
+            for code in &mut codes {
+                for inp in &inputs {
+                    std::hint::black_box(code.eval(inp, &time, None));
+                }
+            }
+        }
+
+        let total = (LOOPS * codes.len() * inputs.len()) as f64;
+
+        println!(
+            "bytecode: {:.1} ns/eval",
+            start.elapsed().as_nanos() as f64 / total
+        );
+    }
+}
@@ -1,13 +1,15 @@
 %expect-unused Unknown "UNKNOWN"
 
 %start Logic
-%parse-param p: &(&[String], &[String])
+%parse-param p: &Scope
 
 %avoid_insert "INT"
 %avoid_insert "FLT"
+%avoid_insert "DURATION"
 %avoid_insert "IDENTIFIER"
 %avoid_insert "TRUE"
 %avoid_insert "FALSE"
+%avoid_insert "FUNC"
 
 %epp EQ "="
 %epp NE "<>"
@@ -18,31 +20,100 @@
 %epp B_NOT "not"
 %epp B_AND "and"
 %epp B_OR "or"
+%epp IF "if"
+%epp THEN "then"
+%epp ELSE "else"
 %epp ADD "+"
 %epp SUB "-"
 %epp MUL "*"
 %epp DIV "/"
 %epp REM "%"
 %epp COLON ":"
+%epp COMMA ","
+%epp LET "let"
+%epp SEMI ";"
 %epp LBRACE "{"
 %epp RBRACE "}"
 
 %%
 
-Logic -> Result<Program>:
-    BoolExpr "CONTROL" "LBRACE" "IDENTIFIER" "RBRACE"
+Logic -> Result<Block>:
+    Lets BoolExpr "CONTROL" Targets
     {
-	let v = $4.map_err(|_| Error::ParseError(
+	let mut lets = $1?;
+	let mut expr = $2?;
+	let outputs = $4?;
+
+	// When the result goes to more than one output, it's computed
+	// once, into a local definition, unless it's trivial.
+
+	if outputs.len() > 1
+	    && !matches!(expr, Expr::Var(_) | Expr::Lit(_))
+	{
+	    let idx = p.add_local(String::new());
+
+	    lets.push(Program(expr, idx));
+	    expr = Expr::Var(idx)
+	}
+	Ok(Block { lets, expr, outputs })
+    }
+    ;
+
+Lets -> Result<Vec<Program>>:
+    { Ok(vec![]) }
+    | Lets "LET" "FUNC" "EQ" BoolExpr "SEMI"
+    {
+	let mut lets = $1?;
+	let expr = $5?;
+	let name = get_str("local name", $3, $lexer)?;
+
+	if p.lookup(name).is_some() {
+	    return Err(Error::ParseError(
+		format!("'{}' is already defined", name)
+	    ));
+	}
+	lets.push(Program(expr, p.add_local(name.into())));
+	Ok(lets)
+    }
+    ;
+
+Targets -> Result<Vec<usize>>:
+      Target { Ok(vec![$1?]) }
+    | Targets "COMMA" Target
+    {
+	let mut targets = $1?;
+	let idx = $3?;
+
+	if targets.contains(&idx) {
+	    return Err(Error::ParseError(
+		format!("output '{}' is set more than once", &p.env.1[idx])
+	    ));
+	}
+	targets.push(idx);
+	Ok(targets)
+    }
+    ;
+
+Target -> Result<usize>:
+    "LBRACE" "IDENTIFIER" "RBRACE"
+    {
+	let v = $2.map_err(|_| Error::ParseError(
 	        String::from("error reading target device")
             ))?;
 	let s = $lexer.span_str(v.span());
 
-	Ok(Program($1?, parse_device(s, p.1)?))
+	parse_device(s, p.env.1)
     }
     ;
 
 BoolExpr -> Result<Expr>:
-    OrExpr { $1 }
+      "IF" BoolExpr "THEN" BoolExpr "ELSE" BoolExpr
+      { Ok(Expr::IfElse(
+		  Box::new($2?),
+		  Box::new($4?),
+		  Box::new($6?)
+	      )) }
+    | OrExpr { $1 }
     ;
 
 OrExpr -> Result<Expr>:
@@ -137,6 +208,12 @@ Factor -> Result<Expr>:
 
 	  parse_flt(s)
       }
+    | "DURATION"
+      {
+	  let s = get_str("literal duration", $1, $lexer)?;
+
+	  parse_duration(s)
+      }
     | "STRING"
     {
 	let s = get_str("literal string", $1, $lexer)?;
@@ -169,6 +246,23 @@ Factor -> Result<Expr>:
 	}
     }
     | Device { $1 }
+    | "FUNC" "(" Args ")"
+    {
+	let s = get_str("function name", $1, $lexer)?;
+
+	parse_call(s, $3?)
+    }
+    ;
+
+Args -> Result<Vec<Expr>>:
+      BoolExpr { Ok(vec![$1?]) }
+    | Args "COMMA" BoolExpr
+    {
+	let mut args = $1?;
+
+	args.push($3?);
+	Ok(args)
+    }
     ;
 
 Device -> Result<Expr>:
@@ -178,13 +272,34 @@ Device -> Result<Expr>:
 	let cat = get_str("built-in category", $2, lexer)?;
 	let fld = get_str("built-in field", $4, lexer)?;
 
-	parse_builtin(cat, fld)
+	if cat == CAT_CAL {
+	    parse_calendar(fld, None, p)
+	} else {
+	    parse_builtin(cat, fld)
+	}
+    }
+    | "LBRACE" "IDENTIFIER" "COLON" "IDENTIFIER" "(" "STRING" ")" "RBRACE"
+    {
+	let lexer = $lexer;
+	let cat = get_str("built-in category", $2, lexer)?;
+	let fld = get_str("built-in field", $4, lexer)?;
+	let arg = get_str("built-in argument", $6, lexer)?;
+
+	if cat == CAT_CAL {
+	    parse_calendar(fld, Some(&arg[1..arg.len() - 1]), p)
+	} else {
+	    Err(Error::ParseError(
+		format!("built-in {}:{} doesn't take an argument", cat, fld)
+	    ))
+	}
     }
     | "LBRACE" "IDENTIFIER" "RBRACE"
     {
 	let s = get_str("device name", $2, $lexer)?;
 
-	Ok(Expr::Var(parse_device(s, p.0)?))
+	p.lookup(s).map(Expr::Var).ok_or_else(|| Error::ParseError(
+	    format!("variable '{}' is not defined", s)
+	))
     }
     ;
 
@@ -197,7 +312,12 @@ Unknown -> ():
 use drmem_api::{Result, Error, device};
 use chrono::{Timelike, Datelike};
 use palette::{LinSrgba, LinSrgb, Srgb, named, WithAlpha};
-use super::{TimeField, SolarField, super::tod, super::solar, Expr, Program};
+use super::{
+    TimeField, SolarField, LunarField, CalField, super::tod, super::solar,
+    super::schedule::Schedule, ArgType, Block, Expr, Program, Scope,
+    TimerKind, BUILTINS, STATEFUL_BUILTINS
+};
+use std::sync::Arc;
 use std::str::FromStr;
 
 use lrlex::{DefaultLexeme, DefaultLexerTypes};
@@ -237,6 +357,30 @@ fn parse_flt(s: &str) -> Result<Expr> {
 	))
 }
 
+// Durations are a number followed by a unit. They're converted to a
+// floating point number of seconds.
+
+fn parse_duration(s: &str) -> Result<Expr> {
+    let (num, unit) = s.split_at(
+	s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len())
+    );
+    let scale = match unit {
+	"ms" => 0.001,
+	"s" => 1.0,
+	"m" => 60.0,
+	"h" => 3600.0,
+	_ => return Err(Error::ParseError(
+	    format!("unknown unit of time in '{}'", s)
+	))
+    };
+
+    num.parse::<f64>()
+	.map(|v| Expr::Lit(device::Value::Flt(v * scale)))
+	.map_err(|_| Error::ParseError(
+	     format!("{} cannot be represented as a duration", s)
+	))
+}
+
 fn parse_device(name: &str, env: &[String]) -> Result<usize> {
     for ii in env.iter().enumerate() {
         if *ii.1 == name {
@@ -246,9 +390,140 @@ fn parse_device(name: &str, env: &[String]) -> Result<usize> {
     Err(Error::ParseError(format!("variable '{}' is not defined", &name)))
 }
 
+// Makes sure a built-in function was given the correct number, and
+// kind, of arguments.
+
+fn check_args(name: &str, types: &[ArgType], args: &[Expr]) -> Result<()> {
+    if types.len() != args.len() {
+	return Err(Error::ParseError(format!(
+	    "function '{}' expects {} argument(s) but was given {}",
+	    name,
+	    types.len(),
+	    args.len()
+	)));
+    }
+
+    for (idx, (ty, arg)) in types.iter().zip(args.iter()).enumerate() {
+	if !ty.accepts(arg) {
+	    return Err(Error::ParseError(format!(
+		"argument {} of '{}' must be {} : {}",
+		idx + 1,
+		name,
+		ty,
+		arg
+	    )));
+	}
+    }
+    Ok(())
+}
+
+// Looks up the named built-in function. Each call of a stateful
+// function gets its own, cleared state.
+
+fn parse_call(name: &str, args: Vec<Expr>) -> Result<Expr> {
+    if let Some(func) = BUILTINS.iter().find(|f| f.name == name) {
+	check_args(name, func.args, &args)?;
+	Ok(Expr::Call(func, args))
+    } else if let Some(func) = STATEFUL_BUILTINS.iter().find(|f| f.name == name) {
+	check_args(name, func.args, &args)?;
+	Ok(Expr::StatefulCall(func, args))
+    } else if let Some(kind) = TimerKind::from_name(name) {
+	check_args(name, &[ArgType::Bool, ArgType::Duration], &args)?;
+	parse_timer(kind, args)
+    } else if name == "format" {
+	parse_format(args)
+    } else if name == "schedule" {
+	parse_schedule(args)
+    } else {
+	Err(Error::ParseError(format!("unknown function '{}'", name)))
+    }
+}
+
+// Builds a format expression. The first argument has to be a literal
+// string. It's split at each "{}" and there has to be an argument for
+// each one.
+
+fn parse_format(mut args: Vec<Expr>) -> Result<Expr> {
+    let fmt = match args.first() {
+	Some(Expr::Lit(device::Value::Str(s))) => s.clone(),
+	_ => return Err(Error::ParseError(
+	    "format() requires a literal string as its first argument".into()
+	))
+    };
+    let mut text = vec![String::new()];
+    let mut chars = fmt.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+	match (ch, chars.peek()) {
+	    ('{', Some('{')) | ('}', Some('}')) => {
+		chars.next();
+		text.last_mut().unwrap().push(ch)
+	    }
+	    ('{', Some('}')) => {
+		chars.next();
+		text.push(String::new())
+	    }
+	    ('{', _) | ('}', _) => return Err(Error::ParseError(format!(
+		"unmatched brace in format string \"{}\"", fmt
+	    ))),
+	    _ => text.last_mut().unwrap().push(ch)
+	}
+    }
+
+    if text.len() != args.len() {
+	return Err(Error::ParseError(format!(
+	    "format string has {} placeholder(s) but {} argument(s) were given",
+	    text.len() - 1,
+	    args.len() - 1
+	)));
+    }
+
+    args.remove(0);
+    Ok(Expr::Format(text, args))
+}
+
+// Builds a schedule. Its argument has to be a literal string holding
+// the times in the format used by cron.
+
+fn parse_schedule(args: Vec<Expr>) -> Result<Expr> {
+    match &args[..] {
+	[Expr::Lit(device::Value::Str(s))] => Schedule::parse(s)
+	    .map(|s| Expr::Schedule(Arc::new(s))),
+	_ => Err(Error::ParseError(
+	    "schedule() requires a literal string as its only argument".into()
+	))
+    }
+}
+
+// Builds a timer function. The arguments have already been checked
+// so the second one is a literal number of seconds. It gets converted
+// to a duration, which has to be positive.
+
+fn parse_timer(kind: TimerKind, mut args: Vec<Expr>) -> Result<Expr> {
+    let secs = match args.pop() {
+	Some(Expr::Lit(device::Value::Int(v))) => v as f64,
+	Some(Expr::Lit(device::Value::Flt(v))) => v,
+	_ => unreachable!(),
+    };
+    let delay = Some((secs * 1000.0).round())
+	.filter(|v| *v > 0.0 && *v < i64::MAX as f64)
+	.and_then(|v| chrono::Duration::try_milliseconds(v as i64))
+	.ok_or_else(|| Error::ParseError(format!(
+	    "'{}' needs a positive duration : {}", kind, secs
+	)))?;
+
+    Ok(Expr::Timer(
+	kind,
+	Box::new(args.pop().unwrap()),
+	delay
+    ))
+}
+
 const CAT_UTC: &str = "utc";
 const CAT_LOCAL: &str = "local";
 const CAT_SOLAR: &str = "solar";
+const CAT_LUNAR: &str = "lunar";
+const CAT_CAL: &str = "cal";
 
 const FLD_SECOND: &str = "second";
 const FLD_MINUTE: &str = "minute";
@@ -258,10 +533,28 @@ const FLD_MONTH: &str = "month";
 const FLD_YEAR: &str = "year";
 const FLD_DOW: &str = "DOW";
 const FLD_DOY: &str = "DOY";
+const FLD_WOM: &str = "week_of_month";
+const FLD_HOLIDAY: &str = "holiday";
+const FLD_EVENT: &str = "event";
 const FLD_ALT: &str = "alt";
 const FLD_AZ: &str = "az";
 const FLD_RA: &str = "ra";
 const FLD_DEC: &str = "dec";
+const FLD_SUNRISE: &str = "sunrise";
+const FLD_SUNSET: &str = "sunset";
+const FLD_CIVIL_DAWN: &str = "civil_dawn";
+const FLD_CIVIL_DUSK: &str = "civil_dusk";
+const FLD_NAUTICAL_DAWN: &str = "nautical_dawn";
+const FLD_NAUTICAL_DUSK: &str = "nautical_dusk";
+const FLD_ASTRO_DAWN: &str = "astro_dawn";
+const FLD_ASTRO_DUSK: &str = "astro_dusk";
+const FLD_IS_DAY: &str = "is_day";
+const FLD_ILLUM: &str = "illum";
+const FLD_PHASE: &str = "phase";
+const FLD_MINUTES_TO_SUNRISE: &str = "minutes_to_sunrise";
+const FLD_MINUTES_TO_SUNSET: &str = "minutes_to_sunset";
+const FLD_MINUTES_TO_CIVIL_DAWN: &str = "minutes_to_civil_dawn";
+const FLD_MINUTES_TO_CIVIL_DUSK: &str = "minutes_to_civil_dusk";
 
 fn get_utc_second(info: &tod::Info) -> device::Value {
     device::Value::Int(info.0.second() as i32)
@@ -295,6 +588,10 @@ fn get_utc_day_of_year(info: &tod::Info) -> device::Value {
     device::Value::Int(info.0.ordinal0() as i32)
 }
 
+fn get_utc_week_of_month(info: &tod::Info) -> device::Value {
+    device::Value::Int((info.0.day0() / 7 + 1) as i32)
+}
+
 fn get_local_second(info: &tod::Info) -> device::Value {
     device::Value::Int(info.1.second() as i32)
 }
@@ -327,20 +624,179 @@ fn get_local_day_of_year(info: &tod::Info) -> device::Value {
     device::Value::Int(info.1.ordinal0() as i32)
 }
 
-fn get_solar_altitude(info: &solar::Info) -> device::Value {
-    device::Value::Flt(info.elevation)
+fn get_local_week_of_month(info: &tod::Info) -> device::Value {
+    device::Value::Int((info.1.day0() / 7 + 1) as i32)
+}
+
+fn get_solar_altitude(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    Some(device::Value::Flt(info.elevation))
+}
+
+fn get_solar_azimuth(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    Some(device::Value::Flt(info.azimuth))
+}
+
+fn get_solar_right_ascension(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    Some(device::Value::Flt(info.right_ascension))
+}
+
+fn get_solar_declination(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    Some(device::Value::Flt(info.declination))
+}
+
+// The times of the daily solar events are given as the number of
+// minutes after local midnight so they can be compared with
+// `{local:hour} * 60 + {local:minute}`. The local time uses the
+// node's time zone, which is carried by the time-of-day info.
+
+fn time_of_day(
+    event: Option<chrono::DateTime<chrono::Utc>>,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    event.map(|t| {
+	let t = t.with_timezone(time.1.offset());
+
+	device::Value::Flt(
+	    (t.hour() * 60 + t.minute()) as f64 + t.second() as f64 / 60.0
+	)
+    })
+}
+
+// Returns the number of minutes until a daily event. Once the event
+// has passed, the value is negative.
+
+fn minutes_until(
+    info: &solar::Info,
+    time: Option<chrono::DateTime<chrono::Utc>>
+) -> Option<device::Value> {
+    time.map(|t| {
+	device::Value::Flt((t - info.time).num_seconds() as f64 / 60.0)
+    })
+}
+
+fn get_solar_sunrise(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.sunrise, time)
+}
+
+fn get_solar_sunset(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.sunset, time)
+}
+
+fn get_solar_civil_dawn(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.civil_dawn, time)
+}
+
+fn get_solar_civil_dusk(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.civil_dusk, time)
+}
+
+fn get_solar_nautical_dawn(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.nautical_dawn, time)
+}
+
+fn get_solar_nautical_dusk(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.nautical_dusk, time)
+}
+
+fn get_solar_astro_dawn(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.astro_dawn, time)
+}
+
+fn get_solar_astro_dusk(
+    info: &solar::Info,
+    time: &tod::Info,
+) -> Option<device::Value> {
+    time_of_day(info.astro_dusk, time)
+}
+
+fn get_solar_is_day(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    Some(device::Value::Bool(info.is_day()))
+}
+
+fn get_solar_minutes_to_sunrise(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    minutes_until(info, info.sunrise)
+}
+
+fn get_solar_minutes_to_sunset(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    minutes_until(info, info.sunset)
 }
 
-fn get_solar_azimuth(info: &solar::Info) -> device::Value {
-    device::Value::Flt(info.azimuth)
+fn get_solar_minutes_to_civil_dawn(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    minutes_until(info, info.civil_dawn)
 }
 
-fn get_solar_right_ascension(info: &solar::Info) -> device::Value {
-    device::Value::Flt(info.right_ascension)
+fn get_solar_minutes_to_civil_dusk(
+    info: &solar::Info,
+    _time: &tod::Info,
+) -> Option<device::Value> {
+    minutes_until(info, info.civil_dusk)
 }
 
-fn get_solar_declination(info: &solar::Info) -> device::Value {
-    device::Value::Flt(info.declination)
+// Builds a `{cal:...}` value. The dates are looked up now so an
+// unknown event is reported when the expression is compiled.
+
+fn parse_calendar(fld: &str, arg: Option<&str>, p: &Scope) -> Result<Expr> {
+    match (fld, arg) {
+	(FLD_HOLIDAY, None) => Ok(Expr::CalVal(
+	    CalField::Holiday, p.cal.holidays()
+	)),
+	(FLD_EVENT, Some(name)) => p.cal.event(name)
+	    .map(|dates| Expr::CalVal(CalField::Event(name.into()), dates))
+	    .ok_or_else(|| Error::ParseError(
+		format!("unknown calendar event \"{}\"", name)
+	    )),
+	(FLD_EVENT, None) => Err(Error::ParseError(
+	    String::from("cal:event needs the name of an event")
+	)),
+	_ => Err(Error::ParseError(
+	    format!("unknown built-in: {}:{}", CAT_CAL, fld)
+	))
+    }
 }
 
 fn parse_builtin(cat: &str, fld: &str) -> Result<Expr> {
@@ -369,6 +825,9 @@ fn parse_builtin(cat: &str, fld: &str) -> Result<Expr> {
 	(CAT_UTC, FLD_DOY) => Ok(Expr::TimeVal(
             CAT_UTC, TimeField::DoY, get_utc_day_of_year
         )),
+	(CAT_UTC, FLD_WOM) => Ok(Expr::TimeVal(
+            CAT_UTC, TimeField::WoM, get_utc_week_of_month
+        )),
 	(CAT_LOCAL, FLD_SECOND) => Ok(Expr::TimeVal(
             CAT_LOCAL, TimeField::Second, get_local_second
         )),
@@ -393,6 +852,9 @@ fn parse_builtin(cat: &str, fld: &str) -> Result<Expr> {
 	(CAT_LOCAL, FLD_DOY) => Ok(Expr::TimeVal(
             CAT_LOCAL, TimeField::DoY, get_local_day_of_year
         )),
+	(CAT_LOCAL, FLD_WOM) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::WoM, get_local_week_of_month
+        )),
 	(CAT_SOLAR, FLD_ALT) => Ok(Expr::SolarVal(
 	    SolarField::Elevation, get_solar_altitude
         )),
@@ -405,6 +867,49 @@ fn parse_builtin(cat: &str, fld: &str) -> Result<Expr> {
 	(CAT_SOLAR, FLD_DEC) => Ok(Expr::SolarVal(
             SolarField::Declination, get_solar_declination
         )),
+	(CAT_SOLAR, FLD_SUNRISE) => Ok(Expr::SolarVal(
+            SolarField::Sunrise, get_solar_sunrise
+        )),
+	(CAT_SOLAR, FLD_SUNSET) => Ok(Expr::SolarVal(
+            SolarField::Sunset, get_solar_sunset
+        )),
+	(CAT_SOLAR, FLD_CIVIL_DAWN) => Ok(Expr::SolarVal(
+            SolarField::CivilDawn, get_solar_civil_dawn
+        )),
+	(CAT_SOLAR, FLD_CIVIL_DUSK) => Ok(Expr::SolarVal(
+            SolarField::CivilDusk, get_solar_civil_dusk
+        )),
+	(CAT_SOLAR, FLD_NAUTICAL_DAWN) => Ok(Expr::SolarVal(
+            SolarField::NauticalDawn, get_solar_nautical_dawn
+        )),
+	(CAT_SOLAR, FLD_NAUTICAL_DUSK) => Ok(Expr::SolarVal(
+            SolarField::NauticalDusk, get_solar_nautical_dusk
+        )),
+	(CAT_SOLAR, FLD_ASTRO_DAWN) => Ok(Expr::SolarVal(
+            SolarField::AstroDawn, get_solar_astro_dawn
+        )),
+	(CAT_SOLAR, FLD_ASTRO_DUSK) => Ok(Expr::SolarVal(
+            SolarField::AstroDusk, get_solar_astro_dusk
+        )),
+	(CAT_SOLAR, FLD_IS_DAY) => Ok(Expr::SolarVal(
+            SolarField::IsDay, get_solar_is_day
+        )),
+	(CAT_SOLAR, FLD_MINUTES_TO_SUNRISE) => Ok(Expr::SolarVal(
+            SolarField::MinutesToSunrise, get_solar_minutes_to_sunrise
+        )),
+	(CAT_SOLAR, FLD_MINUTES_TO_SUNSET) => Ok(Expr::SolarVal(
+            SolarField::MinutesToSunset, get_solar_minutes_to_sunset
+        )),
+	(CAT_SOLAR, FLD_MINUTES_TO_CIVIL_DAWN) => Ok(Expr::SolarVal(
+            SolarField::MinutesToCivilDawn, get_solar_minutes_to_civil_dawn
+        )),
+	(CAT_SOLAR, FLD_MINUTES_TO_CIVIL_DUSK) => Ok(Expr::SolarVal(
+            SolarField::MinutesToCivilDusk, get_solar_minutes_to_civil_dusk
+        )),
+	(CAT_LUNAR, FLD_ALT) => Ok(Expr::LunarVal(LunarField::Elevation)),
+	(CAT_LUNAR, FLD_AZ) => Ok(Expr::LunarVal(LunarField::Azimuth)),
+	(CAT_LUNAR, FLD_ILLUM) => Ok(Expr::LunarVal(LunarField::Illumination)),
+	(CAT_LUNAR, FLD_PHASE) => Ok(Expr::LunarVal(LunarField::Phase)),
 	_ => Err(Error::ParseError(
 		 format!("unknown built-in: {}:{}", cat, fld)
 	     ))
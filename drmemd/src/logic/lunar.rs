@@ -0,0 +1,330 @@
+// The position of the moon is computed with the low-precision
+// formulas from the "Astronomical Almanac". They're accurate to
+// about 0.3 degrees, which is plenty for controlling lights. The
+// phase is computed from the angle between the moon and the sun, as
+// described in chapter 48 of Jean Meeus' "Astronomical Algorithms".
+
+use std::sync::Arc;
+use tokio::{sync::broadcast, time};
+use tracing::{debug, info, info_span};
+use tracing_futures::Instrument;
+
+// The names of the phases of the moon. The first entry is centered
+// on the new moon and each following one is 45 degrees further
+// along the moon's orbit.
+
+const PHASES: [&str; 8] = [
+    "new",
+    "waxing crescent",
+    "first quarter",
+    "waxing gibbous",
+    "full",
+    "waning gibbous",
+    "last quarter",
+    "waning crescent",
+];
+
+pub struct LunarInfo {
+    pub elevation: f64,
+    pub azimuth: f64,
+    pub illumination: f64,
+    pub phase: &'static str,
+}
+
+pub type Info = Arc<LunarInfo>;
+
+// Computes the moon's geocentric ecliptic longitude and latitude,
+// and its horizontal parallax, in degrees. `n` is the number of days
+// since noon, Jan 1st, 2000 UTC.
+
+fn get_ecliptic_position(n: f64) -> (f64, f64, f64) {
+    let t: f64 = n / 36525.0;
+    let sin = |v: f64| f64::sin(v.to_radians());
+    let cos = |v: f64| f64::cos(v.to_radians());
+
+    let lambda: f64 =
+        218.32 + 481267.881 * t + 6.29 * sin(135.0 + 477198.87 * t)
+            - 1.27 * sin(259.3 - 413335.36 * t)
+            + 0.66 * sin(235.7 + 890534.22 * t)
+            + 0.21 * sin(269.9 + 954397.74 * t)
+            - 0.19 * sin(357.5 + 35999.05 * t)
+            - 0.11 * sin(186.5 + 966404.03 * t);
+
+    let beta: f64 = 5.13 * sin(93.3 + 483202.02 * t)
+        + 0.28 * sin(228.2 + 960400.89 * t)
+        - 0.28 * sin(318.3 + 6003.15 * t)
+        - 0.17 * sin(217.6 - 407332.21 * t);
+
+    let parallax: f64 = 0.9508
+        + 0.0518 * cos(135.0 + 477198.87 * t)
+        + 0.0095 * cos(259.3 - 413335.36 * t)
+        + 0.0078 * cos(235.7 + 890534.22 * t)
+        + 0.0028 * cos(269.9 + 954397.74 * t);
+
+    (lambda.rem_euclid(360.0), beta, parallax)
+}
+
+// Computes the sun's ecliptic longitude, in degrees, using the same
+// formula as the `solar` module.
+
+fn get_sun_longitude(n: f64) -> f64 {
+    let l: f64 = (280.466 + 0.9856474 * n).rem_euclid(360.0);
+    let g: f64 = (357.528 + 0.9856003 * n).rem_euclid(360.0).to_radians();
+
+    (l + 1.915 * f64::sin(g) + 0.020 * f64::sin(2.0 * g)).rem_euclid(360.0)
+}
+
+// Returns the illuminated fraction of the moon's disk and the name
+// of its phase. The moon's elongation is approximated by the angle
+// between the moon and the sun as seen from the center of the earth.
+
+fn get_phase(lambda: f64, beta: f64, sun: f64) -> (f64, &'static str) {
+    let cos_psi: f64 =
+        f64::cos(beta.to_radians()) * f64::cos((lambda - sun).to_radians());
+    let idx = ((lambda - sun).rem_euclid(360.0) / 45.0).round() as usize;
+
+    ((1.0 - cos_psi) / 2.0, PHASES[idx % PHASES.len()])
+}
+
+// Compute the moon's position and phase based on the latitude
+// (degrees), longitude (degrees), and time-of-day. The altitude is
+// corrected for parallax so it's the altitude seen from the surface
+// of the earth, rather than its center.
+
+pub(super) fn get_lunar_position(
+    lat: f64,
+    long: f64,
+    time: &chrono::DateTime<chrono::Utc>,
+) -> Arc<LunarInfo> {
+    use chrono::TimeZone;
+
+    // Calculate the number of days since the "base date" used by
+    // these formulas (noon, Jan 1st, 2000 UTC).
+
+    let base = chrono::Utc
+        .with_ymd_and_hms(2000, 1, 1, 12, 0, 0)
+        .single()
+        .unwrap();
+    let n: f64 = time.signed_duration_since(base).num_milliseconds() as f64
+        / 86_400_000.0;
+
+    let (lambda, beta, parallax) = get_ecliptic_position(n);
+    let (illumination, phase) = get_phase(lambda, beta, get_sun_longitude(n));
+
+    // Convert the ecliptic coordinates into right ascension and
+    // declination.
+
+    let epsilon: (f64, f64) = (23.439 - 0.0000004 * n).to_radians().sin_cos();
+    let lambda_sc: (f64, f64) = lambda.to_radians().sin_cos();
+    let beta_sc: (f64, f64) = beta.to_radians().sin_cos();
+
+    let alpha: f64 = f64::atan2(
+        lambda_sc.0 * epsilon.1 - beta_sc.0 / beta_sc.1 * epsilon.0,
+        lambda_sc.1,
+    );
+    let delta: f64 =
+        f64::asin(beta_sc.0 * epsilon.1 + beta_sc.1 * epsilon.0 * lambda_sc.0);
+
+    // Compute the local hour angle from the Greenwich mean sidereal
+    // time.
+
+    let gmst: f64 = 280.46061837 + 360.98564736629 * n;
+    let h: (f64, f64) = (gmst + long - alpha.to_degrees())
+        .rem_euclid(360.0)
+        .to_radians()
+        .sin_cos();
+
+    let lat_sc: (f64, f64) = lat.to_radians().sin_cos();
+    let delta_sc: (f64, f64) = delta.sin_cos();
+
+    let elevation: f64 =
+        f64::asin(lat_sc.0 * delta_sc.0 + lat_sc.1 * delta_sc.1 * h.1)
+            .to_degrees();
+    let azimuth: f64 = f64::atan2(
+        -h.0 * delta_sc.1,
+        lat_sc.1 * delta_sc.0 - lat_sc.0 * delta_sc.1 * h.1,
+    )
+    .to_degrees()
+    .rem_euclid(360.0);
+    let elevation: f64 = elevation
+        - f64::asin(
+            f64::sin(parallax.to_radians()) * f64::cos(elevation.to_radians()),
+        )
+        .to_degrees();
+
+    debug!(
+        "alt: {:.2}, az: {:.2}, illum: {:.2}, phase: {}",
+        round(elevation, 0.02),
+        round(azimuth, 0.1),
+        round(illumination, 0.01),
+        phase
+    );
+
+    Arc::new(LunarInfo {
+        elevation: round(elevation, 0.02),
+        azimuth: round(azimuth, 0.1),
+        illumination: round(illumination, 0.001),
+        phase,
+    })
+}
+
+fn round(v: f64, prec: f64) -> f64 {
+    (v / prec).round() * prec
+}
+
+async fn run(tx: broadcast::Sender<Info>, lat: f64, long: f64) {
+    let mut interval = time::interval(time::Duration::from_secs(15));
+
+    info!("starting task");
+
+    // Logic nodes can be started after this task (by a reload, for
+    // instance), so it keeps running when there are no receivers.
+
+    loop {
+        if tx.receiver_count() > 0 {
+            let _ = tx.send(get_lunar_position(lat, long, &chrono::Utc::now()));
+        }
+        let _ = interval.tick().await;
+    }
+}
+
+pub fn create_task(
+    lat: f64,
+    long: f64,
+) -> (broadcast::Sender<Info>, broadcast::Receiver<Info>) {
+    let (tx, rx) = broadcast::channel(10);
+    let tx_copy = tx.clone();
+
+    tokio::spawn(
+        async move { run(tx_copy, lat, long).await }
+            .instrument(info_span!("lunar")),
+    );
+
+    (tx, rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_ecliptic_position, get_lunar_position, get_phase, get_sun_longitude,
+    };
+    use crate::logic::solar;
+
+    fn close_enough(a: f64, b: f64, delta: f64) -> bool {
+        (a - b).abs() <= delta
+    }
+
+    fn time(s: &str) -> chrono::DateTime<chrono::Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ecliptic_position() {
+        // Examples 47.a and 48.a of "Astronomical Algorithms" give
+        // the moon's position and illuminated fraction on April 12th,
+        // 1992 at 0h TD (58.7 seconds before midnight UTC.) This is
+        // 2,820.5 days before the base date.
+
+        let n = -2820.5007;
+        let (lambda, beta, parallax) = get_ecliptic_position(n);
+
+        assert!(close_enough(lambda, 133.162655, 0.3), "lambda: {}", lambda);
+        assert!(close_enough(beta, -3.229126, 0.2), "beta: {}", beta);
+
+        // The example's distance is 368,409.7 km.
+
+        let dist = 6378.14 / f64::sin(parallax.to_radians());
+
+        assert!(close_enough(dist, 368409.7, 1000.0), "distance: {}", dist);
+
+        let (illum, phase) = get_phase(lambda, beta, get_sun_longitude(n));
+
+        assert!(close_enough(illum, 0.6786, 0.005), "illum: {}", illum);
+        assert_eq!(phase, "first quarter");
+    }
+
+    #[test]
+    fn test_phases() {
+        // The times of these phases were taken from published
+        // tables. The full moons were lunar eclipses.
+
+        const TEST_DATA: &[(&str, f64, &str)] = &[
+            ("2024-04-08T18:18:00Z", 0.0, "new"),
+            ("2024-04-15T19:13:00Z", 0.5, "first quarter"),
+            ("2024-09-18T02:34:00Z", 1.0, "full"),
+            ("2022-11-08T11:02:00Z", 1.0, "full"),
+            ("2025-03-14T06:55:00Z", 1.0, "full"),
+            ("2017-08-21T18:25:00Z", 0.0, "new"),
+        ];
+
+        for (t, illum, phase) in TEST_DATA {
+            let info = get_lunar_position(0.0, 0.0, &time(t));
+
+            assert!(
+                close_enough(info.illumination, *illum, 0.01),
+                "illumination at {}: {} <> {}",
+                t,
+                info.illumination,
+                illum
+            );
+            assert_eq!(info.phase, *phase, "phase at {}", t);
+        }
+
+        // Half-way between the phases.
+
+        let info = get_lunar_position(0.0, 0.0, &time("2024-04-12T06:00:00Z"));
+
+        assert_eq!(info.phase, "waxing crescent");
+
+        let info = get_lunar_position(0.0, 0.0, &time("2024-09-22T00:00:00Z"));
+
+        assert_eq!(info.phase, "waning gibbous");
+    }
+
+    #[test]
+    fn test_lunar_position() {
+        // During a total solar eclipse, the moon covers the sun so
+        // they're in the same place in the sky. These are the
+        // locations and times of greatest eclipse for 2017 and 2024.
+        // The solar position is tested against NOAA's calculator. Near
+        // the zenith, small errors make the azimuths differ more so
+        // the angle between the two is checked.
+
+        const TEST_DATA: &[(&str, f64, f64)] = &[
+            ("2017-08-21T18:25:32Z", 36.97, -87.67),
+            ("2024-04-08T18:17:20Z", 25.29, -104.14),
+        ];
+
+        for (t, lat, long) in TEST_DATA {
+            let t = time(t);
+            let moon = get_lunar_position(*lat, *long, &t);
+            let sun = solar::get_solar_position(*lat, *long, &t);
+
+            let (m_alt, s_alt) =
+                (moon.elevation.to_radians(), sun.elevation.to_radians());
+            let sep = f64::acos(
+                m_alt.sin() * s_alt.sin()
+                    + m_alt.cos()
+                        * s_alt.cos()
+                        * (moon.azimuth - sun.azimuth).to_radians().cos(),
+            )
+            .to_degrees();
+
+            assert!(
+                sep < 0.5,
+                "moon at {}, {} and sun at {}, {}",
+                moon.elevation,
+                moon.azimuth,
+                sun.elevation,
+                sun.azimuth
+            );
+        }
+
+        // Twelve hours later, the moon is on the other side of the
+        // earth.
+
+        let t = time("2024-04-09T06:17:20Z");
+
+        assert!(get_lunar_position(25.29, -104.14, &t).elevation < -45.0);
+    }
+}
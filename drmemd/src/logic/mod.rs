@@ -2,19 +2,21 @@ use drmem_api::{client, device, driver, Result};
 use futures::future::{join_all, pending};
 use std::collections::HashMap;
 use std::convert::Infallible;
-use std::sync::Arc;
-use tokio::{
-    sync::{broadcast, oneshot},
-    task::JoinHandle,
-};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_stream::{wrappers::BroadcastStream, StreamExt, StreamMap};
-use tracing::{debug, error, info, info_span, warn};
-use tracing_futures::Instrument;
+use tracing::{debug, error, info, warn};
 
 use super::config;
 
+pub mod calendar;
 mod compile;
+pub mod lunar;
+pub mod reload;
+mod schedule;
+pub mod simulate;
 pub mod solar;
+pub mod status;
 pub mod tod;
 
 // These are some helpful type aliases.
@@ -29,6 +31,16 @@ type Inputs = Option<device::Value>;
 
 type InputStream = StreamMap<usize, device::DataStream<device::Reading>>;
 
+// A definition whose value is reported to a read-only device. It
+// holds the definition's index in the inputs, the last value that was
+// reported and the device.
+
+type Published = (
+    usize,
+    Option<device::Value>,
+    driver::ReadOnlyDevice<device::Value>,
+);
+
 // Manages settings to a device. It makes sure we don't send duplicate
 // settings and it encapsulates the request/reply transaction.
 
@@ -93,8 +105,16 @@ pub struct Node {
     in_stream: InputStream,
     time_ch: Option<tod::TimeFilter>,
     solar_ch: Option<broadcast::Receiver<solar::Info>>,
-    def_exprs: Vec<compile::Program>,
-    exprs: Vec<(compile::Program, Output)>,
+    lunar_ch: Option<broadcast::Receiver<lunar::Info>>,
+    def_exprs: Vec<(compile::Code, usize)>,
+    exprs: Vec<(compile::Code, Output)>,
+    uses_timers: bool,
+    tz: tod::Zone,
+    n_devices: usize,
+    status: Arc<Mutex<status::Status>>,
+    enable: Option<driver::ReadWriteDevice<bool>>,
+    enabled: bool,
+    published: Vec<Published>,
 }
 
 impl Node {
@@ -112,6 +132,7 @@ impl Node {
         c_req: &client::RequestChan,
         vars: &HashMap<String, device::Name>,
         defs: &HashMap<String, String>,
+        cal: &calendar::Calendar,
     ) -> Result<(Vec<String>, InputStream, Vec<compile::Program>)> {
         let mut inputs = Vec::with_capacity(vars.len() + defs.len());
         let mut def_exprs = Vec::with_capacity(defs.len());
@@ -163,9 +184,10 @@ impl Node {
             // input parameter.
 
             let env = (&inputs[..vars.len()], &inputs[..]);
-            let result = compile::Program::compile(
+            let result = compile::Program::compile_with(
                 &format!("{} -> {{{}}}", &expr, &name),
                 &env,
+                cal,
             )?;
 
             debug!("inp[{}] = {}", result.1, &result.0);
@@ -178,6 +200,66 @@ impl Node {
         Ok((inputs, in_stream, def_exprs))
     }
 
+    // Looks up the type of each input device. The backend doesn't
+    // record a type for devices so the type of the most recent
+    // reading is used. If a device has no readings, or its info
+    // can't be retrieved, its type is unknown and expressions using
+    // it are checked when they're evaluated.
+
+    async fn input_types(
+        c_req: &client::RequestChan,
+        vars: &HashMap<String, device::Name>,
+        inputs: &[String],
+    ) -> Vec<Option<compile::Type>> {
+        let mut types = Vec::with_capacity(inputs.len());
+
+        for name in inputs {
+            let ty = if let Some(dev) = vars.get(name) {
+                match c_req.get_device_info(Some(dev.to_string())).await {
+                    Ok(info) => info
+                        .iter()
+                        .find(|v| &v.name == dev)
+                        .and_then(|v| v.last_point.as_ref())
+                        .map(|v| compile::Type::of(&v.value)),
+                    Err(e) => {
+                        debug!("couldn't get info for '{}': {}", &dev, &e);
+                        None
+                    }
+                }
+                .or_else(|| {
+                    warn!(
+                        "type of '{}' is unknown -- expressions using it \
+			 will be checked when they run",
+                        &dev
+                    );
+                    None
+                })
+            } else {
+                None
+            };
+
+            types.push(ty)
+        }
+        types
+    }
+
+    // Determines the type of each expression's result. Expressions
+    // that can never produce a value are reported as a configuration
+    // error.
+
+    fn check_types(
+        src: &str,
+        expr: &compile::Expr,
+        types: &[Option<compile::Type>],
+    ) -> Result<Option<compile::Type>> {
+        expr.infer_type(types).map_err(|e| {
+            drmem_api::Error::ConfigError(format!(
+                "type error in '{}': {}",
+                src, e
+            ))
+        })
+    }
+
     async fn setup_outputs(
         c_req: &client::RequestChan,
         vars: &HashMap<String, device::Name>,
@@ -209,13 +291,80 @@ impl Node {
         Ok((outputs, out_chans))
     }
 
+    // Returns the channel which registers the node's own devices.
+    // They're registered through the same channel that drivers use,
+    // so the backend saves their values, and their names start with
+    // `logic:<name>`.
+
+    fn device_chan(
+        d_req: &mpsc::Sender<driver::Request>,
+        name: &str,
+    ) -> Result<driver::RequestChan> {
+        device::Path::create(&format!("logic:{}", name))
+            .map(|path| {
+                driver::RequestChan::new("logic".into(), &path, None, d_req)
+            })
+            .map_err(|_| {
+                drmem_api::Error::ConfigError(format!(
+                    "'{}' can't be used in a device name",
+                    name
+                ))
+            })
+    }
+
+    // Registers the `logic:<name>:enabled` device, which turns the
+    // node's outputs on and off.
+
+    async fn setup_enable_device(
+        chan: &driver::RequestChan,
+    ) -> Result<driver::ReadWriteDevice<bool>> {
+        chan.add_rw_device(device::Base::create("enabled")?, None, None)
+            .await
+            .inspect_err(|e| error!("couldn't register 'enabled' : {}", e))
+    }
+
+    // Registers a read-only device for each definition in `publish`.
+    // The device has the same name as the definition.
+
+    async fn setup_published(
+        chan: &driver::RequestChan,
+        publish: &[String],
+        inputs: &[String],
+    ) -> Result<Vec<Published>> {
+        let mut published = Vec::with_capacity(publish.len());
+
+        for name in publish {
+            let base = device::Base::create(name).map_err(|_| {
+                drmem_api::Error::ConfigError(format!(
+                    "'{}' can't be used in a device name",
+                    name
+                ))
+            })?;
+            let dev = chan.add_ro_device(base, None, None).await.inspect_err(
+                |e| error!("couldn't register '{}' : {}", name, e),
+            )?;
+
+            // The names were checked against `defs` so they're
+            // always found.
+
+            if let Some(idx) = inputs.iter().position(|v| v == name) {
+                debug!("inp[{}] is published", idx);
+                published.push((idx, None, dev))
+            }
+        }
+        Ok(published)
+    }
+
     // Creates an instance of `Node` and initializes its state using
     // the configuration information.
 
     async fn init(
         c_req: client::RequestChan,
+        d_req: mpsc::Sender<driver::Request>,
         c_time: broadcast::Receiver<tod::Info>,
         c_solar: broadcast::Receiver<solar::Info>,
+        c_lunar: broadcast::Receiver<lunar::Info>,
+        cal: calendar::Info,
         cfg: config::Logic,
     ) -> Result<Node> {
         debug!("compiling expressions");
@@ -226,6 +375,22 @@ impl Node {
             ));
         }
 
+        // If the node has its own time zone, look it up. Otherwise
+        // the local time uses the system's time zone.
+
+        let tz = cfg
+            .timezone
+            .as_deref()
+            .map(|name| {
+                name.parse::<chrono_tz::Tz>().map_err(|_| {
+                    drmem_api::Error::ConfigError(format!(
+                        "unknown time zone '{}'",
+                        name
+                    ))
+                })
+            })
+            .transpose()?;
+
         // Validate the inputs.
         //
         // We add the names of the `inputs` and `defs` variables to a
@@ -302,33 +467,101 @@ impl Node {
             }
         }
 
+        // Validate the published definitions. Each has to be found in
+        // `defs` and can only be listed once. Since the devices are
+        // named after them, `enabled` can't be used when the node
+        // has an enable device.
+
+        {
+            use std::collections::HashSet;
+
+            let mut name_set: HashSet<&str> =
+                HashSet::with_capacity(cfg.publish.len());
+
+            for k in &cfg.publish {
+                if !cfg.defs.contains_key(k) {
+                    return Err(drmem_api::Error::ConfigError(format!(
+                        "'{}' is in 'publish' but isn't defined in 'defs'",
+                        k
+                    )));
+                }
+                if !name_set.insert(k.as_str()) {
+                    return Err(drmem_api::Error::ConfigError(format!(
+                        "'{}' is defined more than once in 'publish'",
+                        k
+                    )));
+                }
+                if cfg.enable_device && k == "enabled" {
+                    return Err(drmem_api::Error::ConfigError(
+                        "'enabled' can't be published when the node has \
+                         an enable device"
+                            .into(),
+                    ));
+                }
+            }
+        }
+
         let (inputs, in_stream, def_exprs) =
-            Node::setup_inputs(&c_req, &cfg.inputs, &cfg.defs).await?;
+            Node::setup_inputs(&c_req, &cfg.inputs, &cfg.defs, &cal).await?;
 
         let (outputs, out_chans) =
             Node::setup_outputs(&c_req, &cfg.outputs).await?;
 
+        // Determine the types of the inputs and the definitions. The
+        // definitions only use the inputs so they can be checked in
+        // any order.
+
+        let mut types = Node::input_types(&c_req, &cfg.inputs, &inputs).await;
+
+        for prog in &def_exprs {
+            let src = &cfg.defs[&inputs[prog.1]];
+
+            types[prog.1] = Node::check_types(src, &prog.0, &types)?;
+        }
+
+        let mut def_exprs: Vec<compile::Program> = def_exprs
+            .into_iter()
+            .map(compile::Program::optimize)
+            .collect();
+
         // Create the input/output environment that the compiler can
         // use to compute the variables in the expression.
 
         let env = (&inputs[..], &outputs[..]);
 
         // Iterate through the vector of strings. For each, compile it
-        // into a `Program` type. Report the success or failure.
+        // into a `Block` and report the success or failure. The
+        // `let` definitions of a block are stored in inputs after
+        // the ones already used and are computed with the `defs`.
+        // Each one can use the ones before it, so they're checked in
+        // order. The block's expression becomes a `Program` for
+        // each of its outputs.
+
+        let mut exprs = Vec::with_capacity(cfg.exprs.len());
+
+        for s in &cfg.exprs {
+            let block =
+                compile::Block::compile(s.as_str(), &env, &cal, types.len())
+                    .and_then(|b| {
+                        for prog in &b.lets {
+                            types.push(Node::check_types(s, &prog.0, &types)?)
+                        }
+                        Node::check_types(s, &b.expr, &types).map(|_| b)
+                    })
+                    .inspect_err(|e| error!("{}", &e))?;
 
-        let exprs: Result<Vec<compile::Program>> = cfg
-            .exprs
-            .iter()
-            .map(|s| {
-                compile::Program::compile(s.as_str(), &env)
-                    .map(compile::Program::optimize)
-            })
-            .inspect(|e| match e {
-                Ok(ex) => debug!("out[{}] = {}", ex.1, &ex.0),
-                Err(e) => error!("{}", &e),
-            })
-            .collect();
-        let mut exprs = exprs?;
+            for prog in block.lets {
+                debug!("inp[{}] = {}", prog.1, &prog.0);
+                def_exprs.push(prog.optimize())
+            }
+
+            let expr = compile::optimize(block.expr);
+
+            for idx in block.outputs {
+                debug!("out[{}] = {}", idx, &expr);
+                exprs.push(compile::Program(expr.clone(), idx))
+            }
+        }
 
         // Sort the expressions based on the index of the outputs. The
         // output variables are in a hash map, so the vector is built
@@ -355,6 +588,18 @@ impl Node {
             |compile::Program(_, a), compile::Program(_, b)| a.cmp(b),
         );
 
+        // Subexpressions that are used more than once in the node
+        // are computed once, into an extra input, and shared.
+
+        let (def_exprs, n_inputs) =
+            compile::share_subexprs(def_exprs, &mut exprs, types.len());
+
+        for compile::Program(e, idx) in &def_exprs {
+            if *idx >= inputs.len() {
+                debug!("inp[{}] = {} (shared)", idx, e)
+            }
+        }
+
         // Look at each expression and see if it needs the
         // time-of-day.
 
@@ -372,28 +617,209 @@ impl Node {
             .chain(&def_exprs)
             .any(|compile::Program(e, _)| e.uses_solar());
 
+        // Look at each expression and see if it needs any lunar
+        // information.
+
+        let needs_lunar = exprs
+            .iter()
+            .chain(&def_exprs)
+            .any(|compile::Program(e, _)| e.uses_lunar());
+
+        // Look at each expression and see if it uses timer
+        // functions. If so, the node needs to schedule its own
+        // wake-ups.
+
+        let uses_timers = exprs
+            .iter()
+            .chain(&def_exprs)
+            .any(|compile::Program(e, _)| e.uses_timers());
+
+        // Record what the node computes so it can be reported while
+        // it runs.
+
+        let status = status::Status {
+            name: cfg.name.clone(),
+            summary: cfg.summary.clone(),
+            variables: inputs
+                .iter()
+                .map(|name| status::Variable {
+                    name: name.clone(),
+                    device: cfg.inputs.get(name).cloned(),
+                    value: None,
+                })
+                .collect(),
+            defs: def_exprs
+                .iter()
+                .map(|compile::Program(e, idx)| {
+                    format!("{} -> inp[{}]", e, idx)
+                })
+                .collect(),
+            exprs: exprs
+                .iter()
+                .map(|prog| status::Expression {
+                    program: prog.to_string(),
+                    output: outputs[prog.1].clone(),
+                    device: cfg.outputs[&outputs[prog.1]].clone(),
+                    last: None,
+                    errors: 0,
+                })
+                .collect(),
+        };
+
+        // Register the node's own devices: the enable device, if the
+        // node can be disabled, and one for each published
+        // definition. The backend provides the enable device's last
+        // value so a node that was disabled stays disabled after a
+        // restart. A new device starts enabled.
+
+        let (enable, published) =
+            if cfg.enable_device || !cfg.publish.is_empty() {
+                let chan = Node::device_chan(&d_req, &cfg.name)?;
+                let enable = if cfg.enable_device {
+                    Some(Node::setup_enable_device(&chan).await?)
+                } else {
+                    None
+                };
+
+                (
+                    enable,
+                    Node::setup_published(&chan, &cfg.publish, &inputs).await?,
+                )
+            } else {
+                (None, vec![])
+            };
+        let enabled = enable
+            .as_ref()
+            .and_then(|dev| dev.get_last().copied())
+            .unwrap_or(true);
+
+        // Compile the expressions into the form that gets run.
+
+        let def_exprs = def_exprs
+            .iter()
+            .map(|compile::Program(e, idx)| (compile::Code::new(e), *idx))
+            .collect();
+        let exprs = exprs
+            .iter()
+            .map(|compile::Program(e, _)| compile::Code::new(e))
+            .zip(out_chans)
+            .collect();
+
         // Return the initialized `Node`.
 
         Ok(Node {
-            inputs: vec![None; inputs.len()],
+            inputs: vec![None; n_inputs],
             in_stream,
-            time_ch: needs_time
-                .map(|tf| tod::time_filter(BroadcastStream::new(c_time), tf)),
+            time_ch: needs_time.map(|tf| {
+                tod::time_filter(BroadcastStream::new(c_time), tf, tz)
+            }),
             solar_ch: if needs_solar { Some(c_solar) } else { None },
+            lunar_ch: if needs_lunar { Some(c_lunar) } else { None },
             def_exprs,
-            exprs: exprs.drain(..).zip(out_chans).collect(),
+            exprs,
+            uses_timers,
+            tz,
+            n_devices: cfg.inputs.len(),
+            status: Arc::new(Mutex::new(status)),
+            enable,
+            enabled,
+            published,
         })
     }
 
+    // Returns the node's status, which is updated as the node runs.
+
+    pub fn status(&self) -> Arc<Mutex<status::Status>> {
+        self.status.clone()
+    }
+
+    // Returns when the next timer function needs to change its
+    // result. If no timer is running, `None` is returned.
+
+    fn next_deadline(
+        &self,
+        now: &chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.exprs
+            .iter()
+            .map(|(code, _)| code)
+            .chain(self.def_exprs.iter().map(|(code, _)| code))
+            .filter_map(|code| code.next_deadline(now, self.tz))
+            .min()
+    }
+
+    // Calculates each expression of the `defs` array. Each
+    // expression's result is stored in the associated `input` cell.
+
+    fn compute_defs(&mut self, time: &tod::Info, sky: &compile::Sky) {
+        self.def_exprs.iter_mut().for_each(|(code, idx)| {
+            self.inputs[*idx] = code.eval(&self.inputs, time, Some(sky))
+        });
+    }
+
+    // Calculates each of the final expressions and returns their
+    // results, in the order of `exprs`.
+
+    fn compute_exprs(
+        &mut self,
+        time: &tod::Info,
+        sky: &compile::Sky,
+    ) -> Vec<Option<device::Value>> {
+        self.exprs
+            .iter_mut()
+            .map(|(code, _)| code.eval(&self.inputs, time, Some(sky)))
+            .collect()
+    }
+
+    // Copies the current values of the variables and outputs into
+    // the node's status. `failed` indicates which expressions didn't
+    // produce a value. Until every input device has reported a
+    // value, that's expected so it's only counted as an error
+    // afterwards.
+
+    fn report(&self, failed: &[bool]) {
+        let ready = self.inputs[..self.n_devices].iter().all(Option::is_some);
+        let mut status = self.status.lock().unwrap();
+
+        for (var, value) in status.variables.iter_mut().zip(&self.inputs) {
+            var.value.clone_from(value)
+        }
+
+        for ((expr, (_, out)), failed) in
+            status.exprs.iter_mut().zip(&self.exprs).zip(failed)
+        {
+            expr.last.clone_from(&out.prev);
+            if *failed && ready {
+                expr.errors += 1
+            }
+        }
+    }
+
     // Runs the node logic. This method should never return.
 
     async fn run(mut self) -> Result<Infallible> {
-        let mut time = Arc::new((chrono::Utc::now(), chrono::Local::now()));
-        let mut solar = None;
+        let mut time = tod::now(self.tz);
+        let mut sky = compile::Sky::default();
 
         info!("starting");
 
+        // Report the state of the enable device so it has a value
+        // when the node is first started.
+
+        if let Some(dev) = self.enable.as_mut() {
+            if !self.enabled {
+                warn!("node is disabled")
+            }
+            dev.report_update(self.enabled).await
+        }
+
         loop {
+            // Find when the next timer function changes its result.
+            // This has to be done before the futures below borrow
+            // parts of the node.
+
+            let next_deadline = self.next_deadline(&time.0);
+
             // Create a future that yields the time-of-day using the
             // TimeFilter. If no expression uses time, then `time_ch`
             // will be `None` and we return a future that immediately
@@ -418,6 +844,45 @@ impl Node {
                 }
             };
 
+            // Create a future that yields the next lunar update, in
+            // the same way.
+
+            let wait_for_lunar = async {
+                match self.lunar_ch.as_mut() {
+                    None => pending().await,
+                    Some(ch) => ch.recv().await,
+                }
+            };
+
+            // Create a future that yields the next setting of the
+            // enable device. If the node doesn't have one, the
+            // future never completes.
+
+            let wait_for_setting = async {
+                match self.enable.as_mut() {
+                    None => pending().await,
+                    Some(dev) => dev.next_setting().await,
+                }
+            };
+            let mut setting = None;
+
+            // Create a future that completes when the next timer
+            // function needs to change its result. If no timer is
+            // running, the future never completes.
+
+            let wait_for_timer = async {
+                match next_deadline {
+                    None => pending().await,
+                    Some(t) => {
+                        let delay = (t - chrono::Utc::now())
+                            .to_std()
+                            .unwrap_or_default();
+
+                        tokio::time::sleep(delay).await
+                    }
+                }
+            };
+
             #[rustfmt::skip]
 	    tokio::select! {
 		biased;
@@ -427,7 +892,18 @@ impl Node {
 
 		v = wait_for_solar => {
 		    match v {
-			Ok(v) => solar = Some(v),
+			Ok(v) => {
+			    sky.solar = Some(v);
+
+			    // The times of solar events are reported in
+			    // local time. If no expression reads the
+			    // time-of-day, update it so the offset
+			    // follows daylight saving changes.
+
+			    if self.time_ch.is_none() {
+				time = tod::now(self.tz);
+			    }
+			}
 			Err(broadcast::error::RecvError::Lagged(_)) => {
 			    warn!("not handling solar info fast enough");
 			    continue
@@ -441,6 +917,25 @@ impl Node {
 		    }
 		}
 
+		// If we need the lunar channel, wait for the next
+		// update.
+
+		v = wait_for_lunar => {
+		    match v {
+			Ok(v) => sky.lunar = Some(v),
+			Err(broadcast::error::RecvError::Lagged(_)) => {
+			    warn!("not handling lunar info fast enough");
+			    continue
+			}
+			Err(broadcast::error::RecvError::Closed) => {
+			    error!("lunar info channel is closed");
+			    return Err(drmem_api::Error::OperationError(
+				"lunar channel closed".into()
+			    ));
+			}
+		    }
+		}
+
 		// If we need the time channel, wait for the next
 		// second.
 
@@ -448,6 +943,19 @@ impl Node {
 		    time = v;
 		}
 
+		// If a timer function is running, wake up when its
+		// result changes.
+
+		() = wait_for_timer => {}
+
+		// If the node can be disabled, handle settings to its
+		// enable device.
+
+		Some((v, reply)) = wait_for_setting => {
+		    reply(Ok(v));
+		    setting = Some(v);
+		}
+
 		// Wait for the next reading to arrive. All the
 		// incoming streams have been combined into one and
 		// the returned value is a pair consisting of an index
@@ -461,59 +969,73 @@ impl Node {
 		}
 	    }
 
-            // Calculate each expression of the `defs` array. Store
-            // each expression's result in the associated `input`
-            // cell.
-
-            self.def_exprs
-                .iter()
-                .for_each(|compile::Program(expr, idx)| {
-                    self.inputs[*idx] =
-                        compile::eval(expr, &self.inputs, &time, solar.as_ref())
-                });
+            // If the node's enable device was set, save its new
+            // value. When the node is enabled, the previous settings
+            // are forgotten so every output gets updated.
 
-            // Calculate each of the final expressions. If there are
-            // more than one expressions in this node, they are
-            // evaluated concurrently.
+            if let Some(v) = setting {
+                if let Some(dev) = self.enable.as_mut() {
+                    dev.report_update(v).await
+                }
+                if v != self.enabled {
+                    info!("node {}", if v { "enabled" } else { "disabled" });
+                    if v {
+                        self.exprs
+                            .iter_mut()
+                            .for_each(|(_, out)| out.prev = None)
+                    }
+                    self.enabled = v
+                }
+            }
 
-            join_all(self.exprs.iter_mut().filter_map(
-                |(compile::Program(expr, _), out)| {
-                    compile::eval(expr, &self.inputs, &time, solar.as_ref())
-                        .map(|v| out.send(v))
-                },
-            ))
-            .await;
-        }
-    }
+            // Timer functions need the current time, which may be
+            // more precise than the time-of-day channel provides.
 
-    // Starts a new instance of a logic node.
+            if self.uses_timers {
+                time = tod::now(self.tz);
+            }
 
-    pub fn start(
-        c_req: client::RequestChan,
-        rx_tod: broadcast::Receiver<tod::Info>,
-        rx_solar: broadcast::Receiver<solar::Info>,
-        cfg: config::Logic,
-    ) -> JoinHandle<Result<Infallible>> {
-        let name = cfg.name.clone();
+            self.compute_defs(&time, &sky);
 
-        // Put the node in the background.
+            // Report the published definitions whose values changed.
 
-        tokio::spawn(async move {
-            // Create a new instance and let it initialize itself. If
-            // an error occurs, return it.
+            for (idx, last, dev) in self.published.iter_mut() {
+                if let Some(v) = &self.inputs[*idx] {
+                    if last.as_ref() != Some(v) {
+                        dev.report_update(v.clone()).await;
+                        *last = Some(v.clone())
+                    }
+                }
+            }
 
-            let node = Node::init(c_req, rx_tod, rx_solar, cfg)
-                .instrument(info_span!("logic-init", name = &name))
-                .await?;
+            // Calculate each of the final expressions. If there are
+            // more than one expressions in this node, the results
+            // are sent concurrently. A disabled node still computes
+            // them, so its stateful functions keep up with the
+            // inputs, but nothing is sent.
+
+            let results = self.compute_exprs(&time, &sky);
+            let failed: Vec<bool> =
+                results.iter().map(Option::is_none).collect();
+
+            if self.enabled {
+                join_all(
+                    self.exprs
+                        .iter_mut()
+                        .zip(results)
+                        .filter_map(|((_, out), v)| v.map(|v| out.send(v))),
+                )
+                .await;
+            }
 
-            node.run().instrument(info_span!("logic", name)).await
-        })
+            self.report(&failed);
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{config, solar, tod, Node};
+    use super::{calendar, config, lunar, reload, solar, status, tod, Node};
     use drmem_api::{
         client::{self, Request},
         device, driver, Error, Result,
@@ -530,10 +1052,23 @@ mod test {
     // spin up a logic block using the provided configuration. The
     // unit tests can provide channels to send readings and receive
     // settings and verify correct operation.
+    //
+    // `start()` launches a block with the defaults from `new()`. A
+    // test that needs more sets the other fields before calling
+    // `launch()`: `last_values` are reported as the inputs' most
+    // recent readings, `calendar` is the block's calendar, `db`
+    // receives the node's status, `enable` is the setting channel
+    // and saved value of the enable device and `reports` receives
+    // the values reported to the node's read-only devices.
 
     struct Emulator {
         inputs: HashMap<Arc<str>, mpsc::Receiver<device::Value>>,
         outputs: HashMap<Arc<str>, driver::TxDeviceSetting>,
+        last_values: HashMap<Arc<str>, device::Value>,
+        calendar: calendar::Info,
+        db: status::Db,
+        enable: Option<(driver::RxDeviceSetting, Option<device::Value>)>,
+        reports: Option<mpsc::Sender<(device::Name, device::Value)>>,
     }
 
     impl Emulator {
@@ -544,6 +1079,7 @@ mod test {
         ) -> Result<(
             broadcast::Sender<tod::Info>,
             broadcast::Sender<solar::Info>,
+            broadcast::Sender<lunar::Info>,
             task::JoinHandle<Result<bool>>,
             oneshot::Sender<()>,
         )> {
@@ -560,6 +1096,11 @@ mod test {
             Emulator {
                 inputs: HashMap::from_iter(inputs.drain(..)),
                 outputs: HashMap::from_iter(outputs.drain(..)),
+                last_values: HashMap::new(),
+                calendar: Arc::default(),
+                db: status::Db::default(),
+                enable: None,
+                reports: None,
             }
         }
 
@@ -571,24 +1112,90 @@ mod test {
         ) -> Result<(
             broadcast::Sender<tod::Info>,
             broadcast::Sender<solar::Info>,
+            broadcast::Sender<lunar::Info>,
             task::JoinHandle<Result<bool>>,
             oneshot::Sender<()>,
         )> {
             // Create the common channels used by DrMem.
 
             let (tx_req, mut c_recv) = mpsc::channel(100);
+            let (tx_drv_req, mut d_recv) = mpsc::channel(100);
             let (tx_tod, _) = broadcast::channel(100);
             let (tx_solar, _) = broadcast::channel(100);
+            let (tx_lunar, _) = broadcast::channel(100);
 
             // Start the logic block with the proper communciation
-            // channels and configuration.
-
-            let node = Node::start(
-                client::RequestChan::new(tx_req),
-                tx_tod.subscribe(),
-                tx_solar.subscribe(),
-                cfg,
-            );
+            // channels and configuration. It's initialized and run
+            // the same way `reload` starts the nodes of `drmemd`.
+
+            let shared = reload::Shared {
+                c_req: client::RequestChan::new(tx_req),
+                d_req: tx_drv_req,
+                tx_tod: tx_tod.clone(),
+                tx_solar: tx_solar.clone(),
+                tx_lunar: tx_lunar.clone(),
+                cal: self.calendar.clone(),
+                db: status::Db::default(),
+            };
+            let init = reload::init(&shared, cfg);
+            let db = self.db.clone();
+
+            // The emulator knows the node is initialized when the
+            // request channel closes, so the shared channels can't
+            // outlive the initialization.
+
+            drop(shared);
+
+            let node =
+                task::spawn(async move { reload::run(&db, init.await?).await });
+
+            // Handle the registration of the node's own devices. The
+            // enable device's reports are ignored. The other devices'
+            // reports are forwarded, if the test asked for them.
+
+            let mut enable = self.enable.take();
+            let reports = self.reports.take();
+
+            task::spawn(async move {
+                while let Some(req) = d_recv.recv().await {
+                    match req {
+                        driver::Request::AddReadWriteDevice {
+                            rpy_chan,
+                            ..
+                        } => {
+                            let _ = rpy_chan.send(match enable.take() {
+                                Some((rx_set, prev)) => {
+                                    let report: driver::ReportReading =
+                                        Box::new(|_| Box::pin(async {}));
+
+                                    Ok((report, rx_set, prev))
+                                }
+                                None => Err(Error::InUse),
+                            });
+                        }
+                        driver::Request::AddReadonlyDevice {
+                            dev_name,
+                            rpy_chan,
+                            ..
+                        } => {
+                            let tx = reports.clone();
+                            let report: driver::ReportReading =
+                                Box::new(move |v| {
+                                    let tx = tx.clone();
+                                    let name = dev_name.clone();
+
+                                    Box::pin(async move {
+                                        if let Some(tx) = tx {
+                                            let _ = tx.send((name, v)).await;
+                                        }
+                                    })
+                                });
+
+                            let _ = rpy_chan.send(Ok(report));
+                        }
+                    }
+                }
+            });
 
             // Create the 'stop' channel.
 
@@ -619,6 +1226,32 @@ mod test {
                                     },
                                 );
                             }
+                            Request::QueryDeviceInfo {
+                                pattern: Some(pattern),
+                                rpy_chan,
+                            } if self
+                                .last_values
+                                .contains_key(pattern.as_str()) =>
+                            {
+                                let value =
+                                    self.last_values[pattern.as_str()].clone();
+
+                                let _ = rpy_chan.send(Ok(vec![
+                                    client::DevInfoReply {
+                                        name: device::Name::create(&pattern)
+                                            .unwrap(),
+                                        units: None,
+                                        settable: false,
+                                        total_points: 1,
+                                        first_point: None,
+                                        last_point: Some(device::Reading {
+                                            ts: std::time::SystemTime::now(),
+                                            value,
+                                        }),
+                                        driver: "emulator".into(),
+                                    },
+                                ]));
+                            }
                             Request::QueryDeviceInfo { rpy_chan, .. } => {
                                 let _ = rpy_chan.send(Err(
                                     Error::ProtocolError("bad request".into()),
@@ -697,7 +1330,7 @@ mod test {
                 Ok(true)
             });
 
-            Ok((tx_tod, tx_solar, emu, tx_stop))
+            Ok((tx_tod, tx_solar, tx_lunar, emu, tx_stop))
         }
     }
 
@@ -733,6 +1366,7 @@ mod test {
     // requests can be monitoring requests for other devices or
     // requests for a setting channel to a device.
 
+    #[allow(clippy::type_complexity)]
     fn init_node<'a>(
         cfg: config::Logic,
     ) -> (
@@ -740,14 +1374,25 @@ mod test {
         mpsc::Receiver<client::Request>,
         broadcast::Sender<tod::Info>,
         broadcast::Sender<solar::Info>,
+        broadcast::Sender<lunar::Info>,
     ) {
         let (mpsc_tx, mpsc_rx) = mpsc::channel(10);
         let c_req = client::RequestChan::new(mpsc_tx);
+        let (d_req, _) = mpsc::channel(10);
         let (tod_tx, c_time) = broadcast::channel(10);
         let (sol_tx, c_solar) = broadcast::channel(10);
-        let node_fut = Node::init(c_req, c_time, c_solar, cfg);
+        let (lun_tx, c_lunar) = broadcast::channel(10);
+        let node_fut = Node::init(
+            c_req,
+            d_req,
+            c_time,
+            c_solar,
+            c_lunar,
+            Arc::default(),
+            cfg,
+        );
 
-        (node_fut, mpsc_rx, tod_tx, sol_tx)
+        (node_fut, mpsc_rx, tod_tx, sol_tx, lun_tx)
     }
 
     // Builds a `config::Logic` type using arrays of config
@@ -773,6 +1418,9 @@ mod test {
                 .collect(),
             defs: defs.iter().map(|&(a, b)| (a.into(), b.into())).collect(),
             exprs: exprs.iter().map(|&a| a.into()).collect(),
+            timezone: None,
+            enable_device: false,
+            publish: vec![],
         }
     }
 
@@ -787,7 +1435,7 @@ mod test {
 
         {
             let cfg = build_config(&[], &[], &[], &[]);
-            let (node, _, tod_tx, sol_tx) = init_node(cfg);
+            let (node, _, tod_tx, sol_tx, lun_tx) = init_node(cfg);
 
             tokio::pin!(node);
 
@@ -797,11 +1445,12 @@ mod test {
 
             assert!(matches!(node.as_mut().await, Err(Error::ConfigError(_))));
 
-            // With no config, the TOD and solar channel handles
-            // should have been dropped.
+            // With no config, the TOD, solar and lunar channel
+            // handles should have been dropped.
 
             assert_eq!(tod_tx.receiver_count(), 0);
             assert_eq!(sol_tx.receiver_count(), 0);
+            assert_eq!(lun_tx.receiver_count(), 0);
 
             // This call allows us to keep ownership of the node so
             // we're sure the Node dropped the broadcast receivers and
@@ -819,7 +1468,7 @@ mod test {
                 &[],
                 &["{in} -> {out}"],
             );
-            let (node, _, _, _) = init_node(cfg);
+            let (node, _, _, _, _) = init_node(cfg);
 
             // `await` on the future. This should return immediately
             // as an error because there are no expressions to
@@ -837,7 +1486,7 @@ mod test {
                 &[("in", "{in}")],
                 &["{in} -> {out}"],
             );
-            let (node, _, _, _) = init_node(cfg);
+            let (node, _, _, _, _) = init_node(cfg);
 
             // `await` on the future. This should return immediately
             // as an error because there are no expressions to
@@ -855,7 +1504,7 @@ mod test {
                 &[],
                 &["{in} -> {out}"],
             );
-            let (node, _, _, _) = init_node(cfg);
+            let (node, _, _, _, _) = init_node(cfg);
 
             // `await` on the future. This should return immediately
             // as an error because there are no expressions to
@@ -863,6 +1512,47 @@ mod test {
 
             assert!(matches!(node.await, Err(Error::ConfigError(_))));
         }
+
+        // Test that we reject bad entries in `publish`: names that
+        // aren't in `defs`, names listed twice and a name that
+        // clashes with the enable device.
+
+        for (publish, enable_device) in [
+            (vec!["in"], false),
+            (vec!["big", "big"], false),
+            (vec!["enabled"], true),
+        ] {
+            let mut cfg = build_config(
+                &[("in", "device:in")],
+                &[("out", "device:out")],
+                &[("big", "{in} > 5"), ("enabled", "true")],
+                &["{big} -> {out}"],
+            );
+
+            cfg.publish = publish.into_iter().map(String::from).collect();
+            cfg.enable_device = enable_device;
+
+            let (node, _, _, _, _) = init_node(cfg);
+
+            assert!(matches!(node.await, Err(Error::ConfigError(_))));
+        }
+
+        // Test that we reject an unknown time zone.
+
+        {
+            let mut cfg = build_config(
+                &[],
+                &[("out", "device:out")],
+                &[],
+                &["{local:hour} -> {out}"],
+            );
+
+            cfg.timezone = Some("Mars/Olympus_Mons".into());
+
+            let (node, _, _, _, _) = init_node(cfg);
+
+            assert!(matches!(node.await, Err(Error::ConfigError(_))));
+        }
     }
 
     // Test a basic logic block in which an input device's value is
@@ -879,7 +1569,7 @@ mod test {
         let (tx_in, rx_in) = mpsc::channel(100);
         let (tx_out, mut rx_out) = mpsc::channel(100);
 
-        let (_, _, emu, tx_stop) = Emulator::start(
+        let (_, _, _, emu, tx_stop) = Emulator::start(
             vec![("device:in".into(), rx_in)],
             vec![("device:out".into(), tx_out)],
             cfg,
@@ -933,7 +1623,7 @@ mod test {
         let (tx_in, rx_in) = mpsc::channel(100);
         let (tx_out, mut rx_out) = mpsc::channel(100);
 
-        let (_, _, emu, tx_stop) = Emulator::start(
+        let (_, _, _, emu, tx_stop) = Emulator::start(
             vec![("device:in".into(), rx_in)],
             vec![("device:out".into(), tx_out)],
             cfg,
@@ -990,7 +1680,7 @@ mod test {
         let (tx_out1, mut rx_out1) = mpsc::channel(100);
         let (tx_out2, mut rx_out2) = mpsc::channel(100);
 
-        let (_, tx_solar, emu, tx_stop) = Emulator::start(
+        let (_, tx_solar, _, emu, tx_stop) = Emulator::start(
             vec![],
             vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
             cfg,
@@ -1005,7 +1695,8 @@ mod test {
                 elevation: 1.0,
                 azimuth: 2.0,
                 right_ascension: 3.0,
-                declination: 4.0
+                declination: 4.0,
+                ..Default::default()
             }))
             .is_ok());
 
@@ -1030,32 +1721,449 @@ mod test {
         assert_eq!(emu.await.unwrap(), Ok(true));
     }
 
-    // Test a logic block with two outputs. Make sure they are sent
-    // "in parallel".
+    // This test verifies that lunar information reaches expressions
+    // that use the `lunar` category.
 
     #[tokio::test]
-    async fn test_node_concurrency() {
-        const IN1: &str = "device:in";
+    async fn test_basic_lunar_node() {
         const OUT1: &str = "device:out1";
         const OUT2: &str = "device:out2";
+        let cfg = build_config(
+            &[],
+            &[("illum", OUT1), ("phase", OUT2)],
+            &[],
+            &["{lunar:illum} -> {illum}", "{lunar:phase} -> {phase}"],
+        );
+        let (tx_out1, mut rx_out1) = mpsc::channel(100);
+        let (tx_out2, mut rx_out2) = mpsc::channel(100);
 
-        // This section sets the output expressions in out1 -> out2
-        // order. It computes a different value that is sent to the
-        // outputs and verifies the correct value is sent to the
-        // correct device.
+        let (_, _, tx_lunar, emu, tx_stop) = Emulator::start(
+            vec![],
+            vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
+            cfg,
+        )
+        .await
+        .unwrap();
+
+        assert!(tx_lunar
+            .send(Arc::new(lunar::LunarInfo {
+                elevation: 10.0,
+                azimuth: 90.0,
+                illumination: 0.5,
+                phase: "first quarter",
+            }))
+            .is_ok());
 
         {
-            let cfg = build_config(
-                &[("in", IN1)],
-                &[("out1", OUT1), ("out2", OUT2)],
-                &[],
-                &["{in} -> {out1}", "{in} * 2 -> {out2}"],
-            );
-            let (tx_in, rx_in) = mpsc::channel(100);
-            let (tx_out1, mut rx_out1) = mpsc::channel(100);
-            let (tx_out2, mut rx_out2) = mpsc::channel(100);
+            let (value, rpy) = rx_out1.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
 
-            let (_, _, emu, tx_stop) = Emulator::start(
+            assert_eq!(value, device::Value::Flt(0.5));
+        }
+
+        {
+            let (value, rpy) = rx_out2.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Str("first quarter".into()));
+        }
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+    }
+
+    // Test a logic block that uses a stateful function. The state
+    // needs to be kept between evaluations and a new instance of the
+    // node needs to start with a cleared state.
+
+    #[tokio::test]
+    async fn test_stateful_node() {
+        const IN1: &str = "device:in1";
+        const IN2: &str = "device:in2";
+        const OUT: &str = "device:out";
+
+        for _ in 0..2 {
+            let cfg = build_config(
+                &[("set", IN1), ("reset", IN2)],
+                &[("out", OUT)],
+                &[],
+                &["latch({set}, {reset}) -> {out}"],
+            );
+            let (tx_in1, rx_in1) = mpsc::channel(100);
+            let (tx_in2, rx_in2) = mpsc::channel(100);
+            let (tx_out, mut rx_out) = mpsc::channel(100);
+
+            let (_, _, _, emu, tx_stop) = Emulator::start(
+                vec![(IN1.into(), rx_in1), (IN2.into(), rx_in2)],
+                vec![(OUT.into(), tx_out)],
+                cfg,
+            )
+            .await
+            .unwrap();
+
+            // Both inputs need a value before the latch computes a
+            // result.
+
+            assert!(tx_in2.send(device::Value::Bool(false)).await.is_ok());
+            assert!(tx_in1.send(device::Value::Bool(false)).await.is_ok());
+
+            let (value, rpy) = rx_out.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Bool(false));
+
+            // Set the latch. It should stay set after `set` goes
+            // false.
+
+            assert!(tx_in1.send(device::Value::Bool(true)).await.is_ok());
+
+            let (value, rpy) = rx_out.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Bool(true));
+
+            assert!(tx_in1.send(device::Value::Bool(false)).await.is_ok());
+            assert!(time::timeout(Duration::from_millis(100), rx_out.recv())
+                .await
+                .is_err());
+
+            // Now reset it.
+
+            assert!(tx_in2.send(device::Value::Bool(true)).await.is_ok());
+
+            let (value, rpy) = rx_out.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Bool(false));
+
+            // Leave the latch set when stopping the node. The next
+            // pass of the loop starts a new node which should start
+            // with the latch cleared.
+
+            assert!(tx_in2.send(device::Value::Bool(false)).await.is_ok());
+            assert!(tx_in1.send(device::Value::Bool(true)).await.is_ok());
+
+            let (value, rpy) = rx_out.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Bool(true));
+
+            let _ = tx_stop.send(());
+
+            assert_eq!(emu.await.unwrap(), Ok(true));
+        }
+    }
+
+    // Test that expressions are type checked when the node starts.
+
+    #[tokio::test]
+    async fn test_node_type_checking() {
+        const IN1: &str = "device:temp";
+        const IN2: &str = "device:state";
+        const OUT: &str = "device:out";
+
+        // Runs a node with the given expression and reports the
+        // result of the emulator.
+
+        async fn run(defs: &[(&str, &str)], expr: &str) -> Result<bool> {
+            let cfg = build_config(
+                &[("temp", IN1), ("state", IN2)],
+                &[("out", OUT)],
+                defs,
+                &[expr],
+            );
+            let (_tx_in1, rx_in1) = mpsc::channel(100);
+            let (_tx_in2, rx_in2) = mpsc::channel(100);
+            let (tx_out, _rx_out) = mpsc::channel(100);
+
+            let (_, _, _, emu, tx_stop) = Emulator {
+                last_values: HashMap::from([(
+                    IN1.into(),
+                    device::Value::Flt(71.5),
+                )]),
+                ..Emulator::new(
+                    vec![(IN1.into(), rx_in1), (IN2.into(), rx_in2)],
+                    vec![(OUT.into(), tx_out)],
+                )
+            }
+            .launch(cfg)
+            .await
+            .unwrap();
+
+            time::sleep(Duration::from_millis(100)).await;
+
+            let _ = tx_stop.send(());
+
+            emu.await.unwrap()
+        }
+
+        assert!(matches!(
+            run(&[], "{temp} and true -> {out}").await,
+            Err(Error::ConfigError(_))
+        ));
+        assert!(matches!(
+            run(&[], "{temp} + \"F\" -> {out}").await,
+            Err(Error::ConfigError(_))
+        ));
+        assert!(matches!(
+            run(&[("hot", "{temp} > 80")], "{hot} < 1 -> {out}").await,
+            Err(Error::ConfigError(_))
+        ));
+        assert!(matches!(
+            run(&[("bad", "not {temp}")], "true -> {out}").await,
+            Err(Error::ConfigError(_))
+        ));
+        assert_eq!(run(&[], "{temp} > 70.0 -> {out}").await, Ok(true));
+
+        // The type of `state` is unknown, so it isn't checked.
+
+        assert_eq!(run(&[], "{state} and true -> {out}").await, Ok(true));
+    }
+
+    // Test a logic block that uses the calendar. Its expression is
+    // evaluated when the time-of-day arrives.
+
+    #[tokio::test]
+    async fn test_calendar_node() {
+        const OUT: &str = "device:out";
+        let today = chrono::Local::now().date_naive();
+        let cal = calendar::load(&[config::Calendar {
+            name: "vacation".into(),
+            holiday: false,
+            file: None,
+            dates: vec![format!("{}..{}", today.pred_opt().unwrap(), today)],
+        }])
+        .await
+        .unwrap();
+
+        // An unknown event stops the node when it starts.
+
+        {
+            let cfg = build_config(
+                &[],
+                &[("out", OUT)],
+                &[],
+                &["{cal:event(\"trip\")} -> {out}"],
+            );
+            let (tx_out, _rx_out) = mpsc::channel(100);
+
+            let (_, _, _, emu, _tx_stop) = Emulator {
+                calendar: cal.clone(),
+                ..Emulator::new(vec![], vec![(OUT.into(), tx_out)])
+            }
+            .launch(cfg)
+            .await
+            .unwrap();
+
+            assert!(matches!(emu.await.unwrap(), Err(Error::ParseError(_))));
+        }
+
+        let cfg = build_config(
+            &[],
+            &[("out", OUT)],
+            &[],
+            &["{cal:event(\"vacation\")} and not {cal:holiday} -> {out}"],
+        );
+        let (tx_out, mut rx_out) = mpsc::channel(100);
+
+        let (tx_tod, _, _, emu, tx_stop) = Emulator {
+            calendar: cal,
+            ..Emulator::new(vec![], vec![(OUT.into(), tx_out)])
+        }
+        .launch(cfg)
+        .await
+        .unwrap();
+
+        assert!(tx_tod
+            .send(Arc::new((
+                chrono::Utc::now(),
+                chrono::Local::now().fixed_offset()
+            )))
+            .is_ok());
+
+        {
+            let (value, rpy) = rx_out.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Bool(true));
+        }
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+    }
+
+    // Test that a node with its own time zone computes the local
+    // time in that zone.
+
+    #[tokio::test]
+    async fn test_timezone_node() {
+        const OUT1: &str = "device:out1";
+        const OUT2: &str = "device:out2";
+
+        let mut cfg = build_config(
+            &[],
+            &[("out1", OUT1), ("out2", OUT2)],
+            &[],
+            &[
+                "{local:hour} * 60 + {local:minute} -> {out1}",
+                "{local:day} -> {out2}",
+            ],
+        );
+
+        cfg.timezone = Some("Asia/Kolkata".into());
+
+        let (tx_out1, mut rx_out1) = mpsc::channel(100);
+        let (tx_out2, mut rx_out2) = mpsc::channel(100);
+
+        let (tx_tod, _, _, emu, tx_stop) = Emulator::start(
+            vec![],
+            vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
+            cfg,
+        )
+        .await
+        .unwrap();
+
+        // The time-of-day task sends the system's local time. The
+        // node converts it to its own zone. 20:00 UTC is 1:30 the
+        // next morning in India.
+
+        assert!(tx_tod
+            .send(tod::at("2024-06-01T20:00:00Z".parse().unwrap(), None))
+            .is_ok());
+
+        {
+            let (value, rpy) = rx_out1.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Int(90));
+        }
+
+        {
+            let (value, rpy) = rx_out2.recv().await.unwrap();
+            let _ = rpy.send(Ok(value.clone()));
+
+            assert_eq!(value, device::Value::Int(2));
+        }
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+    }
+
+    // Test that timer functions update their outputs without new
+    // input readings arriving.
+
+    #[tokio::test]
+    async fn test_timer_node() {
+        const IN: &str = "device:in";
+        const OUT1: &str = "device:out1";
+        const OUT2: &str = "device:out2";
+
+        let cfg = build_config(
+            &[("in", IN)],
+            &[("held", OUT1), ("off", OUT2)],
+            &[],
+            &[
+                "held({in}, 200ms) -> {held}",
+                "delay_off({in}, 200ms) -> {off}",
+            ],
+        );
+        let (tx_in, rx_in) = mpsc::channel(100);
+        let (tx_out1, mut rx_out1) = mpsc::channel(100);
+        let (tx_out2, mut rx_out2) = mpsc::channel(100);
+
+        let (_, _, _, emu, tx_stop) = Emulator::start(
+            vec![(IN.into(), rx_in)],
+            vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
+            cfg,
+        )
+        .await
+        .unwrap();
+
+        // When the input goes true, `held()` stays false until the
+        // delay expires.
+
+        assert!(tx_in.send(device::Value::Bool(true)).await.is_ok());
+
+        let (value, rpy) = rx_out1.recv().await.unwrap();
+        let _ = rpy.send(Ok(value.clone()));
+
+        assert_eq!(value, device::Value::Bool(false));
+
+        let (value, rpy) = rx_out2.recv().await.unwrap();
+        let _ = rpy.send(Ok(value.clone()));
+
+        assert_eq!(value, device::Value::Bool(true));
+
+        assert!(time::timeout(Duration::from_millis(100), rx_out1.recv())
+            .await
+            .is_err());
+
+        let (value, rpy) =
+            time::timeout(Duration::from_millis(500), rx_out1.recv())
+                .await
+                .unwrap()
+                .unwrap();
+        let _ = rpy.send(Ok(value.clone()));
+
+        assert_eq!(value, device::Value::Bool(true));
+
+        // When the input goes false, `held()` immediately goes false
+        // and `delay_off()` goes false after the delay.
+
+        assert!(tx_in.send(device::Value::Bool(false)).await.is_ok());
+
+        let (value, rpy) = rx_out1.recv().await.unwrap();
+        let _ = rpy.send(Ok(value.clone()));
+
+        assert_eq!(value, device::Value::Bool(false));
+
+        assert!(time::timeout(Duration::from_millis(100), rx_out2.recv())
+            .await
+            .is_err());
+
+        let (value, rpy) =
+            time::timeout(Duration::from_millis(500), rx_out2.recv())
+                .await
+                .unwrap()
+                .unwrap();
+        let _ = rpy.send(Ok(value.clone()));
+
+        assert_eq!(value, device::Value::Bool(false));
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+    }
+
+    // Test a logic block with two outputs. Make sure they are sent
+    // "in parallel".
+
+    #[tokio::test]
+    async fn test_node_concurrency() {
+        const IN1: &str = "device:in";
+        const OUT1: &str = "device:out1";
+        const OUT2: &str = "device:out2";
+
+        // This section sets the output expressions in out1 -> out2
+        // order. It computes a different value that is sent to the
+        // outputs and verifies the correct value is sent to the
+        // correct device.
+
+        {
+            let cfg = build_config(
+                &[("in", IN1)],
+                &[("out1", OUT1), ("out2", OUT2)],
+                &[],
+                &["{in} -> {out1}", "{in} * 2 -> {out2}"],
+            );
+            let (tx_in, rx_in) = mpsc::channel(100);
+            let (tx_out1, mut rx_out1) = mpsc::channel(100);
+            let (tx_out2, mut rx_out2) = mpsc::channel(100);
+
+            let (_, _, _, emu, tx_stop) = Emulator::start(
                 vec![(IN1.into(), rx_in)],
                 vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
                 cfg,
@@ -1109,7 +2217,7 @@ mod test {
             let (tx_out1, mut rx_out1) = mpsc::channel(100);
             let (tx_out2, mut rx_out2) = mpsc::channel(100);
 
-            let (_, _, emu, tx_stop) = Emulator::start(
+            let (_, _, _, emu, tx_stop) = Emulator::start(
                 vec![(IN1.into(), rx_in)],
                 vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
                 cfg,
@@ -1167,7 +2275,7 @@ mod test {
             let (tx_out1, mut rx_out1) = mpsc::channel(100);
             let (tx_out2, mut rx_out2) = mpsc::channel(100);
 
-            let (_, _, emu, tx_stop) = Emulator::start(
+            let (_, _, _, emu, tx_stop) = Emulator::start(
                 vec![(IN1.into(), rx_in)],
                 vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
                 cfg,
@@ -1205,4 +2313,292 @@ mod test {
             assert_eq!(emu.await.unwrap(), Ok(true));
         }
     }
+
+    // Test an expression that uses `let` definitions and sets two
+    // outputs.
+
+    #[tokio::test]
+    async fn test_multiple_outputs() {
+        const IN1: &str = "device:in";
+        const OUT1: &str = "device:out1";
+        const OUT2: &str = "device:out2";
+
+        let cfg = build_config(
+            &[("in", IN1)],
+            &[("out1", OUT1), ("out2", OUT2)],
+            &[("def1", "{in} * 10")],
+            &["let x = {def1} + 1; let y = {x} * 2; {y} + {x} -> {out2}, {out1}"],
+        );
+        let (tx_in, rx_in) = mpsc::channel(100);
+        let (tx_out1, mut rx_out1) = mpsc::channel(100);
+        let (tx_out2, mut rx_out2) = mpsc::channel(100);
+
+        let (_, _, _, emu, tx_stop) = Emulator::start(
+            vec![(IN1.into(), rx_in)],
+            vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
+            cfg,
+        )
+        .await
+        .unwrap();
+
+        for (input, output) in [(4, 123), (1, 33)] {
+            assert!(tx_in.send(device::Value::Int(input)).await.is_ok());
+
+            let (value1, rpy1) =
+                time::timeout(Duration::from_millis(100), rx_out1.recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+            let (value2, rpy2) =
+                time::timeout(Duration::from_millis(100), rx_out2.recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+
+            assert_eq!(value1, device::Value::Int(output));
+            assert_eq!(value2, device::Value::Int(output));
+
+            let _ = rpy1.send(Ok(value1.clone()));
+            let _ = rpy2.send(Ok(value2.clone()));
+        }
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+    }
+
+    // Test that a running node reports its state.
+
+    #[tokio::test]
+    async fn test_node_status() {
+        const IN: &str = "device:in";
+        const OUT1: &str = "device:out1";
+        const OUT2: &str = "device:out2";
+
+        let cfg = build_config(
+            &[("in", IN)],
+            &[("out1", OUT1), ("out2", OUT2)],
+            &[("big", "{in} > 5")],
+            &["{big} -> {out1}", "not {in} -> {out2}"],
+        );
+        let (tx_in, rx_in) = mpsc::channel(100);
+        let (tx_out1, mut rx_out1) = mpsc::channel(100);
+        let (tx_out2, _rx_out2) = mpsc::channel(100);
+        let db = status::Db::default();
+
+        let (_, _, _, emu, tx_stop) = Emulator {
+            db: db.clone(),
+            ..Emulator::new(
+                vec![(IN.into(), rx_in)],
+                vec![(OUT1.into(), tx_out1), (OUT2.into(), tx_out2)],
+            )
+        }
+        .launch(cfg)
+        .await
+        .unwrap();
+
+        // The second expression can't compute `not` of an integer
+        // so it never sets its output.
+
+        assert!(tx_in.send(device::Value::Int(9)).await.is_ok());
+
+        let (value, rpy) =
+            time::timeout(Duration::from_millis(100), rx_out1.recv())
+                .await
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(value, device::Value::Bool(true));
+        let _ = rpy.send(Ok(value));
+
+        time::sleep(Duration::from_millis(50)).await;
+
+        let info = db.get("test").unwrap();
+
+        assert_eq!(db.get_all(), vec![info.clone()]);
+        assert_eq!(
+            info.variables,
+            vec![
+                status::Variable {
+                    name: "in".into(),
+                    device: Some(device::Name::create(IN).unwrap()),
+                    value: Some(device::Value::Int(9)),
+                },
+                status::Variable {
+                    name: "big".into(),
+                    device: None,
+                    value: Some(device::Value::Bool(true)),
+                }
+            ]
+        );
+        assert_eq!(info.defs, vec!["5 < inp[0] -> inp[1]"]);
+
+        let out1 = info.exprs.iter().find(|e| e.output == "out1").unwrap();
+        let out2 = info.exprs.iter().find(|e| e.output == "out2").unwrap();
+
+        assert_eq!(out1.device, device::Name::create(OUT1).unwrap());
+        assert!(out1.program.starts_with("inp[1] -> out["));
+        assert_eq!(out1.last, Some(device::Value::Bool(true)));
+        assert_eq!(out1.errors, 0);
+        assert!(out2.program.starts_with("not inp[0] -> out["));
+        assert_eq!(out2.last, None);
+        assert_eq!(out2.errors, 1);
+
+        // When the node stops, its status is removed.
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+        assert_eq!(db.get("test"), None);
+    }
+
+    // Test that the enable device stops and restarts the outputs.
+
+    #[tokio::test]
+    async fn test_enable_device() {
+        const IN: &str = "device:in";
+        const OUT: &str = "device:out";
+
+        async fn set(tx: &driver::TxDeviceSetting, v: bool) {
+            let (tx_rpy, rx_rpy) = oneshot::channel();
+
+            assert!(tx.send((v.into(), tx_rpy)).await.is_ok());
+            assert_eq!(rx_rpy.await.unwrap(), Ok(v.into()));
+        }
+
+        let mut cfg = build_config(
+            &[("in", IN)],
+            &[("out", OUT)],
+            &[],
+            &["{in} -> {out}"],
+        );
+
+        cfg.enable_device = true;
+
+        let (tx_in, rx_in) = mpsc::channel(100);
+        let (tx_out, mut rx_out) = mpsc::channel(100);
+        let (tx_set, rx_set) = mpsc::channel(100);
+
+        // The backend says the node was disabled, so it starts
+        // disabled.
+
+        let (_, _, _, emu, tx_stop) = Emulator {
+            enable: Some((rx_set, Some(false.into()))),
+            ..Emulator::new(
+                vec![(IN.into(), rx_in)],
+                vec![(OUT.into(), tx_out)],
+            )
+        }
+        .launch(cfg)
+        .await
+        .unwrap();
+
+        assert!(tx_in.send(device::Value::Int(1)).await.is_ok());
+        assert!(time::timeout(Duration::from_millis(100), rx_out.recv())
+            .await
+            .is_err());
+
+        // Enabling the node sends the current value.
+
+        set(&tx_set, true).await;
+
+        let (value, rpy) =
+            time::timeout(Duration::from_millis(100), rx_out.recv())
+                .await
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(value, device::Value::Int(1));
+        let _ = rpy.send(Ok(value));
+
+        // While disabled, inputs are tracked but nothing is sent.
+
+        set(&tx_set, false).await;
+
+        assert!(tx_in.send(device::Value::Int(2)).await.is_ok());
+        assert!(time::timeout(Duration::from_millis(100), rx_out.recv())
+            .await
+            .is_err());
+
+        set(&tx_set, true).await;
+
+        let (value, rpy) =
+            time::timeout(Duration::from_millis(100), rx_out.recv())
+                .await
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(value, device::Value::Int(2));
+        let _ = rpy.send(Ok(value));
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+    }
+
+    // Test that published definitions are reported to their
+    // devices when their values change.
+
+    #[tokio::test]
+    async fn test_published_defs() {
+        const IN: &str = "device:in";
+        const OUT: &str = "device:out";
+
+        let mut cfg = build_config(
+            &[("in", IN)],
+            &[("out", OUT)],
+            &[("big", "{in} > 5"), ("double", "{in} * 2")],
+            &["{big} -> {out}"],
+        );
+
+        cfg.publish = vec!["big".into()];
+
+        let (tx_in, rx_in) = mpsc::channel(100);
+        let (tx_out, mut rx_out) = mpsc::channel(100);
+        let (tx_rpt, mut rx_rpt) = mpsc::channel(100);
+
+        let (_, _, _, emu, tx_stop) = Emulator {
+            reports: Some(tx_rpt),
+            ..Emulator::new(
+                vec![(IN.into(), rx_in)],
+                vec![(OUT.into(), tx_out)],
+            )
+        }
+        .launch(cfg)
+        .await
+        .unwrap();
+
+        let big = device::Name::create("logic:test:big").unwrap();
+
+        assert!(tx_in.send(device::Value::Int(1)).await.is_ok());
+
+        let (value, rpy) = rx_out.recv().await.unwrap();
+        let _ = rpy.send(Ok(value));
+
+        assert_eq!(
+            rx_rpt.recv().await.unwrap(),
+            (big.clone(), device::Value::Bool(false))
+        );
+
+        // The definition didn't change so nothing is reported.
+
+        assert!(tx_in.send(device::Value::Int(2)).await.is_ok());
+        assert!(time::timeout(Duration::from_millis(100), rx_rpt.recv())
+            .await
+            .is_err());
+
+        assert!(tx_in.send(device::Value::Int(9)).await.is_ok());
+
+        let (value, rpy) = rx_out.recv().await.unwrap();
+        let _ = rpy.send(Ok(value));
+
+        assert_eq!(
+            rx_rpt.recv().await.unwrap(),
+            (big, device::Value::Bool(true))
+        );
+
+        let _ = tx_stop.send(());
+
+        assert_eq!(emu.await.unwrap(), Ok(true));
+    }
 }
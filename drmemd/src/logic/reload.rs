@@ -0,0 +1,336 @@
+// Logic nodes can be changed without restarting `drmemd`. This module
+// starts the nodes described by the `[[logic]]` sections of the
+// configuration and then watches the configuration file. When the
+// file changes, its `logic` array is compared with the running
+// nodes: nodes whose sections were removed are stopped, new sections
+// are started and nodes whose sections changed are replaced. A
+// replacement only happens once the new section has compiled; if it
+// doesn't, the running version is kept. Otherwise the running
+// version is stopped before the new one starts, so the two versions
+// never send to the same outputs.
+//
+// Only the `logic` array is reloaded. Drivers, calendars and the
+// rest of the configuration still require a restart.
+
+use super::{calendar, lunar, solar, status, tod, Node};
+use crate::config;
+use drmem_api::{client, driver, Result};
+use futures::future::pending;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+use tokio::{
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+    time,
+};
+use tracing::{error, info, info_span, warn};
+use tracing_futures::Instrument;
+
+// How often the configuration file is checked for changes.
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// The channels and calendars that every logic node is started with.
+
+pub struct Shared {
+    pub c_req: client::RequestChan,
+    pub d_req: mpsc::Sender<driver::Request>,
+    pub tx_tod: broadcast::Sender<tod::Info>,
+    pub tx_solar: broadcast::Sender<solar::Info>,
+    pub tx_lunar: broadcast::Sender<lunar::Info>,
+    pub cal: calendar::Info,
+    pub db: status::Db,
+}
+
+// A node started from a `[[logic]]` section. If the section couldn't
+// be started, `task` is `None`. Dropping the entry stops the node.
+
+struct Running {
+    cfg: config::Logic,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Running {
+    // Stops the node and waits for its task to end.
+
+    async fn stop(mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort()
+        }
+    }
+}
+
+// Compares the running nodes with the `[[logic]]` sections. Returns
+// the names of the nodes that need to be stopped and the sections
+// that need to be started, either because they're new or because
+// they changed.
+
+fn changes<'a>(
+    nodes: &HashMap<String, Running>,
+    cfgs: &'a [config::Logic],
+) -> (Vec<String>, Vec<&'a config::Logic>) {
+    let names: HashSet<&str> = cfgs.iter().map(|c| c.name.as_str()).collect();
+    let mut removed: Vec<String> = nodes
+        .keys()
+        .filter(|name| !names.contains(name.as_str()))
+        .cloned()
+        .collect();
+
+    removed.sort();
+
+    let started = cfgs
+        .iter()
+        .filter(|cfg| {
+            nodes.get(&cfg.name).map(|n| &n.cfg != *cfg).unwrap_or(true)
+        })
+        .collect();
+
+    (removed, started)
+}
+
+// Initializes a node. The node doesn't run until it's passed to
+// `run()`. The node subscribes to the time, solar and lunar channels
+// when this is called, rather than when the future is first polled,
+// so it doesn't miss the values sent in between.
+
+pub(super) fn init(
+    shared: &Shared,
+    cfg: config::Logic,
+) -> impl Future<Output = Result<Node>> {
+    let name = cfg.name.clone();
+
+    Node::init(
+        shared.c_req.clone(),
+        shared.d_req.clone(),
+        shared.tx_tod.subscribe(),
+        shared.tx_solar.subscribe(),
+        shared.tx_lunar.subscribe(),
+        shared.cal.clone(),
+        cfg,
+    )
+    .instrument(info_span!("logic-init", name = &name))
+}
+
+// Runs an initialized node. The node's status is available while it
+// runs. When the future completes, or is dropped, the entry is
+// removed. It only completes if the node fails.
+
+pub(super) async fn run(db: &status::Db, node: Node) -> Result<Infallible> {
+    let _entry = db.register(node.status());
+
+    node.run().await
+}
+
+// Runs an initialized node in a new task.
+
+fn launch(shared: &Shared, name: String, node: Node) -> JoinHandle<()> {
+    let db = shared.db.clone();
+
+    tokio::spawn(
+        async move {
+            let Err(e) = run(&db, node).await;
+
+            error!("node stopped -- {}", &e)
+        }
+        .instrument(info_span!("logic", name)),
+    )
+}
+
+// Brings the running nodes in line with the `[[logic]]` sections.
+
+async fn update(
+    shared: &Shared,
+    nodes: &mut HashMap<String, Running>,
+    cfgs: Vec<config::Logic>,
+) {
+    let (removed, started) = changes(nodes, &cfgs);
+
+    for name in removed {
+        info!("stopping logic node '{}'", &name);
+        if let Some(node) = nodes.remove(&name) {
+            node.stop().await
+        }
+    }
+
+    for cfg in started {
+        let running = nodes.get(&cfg.name).is_some_and(|n| n.task.is_some());
+
+        match init(shared, cfg.clone()).await {
+            Ok(node) => {
+                // The new version compiled, so stop the previous
+                // version before the new one runs.
+
+                if let Some(prev) = nodes.remove(&cfg.name) {
+                    prev.stop().await
+                }
+
+                if running {
+                    info!("restarted logic node '{}'", &cfg.name)
+                }
+
+                nodes.insert(
+                    cfg.name.clone(),
+                    Running {
+                        cfg: cfg.clone(),
+                        task: Some(launch(shared, cfg.name.clone(), node)),
+                    },
+                );
+            }
+            Err(e) if running => {
+                error!(
+                    "keeping the running version of logic node '{}' -- {}",
+                    &cfg.name, &e
+                )
+            }
+            Err(e) => {
+                error!("couldn't start logic node '{}' -- {}", &cfg.name, &e);
+                nodes.insert(
+                    cfg.name.clone(),
+                    Running {
+                        cfg: cfg.clone(),
+                        task: None,
+                    },
+                );
+            }
+        }
+    }
+}
+
+// Returns the time the file was last modified.
+
+async fn modified(file: &str) -> Option<SystemTime> {
+    tokio::fs::metadata(file)
+        .await
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+// Starts the logic nodes. If the configuration was read from `file`,
+// the task reloads the `logic` array whenever the file changes.
+
+pub fn start(
+    shared: Shared,
+    cfgs: Vec<config::Logic>,
+    file: Option<String>,
+) -> JoinHandle<Result<Infallible>> {
+    tokio::spawn(async move {
+        let mut nodes = HashMap::new();
+
+        update(&shared, &mut nodes, cfgs).await;
+
+        let Some(file) = file else {
+            return pending().await;
+        };
+        let mut last = modified(&file).await;
+        let mut interval = time::interval(POLL_INTERVAL);
+
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            let current = modified(&file).await;
+
+            if current.is_none() || current == last {
+                continue;
+            }
+            last = current;
+
+            info!("'{}' changed -- reloading logic nodes", &file);
+
+            match config::read(&file).await {
+                Ok(cfg) => update(&shared, &mut nodes, cfg.logic).await,
+                Err(e) => warn!("ignoring changes to '{}' -- {}", &file, &e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logic(name: &str, expr: &str) -> config::Logic {
+        config::Logic {
+            name: name.into(),
+            summary: None,
+            defs: HashMap::new(),
+            exprs: vec![expr.into()],
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            timezone: None,
+            enable_device: false,
+            publish: vec![],
+        }
+    }
+
+    fn running(cfgs: &[config::Logic]) -> HashMap<String, Running> {
+        cfgs.iter()
+            .map(|cfg| {
+                (
+                    cfg.name.clone(),
+                    Running {
+                        cfg: cfg.clone(),
+                        task: None,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_changes() {
+        let a = logic("a", "true -> {out}");
+        let b = logic("b", "false -> {out}");
+        let c = logic("c", "1 -> {out}");
+        let nodes = running(&[a.clone(), b.clone()]);
+
+        // Nothing changed.
+
+        assert_eq!(changes(&nodes, &[b.clone(), a.clone()]), (vec![], vec![]));
+
+        // Sections that are added or removed.
+
+        assert_eq!(
+            changes(&nodes, &[a.clone(), b.clone(), c.clone()]),
+            (vec![], vec![&c])
+        );
+        assert_eq!(
+            changes(&nodes, std::slice::from_ref(&b)),
+            (vec!["a".into()], vec![])
+        );
+        assert_eq!(
+            changes(&nodes, &[]),
+            (vec!["a".into(), "b".into()], vec![])
+        );
+
+        // A section that changed is started again.
+
+        let mut b2 = b.clone();
+
+        b2.timezone = Some("Europe/Paris".into());
+
+        assert_eq!(
+            changes(&nodes, &[a.clone(), b2.clone()]),
+            (vec![], vec![&b2])
+        );
+
+        let b3 = logic("b", "true -> {out}");
+
+        assert_eq!(
+            changes(&nodes, &[b3.clone(), c.clone()]),
+            (vec!["a".into()], vec![&b3, &c])
+        );
+    }
+}
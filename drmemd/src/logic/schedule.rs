@@ -0,0 +1,451 @@
+// Schedules let logic expressions be true at times given in the
+// format used by cron: five fields giving the minute, hour, day of
+// the month, month and day of the week. For instance, "30 7 * * 1-5"
+// is 7:30 on weekdays. Each field is "*", a value, a range of values
+// ("1-5") or a list of them ("1,3,5"). "*" and ranges can be followed
+// by a step ("*/15" is every 15 minutes.) Months and days of the week
+// can be given by name ("JAN", "MON-FRI".) The day of the week is 0
+// (or 7) for Sunday through 6 for Saturday.
+//
+// Like cron, if both the day of the month and the day of the week
+// are restricted, a day matches when either one does.
+//
+// The fields are matched against the local time. A schedule in the
+// hour skipped when the clocks spring forward doesn't match that day
+// and one in the repeated hour, when they fall back, matches twice.
+
+use super::tod;
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime,
+    TimeZone, Timelike, Utc,
+};
+use drmem_api::{Error, Result};
+use std::fmt;
+
+const MONTHS: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT",
+    "NOV", "DEC",
+];
+const DAYS: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+// Searches for the next matching minute give up after this many
+// years. It's long enough to find Feb 29th.
+
+const SEARCH_YEARS: i32 = 8;
+
+// The set of values a field accepts, stored as a bit mask. `star` is
+// true when the field starts with "*", which affects how the day
+// fields are combined.
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    bits: u64,
+    star: bool,
+}
+
+impl Field {
+    fn has(&self, v: u32) -> bool {
+        self.bits & (1 << v) != 0
+    }
+
+    // Parses a field whose values go from `lo` to `hi`. If `names`
+    // isn't empty, its entries can be used in place of the values,
+    // starting with `lo`.
+
+    fn parse(s: &str, lo: u32, hi: u32, names: &[&str]) -> Option<Field> {
+        let value = |s: &str| -> Option<u32> {
+            names
+                .iter()
+                .position(|n| n.eq_ignore_ascii_case(s))
+                .map(|idx| idx as u32 + lo)
+                .or_else(|| s.parse().ok())
+                .filter(|v| (lo..=hi).contains(v))
+        };
+        let mut bits = 0u64;
+
+        for item in s.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => {
+                    (range, step.parse::<u32>().ok().filter(|v| *v > 0)?)
+                }
+                None => (item, 1),
+            };
+            let (first, last) = match range.split_once('-') {
+                _ if range == "*" => (lo, hi),
+                Some((a, b)) => (value(a)?, value(b)?),
+
+                // A single value with a step runs to the end of the
+                // field's range.
+                None if step > 1 => (value(range)?, hi),
+                None => (value(range)?, value(range)?),
+            };
+
+            if first > last {
+                return None;
+            }
+
+            bits |= (first..=last)
+                .step_by(step as usize)
+                .fold(0, |acc, v| acc | (1 << v));
+        }
+
+        Some(Field {
+            bits,
+            star: s.starts_with('*'),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    text: String,
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+}
+
+impl Schedule {
+    // Parses a schedule. Besides the five fields, the shortcuts
+    // "@yearly" (or "@annually"), "@monthly", "@weekly", "@daily" (or
+    // "@midnight") and "@hourly" are accepted.
+
+    pub fn parse(s: &str) -> Result<Schedule> {
+        let err = |msg: &str| {
+            Error::ParseError(format!("schedule \"{}\" {}", s, msg))
+        };
+        let spec = match s.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            spec => spec,
+        };
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(err("needs 5 fields"));
+        };
+        let field = |name: &str, s: &str, lo, hi, names| {
+            Field::parse(s, lo, hi, names).ok_or_else(|| {
+                err(&format!("has a bad {} field '{}'", name, s))
+            })
+        };
+        let mut weekdays = field("day of week", weekdays, 0, 7, DAYS)?;
+
+        // Sunday can be given as 0 or 7.
+
+        if weekdays.has(7) {
+            weekdays.bits |= 1
+        }
+
+        let sched = Schedule {
+            text: s.into(),
+            minutes: field("minute", minutes, 0, 59, &[])?,
+            hours: field("hour", hours, 0, 23, &[])?,
+            days: field("day of month", days, 1, 31, &[])?,
+            months: field("month", months, 1, 12, MONTHS)?,
+            weekdays,
+        };
+
+        // Reject schedules that name dates which don't exist, like
+        // February 30th.
+
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .unwrap();
+
+        if sched.next_match(start).is_none() {
+            return Err(err("never matches"));
+        }
+        Ok(sched)
+    }
+
+    fn day_matches(&self, t: &NaiveDateTime) -> bool {
+        let dom = self.days.has(t.day());
+        let dow = self.weekdays.has(t.weekday().num_days_from_sunday());
+
+        if self.days.star || self.weekdays.star {
+            dom && dow
+        } else {
+            dom || dow
+        }
+    }
+
+    // Returns `true` if the local time is in a minute given by the
+    // schedule.
+
+    pub fn matches(&self, t: &NaiveDateTime) -> bool {
+        self.months.has(t.month())
+            && self.day_matches(t)
+            && self.hours.has(t.hour())
+            && self.minutes.has(t.minute())
+    }
+
+    // Returns the first minute, starting with `t`, that matches the
+    // schedule. Rather than step through each minute, it skips over
+    // the months, days and hours that can't match.
+
+    fn next_match(&self, t: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = t.date().year() + SEARCH_YEARS;
+        let mut t = t.with_second(0)?.with_nanosecond(0)?;
+
+        while t.year() < limit {
+            let date = t.date();
+
+            t = if !self.months.has(t.month()) {
+                let (yr, mo) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    mo => (t.year(), mo + 1),
+                };
+
+                NaiveDate::from_ymd_opt(yr, mo, 1)?.and_hms_opt(0, 0, 0)?
+            } else if !self.day_matches(&t) {
+                date.succ_opt()?.and_hms_opt(0, 0, 0)?
+            } else if !self.hours.has(t.hour()) {
+                t.with_minute(0)? + Duration::hours(1)
+            } else if !self.minutes.has(t.minute()) {
+                t + Duration::minutes(1)
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    // Returns the time, after `now`, at which the result of the
+    // schedule changes. While the current minute matches, that's the
+    // start of the next minute. Otherwise it's the start of the next
+    // minute that matches.
+
+    pub fn next_change(
+        &self,
+        now: &DateTime<Utc>,
+        tz: tod::Zone,
+    ) -> Option<DateTime<Utc>> {
+        let local = tod::at(*now, tz).1.naive_local();
+
+        if self.matches(&local) {
+            return Some(
+                now.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1),
+            );
+        }
+
+        // Start looking an hour back so, if the clocks are about to
+        // fall back, the repeated times are found. Times that have
+        // passed are skipped.
+
+        let mut start = local - Duration::hours(1);
+
+        loop {
+            let t = self.next_match(start)?;
+            let found = match tz {
+                Some(tz) => tz.from_local_datetime(&t).map(|v| v.to_utc()),
+                None => {
+                    chrono::Local.from_local_datetime(&t).map(|v| v.to_utc())
+                }
+            };
+
+            match found {
+                LocalResult::Single(a) if a > *now => return Some(a),
+                LocalResult::Ambiguous(a, _) if a > *now => return Some(a),
+                LocalResult::Ambiguous(_, b) if b > *now => return Some(b),
+                _ => start = t + Duration::minutes(1),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+
+    fn local(s: &str) -> NaiveDateTime {
+        s.parse().unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(Schedule::parse("30 7 * * MON-FRI").is_ok());
+        assert!(Schedule::parse("*/15 * * * *").is_ok());
+        assert!(Schedule::parse("0 8-18/2 1,15 jan,jul *").is_ok());
+        assert!(Schedule::parse("0 0 29 2 *").is_ok());
+        assert!(Schedule::parse("0 12 * * 7").is_ok());
+        assert!(Schedule::parse("@daily").is_ok());
+        assert!(Schedule::parse("@hourly").is_ok());
+
+        assert!(Schedule::parse("").is_err());
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("* * * * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("* 24 * * *").is_err());
+        assert!(Schedule::parse("* * 0 * *").is_err());
+        assert!(Schedule::parse("* * * 13 *").is_err());
+        assert!(Schedule::parse("* * * * 8").is_err());
+        assert!(Schedule::parse("* * * * FUN").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("1,,2 * * * *").is_err());
+        assert!(Schedule::parse("@sometimes").is_err());
+        assert!(Schedule::parse("0 0 30 2 *").is_err());
+        assert!(Schedule::parse("0 0 31 4,6 *").is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        let s = Schedule::parse("30 7 * * MON-FRI").unwrap();
+
+        // 2024-06-03 is a Monday.
+
+        assert!(s.matches(&local("2024-06-03T07:30:00")));
+        assert!(s.matches(&local("2024-06-03T07:30:59")));
+        assert!(!s.matches(&local("2024-06-03T07:31:00")));
+        assert!(!s.matches(&local("2024-06-03T08:30:00")));
+        assert!(s.matches(&local("2024-06-07T07:30:00")));
+        assert!(!s.matches(&local("2024-06-08T07:30:00")));
+        assert!(!s.matches(&local("2024-06-09T07:30:00")));
+
+        let s = Schedule::parse("*/20 9-17/4 * * *").unwrap();
+
+        assert!(s.matches(&local("2024-06-08T09:00:00")));
+        assert!(s.matches(&local("2024-06-08T13:40:00")));
+        assert!(s.matches(&local("2024-06-08T17:20:00")));
+        assert!(!s.matches(&local("2024-06-08T09:10:00")));
+        assert!(!s.matches(&local("2024-06-08T10:00:00")));
+        assert!(!s.matches(&local("2024-06-08T21:00:00")));
+
+        // Sunday is 0 or 7.
+
+        let s = Schedule::parse("0 12 * * 7").unwrap();
+
+        assert!(s.matches(&local("2024-06-09T12:00:00")));
+        assert!(!s.matches(&local("2024-06-10T12:00:00")));
+
+        // When both day fields are restricted, either can match.
+
+        let s = Schedule::parse("0 0 13 * FRI").unwrap();
+
+        assert!(s.matches(&local("2024-06-13T00:00:00")));
+        assert!(s.matches(&local("2024-06-14T00:00:00")));
+        assert!(!s.matches(&local("2024-06-15T00:00:00")));
+
+        // When one of them is "*", both have to match.
+
+        let s = Schedule::parse("0 0 1-7 * */7").unwrap();
+
+        assert!(s.matches(&local("2024-06-02T00:00:00")));
+        assert!(!s.matches(&local("2024-06-03T00:00:00")));
+        assert!(!s.matches(&local("2024-06-09T00:00:00")));
+
+        let s = Schedule::parse("0 0 1 JAN,jul *").unwrap();
+
+        assert!(s.matches(&local("2024-01-01T00:00:00")));
+        assert!(s.matches(&local("2024-07-01T00:00:00")));
+        assert!(!s.matches(&local("2024-06-01T00:00:00")));
+    }
+
+    #[test]
+    fn test_next_change() {
+        let tz = Some(chrono_tz::UTC);
+        let s = Schedule::parse("30 7 * * MON-FRI").unwrap();
+
+        // Before the schedule matches, the change is at the start of
+        // the next matching minute. While it matches, the change is
+        // at the end of the minute.
+
+        assert_eq!(
+            s.next_change(&utc("2024-06-03T06:00:00Z"), tz),
+            Some(utc("2024-06-03T07:30:00Z"))
+        );
+        assert_eq!(
+            s.next_change(&utc("2024-06-03T07:30:15.5Z"), tz),
+            Some(utc("2024-06-03T07:31:00Z"))
+        );
+        assert_eq!(
+            s.next_change(&utc("2024-06-03T07:31:00Z"), tz),
+            Some(utc("2024-06-04T07:30:00Z"))
+        );
+        assert_eq!(
+            s.next_change(&utc("2024-06-07T08:00:00Z"), tz),
+            Some(utc("2024-06-10T07:30:00Z"))
+        );
+
+        let s = Schedule::parse("0 0 29 2 *").unwrap();
+
+        assert_eq!(
+            s.next_change(&utc("2024-03-01T00:00:00Z"), tz),
+            Some(utc("2028-02-29T00:00:00Z"))
+        );
+
+        let s = Schedule::parse("0 0 31 * *").unwrap();
+
+        assert_eq!(
+            s.next_change(&utc("2024-04-01T00:00:00Z"), tz),
+            Some(utc("2024-05-31T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_time_zones() {
+        let tz = Some(New_York);
+        let s = Schedule::parse("30 7 * * *").unwrap();
+
+        // The schedule uses the local time of the zone.
+
+        assert_eq!(
+            s.next_change(&utc("2024-06-03T00:00:00Z"), tz),
+            Some(utc("2024-06-03T11:30:00Z"))
+        );
+        assert_eq!(
+            s.next_change(&utc("2024-12-03T00:00:00Z"), tz),
+            Some(utc("2024-12-03T12:30:00Z"))
+        );
+
+        // Across the change to daylight saving time, the next
+        // firing is still at 7:30 local time.
+
+        assert_eq!(
+            s.next_change(&utc("2024-03-09T13:00:00Z"), tz),
+            Some(utc("2024-03-10T11:30:00Z"))
+        );
+
+        // 2:30 is skipped when the clocks spring forward.
+
+        let s = Schedule::parse("30 2 * * *").unwrap();
+
+        assert_eq!(
+            s.next_change(&utc("2024-03-10T06:00:00Z"), tz),
+            Some(utc("2024-03-11T06:30:00Z"))
+        );
+
+        // 1:30 happens twice when the clocks fall back.
+
+        let s = Schedule::parse("30 1 * * *").unwrap();
+
+        assert_eq!(
+            s.next_change(&utc("2024-11-03T05:00:00Z"), tz),
+            Some(utc("2024-11-03T05:30:00Z"))
+        );
+        assert_eq!(
+            s.next_change(&utc("2024-11-03T05:31:00Z"), tz),
+            Some(utc("2024-11-03T06:30:00Z"))
+        );
+        assert_eq!(
+            s.next_change(&utc("2024-11-03T06:31:00Z"), tz),
+            Some(utc("2024-11-04T06:30:00Z"))
+        );
+    }
+}
@@ -0,0 +1,615 @@
+// Runs a logic block against a recorded trace of its inputs, without
+// touching any driver. The block is compiled like a running node
+// but, instead of waiting for readings and the system clock, it's
+// driven by a virtual clock which steps through the trace. The
+// time-of-day, solar and lunar values are computed for the virtual
+// time. The values that the block would have sent to its outputs
+// are returned.
+//
+// A trace is a CSV file. Each line holds a reading:
+//
+//     TIME,DEVICE,VALUE
+//
+// TIME is in RFC 3339 format (e.g. "2024-06-01T06:00:00Z"), DEVICE is
+// the name of an input device and VALUE is written like a literal in
+// a logic expression (true, 12, 1.5, "text", #ff8000.) Blank lines
+// and lines starting with '#' are ignored. The readings don't need
+// to be in order.
+
+use super::{calendar, compile, lunar, solar, tod, Node};
+use crate::config;
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use drmem_api::{client, device, Error, Result};
+use std::{collections::HashMap, fmt, time::SystemTime};
+use tokio::sync::{broadcast, mpsc};
+
+// A reading from the trace.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub time: DateTime<Utc>,
+    pub device: device::Name,
+    pub value: device::Value,
+}
+
+// A value that the logic block would have sent to an output device.
+// It's displayed in the format of a trace record.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sent {
+    pub time: DateTime<Utc>,
+    pub device: device::Name,
+    pub value: device::Value,
+}
+
+impl fmt::Display for Sent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{}",
+            self.time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            &self.device,
+            &self.value
+        )
+    }
+}
+
+// Parses a value written like a literal in a logic expression.
+
+fn parse_value(s: &str) -> Result<device::Value> {
+    let outputs = [String::from("v")];
+    let env = (&[][..], &outputs[..]);
+
+    match compile::Program::compile(&format!("{} -> {{v}}", s), &env)
+        .map(compile::Program::optimize)
+    {
+        Ok(compile::Program(compile::Expr::Lit(v), _)) => Ok(v),
+        _ => Err(Error::ParseError(format!("'{}' isn't a value", s))),
+    }
+}
+
+// Parses the contents of a trace file.
+
+pub fn parse_trace(s: &str) -> Result<Vec<Record>> {
+    s.lines()
+        .enumerate()
+        .map(|(n, line)| (n + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(n, line)| {
+            let err =
+                |msg: String| Error::ParseError(format!("line {}: {}", n, msg));
+            let mut fields = line.splitn(3, ',').map(str::trim);
+
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(time), Some(dev), Some(value)) => Ok(Record {
+                    time: DateTime::parse_from_rfc3339(time)
+                        .map_err(|e| {
+                            err(format!("bad time '{}' : {}", time, e))
+                        })?
+                        .with_timezone(&Utc),
+                    device: device::Name::create(dev).map_err(|_| {
+                        err(format!("bad device name '{}'", dev))
+                    })?,
+                    value: parse_value(value)
+                        .map_err(|e| err(e.to_string()))?,
+                }),
+                _ => Err(err("expected TIME,DEVICE,VALUE".into())),
+            }
+        })
+        .collect()
+}
+
+// Answers the requests that a node makes while it initializes. The
+// inputs get streams which never yield, since their readings come
+// from the trace, and the outputs get setting channels which are
+// never used. The type of each input is taken from its first
+// reading.
+
+async fn serve(
+    mut rx_req: mpsc::Receiver<client::Request>,
+    first: HashMap<device::Name, Record>,
+) {
+    while let Some(req) = rx_req.recv().await {
+        match req {
+            client::Request::QueryDeviceInfo { pattern, rpy_chan } => {
+                let _ = rpy_chan.send(
+                    pattern
+                        .and_then(|p| device::Name::create(&p).ok())
+                        .and_then(|name| first.get(&name))
+                        .map(|rec| {
+                            vec![client::DevInfoReply {
+                                name: rec.device.clone(),
+                                units: None,
+                                settable: false,
+                                total_points: 1,
+                                first_point: None,
+                                last_point: Some(device::Reading {
+                                    ts: SystemTime::from(rec.time),
+                                    value: rec.value.clone(),
+                                }),
+                                driver: "simulation".into(),
+                            }]
+                        })
+                        .ok_or(Error::NotFound),
+                );
+            }
+            client::Request::SetDevice { rpy_chan, .. } => {
+                let _ = rpy_chan.send(Err(Error::ProtocolError(
+                    "devices can't be set in a simulation".into(),
+                )));
+            }
+            client::Request::GetSettingChan { rpy_chan, .. } => {
+                let (tx, _) = mpsc::channel(1);
+
+                let _ = rpy_chan.send(Ok(tx));
+            }
+            client::Request::MonitorDevice { rpy_chan, .. } => {
+                let _ = rpy_chan.send(Ok(Box::pin(tokio_stream::pending())
+                    as device::DataStream<device::Reading>));
+            }
+        }
+    }
+}
+
+// Holds the state of a simulation.
+
+struct Simulation {
+    node: Node,
+    lat: f64,
+    long: f64,
+    time: tod::Info,
+    devices: Vec<device::Name>,
+    prev: Vec<Option<device::Value>>,
+    sent: Vec<Sent>,
+}
+
+impl Simulation {
+    // Moves the time-of-day to `now`. Like a running node, which
+    // only receives the time-of-day through its `TimeFilter`, the
+    // time that the expressions see only changes when the field they
+    // use changes. Returns `true` if it changed.
+
+    fn set_time(&mut self, now: DateTime<Utc>) -> bool {
+        let time = tod::at(now, self.node.tz);
+
+        match self.node.time_ch.as_mut() {
+            Some(ch) => {
+                let changed = ch.accept(&time);
+
+                if changed {
+                    self.time = time
+                }
+                changed
+            }
+            None => {
+                self.time = time;
+                false
+            }
+        }
+    }
+
+    // Evaluates the node at the given time. Like `Output`, a value is
+    // only sent when it's different from the previous one.
+
+    fn step(&mut self, now: DateTime<Utc>) {
+        // Timer functions need the exact time.
+
+        if self.node.uses_timers {
+            self.time = tod::at(now, self.node.tz)
+        }
+
+        let time = self.time.clone();
+        let mut sky = compile::Sky::default();
+
+        if self.node.solar_ch.is_some() {
+            sky.solar =
+                Some(solar::get_solar_position(self.lat, self.long, &now))
+        }
+        if self.node.lunar_ch.is_some() {
+            sky.lunar =
+                Some(lunar::get_lunar_position(self.lat, self.long, &now))
+        }
+
+        self.node.compute_defs(&time, &sky);
+
+        let results = self.node.compute_exprs(&time, &sky);
+
+        for ((v, prev), device) in
+            results.into_iter().zip(&mut self.prev).zip(&self.devices)
+        {
+            if let Some(v) = v {
+                if prev.as_ref() != Some(&v) {
+                    self.sent.push(Sent {
+                        time: now,
+                        device: device.clone(),
+                        value: v.clone(),
+                    });
+                    *prev = Some(v)
+                }
+            }
+        }
+    }
+}
+
+// Returns the first multiple of `tick` after `now`.
+
+fn next_tick(now: &DateTime<Utc>, tick: TimeDelta) -> Option<DateTime<Utc>> {
+    let secs = tick.num_seconds();
+
+    DateTime::from_timestamp((now.timestamp().div_euclid(secs) + 1) * secs, 0)
+}
+
+// Runs the logic block against the trace and returns the values it
+// would have sent. `lat` and `long` are used to compute the solar
+// and lunar values. The block's enable device and published
+// definitions aren't registered.
+
+pub async fn simulate(
+    mut cfg: config::Logic,
+    cal: calendar::Info,
+    lat: f64,
+    long: f64,
+    mut trace: Vec<Record>,
+) -> Result<Vec<Sent>> {
+    cfg.enable_device = false;
+    cfg.publish.clear();
+
+    trace.sort_by_key(|rec| rec.time);
+
+    let mut first = HashMap::new();
+
+    for rec in &trace {
+        first
+            .entry(rec.device.clone())
+            .or_insert_with(|| rec.clone());
+    }
+
+    // Initialize the node. The channels which normally provide the
+    // time-of-day, solar and lunar information are never used.
+
+    let (tx_req, rx_req) = mpsc::channel(10);
+    let (d_req, _) = mpsc::channel(1);
+    let (tx_tod, _) = broadcast::channel(1);
+    let (tx_solar, _) = broadcast::channel(1);
+    let (tx_lunar, _) = broadcast::channel(1);
+
+    tokio::spawn(serve(rx_req, first));
+
+    let node = Node::init(
+        client::RequestChan::new(tx_req),
+        d_req,
+        tx_tod.subscribe(),
+        tx_solar.subscribe(),
+        tx_lunar.subscribe(),
+        cal,
+        cfg,
+    )
+    .await?;
+
+    // Find the input cell of each device and the device of each
+    // expression.
+
+    let (slots, devices): (HashMap<device::Name, usize>, Vec<device::Name>) = {
+        let status = node.status.lock().unwrap();
+
+        (
+            status
+                .variables
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, v)| v.device.clone().map(|d| (d, idx)))
+                .collect(),
+            status.exprs.iter().map(|e| e.device.clone()).collect(),
+        )
+    };
+
+    // Readings of devices that the block doesn't use would be
+    // ignored, which hides misspelled device names.
+
+    if let Some(rec) = trace.iter().find(|rec| !slots.contains_key(&rec.device))
+    {
+        return Err(Error::ParseError(format!(
+            "'{}' isn't an input of the logic block",
+            &rec.device
+        )));
+    }
+
+    // Between readings, the node is woken up when a running node
+    // would be: when the time-of-day field it uses changes, every 15
+    // seconds, when the solar and lunar information is updated, if
+    // it uses them, and when a timer expires. The virtual clock
+    // steps through the times at which these can happen.
+
+    let time_tick = node.time_ch.as_ref().map(|ch| ch.period());
+    let sky_tick = (node.solar_ch.is_some() || node.lunar_ch.is_some())
+        .then(|| TimeDelta::seconds(15));
+
+    let Some(mut now) = trace.first().map(|rec| rec.time) else {
+        return Ok(vec![]);
+    };
+    let time = tod::at(now, node.tz);
+    let mut sim = Simulation {
+        node,
+        lat,
+        long,
+        time,
+        prev: vec![None; devices.len()],
+        devices,
+        sent: vec![],
+    };
+
+    for rec in trace {
+        // Step through the ticks and timer deadlines which occur
+        // before the reading.
+
+        loop {
+            let deadline = sim.node.next_deadline(&now);
+            let Some(t) = [time_tick, sky_tick]
+                .into_iter()
+                .flatten()
+                .filter_map(|tick| next_tick(&now, tick))
+                .chain(deadline)
+                .min()
+                .filter(|t| *t > now && *t < rec.time)
+            else {
+                break;
+            };
+            let sky_update = sky_tick
+                .is_some_and(|tick| t.timestamp() % tick.num_seconds() == 0);
+
+            now = t;
+            if sim.set_time(now) || sky_update || deadline == Some(now) {
+                sim.step(now)
+            }
+        }
+
+        now = rec.time;
+        sim.set_time(now);
+        if let Some(idx) = slots.get(&rec.device) {
+            sim.node.inputs[*idx] = Some(rec.value)
+        }
+        sim.step(now)
+    }
+    Ok(sim.sent)
+}
+
+// Runs the logic block named `name` against the trace in `file` and
+// prints the values that it would have sent, one per line, in the
+// format of the trace.
+
+pub async fn run(cfg: &config::Config, name: &str, file: &str) -> Result<()> {
+    let logic = cfg.logic.iter().find(|l| l.name == name).ok_or_else(|| {
+        Error::ConfigError(format!("no logic block named '{}'", name))
+    })?;
+    let trace = tokio::fs::read_to_string(file)
+        .await
+        .map_err(|e| {
+            Error::ConfigError(format!("couldn't read '{}' : {}", file, e))
+        })
+        .and_then(|s| parse_trace(&s))?;
+    let cal = calendar::load(&cfg.calendar).await?;
+
+    for sent in
+        simulate(logic.clone(), cal, cfg.latitude, cfg.longitude, trace).await?
+    {
+        println!("{}", sent)
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, h, m, s).unwrap()
+    }
+
+    fn name(s: &str) -> device::Name {
+        device::Name::create(s).unwrap()
+    }
+
+    fn logic(exprs: &[&str]) -> config::Logic {
+        config::Logic {
+            name: "sim".into(),
+            summary: None,
+            defs: HashMap::new(),
+            exprs: exprs.iter().map(|&e| e.into()).collect(),
+            inputs: HashMap::from([("in".into(), name("sensor:in"))]),
+            outputs: HashMap::from([("out".into(), name("light:out"))]),
+            timezone: Some("UTC".into()),
+            enable_device: false,
+            publish: vec![],
+        }
+    }
+
+    fn sent(time: DateTime<Utc>, value: device::Value) -> Sent {
+        Sent {
+            time,
+            device: name("light:out"),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("true"), Ok(device::Value::Bool(true)));
+        assert_eq!(parse_value("-12"), Ok(device::Value::Int(-12)));
+        assert_eq!(parse_value("1.5"), Ok(device::Value::Flt(1.5)));
+        assert_eq!(
+            parse_value("\"a, b\""),
+            Ok(device::Value::Str("a, b".into()))
+        );
+        assert!(matches!(
+            parse_value("#ff8000"),
+            Ok(device::Value::Color(_))
+        ));
+        assert!(parse_value("").is_err());
+        assert!(parse_value("{local:hour}").is_err());
+        assert!(parse_value("1 -> {x}").is_err());
+    }
+
+    #[test]
+    fn test_parse_trace() {
+        assert_eq!(
+            parse_trace(
+                "# A comment.\n\
+                 \n\
+                 2024-06-01T10:00:00Z, sensor:in, 5\n\
+                 2024-06-01T05:00:00-05:00,sensor:in,\"x, y\"\n"
+            ),
+            Ok(vec![
+                Record {
+                    time: at(10, 0, 0),
+                    device: name("sensor:in"),
+                    value: device::Value::Int(5),
+                },
+                Record {
+                    time: at(10, 0, 0),
+                    device: name("sensor:in"),
+                    value: device::Value::Str("x, y".into()),
+                }
+            ])
+        );
+
+        assert!(parse_trace("2024-06-01T10:00:00Z,sensor:in").is_err());
+        assert!(parse_trace("yesterday,sensor:in,5").is_err());
+        assert!(parse_trace("2024-06-01T10:00:00Z,sensor,5").is_err());
+        assert!(parse_trace("2024-06-01T10:00:00Z,sensor:in,five").is_err());
+    }
+
+    #[test]
+    fn test_sent_display() {
+        assert_eq!(
+            sent(at(10, 0, 0), device::Value::Bool(true)).to_string(),
+            "2024-06-01T10:00:00Z,light:out,true"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simulate() {
+        let rec = |time, value| Record {
+            time,
+            device: name("sensor:in"),
+            value: device::Value::Int(value),
+        };
+
+        // Readings are replayed in time order and repeated values
+        // aren't sent again.
+
+        assert_eq!(
+            simulate(
+                logic(&["{in} > 5 -> {out}"]),
+                Default::default(),
+                0.0,
+                0.0,
+                vec![
+                    rec(at(10, 0, 2), 7),
+                    rec(at(10, 0, 0), 1),
+                    rec(at(10, 0, 1), 2),
+                    rec(at(10, 0, 3), 9),
+                    rec(at(10, 0, 4), 3),
+                ]
+            )
+            .await,
+            Ok(vec![
+                sent(at(10, 0, 0), false.into()),
+                sent(at(10, 0, 2), true.into()),
+                sent(at(10, 0, 4), false.into()),
+            ])
+        );
+
+        // Timers run on the virtual clock.
+
+        assert_eq!(
+            simulate(
+                logic(&["held({in} > 5, 10m) -> {out}"]),
+                Default::default(),
+                0.0,
+                0.0,
+                vec![rec(at(10, 0, 0), 7), rec(at(11, 0, 0), 1)]
+            )
+            .await,
+            Ok(vec![
+                sent(at(10, 0, 0), false.into()),
+                sent(at(10, 10, 0), true.into()),
+                sent(at(11, 0, 0), false.into()),
+            ])
+        );
+
+        // Time-of-day values follow the virtual clock.
+
+        assert_eq!(
+            simulate(
+                logic(&["{in} > 5 and {local:hour} >= 12 -> {out}"]),
+                Default::default(),
+                0.0,
+                0.0,
+                vec![rec(at(11, 0, 0), 7), rec(at(13, 0, 0), 7)]
+            )
+            .await,
+            Ok(vec![
+                sent(at(11, 0, 0), false.into()),
+                sent(at(12, 0, 0), true.into()),
+            ])
+        );
+
+        // The node isn't evaluated every second, only when the
+        // time-of-day field it uses changes, so edges are reported
+        // until the next reading or hour.
+
+        assert_eq!(
+            simulate(
+                logic(&["rising({in} > 5) and {local:hour} > 6 -> {out}"]),
+                Default::default(),
+                0.0,
+                0.0,
+                vec![
+                    rec(at(10, 0, 0), 1),
+                    rec(at(10, 30, 0), 7),
+                    rec(at(12, 0, 0), 1),
+                    rec(at(12, 0, 10), 7),
+                ]
+            )
+            .await,
+            Ok(vec![
+                sent(at(10, 0, 0), false.into()),
+                sent(at(10, 30, 0), true.into()),
+                sent(at(11, 0, 0), false.into()),
+                sent(at(12, 0, 10), true.into()),
+            ])
+        );
+
+        // Readings of devices that aren't inputs are rejected.
+
+        assert!(simulate(
+            logic(&["{in} -> {out}"]),
+            Default::default(),
+            0.0,
+            0.0,
+            vec![
+                rec(at(10, 0, 0), 1),
+                Record {
+                    time: at(10, 0, 1),
+                    device: name("sensor:inn"),
+                    value: device::Value::Int(2),
+                }
+            ]
+        )
+        .await
+        .is_err());
+
+        // Unknown inputs are rejected.
+
+        assert!(simulate(
+            logic(&["{other} -> {out}"]),
+            Default::default(),
+            0.0,
+            0.0,
+            vec![]
+        )
+        .await
+        .is_err());
+    }
+}
@@ -2,19 +2,59 @@
 //
 //	https://www.sciencedirect.com/science/article/pii/S0960148121004031
 //
-// in late Feb of 2024.
+// in late Feb of 2024. The times of sunrise, sunset and twilight are
+// computed with the "sunrise equation" found at
+//
+//	https://en.wikipedia.org/wiki/Sunrise_equation
 
 use chrono::{Datelike, Timelike};
 use std::sync::Arc;
 use tokio::{sync::broadcast, time};
-use tracing::{debug, info, info_span, warn};
+use tracing::{debug, info, info_span};
 use tracing_futures::Instrument;
 
+// Altitudes of the center of the sun, in degrees, when the daily
+// events occur. Sunrise and sunset happen when the top edge of the sun
+// appears to touch the horizon, which includes the effect of
+// atmospheric refraction.
+
+const SUNRISE_ALT: f64 = -0.833;
+const CIVIL_ALT: f64 = -6.0;
+const NAUTICAL_ALT: f64 = -12.0;
+const ASTRO_ALT: f64 = -18.0;
+
+// Holds the position of the sun at `time` and the times of the daily
+// events on the site's date at `time` (see `site_date()`.) Events that don't occur on that
+// day (e.g. sunrise during a polar night) are `None`. Dawn is the
+// start of a twilight and dusk is the end.
+
+#[derive(Default)]
 pub struct SolarInfo {
+    pub time: chrono::DateTime<chrono::Utc>,
     pub elevation: f64,
     pub azimuth: f64,
     pub right_ascension: f64,
     pub declination: f64,
+    pub sunrise: Option<chrono::DateTime<chrono::Utc>>,
+    pub sunset: Option<chrono::DateTime<chrono::Utc>>,
+    pub civil_dawn: Option<chrono::DateTime<chrono::Utc>>,
+    pub civil_dusk: Option<chrono::DateTime<chrono::Utc>>,
+    pub nautical_dawn: Option<chrono::DateTime<chrono::Utc>>,
+    pub nautical_dusk: Option<chrono::DateTime<chrono::Utc>>,
+    pub astro_dawn: Option<chrono::DateTime<chrono::Utc>>,
+    pub astro_dusk: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl SolarInfo {
+    // Returns `true` between sunrise and sunset. On days without a
+    // sunrise or sunset, the sun's altitude tells whether it's up.
+
+    pub fn is_day(&self) -> bool {
+        match (self.sunrise, self.sunset) {
+            (Some(rise), Some(set)) => rise <= self.time && self.time < set,
+            _ => self.elevation > SUNRISE_ALT,
+        }
+    }
 }
 
 pub type Info = Arc<SolarInfo>;
@@ -24,9 +64,9 @@ pub type Info = Arc<SolarInfo>;
 // in this function were obtained from a paper link to from the
 // Wikipedia page. The paper included FORTRAN code to perform the
 // calculations. That code was used as a reference to built this
-// function.
+// function. The daily events are computed for the site's date.
 
-fn get_solar_position(
+pub(super) fn get_solar_position(
     lat: f64,
     long: f64,
     time: &chrono::DateTime<chrono::Utc>,
@@ -105,14 +145,119 @@ fn get_solar_position(
         round(delta, 0.1)
     );
 
+    let noon = get_solar_noon(long, site_date(long, time));
+    let (sunrise, sunset) = get_crossing(lat, &noon, SUNRISE_ALT);
+    let (civil_dawn, civil_dusk) = get_crossing(lat, &noon, CIVIL_ALT);
+    let (nautical_dawn, nautical_dusk) = get_crossing(lat, &noon, NAUTICAL_ALT);
+    let (astro_dawn, astro_dusk) = get_crossing(lat, &noon, ASTRO_ALT);
+
     Arc::new(SolarInfo {
+        time: *time,
         elevation: round(elevation, 0.02),
         azimuth: round(azimuth, 0.1),
         right_ascension: round(alpha, 0.1),
         declination: round(delta, 0.1),
+        sunrise,
+        sunset,
+        civil_dawn,
+        civil_dusk,
+        nautical_dawn,
+        nautical_dusk,
+        astro_dawn,
+        astro_dusk,
     })
 }
 
+// Returns the date at a longitude (degrees) using mean solar time,
+// which is within an hour or so of the site's civil time. The time
+// zone of `drmemd`, or of a logic node, may not be the site's so
+// they can't be used to pick the day of the events.
+
+fn site_date(
+    long: f64,
+    time: &chrono::DateTime<chrono::Utc>,
+) -> chrono::NaiveDate {
+    chrono::Duration::try_milliseconds((long * 240_000.0).round() as i64)
+        .and_then(|offset| time.checked_add_signed(offset))
+        .unwrap_or(*time)
+        .date_naive()
+}
+
+// The time of solar noon, in days since noon, Jan 1st, 2000 UTC, and
+// the sun's declination (radians) at that time.
+
+struct SolarNoon {
+    transit: f64,
+    declination: f64,
+}
+
+// Computes solar noon at a longitude (degrees) on a calendar date.
+
+fn get_solar_noon(long: f64, date: chrono::NaiveDate) -> SolarNoon {
+    // Mean solar time, in days since the base date, of noon at the
+    // longitude. Jan 1st, 2000 is day 730,120 of the common era.
+
+    let j: f64 = (date.num_days_from_ce() - 730_120) as f64 - long / 360.0;
+
+    // The sun's mean anomaly, equation of the center and ecliptic
+    // longitude.
+
+    let m: f64 = (357.5291 + 0.98560028 * j).rem_euclid(360.0);
+    let m_r: f64 = m.to_radians();
+    let c: f64 = 1.9148 * f64::sin(m_r)
+        + 0.0200 * f64::sin(2.0 * m_r)
+        + 0.0003 * f64::sin(3.0 * m_r);
+    let lambda: f64 = (m + c + 180.0 + 102.9372).rem_euclid(360.0).to_radians();
+
+    SolarNoon {
+        transit: j + 0.0053 * f64::sin(m_r) - 0.0069 * f64::sin(2.0 * lambda),
+        declination: f64::asin(
+            f64::sin(lambda) * f64::sin(23.4397_f64.to_radians()),
+        ),
+    }
+}
+
+// Computes when the sun's center rises above, and sets below, an
+// altitude (degrees) on the day of `noon`. If the sun stays above, or
+// below, the altitude all day, both times are `None`.
+
+fn get_crossing(
+    lat: f64,
+    noon: &SolarNoon,
+    alt: f64,
+) -> (
+    Option<chrono::DateTime<chrono::Utc>>,
+    Option<chrono::DateTime<chrono::Utc>>,
+) {
+    let lat_sc: (f64, f64) = lat.to_radians().sin_cos();
+    let decl_sc: (f64, f64) = noon.declination.sin_cos();
+    let cos_w: f64 = (f64::sin(alt.to_radians()) - lat_sc.0 * decl_sc.0)
+        / (lat_sc.1 * decl_sc.1);
+
+    if (-1.0..=1.0).contains(&cos_w) {
+        let w: f64 = f64::acos(cos_w).to_degrees() / 360.0;
+
+        (from_days(noon.transit - w), from_days(noon.transit + w))
+    } else {
+        (None, None)
+    }
+}
+
+// Converts a number of days since noon, Jan 1st, 2000 UTC into a
+// time.
+
+fn from_days(days: f64) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::TimeZone;
+
+    chrono::Duration::try_milliseconds((days * 86_400_000.0).round() as i64)
+        .and_then(|d| {
+            chrono::Utc
+                .with_ymd_and_hms(2000, 1, 1, 12, 0, 0)
+                .single()
+                .map(|base| base + d)
+        })
+}
+
 fn round(v: f64, prec: f64) -> f64 {
     (v / prec).round() * prec
 }
@@ -122,13 +267,18 @@ async fn run(tx: broadcast::Sender<Info>, lat: f64, long: f64) {
 
     info!("starting task");
 
-    while tx
-        .send(get_solar_position(lat, long, &chrono::Utc::now()))
-        .is_ok()
-    {
+    // Logic nodes can be started after this task (by a reload, for
+    // instance), so it keeps running when there are no receivers.
+
+    loop {
+        if tx.receiver_count() > 0 {
+            let now = chrono::Utc::now();
+
+            let _ = tx.send(get_solar_position(lat, long, &now));
+        }
+
         let _ = interval.tick().await;
     }
-    warn!("no remaining clients ... terminating");
 }
 
 pub fn create_task(
@@ -450,4 +600,198 @@ mod tests {
             );
         }
     }
+
+    struct EventData {
+        date: (i32, u32, u32),
+        lat: f64,
+        long: f64,
+        events: [Option<&'static str>; 8],
+    }
+
+    #[test]
+    fn test_solar_events() {
+        // The expected times were computed with the algorithms used
+        // by https://gml.noaa.gov/grad/solcalc/ and are in UTC. The
+        // events are sunrise, sunset, civil dawn and dusk, nautical
+        // dawn and dusk, and astronomical dawn and dusk.
+
+        const TEST_DATA: &[EventData] = &[
+            // New York
+            EventData {
+                date: (2024, 6, 20),
+                lat: 40.7128,
+                long: -74.006,
+                events: [
+                    Some("2024-06-20T09:25:00Z"),
+                    Some("2024-06-21T00:31:00Z"),
+                    Some("2024-06-20T08:51:00Z"),
+                    Some("2024-06-21T01:04:00Z"),
+                    Some("2024-06-20T08:09:00Z"),
+                    Some("2024-06-21T01:47:00Z"),
+                    Some("2024-06-20T07:18:00Z"),
+                    Some("2024-06-21T02:37:00Z"),
+                ],
+            },
+            // London
+            EventData {
+                date: (2024, 6, 20),
+                lat: 51.5074,
+                long: -0.1278,
+                events: [
+                    Some("2024-06-20T03:43:00Z"),
+                    Some("2024-06-20T20:21:00Z"),
+                    Some("2024-06-20T02:55:00Z"),
+                    Some("2024-06-20T21:09:00Z"),
+                    Some("2024-06-20T01:41:00Z"),
+                    Some("2024-06-20T22:24:00Z"),
+                    None,
+                    None,
+                ],
+            },
+            // London
+            EventData {
+                date: (2024, 12, 21),
+                lat: 51.5074,
+                long: -0.1278,
+                events: [
+                    Some("2024-12-21T08:04:00Z"),
+                    Some("2024-12-21T15:54:00Z"),
+                    Some("2024-12-21T07:24:00Z"),
+                    Some("2024-12-21T16:34:00Z"),
+                    Some("2024-12-21T06:40:00Z"),
+                    Some("2024-12-21T17:17:00Z"),
+                    Some("2024-12-21T06:00:00Z"),
+                    Some("2024-12-21T17:58:00Z"),
+                ],
+            },
+            // Sydney
+            EventData {
+                date: (2024, 6, 21),
+                lat: -33.8688,
+                long: 151.2093,
+                events: [
+                    Some("2024-06-20T21:00:00Z"),
+                    Some("2024-06-21T06:54:00Z"),
+                    Some("2024-06-20T20:32:00Z"),
+                    Some("2024-06-21T07:22:00Z"),
+                    Some("2024-06-20T20:01:00Z"),
+                    Some("2024-06-21T07:53:00Z"),
+                    Some("2024-06-20T19:31:00Z"),
+                    Some("2024-06-21T08:23:00Z"),
+                ],
+            },
+            // Quito
+            EventData {
+                date: (2024, 3, 20),
+                lat: -0.1807,
+                long: -78.4678,
+                events: [
+                    Some("2024-03-20T11:18:00Z"),
+                    Some("2024-03-20T23:24:00Z"),
+                    Some("2024-03-20T10:57:00Z"),
+                    Some("2024-03-20T23:45:00Z"),
+                    Some("2024-03-20T10:33:00Z"),
+                    Some("2024-03-21T00:09:00Z"),
+                    Some("2024-03-20T10:09:00Z"),
+                    Some("2024-03-21T00:33:00Z"),
+                ],
+            },
+            // Tromsø
+            EventData {
+                date: (2024, 12, 21),
+                lat: 69.6492,
+                long: 18.9553,
+                events: [
+                    None,
+                    None,
+                    Some("2024-12-21T08:32:00Z"),
+                    Some("2024-12-21T12:53:00Z"),
+                    Some("2024-12-21T06:47:00Z"),
+                    Some("2024-12-21T14:38:00Z"),
+                    Some("2024-12-21T05:29:00Z"),
+                    Some("2024-12-21T15:56:00Z"),
+                ],
+            },
+            // Tromsø
+            EventData {
+                date: (2024, 6, 21),
+                lat: 69.6492,
+                long: 18.9553,
+                events: [None, None, None, None, None, None, None, None],
+            },
+        ];
+
+        for data in TEST_DATA {
+            let (year, month, day) = data.date;
+            let date =
+                chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap();
+            let time = date.and_hms_opt(12, 0, 0).unwrap().and_utc();
+            let info = get_solar_position(data.lat, data.long, &time);
+            let events = [
+                info.sunrise,
+                info.sunset,
+                info.civil_dawn,
+                info.civil_dusk,
+                info.nautical_dawn,
+                info.nautical_dusk,
+                info.astro_dawn,
+                info.astro_dusk,
+            ];
+
+            for (event, expected) in events.iter().zip(data.events) {
+                let expected = expected.map(|v| {
+                    v.parse::<chrono::DateTime<chrono::Utc>>().unwrap()
+                });
+
+                match (event, expected) {
+                    (Some(a), Some(b)) => assert!(
+                        (*a - b).num_seconds().abs() <= 60,
+                        "{} <> {} at {}, {}",
+                        a,
+                        b,
+                        data.lat,
+                        data.long
+                    ),
+                    (None, None) => (),
+                    (a, b) => panic!(
+                        "{:?} <> {:?} at {}, {}",
+                        a, b, data.lat, data.long
+                    ),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_day() {
+        let at = |lat: f64, long: f64, time: &str| {
+            let time = time.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+
+            get_solar_position(lat, long, &time).is_day()
+        };
+
+        assert!(!at(-0.1807, -78.4678, "2024-03-20T11:10:00Z"));
+        assert!(at(-0.1807, -78.4678, "2024-03-20T11:25:00Z"));
+        assert!(at(-0.1807, -78.4678, "2024-03-20T23:15:00Z"));
+        assert!(!at(-0.1807, -78.4678, "2024-03-20T23:30:00Z"));
+
+        // In New York, the sun sets after midnight UTC in the summer.
+        // The events are those of the site's date, not the UTC date.
+
+        assert!(at(40.7128, -74.006, "2024-06-21T00:10:00Z"));
+        assert!(!at(40.7128, -74.006, "2024-06-21T00:45:00Z"));
+        assert!(at(40.7128, -74.006, "2024-06-21T10:00:00Z"));
+
+        // East of Greenwich, the site's date starts before the UTC
+        // date.
+
+        assert!(!at(-33.8688, 151.2093, "2024-06-20T20:30:00Z"));
+        assert!(at(-33.8688, 151.2093, "2024-06-20T21:30:00Z"));
+
+        // North of the arctic circle, the sun doesn't set in the
+        // summer and doesn't rise in the winter.
+
+        assert!(at(69.6492, 18.9553, "2024-06-21T00:00:00Z"));
+        assert!(!at(69.6492, 18.9553, "2024-12-21T11:00:00Z"));
+    }
 }
@@ -0,0 +1,168 @@
+// Running logic nodes report their state so it can be inspected
+// (the GraphQL interface, for instance, provides it with its
+// `logicInfo` query.) Each node owns a `Status`, which it updates
+// after every evaluation, and registers it in a `Db` under the
+// node's name.
+
+use drmem_api::device;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+// A variable of a logic node. Variables from the `inputs` table are
+// bound to a device. The ones from `defs` aren't.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub device: Option<device::Name>,
+    pub value: Option<device::Value>,
+}
+
+// An expression that computes the value of an output. `program` is
+// the optimized form of the expression. `last` is the last value
+// that the output device accepted.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    pub program: String,
+    pub output: String,
+    pub device: device::Name,
+    pub last: Option<device::Value>,
+    pub errors: u64,
+}
+
+// The state of a logic node. `defs` holds the optimized programs
+// which compute the definitions, the `let` variables and the shared
+// subexpressions.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Status {
+    pub name: String,
+    pub summary: Option<String>,
+    pub variables: Vec<Variable>,
+    pub defs: Vec<String>,
+    pub exprs: Vec<Expression>,
+}
+
+// Holds the status of every running logic node.
+
+#[derive(Clone, Default)]
+pub struct Db(Arc<Mutex<BTreeMap<String, Arc<Mutex<Status>>>>>);
+
+impl Db {
+    // Returns a copy of the status of each running node, ordered by
+    // name.
+
+    #[cfg(any(feature = "graphql", test))]
+    pub fn get_all(&self) -> Vec<Status> {
+        self.0
+            .lock()
+            .unwrap()
+            .values()
+            .map(|s| s.lock().unwrap().clone())
+            .collect()
+    }
+
+    // Returns a copy of the status of the named node.
+
+    #[cfg(any(feature = "graphql", test))]
+    pub fn get(&self, name: &str) -> Option<Status> {
+        self.0
+            .lock()
+            .unwrap()
+            .get(name)
+            .map(|s| s.lock().unwrap().clone())
+    }
+
+    // Adds a node's status to the database. The status is removed
+    // when the returned `Entry` is dropped. If another node with the
+    // same name was registered, its status is replaced.
+
+    pub fn register(&self, status: Arc<Mutex<Status>>) -> Entry {
+        let name = status.lock().unwrap().name.clone();
+
+        self.0.lock().unwrap().insert(name, status.clone());
+        Entry {
+            db: self.clone(),
+            status,
+        }
+    }
+}
+
+// Keeps a node's status in the `Db`.
+
+pub struct Entry {
+    db: Db,
+    status: Arc<Mutex<Status>>,
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        let name = self.status.lock().unwrap().name.clone();
+        let mut db = self.db.0.lock().unwrap();
+
+        // A node which replaced this one may have registered under
+        // the same name. Only remove our own status.
+
+        if db.get(&name).is_some_and(|s| Arc::ptr_eq(s, &self.status)) {
+            db.remove(&name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(name: &str, summary: &str) -> Arc<Mutex<Status>> {
+        Arc::new(Mutex::new(Status {
+            name: name.into(),
+            summary: Some(summary.into()),
+            variables: vec![],
+            defs: vec![],
+            exprs: vec![],
+        }))
+    }
+
+    #[test]
+    fn test_db() {
+        let db = Db::default();
+
+        assert_eq!(db.get_all(), vec![]);
+        assert_eq!(db.get("a"), None);
+
+        let b = db.register(status("b", "first"));
+        let a = db.register(status("a", "first"));
+
+        assert_eq!(
+            db.get_all()
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+
+        // Changes made by the node are visible.
+
+        a.status.lock().unwrap().defs.push("true -> out[0]".into());
+
+        assert_eq!(db.get("a").unwrap().defs, vec!["true -> out[0]"]);
+
+        // Replacing a node and then dropping the old one keeps the
+        // new status.
+
+        let b2 = db.register(status("b", "second"));
+
+        std::mem::drop(b);
+
+        assert_eq!(db.get("b").unwrap().summary, Some("second".into()));
+
+        std::mem::drop(b2);
+
+        assert_eq!(db.get("b"), None);
+
+        std::mem::drop(a);
+
+        assert_eq!(db.get_all(), vec![]);
+    }
+}
@@ -4,19 +4,47 @@ use core::task::{Context, Poll};
 use std::sync::Arc;
 use tokio::{sync::broadcast, time};
 use tokio_stream::{wrappers::BroadcastStream, Stream};
-use tracing::{info, info_span, warn};
+use tracing::{info, info_span};
 use tracing_futures::Instrument;
 
 // Information related to time-of-day. We keep both UTC and local time
 // so clients don't have to convert between the time zones. It is
 // stored in an `Arc` so it can be cheaply sent and received over a
 // broadcast channel.
+//
+// The local time holds the offset from UTC that was in effect at
+// that moment. The time-of-day task uses the time zone of the
+// `drmemd` process but logic nodes can convert it to another zone
+// (see `at()`.)
 
 pub type Info = Arc<(
     chrono::DateTime<chrono::Utc>,
-    chrono::DateTime<chrono::Local>,
+    chrono::DateTime<chrono::FixedOffset>,
 )>;
 
+// The time zone of a logic node's local time. `None` uses the time
+// zone of the `drmemd` process.
+
+pub type Zone = Option<chrono_tz::Tz>;
+
+// Builds the time-of-day information for a moment in time. The local
+// time is given in the time zone `tz`.
+
+pub fn at(utc: chrono::DateTime<chrono::Utc>, tz: Zone) -> Info {
+    let local = match tz {
+        Some(tz) => utc.with_timezone(&tz).fixed_offset(),
+        None => utc.with_timezone(&chrono::Local).fixed_offset(),
+    };
+
+    Arc::new((utc, local))
+}
+
+// Returns the current time-of-day information.
+
+pub fn now(tz: Zone) -> Info {
+    at(chrono::Utc::now(), tz)
+}
+
 // Each variant of this enumeration selects a field of a Date/Time
 // type. They are defined in order of shortest time span to largest so
 // they can be compared. This enumeration is used as an optimization
@@ -37,11 +65,18 @@ pub enum TimeField {
 
 pub struct TimeFilter {
     field: TimeField,
+    tz: Zone,
     prev: Option<Info>,
     inner: BroadcastStream<Info>,
 }
 
 impl TimeFilter {
+    // Both the UTC and the local times are checked for changes. When
+    // clocks are set back for daylight saving time, the local hour
+    // repeats but the UTC hour still changes, so an hour isn't
+    // missed. When they're set forward, the local hour skips ahead
+    // and is reported as a change.
+
     fn changed(&self, curr: &Info) -> bool {
         if let Some(ref v) = self.prev {
             match self.field {
@@ -71,6 +106,30 @@ impl TimeFilter {
             true
         }
     }
+
+    // Saves `curr` and returns `true` if it's different enough from
+    // the previously accepted value. Otherwise it returns `false`.
+
+    pub fn accept(&mut self, curr: &Info) -> bool {
+        let changed = self.changed(curr);
+
+        if changed {
+            self.prev = Some(curr.clone())
+        }
+        changed
+    }
+
+    // Returns how often the field can change. UTC offsets are
+    // multiples of 15 minutes so local hours and dates change on a
+    // 15 minute boundary of UTC.
+
+    pub fn period(&self) -> chrono::TimeDelta {
+        match self.field {
+            TimeField::Second => chrono::TimeDelta::seconds(1),
+            TimeField::Minute => chrono::TimeDelta::minutes(1),
+            _ => chrono::TimeDelta::minutes(15),
+        }
+    }
 }
 
 // Make TimeFilter able to be used as a BroadcastStream wrapper.
@@ -89,12 +148,17 @@ impl Stream for TimeFilter {
                 Poll::Pending => return Poll::Pending,
                 Poll::Ready(None) => return Poll::Ready(None),
 
-                // If we got a value, check to see if it has changed
-                // enough from the previous value to return it. If
-                // not, loop for the next value.
+                // If we got a value, convert it to our time zone and
+                // check to see if it has changed enough from the
+                // previous value to return it. If not, loop for the
+                // next value.
                 Poll::Ready(Some(Ok(tod))) => {
-                    if self.changed(&tod) {
-                        self.prev = Some(tod.clone());
+                    let tod = match self.tz {
+                        Some(_) => at(tod.0, self.tz),
+                        None => tod,
+                    };
+
+                    if self.accept(&tod) {
                         return Poll::Ready(Some(tod));
                     }
                 }
@@ -109,13 +173,19 @@ impl Stream for TimeFilter {
     }
 }
 
+// Wraps a stream of time-of-day information so it only yields a
+// value when `field` changes. The values are converted to the time
+// zone `tz`.
+
 pub fn time_filter(
     stream: BroadcastStream<Info>,
     field: TimeField,
+    tz: Zone,
 ) -> TimeFilter {
     TimeFilter {
         inner: stream,
         field,
+        tz,
         prev: None,
     }
 }
@@ -135,13 +205,15 @@ async fn run(tx: broadcast::Sender<Info>) {
 
     info!("starting time-of-day task");
 
-    while tx
-        .send(Arc::new((chrono::Utc::now(), chrono::Local::now())))
-        .is_ok()
-    {
+    // Logic nodes can be started after this task (by a reload, for
+    // instance), so it keeps running when there are no receivers.
+
+    loop {
+        if tx.receiver_count() > 0 {
+            let _ = tx.send(now(None));
+        }
         let _ = interval.tick().await;
     }
-    warn!("no remaining clients ... terminating");
 }
 
 pub fn create_task() -> (broadcast::Sender<Info>, broadcast::Receiver<Info>) {
@@ -157,7 +229,7 @@ pub fn create_task() -> (broadcast::Sender<Info>, broadcast::Receiver<Info>) {
 
 #[cfg(test)]
 mod tests {
-    use super::{time_filter, Info, TimeField};
+    use super::{at, time_filter, Info, TimeField, Zone};
     use chrono::{Local, TimeZone, Utc};
     use core::pin::Pin;
     use futures::future::poll_fn;
@@ -172,7 +244,8 @@ mod tests {
                 .unwrap(),
             Local::with_ymd_and_hms(&Local, yr, mo, da, hr, mn, se)
                 .single()
-                .unwrap(),
+                .unwrap()
+                .fixed_offset(),
         ))
     }
 
@@ -182,7 +255,8 @@ mod tests {
         field: TimeField,
     ) {
         let (tx, rx) = broadcast::channel::<Info>(inputs.len());
-        let mut strm = time_filter(BroadcastStream::new(rx), field.clone());
+        let mut strm =
+            time_filter(BroadcastStream::new(rx), field.clone(), None);
 
         for input in inputs {
             assert!(tx.send(input.clone()).is_ok());
@@ -305,4 +379,128 @@ mod tests {
             .await
         }
     }
+
+    // Sends the UTC times through a filter that uses the time zone
+    // and checks that the expected local times are returned.
+
+    async fn test_zone_filter(
+        tz: Zone,
+        inputs: &[&str],
+        outputs: &[&str],
+        field: TimeField,
+    ) {
+        let (tx, rx) = broadcast::channel::<Info>(inputs.len());
+        let mut strm = time_filter(BroadcastStream::new(rx), field.clone(), tz);
+
+        for input in inputs {
+            assert!(tx.send(at(input.parse().unwrap(), None)).is_ok());
+        }
+        std::mem::drop(tx);
+
+        let mut result = vec![];
+
+        while let Some(v) =
+            poll_fn(|cx| Pin::new(&mut strm).poll_next(cx)).await
+        {
+            result.push(v.1.to_rfc3339())
+        }
+
+        assert_eq!(result, outputs, "error in {:?} test", field);
+    }
+
+    #[tokio::test]
+    async fn test_time_zones() {
+        use chrono_tz::{America::New_York, Asia::Kolkata};
+
+        // When the clocks spring forward, the local time skips from
+        // 2:00 to 3:00.
+
+        test_zone_filter(
+            Some(New_York),
+            &[
+                "2024-03-10T05:30:00Z",
+                "2024-03-10T06:00:00Z",
+                "2024-03-10T06:59:59Z",
+                "2024-03-10T07:00:00Z",
+                "2024-03-10T07:30:00Z",
+                "2024-03-10T08:00:00Z",
+            ],
+            &[
+                "2024-03-10T00:30:00-05:00",
+                "2024-03-10T01:00:00-05:00",
+                "2024-03-10T03:00:00-04:00",
+                "2024-03-10T04:00:00-04:00",
+            ],
+            TimeField::Hour,
+        )
+        .await;
+
+        // When the clocks fall back, the 1:00 hour happens twice.
+        // Both are reported.
+
+        test_zone_filter(
+            Some(New_York),
+            &[
+                "2024-11-03T04:30:00Z",
+                "2024-11-03T05:00:00Z",
+                "2024-11-03T05:30:00Z",
+                "2024-11-03T06:00:00Z",
+                "2024-11-03T06:30:00Z",
+                "2024-11-03T07:00:00Z",
+            ],
+            &[
+                "2024-11-03T00:30:00-04:00",
+                "2024-11-03T01:00:00-04:00",
+                "2024-11-03T01:00:00-05:00",
+                "2024-11-03T02:00:00-05:00",
+            ],
+            TimeField::Hour,
+        )
+        .await;
+
+        // The repeated hour doesn't start another day. The UTC day
+        // changing during the local day is reported, though.
+
+        test_zone_filter(
+            Some(New_York),
+            &[
+                "2024-11-03T03:59:00Z",
+                "2024-11-03T04:00:00Z",
+                "2024-11-03T06:00:00Z",
+                "2024-11-03T23:00:00Z",
+                "2024-11-04T00:00:00Z",
+                "2024-11-04T04:59:00Z",
+                "2024-11-04T05:00:00Z",
+            ],
+            &[
+                "2024-11-02T23:59:00-04:00",
+                "2024-11-03T00:00:00-04:00",
+                "2024-11-03T19:00:00-05:00",
+                "2024-11-04T00:00:00-05:00",
+            ],
+            TimeField::Day,
+        )
+        .await;
+
+        // In a zone that's offset by a half hour, the local hour and
+        // the UTC hour change at different times.
+
+        test_zone_filter(
+            Some(Kolkata),
+            &[
+                "2024-06-01T10:00:00Z",
+                "2024-06-01T10:29:00Z",
+                "2024-06-01T10:30:00Z",
+                "2024-06-01T10:59:00Z",
+                "2024-06-01T11:00:00Z",
+            ],
+            &[
+                "2024-06-01T15:30:00+05:30",
+                "2024-06-01T16:00:00+05:30",
+                "2024-06-01T16:30:00+05:30",
+            ],
+            TimeField::Hour,
+        )
+        .await;
+    }
 }
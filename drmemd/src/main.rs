@@ -73,12 +73,53 @@ async fn wrap_task(
     }
 }
 
+// Completes when `drmemd` is asked to stop, by a SIGINT or, on Unix,
+// a SIGTERM. If the signals can't be caught, it never completes.
+
+async fn shutdown_signal() {
+    let interrupt = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            future::pending::<()>().await
+        }
+    };
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            let terminate = term.recv();
+
+            tokio::select! {
+                _ = interrupt => (),
+                _ = terminate => ()
+            }
+            return;
+        }
+    }
+    interrupt.await
+}
+
 // Runs the main body of the application. This top-level task reads
 // the config, starts the drivers and logic node, and monitors their
 // health.
 
 async fn run() -> Result<()> {
     if let Some(cfg) = init_app().await {
+        // The `simulate` subcommand only needs the logic blocks and
+        // the calendars, so it runs without the core and drivers.
+
+        if let Some((name, file)) = &cfg.simulate {
+            return logic::simulate::run(&cfg, name, file).await;
+        }
+
+        // The `export` and `import` subcommands only need the
+        // back-end.
+
+        if let Some(archive) = &cfg.archive {
+            return backends::archive::run(cfg.get_backend(), archive).await;
+        }
+
         let drv_tbl = driver::DriverDb::create();
 
         // Start the core task. It returns a handle to a channel with
@@ -93,6 +134,10 @@ async fn run() -> Result<()> {
 
         let mut tasks = vec![wrap_task(core_task)];
 
+        // Running logic nodes report their state in this database.
+
+        let logic_db = logic::status::Db::default();
+
         // If the "graphql" feature is specified, start up the web
         // server which accepts GraphQL queries.
 
@@ -105,6 +150,7 @@ async fn run() -> Result<()> {
                 &cfg.graphql,
                 drv_tbl.clone(),
                 tx_clnt_req.clone(),
+                logic_db.clone(),
             )
             .then(|_| async {
                 Err(Error::OperationError("graphql server exited".to_owned()))
@@ -130,6 +176,7 @@ async fn run() -> Result<()> {
                 let chan = RequestChan::new(
                     driver_name.clone(),
                     &driver.prefix,
+                    driver.get_max_age(),
                     &tx_drv_req,
                 );
 
@@ -156,8 +203,10 @@ async fn run() -> Result<()> {
             }
         }
 
-        // Create a nested scope so that the tod and solar handles are
-        // freed up.
+        // Create a nested scope for starting the logic nodes. The
+        // tod, solar and lunar handles are handed to the task that
+        // manages the nodes, since it needs them to start nodes when
+        // the configuration changes.
 
         {
             // Start the time-of-day task. This needs to be done
@@ -173,23 +222,45 @@ async fn run() -> Result<()> {
             let (tx_solar, _) =
                 logic::solar::create_task(cfg.latitude, cfg.longitude);
 
-            // Iterate through the [[logic]] sections of the config.
+            // Start the lunar task, for the same reason.
 
-            for logic in cfg.logic {
-                tasks.push(wrap_task(logic::Node::start(
-                    tx_clnt_req.clone(),
-                    tx_tod.subscribe(),
-                    tx_solar.subscribe(),
-                    logic,
-                )));
-            }
+            let (tx_lunar, _) =
+                logic::lunar::create_task(cfg.latitude, cfg.longitude);
+
+            // Load the calendars. Every logic block shares them.
+
+            let cal = logic::calendar::load(&cfg.calendar).await?;
+
+            // Start the [[logic]] sections of the config. If the
+            // config came from a file, the sections are reloaded
+            // when it changes.
+
+            tasks.push(wrap_task(logic::reload::start(
+                logic::reload::Shared {
+                    c_req: tx_clnt_req.clone(),
+                    d_req: tx_drv_req,
+                    tx_tod,
+                    tx_solar,
+                    tx_lunar,
+                    cal,
+                    db: logic_db,
+                },
+                cfg.logic,
+                cfg.file,
+            )));
         }
 
-        // Now run all the tasks.
+        // Now run all the tasks until they end or `drmemd` is asked
+        // to stop. When `main()` returns, the runtime drops the
+        // tasks, which gives the backend a chance to save its state.
 
-        let _ = future::join_all(tasks).await;
+        let all_tasks = future::join_all(tasks);
+        let signal = shutdown_signal();
 
-        warn!("shutting down")
+        tokio::select! {
+            _ = all_tasks => warn!("shutting down"),
+            _ = signal => warn!("interrupted -- shutting down")
+        }
     }
     Ok(())
 }
@@ -0,0 +1,109 @@
+ mod logic_l {
+use lrlex::{LexerDef, LRNonStreamingLexerDef, Rule, StartState};
+
+#[allow(dead_code)]
+pub fn lexerdef() -> LRNonStreamingLexerDef<lrlex::defaults::DefaultLexerTypes> {
+let regex_options = ::lrlex::RegexOptions {
+            dot_matches_new_line: true,
+            multi_line: true,
+            octal: true,
+            case_insensitive: None,
+            unicode: None,
+            swap_greed: None,
+            ignore_whitespace: None,
+            size_limit: None,
+            dfa_size_limit: None,
+            nest_limit: None,
+        };    let start_states: Vec<StartState> = vec![
+        StartState::new(0, "INITIAL", false, ::cfgrammar::Span::new(0, 0)),
+        StartState::new(1, "VAR", true, ::cfgrammar::Span::new(3, 6)),
+        StartState::new(2, "COLOR", true, ::cfgrammar::Span::new(7, 12)),
+    ];
+    let rules = vec![
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(5), Some("CONTROL".to_string()), ::cfgrammar::Span::new(42, 49), "->".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(24), Some("STRING".to_string()), ::cfgrammar::Span::new(77, 83), "\"[^\"]*\"".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(22), Some("(".to_string()), ::cfgrammar::Span::new(111, 112), "\\(".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(23), Some(")".to_string()), ::cfgrammar::Span::new(139, 140), "\\)".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(21), Some("B_NOT".to_string()), ::cfgrammar::Span::new(168, 173), "not".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(9), Some("B_AND".to_string()), ::cfgrammar::Span::new(200, 205), "and".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(8), Some("B_OR".to_string()), ::cfgrammar::Span::new(232, 236), "or".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(10), Some("EQ".to_string()), ::cfgrammar::Span::new(264, 266), "=".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(11), Some("NE".to_string()), ::cfgrammar::Span::new(293, 295), "<>".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(15), Some("LT_EQ".to_string()), ::cfgrammar::Span::new(322, 327), "<=".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(13), Some("GT_EQ".to_string()), ::cfgrammar::Span::new(354, 359), ">=".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(12), Some("GT".to_string()), ::cfgrammar::Span::new(386, 388), ">".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(14), Some("LT".to_string()), ::cfgrammar::Span::new(415, 417), "<".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(1), Some("FLT".to_string()), ::cfgrammar::Span::new(453, 456), "-?[0-9]+\\.[0-9]*([eE]-?[0-9]+)?".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(0), Some("INT".to_string()), ::cfgrammar::Span::new(483, 486), "-?[0-9]+".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(16), Some("ADD".to_string()), ::cfgrammar::Span::new(514, 517), "\\+".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(17), Some("SUB".to_string()), ::cfgrammar::Span::new(544, 547), "-".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(18), Some("MUL".to_string()), ::cfgrammar::Span::new(574, 577), "\\*".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(19), Some("DIV".to_string()), ::cfgrammar::Span::new(604, 607), "/".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(20), Some("REM".to_string()), ::cfgrammar::Span::new(634, 637), "%".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(3), Some("TRUE".to_string()), ::cfgrammar::Span::new(665, 669), "true".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(4), Some("FALSE".to_string()), ::cfgrammar::Span::new(696, 701), "false".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(6), Some("LBRACE".to_string()), ::cfgrammar::Span::new(729, 735), "\\{".to_string(), [].to_vec(), Some((1, ::lrlex::StartStateOperation::Push)), &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(7), Some("RBRACE".to_string()), ::cfgrammar::Span::new(768, 774), "\\}".to_string(), [1usize].to_vec(), Some((1, ::lrlex::StartStateOperation::Pop)), &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(2), Some("IDENTIFIER".to_string()), ::cfgrammar::Span::new(813, 823), "[a-zA-Z][0-9a-zA-Z_]*".to_string(), [1usize].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(26), Some("COLON".to_string()), ::cfgrammar::Span::new(850, 855), ":".to_string(), [1usize].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(26), None, ::cfgrammar::Span::new(863, 863), "\\#".to_string(), [].to_vec(), Some((2, ::lrlex::StartStateOperation::Push)), &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(25), Some("COLOR".to_string()), ::cfgrammar::Span::new(894, 899), "[0-9a-zA-Z]+".to_string(), [2usize].to_vec(), Some((2, ::lrlex::StartStateOperation::Pop)), &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(28), None, ::cfgrammar::Span::new(921, 921), "[\\t\\n\\r ]+".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+        Rule::new(::lrlex::unstable_api::InternalPublicApi, Some(27), Some("UNKNOWN".to_string()), ::cfgrammar::Span::new(949, 956), ".".to_string(), [].to_vec(), None, &regex_options).unwrap(),
+    ];
+    LRNonStreamingLexerDef::from_rules(start_states, rules)
+}
+
+#[allow(dead_code)]
+pub const T_GT: u32 = 12;
+#[allow(dead_code)]
+pub const T_LBRACE: u32 = 6;
+#[allow(dead_code)]
+pub const T_B_OR: u32 = 8;
+#[allow(dead_code)]
+pub const T_REM: u32 = 20;
+#[allow(dead_code)]
+pub const T_MUL: u32 = 18;
+#[allow(dead_code)]
+pub const T_RBRACE: u32 = 7;
+#[allow(dead_code)]
+pub const T_IDENTIFIER: u32 = 2;
+#[allow(dead_code)]
+pub const T_EQ: u32 = 10;
+#[allow(dead_code)]
+pub const T_NE: u32 = 11;
+#[allow(dead_code)]
+pub const T_TRUE: u32 = 3;
+#[allow(dead_code)]
+pub const T_STRING: u32 = 24;
+#[allow(dead_code)]
+pub const T_B_AND: u32 = 9;
+#[allow(dead_code)]
+pub const T_FLT: u32 = 1;
+#[allow(dead_code)]
+pub const T_ADD: u32 = 16;
+#[allow(dead_code)]
+pub const T_B_NOT: u32 = 21;
+#[allow(dead_code)]
+pub const T_SUB: u32 = 17;
+#[allow(dead_code)]
+pub const T_GT_EQ: u32 = 13;
+#[allow(dead_code)]
+pub const T_COLON: u32 = 26;
+#[allow(dead_code)]
+pub const T_LT_EQ: u32 = 15;
+#[allow(dead_code)]
+pub const T_LT: u32 = 14;
+#[allow(dead_code)]
+pub const T_FALSE: u32 = 4;
+#[allow(dead_code)]
+pub const T_INT: u32 = 0;
+#[allow(dead_code)]
+pub const T_UNKNOWN: u32 = 27;
+#[allow(dead_code)]
+pub const T_DIV: u32 = 19;
+#[allow(dead_code)]
+pub const T_COLOR: u32 = 25;
+#[allow(dead_code)]
+pub const T_CONTROL: u32 = 5;
+}
\ No newline at end of file
@@ -0,0 +1,1723 @@
+ mod logic_y {
+
+// User code from the program section
+
+use drmem_api::{Result, Error, device};
+use chrono::{Timelike, Datelike};
+use palette::{LinSrgba, LinSrgb, Srgb, named, WithAlpha};
+use super::{TimeField, SolarField, super::tod, super::solar, Expr, Program};
+use std::str::FromStr;
+
+use lrlex::{DefaultLexeme, DefaultLexerTypes};
+use lrpar::NonStreamingLexer;
+
+// This complicated beast is an attempt to remove the boilerplate code
+// used when processing the terminal tokens (i.e. the leaf values of
+// the expression tree.)
+
+fn get_str<'a, 'input>(
+    label: &'a str,
+    lexeme: std::result::Result<DefaultLexeme, DefaultLexeme>,
+    lexer: &'a (dyn NonStreamingLexer<'input, DefaultLexerTypes> + 'a)
+) -> Result<&'input str> {
+    let lexeme = lexeme.map_err(|_| Error::ParseError(
+        format!("error reading {}", label)
+    ))?;
+
+    Ok(lexer.span_str(lexeme.span()))
+}
+
+// Any functions here are in scope for all the grammar actions above.
+
+fn parse_int(s: &str) -> Result<Expr> {
+    s.parse::<i32>()
+	.map(|v| Expr::Lit(device::Value::Int(v)))
+	.map_err(|_| Error::ParseError(
+	     format!("{} cannot be represented as an i32", s)
+	))
+}
+
+fn parse_flt(s: &str) -> Result<Expr> {
+    s.parse::<f64>()
+	.map(|v| Expr::Lit(device::Value::Flt(v)))
+	.map_err(|_| Error::ParseError(
+	     format!("{} cannot be represented as an f64", s)
+	))
+}
+
+fn parse_device(name: &str, env: &[String]) -> Result<usize> {
+    for ii in env.iter().enumerate() {
+        if *ii.1 == name {
+	    return Ok(ii.0);
+	}
+    }
+    Err(Error::ParseError(format!("variable '{}' is not defined", &name)))
+}
+
+const CAT_UTC: &str = "utc";
+const CAT_LOCAL: &str = "local";
+const CAT_SOLAR: &str = "solar";
+
+const FLD_SECOND: &str = "second";
+const FLD_MINUTE: &str = "minute";
+const FLD_HOUR: &str = "hour";
+const FLD_DAY: &str = "day";
+const FLD_MONTH: &str = "month";
+const FLD_YEAR: &str = "year";
+const FLD_DOW: &str = "DOW";
+const FLD_DOY: &str = "DOY";
+const FLD_ALT: &str = "alt";
+const FLD_AZ: &str = "az";
+const FLD_RA: &str = "ra";
+const FLD_DEC: &str = "dec";
+
+fn get_utc_second(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.second() as i32)
+}
+
+fn get_utc_minute(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.minute() as i32)
+}
+
+fn get_utc_hour(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.hour() as i32)
+}
+
+fn get_utc_day(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.day() as i32)
+}
+
+fn get_utc_day_of_week(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.weekday().num_days_from_monday() as i32)
+}
+
+fn get_utc_month(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.month() as i32)
+}
+
+fn get_utc_year(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.year())
+}
+
+fn get_utc_day_of_year(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.0.ordinal0() as i32)
+}
+
+fn get_local_second(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.second() as i32)
+}
+
+fn get_local_minute(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.minute() as i32)
+}
+
+fn get_local_hour(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.hour() as i32)
+}
+
+fn get_local_day(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.day() as i32)
+}
+
+fn get_local_day_of_week(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.weekday().num_days_from_monday() as i32)
+}
+
+fn get_local_month(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.month() as i32)
+}
+
+fn get_local_year(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.year())
+}
+
+fn get_local_day_of_year(info: &tod::Info) -> device::Value {
+    device::Value::Int(info.1.ordinal0() as i32)
+}
+
+fn get_solar_altitude(info: &solar::Info) -> device::Value {
+    device::Value::Flt(info.elevation)
+}
+
+fn get_solar_azimuth(info: &solar::Info) -> device::Value {
+    device::Value::Flt(info.azimuth)
+}
+
+fn get_solar_right_ascension(info: &solar::Info) -> device::Value {
+    device::Value::Flt(info.right_ascension)
+}
+
+fn get_solar_declination(info: &solar::Info) -> device::Value {
+    device::Value::Flt(info.declination)
+}
+
+fn parse_builtin(cat: &str, fld: &str) -> Result<Expr> {
+    match (cat, fld) {
+	(CAT_UTC, FLD_SECOND) => Ok(Expr::TimeVal(
+	    CAT_UTC, TimeField::Second, get_utc_second
+        )),
+	(CAT_UTC, FLD_MINUTE) => Ok(Expr::TimeVal(
+            CAT_UTC, TimeField::Minute, get_utc_minute
+        )),
+	(CAT_UTC, FLD_HOUR) => Ok(Expr::TimeVal(
+	    CAT_UTC, TimeField::Hour, get_utc_hour
+        )),
+	(CAT_UTC, FLD_DAY) => Ok(Expr::TimeVal(
+	    CAT_UTC, TimeField::Day, get_utc_day
+        )),
+	(CAT_UTC, FLD_DOW) => Ok(Expr::TimeVal(
+            CAT_UTC, TimeField::DoW, get_utc_day_of_week
+        )),
+	(CAT_UTC, FLD_MONTH) => Ok(Expr::TimeVal(
+            CAT_UTC, TimeField::Month, get_utc_month
+        )),
+	(CAT_UTC, FLD_YEAR) => Ok(Expr::TimeVal(
+            CAT_UTC, TimeField::Year, get_utc_year
+        )),
+	(CAT_UTC, FLD_DOY) => Ok(Expr::TimeVal(
+            CAT_UTC, TimeField::DoY, get_utc_day_of_year
+        )),
+	(CAT_LOCAL, FLD_SECOND) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::Second, get_local_second
+        )),
+	(CAT_LOCAL, FLD_MINUTE) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::Minute, get_local_minute
+        )),
+	(CAT_LOCAL, FLD_HOUR) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::Hour, get_local_hour
+        )),
+	(CAT_LOCAL, FLD_DAY) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::Day, get_local_day
+        )),
+	(CAT_LOCAL, FLD_DOW) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::DoW, get_local_day_of_week
+        )),
+	(CAT_LOCAL, FLD_MONTH) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::Month, get_local_month
+        )),
+	(CAT_LOCAL, FLD_YEAR) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::Year, get_local_year
+        )),
+	(CAT_LOCAL, FLD_DOY) => Ok(Expr::TimeVal(
+            CAT_LOCAL, TimeField::DoY, get_local_day_of_year
+        )),
+	(CAT_SOLAR, FLD_ALT) => Ok(Expr::SolarVal(
+	    SolarField::Elevation, get_solar_altitude
+        )),
+	(CAT_SOLAR, FLD_AZ) => Ok(Expr::SolarVal(
+            SolarField::Azimuth, get_solar_azimuth
+        )),
+	(CAT_SOLAR, FLD_RA) => Ok(Expr::SolarVal(
+            SolarField::RightAscension, get_solar_right_ascension
+        )),
+	(CAT_SOLAR, FLD_DEC) => Ok(Expr::SolarVal(
+            SolarField::Declination, get_solar_declination
+        )),
+	_ => Err(Error::ParseError(
+		 format!("unknown built-in: {}:{}", cat, fld)
+	     ))
+    }
+}
+
+// End of user code from the program section
+
+
+    // User actions
+
+    // Logic
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_0<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_4: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_5: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Program> {
+        let _ = p;
+let v = __gt_arg_4.map_err(|_| Error::ParseError(
+	        String::from("error reading target device")
+            ))?;
+	let s = __gt_lexer.span_str(v.span());
+
+	Ok(Program(__gt_arg_1?, parse_device(s, p.1)?))
+    }
+
+    // BoolExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_1<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // OrExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_2<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Or(
+			    Box::new(__gt_arg_1?),
+			    Box::new(__gt_arg_3?)
+			  ))
+    }
+
+    // OrExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_3<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // AndExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_4<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::And(
+			    Box::new(__gt_arg_1?),
+			    Box::new(__gt_arg_3?)
+			  ))
+    }
+
+    // AndExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_5<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // CmpExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_6<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Eq(
+			        Box::new(__gt_arg_1?),
+			        Box::new(__gt_arg_3?)
+			     ))
+    }
+
+    // CmpExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_7<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Not(
+		                Box::new(
+			           Expr::Eq(
+			              Box::new(__gt_arg_1?),
+			              Box::new(__gt_arg_3?)
+			           )
+			        )
+		             ))
+    }
+
+    // CmpExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_8<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Lt(
+			        Box::new(__gt_arg_3?),
+			        Box::new(__gt_arg_1?)
+			     ))
+    }
+
+    // CmpExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_9<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::LtEq(
+			           Box::new(__gt_arg_3?),
+			           Box::new(__gt_arg_1?)
+			        ))
+    }
+
+    // CmpExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_10<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Lt(
+			        Box::new(__gt_arg_1?),
+			        Box::new(__gt_arg_3?)
+			     ))
+    }
+
+    // CmpExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_11<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::LtEq(
+			           Box::new(__gt_arg_1?),
+			           Box::new(__gt_arg_3?)
+			        ))
+    }
+
+    // CmpExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_12<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // NumExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_13<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // AddSubExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_14<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Add(Box::new(__gt_arg_1?), Box::new(__gt_arg_3?)))
+    }
+
+    // AddSubExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_15<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Sub(Box::new(__gt_arg_1?), Box::new(__gt_arg_3?)))
+    }
+
+    // AddSubExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_16<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // MulDivExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_17<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Mul(Box::new(__gt_arg_1?), Box::new(__gt_arg_3?)))
+    }
+
+    // MulDivExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_18<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Div(Box::new(__gt_arg_1?), Box::new(__gt_arg_3?)))
+    }
+
+    // MulDivExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_19<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Rem(Box::new(__gt_arg_1?), Box::new(__gt_arg_3?)))
+    }
+
+    // MulDivExpr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_20<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // Expr
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_21<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_22<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_2: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Not(Box::new(__gt_arg_2?)))
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_23<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_2: Result<Expr>,
+                     mut __gt_arg_3: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_2
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_24<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Lit(device::Value::Bool(true)))
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_25<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+Ok(Expr::Lit(device::Value::Bool(false)))
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_26<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+let s = get_str("literal integer", __gt_arg_1, __gt_lexer)?;
+
+          parse_int(s)
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_27<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+let s = get_str("literal floating point", __gt_arg_1, __gt_lexer)?;
+
+	  parse_flt(s)
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_28<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+let s = get_str("literal string", __gt_arg_1, __gt_lexer)?;
+
+	Ok(Expr::Lit(device::Value::Str(s[1..s.len() - 1].into())))
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_29<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+let s = get_str("literal color", __gt_arg_1, __gt_lexer)?;
+
+	match LinSrgba::<u8>::from_str(s) {
+	    Ok(v) => Ok(Expr::Lit(device::Value::Color(v))),
+	    Err(_) =>
+		match LinSrgb::<u8>::from_str(s) {
+		    Ok(v) =>
+		        Ok(Expr::Lit(device::Value::Color(v.with_alpha(255u8)))),
+		    Err(_) =>
+		        match named::from_str(s) {
+		            Some(v) => Ok(Expr::Lit(device::Value::Color(
+		                Srgb::<f32>::from_format(v)
+			            .into_linear()
+			            .into_format::<u8>()
+			            .with_alpha(255u8)
+		            ))),
+		            None => Err(Error::ParseError(
+			        format!("invalid color '{}'", s)
+		            ))
+		        }
+	        }
+	}
+    }
+
+    // Factor
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_30<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: Result<Expr>)
+                 -> Result<Expr> {
+        let _ = p;
+__gt_arg_1
+    }
+
+    // Device
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_31<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_4: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_5: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+let lexer = __gt_lexer;
+	let cat = get_str("built-in category", __gt_arg_2, lexer)?;
+	let fld = get_str("built-in field", __gt_arg_4, lexer)?;
+
+	parse_builtin(cat, fld)
+    }
+
+    // Device
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_32<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_2: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>,
+                     mut __gt_arg_3: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>)
+                 -> Result<Expr> {
+        let _ = p;
+let s = get_str("device name", __gt_arg_2, __gt_lexer)?;
+
+	Ok(Expr::Var(parse_device(s, p.0)?))
+    }
+
+    // Unknown
+    #[allow(clippy::too_many_arguments)]
+    fn __gt_action_33<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                     __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                     __gt_span: ::cfgrammar::Span,
+                     p: &(&[String], &[String]),
+                     mut __gt_arg_1: ::std::result::Result<lrlex::defaults::DefaultLexeme, lrlex::defaults::DefaultLexeme>) {
+        let _ = p;
+
+    }
+
+    mod _parser_ {
+        #![allow(clippy::type_complexity)]
+        #![allow(clippy::unnecessary_wraps)]
+        #![deny(unsafe_code)]
+        #[allow(unused_imports)]
+        use super::*;
+#[allow(dead_code)] const __GRM_DATA: &[u8] = &[13,0,0,0,13,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,94,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,5,0,0,0,0,0,0,0,76,111,103,105,99,190,1,0,0,0,0,0,0,195,1,0,0,0,0,0,0,8,0,0,0,0,0,0,0,66,111,111,108,69,120,112,114,224,2,0,0,0,0,0,0,232,2,0,0,0,0,0,0,6,0,0,0,0,0,0,0,79,114,69,120,112,114,19,3,0,0,0,0,0,0,25,3,0,0,0,0,0,0,7,0,0,0,0,0,0,0,65,110,100,69,120,112,114,167,3,0,0,0,0,0,0,174,3,0,0,0,0,0,0,7,0,0,0,0,0,0,0,67,109,112,69,120,112,114,63,4,0,0,0,0,0,0,70,4,0,0,0,0,0,0,7,0,0,0,0,0,0,0,78,117,109,69,120,112,114,119,7,0,0,0,0,0,0,126,7,0,0,0,0,0,0,10,0,0,0,0,0,0,0,65,100,100,83,117,98,69,120,112,114,173,7,0,0,0,0,0,0,183,7,0,0,0,0,0,0,10,0,0,0,0,0,0,0,77,117,108,68,105,118,69,120,112,114,140,8,0,0,0,0,0,0,150,8,0,0,0,0,0,0,4,0,0,0,0,0,0,0,69,120,112,114,165,9,0,0,0,0,0,0,169,9,0,0,0,0,0,0,6,0,0,0,0,0,0,0,70,97,99,116,111,114,212,9,0,0,0,0,0,0,218,9,0,0,0,0,0,0,6,0,0,0,0,0,0,0,68,101,118,105,99,101,242,14,0,0,0,0,0,0,248,14,0,0,0,0,0,0,7,0,0,0,0,0,0,0,85,110,107,110,111,119,110,113,16,0,0,0,0,0,0,120,16,0,0,0,0,0,0,29,0,0,0,0,0,0,0,1,103,0,0,0,0,0,0,0,106,0,0,0,0,0,0,0,3,0,0,0,0,0,0,0,73,78,84,1,123,0,0,0,0,0,0,0,126,0,0,0,0,0,0,0,3,0,0,0,0,0,0,0,70,76,84,1,143,0,0,0,0,0,0,0,153,0,0,0,0,0,0,0,10,0,0,0,0,0,0,0,73,68,69,78,84,73,70,73,69,82,1,170,0,0,0,0,0,0,0,174,0,0,0,0,0,0,0,4,0,0,0,0,0,0,0,84,82,85,69,1,191,0,0,0,0,0,0,0,196,0,0,0,0,0,0,0,5,0,0,0,0,0,0,0,70,65,76,83,69,1,230,1,0,0,0,0,0,0,237,1,0,0,0,0,0,0,7,0,0,0,0,0,0,0,67,79,78,84,82,79,76,1,240,1,0,0,0,0,0,0,246,1,0,0,0,0,0,0,6,0,0,0,0,0,0,0,76,66,82,65,67,69,1,6,2,0,0,0,0,0,0,12,2,0,0,0,0,0,0,6,0,0,0,0,0,0,0,82,66,82,65,67,69,1,58,3,0,0,0,0,0,0,62,3,0,0,0,0,0,0,4,0,0,0,0,0,0,0,66,95,79,82,1,207,3,0,0,0,0,0,0,212,3,0,0,0,0,0,0,5,0,0,0,0,0,0,0,66,95,65,78,68,1,103,4,0,0,0,0,0,0,105,4,0,0,0,0,0,0,2,0,0,0,0,0,0,0,69,81,1,210,4,0,0,0,0,0,0,212,4,0,0,0,0,0,0,2,0,0,0,0,0,0,0,78,69,1,162,5,0,0,0,0,0,0,164,5,0,0,0,0,0,0,2,0,0,0,0,0,0,0,71,84,1,13,6,0,0,0,0,0,0,18,6,0,0,0,0,0,0,5,0,0,0,0,0,0,0,71,84,95,69,81,1,134,6,0,0,0,0,0,0,136,6,0,0,0,0,0,0,2,0,0,0,0,0,0,0,76,84,1,241,6,0,0,0,0,0,0,246,6,0,0,0,0,0,0,5,0,0,0,0,0,0,0,76,84,95,69,81,1,219,7,0,0,0,0,0,0,222,7,0,0,0,0,0,0,3,0,0,0,0,0,0,0,65,68,68,1,45,8,0,0,0,0,0,0,48,8,0,0,0,0,0,0,3,0,0,0,0,0,0,0,83,85,66,1,180,8,0,0,0,0,0,0,183,8,0,0,0,0,0,0,3,0,0,0,0,0,0,0,77,85,76,1,0,9,0,0,0,0,0,0,3,9,0,0,0,0,0,0,3,0,0,0,0,0,0,0,68,73,86,1,76,9,0,0,0,0,0,0,79,9,0,0,0,0,0,0,3,0,0,0,0,0,0,0,82,69,77,1,243,9,0,0,0,0,0,0,248,9,0,0,0,0,0,0,5,0,0,0,0,0,0,0,66,95,78,79,84,1,41,10,0,0,0,0,0,0,42,10,0,0,0,0,0,0,1,0,0,0,0,0,0,0,40,1,54,10,0,0,0,0,0,0,55,10,0,0,0,0,0,0,1,0,0,0,0,0,0,0,41,1,141,11,0,0,0,0,0,0,147,11,0,0,0,0,0,0,6,0,0,0,0,0,0,0,83,84,82,73,78,71,1,23,12,0,0,0,0,0,0,28,12,0,0,0,0,0,0,5,0,0,0,0,0,0,0,67,79,76,79,82,1,37,15,0,0,0,0,0,0,42,15,0,0,0,0,0,0,5,0,0,0,0,0,0,0,67,79,76,79,78,1,133,16,0,0,0,0,0,0,140,16,0,0,0,0,0,0,7,0,0,0,0,0,0,0,85,78,75,78,79,87,78,0,29,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,29,0,0,0,0,0,0,0,1,3,0,0,0,0,0,0,0,73,78,84,1,3,0,0,0,0,0,0,0,70,76,84,1,10,0,0,0,0,0,0,0,73,68,69,78,84,73,70,73,69,82,1,4,0,0,0,0,0,0,0,84,82,85,69,1,5,0,0,0,0,0,0,0,70,65,76,83,69,1,7,0,0,0,0,0,0,0,67,79,78,84,82,79,76,1,1,0,0,0,0,0,0,0,123,1,1,0,0,0,0,0,0,0,125,1,2,0,0,0,0,0,0,0,111,114,1,3,0,0,0,0,0,0,0,97,110,100,1,1,0,0,0,0,0,0,0,61,1,2,0,0,0,0,0,0,0,60,62,1,1,0,0,0,0,0,0,0,62,1,2,0,0,0,0,0,0,0,62,61,1,1,0,0,0,0,0,0,0,60,1,2,0,0,0,0,0,0,0,60,61,1,1,0,0,0,0,0,0,0,43,1,1,0,0,0,0,0,0,0,45,1,1,0,0,0,0,0,0,0,42,1,1,0,0,0,0,0,0,0,47,1,1,0,0,0,0,0,0,0,37,1,3,0,0,0,0,0,0,0,110,111,116,1,1,0,0,0,0,0,0,0,40,1,1,0,0,0,0,0,0,0,41,1,6,0,0,0,0,0,0,0,83,84,82,73,78,71,1,5,0,0,0,0,0,0,0,67,79,76,79,82,1,1,0,0,0,0,0,0,0,58,1,7,0,0,0,0,0,0,0,85,78,75,78,79,87,78,0,29,0,0,0,28,0,0,0,35,0,0,0,34,0,0,0,35,0,0,0,0,0,0,0,5,0,0,0,0,0,0,0,0,0,0,0,2,0,0,0,1,0,0,0,5,0,0,0,1,0,0,0,6,0,0,0,1,0,0,0,2,0,0,0,1,0,0,0,7,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,3,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,4,0,0,0,1,0,0,0,8,0,0,0,0,0,0,0,3,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,4,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,5,0,0,0,1,0,0,0,9,0,0,0,0,0,0,0,4,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,5,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,10,0,0,0,0,0,0,0,6,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,11,0,0,0,0,0,0,0,6,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,12,0,0,0,0,0,0,0,6,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,13,0,0,0,0,0,0,0,6,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,14,0,0,0,0,0,0,0,6,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,15,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,6,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,7,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,8,0,0,0,1,0,0,0,16,0,0,0,0,0,0,0,7,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,8,0,0,0,1,0,0,0,17,0,0,0,0,0,0,0,7,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,8,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,9,0,0,0,1,0,0,0,18,0,0,0,0,0,0,0,8,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,9,0,0,0,1,0,0,0,19,0,0,0,0,0,0,0,8,0,0,0,3,0,0,0,0,0,0,0,0,0,0,0,9,0,0,0,1,0,0,0,20,0,0,0,0,0,0,0,8,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,9,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,10,0,0,0,2,0,0,0,0,0,0,0,1,0,0,0,21,0,0,0,0,0,0,0,10,0,0,0,3,0,0,0,0,0,0,0,1,0,0,0,22,0,0,0,0,0,0,0,2,0,0,0,1,0,0,0,23,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,3,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,4,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,1,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,24,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,25,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,11,0,0,0,5,0,0,0,0,0,0,0,1,0,0,0,6,0,0,0,1,0,0,0,2,0,0,0,1,0,0,0,26,0,0,0,1,0,0,0,2,0,0,0,1,0,0,0,7,0,0,0,3,0,0,0,0,0,0,0,1,0,0,0,6,0,0,0,1,0,0,0,2,0,0,0,1,0,0,0,7,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,27,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,1,0,0,0,13,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,34,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,1,0,0,0,2,0,0,0,0,0,0,0,2,0,0,0,3,0,0,0,2,0,0,0,0,0,0,0,4,0,0,0,5,0,0,0,7,0,0,0,0,0,0,0,6,0,0,0,7,0,0,0,8,0,0,0,9,0,0,0,10,0,0,0,11,0,0,0,12,0,0,0,1,0,0,0,0,0,0,0,13,0,0,0,3,0,0,0,0,0,0,0,14,0,0,0,15,0,0,0,16,0,0,0,4,0,0,0,0,0,0,0,17,0,0,0,18,0,0,0,19,0,0,0,20,0,0,0,1,0,0,0,0,0,0,0,21,0,0,0,9,0,0,0,0,0,0,0,22,0,0,0,23,0,0,0,24,0,0,0,25,0,0,0,26,0,0,0,27,0,0,0,28,0,0,0,29,0,0,0,30,0,0,0,2,0,0,0,0,0,0,0,31,0,0,0,32,0,0,0,1,0,0,0,0,0,0,0,33,0,0,0,35,0,0,0,0,0,0,0,1,0,0,0,2,0,0,0,3,0,0,0,3,0,0,0,4,0,0,0,4,0,0,0,5,0,0,0,5,0,0,0,5,0,0,0,5,0,0,0,5,0,0,0,5,0,0,0,5,0,0,0,6,0,0,0,7,0,0,0,7,0,0,0,7,0,0,0,8,0,0,0,8,0,0,0,8,0,0,0,8,0,0,0,9,0,0,0,10,0,0,0,10,0,0,0,10,0,0,0,10,0,0,0,10,0,0,0,10,0,0,0,10,0,0,0,10,0,0,0,10,0,0,0,11,0,0,0,11,0,0,0,12,0,0,0,0,0,0,0,35,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,35,0,0,0,0,0,0,0,1,189,0,0,0,0,0,0,0,108,101,116,32,118,32,61,32,36,52,46,109,97,112,95,101,114,114,40,124,95,124,32,69,114,114,111,114,58,58,80,97,114,115,101,69,114,114,111,114,40,10,9,32,32,32,32,32,32,32,32,83,116,114,105,110,103,58,58,102,114,111,109,40,34,101,114,114,111,114,32,114,101,97,100,105,110,103,32,116,97,114,103,101,116,32,100,101,118,105,99,101,34,41,10,32,32,32,32,32,32,32,32,32,32,32,32,41,41,63,59,10,9,108,101,116,32,115,32,61,32,36,108,101,120,101,114,46,115,112,97,110,95,115,116,114,40,118,46,115,112,97,110,40,41,41,59,10,10,9,79,107,40,80,114,111,103,114,97,109,40,36,49,63,44,32,112,97,114,115,101,95,100,101,118,105,99,101,40,115,44,32,112,46,49,41,63,41,41,1,2,0,0,0,0,0,0,0,36,49,1,63,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,79,114,40,10,9,9,9,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,44,10,9,9,9,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,10,9,9,9,32,32,41,41,1,2,0,0,0,0,0,0,0,36,49,1,64,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,65,110,100,40,10,9,9,9,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,44,10,9,9,9,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,10,9,9,9,32,32,41,41,1,2,0,0,0,0,0,0,0,36,49,1,74,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,69,113,40,10,9,9,9,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,44,10,9,9,9,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,10,9,9,9,32,32,32,32,32,41,41,1,175,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,78,111,116,40,10,9,9,32,32,32,32,32,32,32,32,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,69,120,112,114,58,58,69,113,40,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,44,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,41,10,9,9,9,32,32,32,32,32,32,32,32,41,10,9,9,32,32,32,32,32,32,32,32,32,32,32,32,32,41,41,1,74,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,76,116,40,10,9,9,9,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,44,10,9,9,9,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,10,9,9,9,32,32,32,32,32,41,41,1,85,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,76,116,69,113,40,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,44,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,10,9,9,9,32,32,32,32,32,32,32,32,41,41,1,74,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,76,116,40,10,9,9,9,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,44,10,9,9,9,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,10,9,9,9,32,32,32,32,32,41,41,1,85,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,76,116,69,113,40,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,49,63,41,44,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,66,111,120,58,58,110,101,119,40,36,51,63,41,10,9,9,9,32,32,32,32,32,32,32,32,41,41,1,2,0,0,0,0,0,0,0,36,49,1,2,0,0,0,0,0,0,0,36,49,1,43,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,65,100,100,40,66,111,120,58,58,110,101,119,40,36,49,63,41,44,32,66,111,120,58,58,110,101,119,40,36,51,63,41,41,41,1,43,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,83,117,98,40,66,111,120,58,58,110,101,119,40,36,49,63,41,44,32,66,111,120,58,58,110,101,119,40,36,51,63,41,41,41,1,2,0,0,0,0,0,0,0,36,49,1,43,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,77,117,108,40,66,111,120,58,58,110,101,119,40,36,49,63,41,44,32,66,111,120,58,58,110,101,119,40,36,51,63,41,41,41,1,43,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,68,105,118,40,66,111,120,58,58,110,101,119,40,36,49,63,41,44,32,66,111,120,58,58,110,101,119,40,36,51,63,41,41,41,1,43,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,82,101,109,40,66,111,120,58,58,110,101,119,40,36,49,63,41,44,32,66,111,120,58,58,110,101,119,40,36,51,63,41,41,41,1,2,0,0,0,0,0,0,0,36,49,1,2,0,0,0,0,0,0,0,36,49,1,28,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,78,111,116,40,66,111,120,58,58,110,101,119,40,36,50,63,41,41,41,1,2,0,0,0,0,0,0,0,36,50,1,40,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,66,111,111,108,40,116,114,117,101,41,41,41,1,41,0,0,0,0,0,0,0,79,107,40,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,66,111,111,108,40,102,97,108,115,101,41,41,41,1,72,0,0,0,0,0,0,0,108,101,116,32,115,32,61,32,103,101,116,95,115,116,114,40,34,108,105,116,101,114,97,108,32,105,110,116,101,103,101,114,34,44,32,36,49,44,32,36,108,101,120,101,114,41,63,59,10,10,32,32,32,32,32,32,32,32,32,32,112,97,114,115,101,95,105,110,116,40,115,41,1,72,0,0,0,0,0,0,0,108,101,116,32,115,32,61,32,103,101,116,95,115,116,114,40,34,108,105,116,101,114,97,108,32,102,108,111,97,116,105,110,103,32,112,111,105,110,116,34,44,32,36,49,44,32,36,108,101,120,101,114,41,63,59,10,10,9,32,32,112,97,114,115,101,95,102,108,116,40,115,41,1,109,0,0,0,0,0,0,0,108,101,116,32,115,32,61,32,103,101,116,95,115,116,114,40,34,108,105,116,101,114,97,108,32,115,116,114,105,110,103,34,44,32,36,49,44,32,36,108,101,120,101,114,41,63,59,10,10,9,79,107,40,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,83,116,114,40,115,91,49,46,46,115,46,108,101,110,40,41,32,45,32,49,93,46,105,110,116,111,40,41,41,41,41,1,171,2,0,0,0,0,0,0,108,101,116,32,115,32,61,32,103,101,116,95,115,116,114,40,34,108,105,116,101,114,97,108,32,99,111,108,111,114,34,44,32,36,49,44,32,36,108,101,120,101,114,41,63,59,10,10,9,109,97,116,99,104,32,76,105,110,83,114,103,98,97,58,58,60,117,56,62,58,58,102,114,111,109,95,115,116,114,40,115,41,32,123,10,9,32,32,32,32,79,107,40,118,41,32,61,62,32,79,107,40,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,67,111,108,111,114,40,118,41,41,41,44,10,9,32,32,32,32,69,114,114,40,95,41,32,61,62,10,9,9,109,97,116,99,104,32,76,105,110,83,114,103,98,58,58,60,117,56,62,58,58,102,114,111,109,95,115,116,114,40,115,41,32,123,10,9,9,32,32,32,32,79,107,40,118,41,32,61,62,10,9,9,32,32,32,32,32,32,32,32,79,107,40,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,67,111,108,111,114,40,118,46,119,105,116,104,95,97,108,112,104,97,40,50,53,53,117,56,41,41,41,41,44,10,9,9,32,32,32,32,69,114,114,40,95,41,32,61,62,10,9,9,32,32,32,32,32,32,32,32,109,97,116,99,104,32,110,97,109,101,100,58,58,102,114,111,109,95,115,116,114,40,115,41,32,123,10,9,9,32,32,32,32,32,32,32,32,32,32,32,32,83,111,109,101,40,118,41,32,61,62,32,79,107,40,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,67,111,108,111,114,40,10,9,9,32,32,32,32,32,32,32,32,32,32,32,32,32,32,32,32,83,114,103,98,58,58,60,102,51,50,62,58,58,102,114,111,109,95,102,111,114,109,97,116,40,118,41,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,32,46,105,110,116,111,95,108,105,110,101,97,114,40,41,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,32,46,105,110,116,111,95,102,111,114,109,97,116,58,58,60,117,56,62,40,41,10,9,9,9,32,32,32,32,32,32,32,32,32,32,32,32,46,119,105,116,104,95,97,108,112,104,97,40,50,53,53,117,56,41,10,9,9,32,32,32,32,32,32,32,32,32,32,32,32,41,41,41,44,10,9,9,32,32,32,32,32,32,32,32,32,32,32,32,78,111,110,101,32,61,62,32,69,114,114,40,69,114,114,111,114,58,58,80,97,114,115,101,69,114,114,111,114,40,10,9,9,9,32,32,32,32,32,32,32,32,102,111,114,109,97,116,33,40,34,105,110,118,97,108,105,100,32,99,111,108,111,114,32,39,123,125,39,34,44,32,115,41,10,9,9,32,32,32,32,32,32,32,32,32,32,32,32,41,41,10,9,9,32,32,32,32,32,32,32,32,125,10,9,32,32,32,32,32,32,32,32,125,10,9,125,1,2,0,0,0,0,0,0,0,36,49,1,148,0,0,0,0,0,0,0,108,101,116,32,108,101,120,101,114,32,61,32,36,108,101,120,101,114,59,10,9,108,101,116,32,99,97,116,32,61,32,103,101,116,95,115,116,114,40,34,98,117,105,108,116,45,105,110,32,99,97,116,101,103,111,114,121,34,44,32,36,50,44,32,108,101,120,101,114,41,63,59,10,9,108,101,116,32,102,108,100,32,61,32,103,101,116,95,115,116,114,40,34,98,117,105,108,116,45,105,110,32,102,105,101,108,100,34,44,32,36,52,44,32,108,101,120,101,114,41,63,59,10,10,9,112,97,114,115,101,95,98,117,105,108,116,105,110,40,99,97,116,44,32,102,108,100,41,1,83,0,0,0,0,0,0,0,108,101,116,32,115,32,61,32,103,101,116,95,115,116,114,40,34,100,101,118,105,99,101,32,110,97,109,101,34,44,32,36,50,44,32,36,108,101,120,101,114,41,63,59,10,10,9,79,107,40,69,120,112,114,58,58,86,97,114,40,112,97,114,115,101,95,100,101,118,105,99,101,40,115,44,32,112,46,48,41,63,41,41,1,0,0,0,0,0,0,0,0,0,1,1,0,0,0,0,0,0,0,112,23,0,0,0,0,0,0,0,38,40,38,91,83,116,114,105,110,103,93,44,32,38,91,83,116,114,105,110,103,93,41,1,120,25,0,0,0,0,0,0,117,115,101,32,100,114,109,101,109,95,97,112,105,58,58,123,82,101,115,117,108,116,44,32,69,114,114,111,114,44,32,100,101,118,105,99,101,125,59,10,117,115,101,32,99,104,114,111,110,111,58,58,123,84,105,109,101,108,105,107,101,44,32,68,97,116,101,108,105,107,101,125,59,10,117,115,101,32,112,97,108,101,116,116,101,58,58,123,76,105,110,83,114,103,98,97,44,32,76,105,110,83,114,103,98,44,32,83,114,103,98,44,32,110,97,109,101,100,44,32,87,105,116,104,65,108,112,104,97,125,59,10,117,115,101,32,115,117,112,101,114,58,58,123,84,105,109,101,70,105,101,108,100,44,32,83,111,108,97,114,70,105,101,108,100,44,32,115,117,112,101,114,58,58,116,111,100,44,32,115,117,112,101,114,58,58,115,111,108,97,114,44,32,69,120,112,114,44,32,80,114,111,103,114,97,109,125,59,10,117,115,101,32,115,116,100,58,58,115,116,114,58,58,70,114,111,109,83,116,114,59,10,10,117,115,101,32,108,114,108,101,120,58,58,123,68,101,102,97,117,108,116,76,101,120,101,109,101,44,32,68,101,102,97,117,108,116,76,101,120,101,114,84,121,112,101,115,125,59,10,117,115,101,32,108,114,112,97,114,58,58,78,111,110,83,116,114,101,97,109,105,110,103,76,101,120,101,114,59,10,10,47,47,32,84,104,105,115,32,99,111,109,112,108,105,99,97,116,101,100,32,98,101,97,115,116,32,105,115,32,97,110,32,97,116,116,101,109,112,116,32,116,111,32,114,101,109,111,118,101,32,116,104,101,32,98,111,105,108,101,114,112,108,97,116,101,32,99,111,100,101,10,47,47,32,117,115,101,100,32,119,104,101,110,32,112,114,111,99,101,115,115,105,110,103,32,116,104,101,32,116,101,114,109,105,110,97,108,32,116,111,107,101,110,115,32,40,105,46,101,46,32,116,104,101,32,108,101,97,102,32,118,97,108,117,101,115,32,111,102,10,47,47,32,116,104,101,32,101,120,112,114,101,115,115,105,111,110,32,116,114,101,101,46,41,10,10,102,110,32,103,101,116,95,115,116,114,60,39,97,44,32,39,105,110,112,117,116,62,40,10,32,32,32,32,108,97,98,101,108,58,32,38,39,97,32,115,116,114,44,10,32,32,32,32,108,101,120,101,109,101,58,32,115,116,100,58,58,114,101,115,117,108,116,58,58,82,101,115,117,108,116,60,68,101,102,97,117,108,116,76,101,120,101,109,101,44,32,68,101,102,97,117,108,116,76,101,120,101,109,101,62,44,10,32,32,32,32,108,101,120,101,114,58,32,38,39,97,32,40,100,121,110,32,78,111,110,83,116,114,101,97,109,105,110,103,76,101,120,101,114,60,39,105,110,112,117,116,44,32,68,101,102,97,117,108,116,76,101,120,101,114,84,121,112,101,115,62,32,43,32,39,97,41,10,41,32,45,62,32,82,101,115,117,108,116,60,38,39,105,110,112,117,116,32,115,116,114,62,32,123,10,32,32,32,32,108,101,116,32,108,101,120,101,109,101,32,61,32,108,101,120,101,109,101,46,109,97,112,95,101,114,114,40,124,95,124,32,69,114,114,111,114,58,58,80,97,114,115,101,69,114,114,111,114,40,10,32,32,32,32,32,32,32,32,102,111,114,109,97,116,33,40,34,101,114,114,111,114,32,114,101,97,100,105,110,103,32,123,125,34,44,32,108,97,98,101,108,41,10,32,32,32,32,41,41,63,59,10,10,32,32,32,32,79,107,40,108,101,120,101,114,46,115,112,97,110,95,115,116,114,40,108,101,120,101,109,101,46,115,112,97,110,40,41,41,41,10,125,10,10,47,47,32,65,110,121,32,102,117,110,99,116,105,111,110,115,32,104,101,114,101,32,97,114,101,32,105,110,32,115,99,111,112,101,32,102,111,114,32,97,108,108,32,116,104,101,32,103,114,97,109,109,97,114,32,97,99,116,105,111,110,115,32,97,98,111,118,101,46,10,10,102,110,32,112,97,114,115,101,95,105,110,116,40,115,58,32,38,115,116,114,41,32,45,62,32,82,101,115,117,108,116,60,69,120,112,114,62,32,123,10,32,32,32,32,115,46,112,97,114,115,101,58,58,60,105,51,50,62,40,41,10,9,46,109,97,112,40,124,118,124,32,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,118,41,41,41,10,9,46,109,97,112,95,101,114,114,40,124,95,124,32,69,114,114,111,114,58,58,80,97,114,115,101,69,114,114,111,114,40,10,9,32,32,32,32,32,102,111,114,109,97,116,33,40,34,123,125,32,99,97,110,110,111,116,32,98,101,32,114,101,112,114,101,115,101,110,116,101,100,32,97,115,32,97,110,32,105,51,50,34,44,32,115,41,10,9,41,41,10,125,10,10,102,110,32,112,97,114,115,101,95,102,108,116,40,115,58,32,38,115,116,114,41,32,45,62,32,82,101,115,117,108,116,60,69,120,112,114,62,32,123,10,32,32,32,32,115,46,112,97,114,115,101,58,58,60,102,54,52,62,40,41,10,9,46,109,97,112,40,124,118,124,32,69,120,112,114,58,58,76,105,116,40,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,70,108,116,40,118,41,41,41,10,9,46,109,97,112,95,101,114,114,40,124,95,124,32,69,114,114,111,114,58,58,80,97,114,115,101,69,114,114,111,114,40,10,9,32,32,32,32,32,102,111,114,109,97,116,33,40,34,123,125,32,99,97,110,110,111,116,32,98,101,32,114,101,112,114,101,115,101,110,116,101,100,32,97,115,32,97,110,32,102,54,52,34,44,32,115,41,10,9,41,41,10,125,10,10,102,110,32,112,97,114,115,101,95,100,101,118,105,99,101,40,110,97,109,101,58,32,38,115,116,114,44,32,101,110,118,58,32,38,91,83,116,114,105,110,103,93,41,32,45,62,32,82,101,115,117,108,116,60,117,115,105,122,101,62,32,123,10,32,32,32,32,102,111,114,32,105,105,32,105,110,32,101,110,118,46,105,116,101,114,40,41,46,101,110,117,109,101,114,97,116,101,40,41,32,123,10,32,32,32,32,32,32,32,32,105,102,32,42,105,105,46,49,32,61,61,32,110,97,109,101,32,123,10,9,32,32,32,32,114,101,116,117,114,110,32,79,107,40,105,105,46,48,41,59,10,9,125,10,32,32,32,32,125,10,32,32,32,32,69,114,114,40,69,114,114,111,114,58,58,80,97,114,115,101,69,114,114,111,114,40,102,111,114,109,97,116,33,40,34,118,97,114,105,97,98,108,101,32,39,123,125,39,32,105,115,32,110,111,116,32,100,101,102,105,110,101,100,34,44,32,38,110,97,109,101,41,41,41,10,125,10,10,99,111,110,115,116,32,67,65,84,95,85,84,67,58,32,38,115,116,114,32,61,32,34,117,116,99,34,59,10,99,111,110,115,116,32,67,65,84,95,76,79,67,65,76,58,32,38,115,116,114,32,61,32,34,108,111,99,97,108,34,59,10,99,111,110,115,116,32,67,65,84,95,83,79,76,65,82,58,32,38,115,116,114,32,61,32,34,115,111,108,97,114,34,59,10,10,99,111,110,115,116,32,70,76,68,95,83,69,67,79,78,68,58,32,38,115,116,114,32,61,32,34,115,101,99,111,110,100,34,59,10,99,111,110,115,116,32,70,76,68,95,77,73,78,85,84,69,58,32,38,115,116,114,32,61,32,34,109,105,110,117,116,101,34,59,10,99,111,110,115,116,32,70,76,68,95,72,79,85,82,58,32,38,115,116,114,32,61,32,34,104,111,117,114,34,59,10,99,111,110,115,116,32,70,76,68,95,68,65,89,58,32,38,115,116,114,32,61,32,34,100,97,121,34,59,10,99,111,110,115,116,32,70,76,68,95,77,79,78,84,72,58,32,38,115,116,114,32,61,32,34,109,111,110,116,104,34,59,10,99,111,110,115,116,32,70,76,68,95,89,69,65,82,58,32,38,115,116,114,32,61,32,34,121,101,97,114,34,59,10,99,111,110,115,116,32,70,76,68,95,68,79,87,58,32,38,115,116,114,32,61,32,34,68,79,87,34,59,10,99,111,110,115,116,32,70,76,68,95,68,79,89,58,32,38,115,116,114,32,61,32,34,68,79,89,34,59,10,99,111,110,115,116,32,70,76,68,95,65,76,84,58,32,38,115,116,114,32,61,32,34,97,108,116,34,59,10,99,111,110,115,116,32,70,76,68,95,65,90,58,32,38,115,116,114,32,61,32,34,97,122,34,59,10,99,111,110,115,116,32,70,76,68,95,82,65,58,32,38,115,116,114,32,61,32,34,114,97,34,59,10,99,111,110,115,116,32,70,76,68,95,68,69,67,58,32,38,115,116,114,32,61,32,34,100,101,99,34,59,10,10,102,110,32,103,101,116,95,117,116,99,95,115,101,99,111,110,100,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,115,101,99,111,110,100,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,117,116,99,95,109,105,110,117,116,101,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,109,105,110,117,116,101,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,117,116,99,95,104,111,117,114,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,104,111,117,114,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,117,116,99,95,100,97,121,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,100,97,121,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,117,116,99,95,100,97,121,95,111,102,95,119,101,101,107,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,119,101,101,107,100,97,121,40,41,46,110,117,109,95,100,97,121,115,95,102,114,111,109,95,109,111,110,100,97,121,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,117,116,99,95,109,111,110,116,104,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,109,111,110,116,104,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,117,116,99,95,121,101,97,114,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,121,101,97,114,40,41,41,10,125,10,10,102,110,32,103,101,116,95,117,116,99,95,100,97,121,95,111,102,95,121,101,97,114,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,48,46,111,114,100,105,110,97,108,48,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,115,101,99,111,110,100,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,115,101,99,111,110,100,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,109,105,110,117,116,101,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,109,105,110,117,116,101,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,104,111,117,114,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,104,111,117,114,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,100,97,121,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,100,97,121,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,100,97,121,95,111,102,95,119,101,101,107,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,119,101,101,107,100,97,121,40,41,46,110,117,109,95,100,97,121,115,95,102,114,111,109,95,109,111,110,100,97,121,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,109,111,110,116,104,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,109,111,110,116,104,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,121,101,97,114,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,121,101,97,114,40,41,41,10,125,10,10,102,110,32,103,101,116,95,108,111,99,97,108,95,100,97,121,95,111,102,95,121,101,97,114,40,105,110,102,111,58,32,38,116,111,100,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,73,110,116,40,105,110,102,111,46,49,46,111,114,100,105,110,97,108,48,40,41,32,97,115,32,105,51,50,41,10,125,10,10,102,110,32,103,101,116,95,115,111,108,97,114,95,97,108,116,105,116,117,100,101,40,105,110,102,111,58,32,38,115,111,108,97,114,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,70,108,116,40,105,110,102,111,46,101,108,101,118,97,116,105,111,110,41,10,125,10,10,102,110,32,103,101,116,95,115,111,108,97,114,95,97,122,105,109,117,116,104,40,105,110,102,111,58,32,38,115,111,108,97,114,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,70,108,116,40,105,110,102,111,46,97,122,105,109,117,116,104,41,10,125,10,10,102,110,32,103,101,116,95,115,111,108,97,114,95,114,105,103,104,116,95,97,115,99,101,110,115,105,111,110,40,105,110,102,111,58,32,38,115,111,108,97,114,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,70,108,116,40,105,110,102,111,46,114,105,103,104,116,95,97,115,99,101,110,115,105,111,110,41,10,125,10,10,102,110,32,103,101,116,95,115,111,108,97,114,95,100,101,99,108,105,110,97,116,105,111,110,40,105,110,102,111,58,32,38,115,111,108,97,114,58,58,73,110,102,111,41,32,45,62,32,100,101,118,105,99,101,58,58,86,97,108,117,101,32,123,10,32,32,32,32,100,101,118,105,99,101,58,58,86,97,108,117,101,58,58,70,108,116,40,105,110,102,111,46,100,101,99,108,105,110,97,116,105,111,110,41,10,125,10,10,102,110,32,112,97,114,115,101,95,98,117,105,108,116,105,110,40,99,97,116,58,32,38,115,116,114,44,32,102,108,100,58,32,38,115,116,114,41,32,45,62,32,82,101,115,117,108,116,60,69,120,112,114,62,32,123,10,32,32,32,32,109,97,116,99,104,32,40,99,97,116,44,32,102,108,100,41,32,123,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,83,69,67,79,78,68,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,9,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,83,101,99,111,110,100,44,32,103,101,116,95,117,116,99,95,115,101,99,111,110,100,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,77,73,78,85,84,69,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,77,105,110,117,116,101,44,32,103,101,116,95,117,116,99,95,109,105,110,117,116,101,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,72,79,85,82,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,9,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,72,111,117,114,44,32,103,101,116,95,117,116,99,95,104,111,117,114,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,68,65,89,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,9,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,68,97,121,44,32,103,101,116,95,117,116,99,95,100,97,121,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,68,79,87,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,68,111,87,44,32,103,101,116,95,117,116,99,95,100,97,121,95,111,102,95,119,101,101,107,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,77,79,78,84,72,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,77,111,110,116,104,44,32,103,101,116,95,117,116,99,95,109,111,110,116,104,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,89,69,65,82,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,89,101,97,114,44,32,103,101,116,95,117,116,99,95,121,101,97,114,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,85,84,67,44,32,70,76,68,95,68,79,89,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,85,84,67,44,32,84,105,109,101,70,105,101,108,100,58,58,68,111,89,44,32,103,101,116,95,117,116,99,95,100,97,121,95,111,102,95,121,101,97,114,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,83,69,67,79,78,68,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,83,101,99,111,110,100,44,32,103,101,116,95,108,111,99,97,108,95,115,101,99,111,110,100,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,77,73,78,85,84,69,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,77,105,110,117,116,101,44,32,103,101,116,95,108,111,99,97,108,95,109,105,110,117,116,101,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,72,79,85,82,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,72,111,117,114,44,32,103,101,116,95,108,111,99,97,108,95,104,111,117,114,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,68,65,89,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,68,97,121,44,32,103,101,116,95,108,111,99,97,108,95,100,97,121,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,68,79,87,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,68,111,87,44,32,103,101,116,95,108,111,99,97,108,95,100,97,121,95,111,102,95,119,101,101,107,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,77,79,78,84,72,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,77,111,110,116,104,44,32,103,101,116,95,108,111,99,97,108,95,109,111,110,116,104,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,89,69,65,82,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,89,101,97,114,44,32,103,101,116,95,108,111,99,97,108,95,121,101,97,114,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,76,79,67,65,76,44,32,70,76,68,95,68,79,89,41,32,61,62,32,79,107,40,69,120,112,114,58,58,84,105,109,101,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,67,65,84,95,76,79,67,65,76,44,32,84,105,109,101,70,105,101,108,100,58,58,68,111,89,44,32,103,101,116,95,108,111,99,97,108,95,100,97,121,95,111,102,95,121,101,97,114,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,83,79,76,65,82,44,32,70,76,68,95,65,76,84,41,32,61,62,32,79,107,40,69,120,112,114,58,58,83,111,108,97,114,86,97,108,40,10,9,32,32,32,32,83,111,108,97,114,70,105,101,108,100,58,58,69,108,101,118,97,116,105,111,110,44,32,103,101,116,95,115,111,108,97,114,95,97,108,116,105,116,117,100,101,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,83,79,76,65,82,44,32,70,76,68,95,65,90,41,32,61,62,32,79,107,40,69,120,112,114,58,58,83,111,108,97,114,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,83,111,108,97,114,70,105,101,108,100,58,58,65,122,105,109,117,116,104,44,32,103,101,116,95,115,111,108,97,114,95,97,122,105,109,117,116,104,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,83,79,76,65,82,44,32,70,76,68,95,82,65,41,32,61,62,32,79,107,40,69,120,112,114,58,58,83,111,108,97,114,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,83,111,108,97,114,70,105,101,108,100,58,58,82,105,103,104,116,65,115,99,101,110,115,105,111,110,44,32,103,101,116,95,115,111,108,97,114,95,114,105,103,104,116,95,97,115,99,101,110,115,105,111,110,10,32,32,32,32,32,32,32,32,41,41,44,10,9,40,67,65,84,95,83,79,76,65,82,44,32,70,76,68,95,68,69,67,41,32,61,62,32,79,107,40,69,120,112,114,58,58,83,111,108,97,114,86,97,108,40,10,32,32,32,32,32,32,32,32,32,32,32,32,83,111,108,97,114,70,105,101,108,100,58,58,68,101,99,108,105,110,97,116,105,111,110,44,32,103,101,116,95,115,111,108,97,114,95,100,101,99,108,105,110,97,116,105,111,110,10,32,32,32,32,32,32,32,32,41,41,44,10,9,95,32,61,62,32,69,114,114,40,69,114,114,111,114,58,58,80,97,114,115,101,69,114,114,111,114,40,10,9,9,32,102,111,114,109,97,116,33,40,34,117,110,107,110,111,119,110,32,98,117,105,108,116,45,105,110,58,32,123,125,58,123,125,34,44,32,99,97,116,44,32,102,108,100,41,10,9,32,32,32,32,32,41,41,10,32,32,32,32,125,10,125,10,13,0,0,0,0,0,0,0,0,1,15,0,0,0,0,0,0,0,82,101,115,117,108,116,60,80,114,111,103,114,97,109,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,12,0,0,0,0,0,0,0,82,101,115,117,108,116,60,69,120,112,114,62,1,2,0,0,0,0,0,0,0,40,41,1,29,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,31,0,0,0,0,0,0,0,0,0,];
+#[allow(dead_code)] const __STABLE_DATA: &[u8] = &[59,0,0,0,0,0,0,0,64,1,0,0,0,0,0,0,27,1,0,0,0,0,0,0,16,1,0,0,0,0,0,0,1,0,0,0,0,0,0,0,99,1,0,0,0,0,0,0,208,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,64,1,0,0,0,0,0,0,16,0,0,0,0,0,0,0,32,0,0,0,0,0,0,0,48,0,0,0,0,0,0,0,64,0,0,0,0,0,0,0,221,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,10,0,0,0,0,0,0,0,32,1,0,0,0,0,0,0,80,0,0,0,0,0,0,0,64,1,0,0,0,0,0,0,96,0,0,0,0,0,0,0,112,0,0,0,0,0,0,0,128,0,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,1,0,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,144,0,0,0,0,0,0,0,220,0,0,0,0,0,0,0,31,0,0,0,0,0,0,0,101,1,0,0,0,0,0,0,43,1,0,0,0,0,0,0,75,1,0,0,0,0,0,0,85,1,0,0,0,0,0,0,87,1,0,0,0,0,0,0,92,1,0,0,0,0,0,0,94,1,0,0,0,0,0,0,1,0,0,0,0,0,0,0,237,0,0,0,0,0,0,0,48,1,0,0,0,0,0,0,64,1,0,0,0,0,0,0,224,0,0,0,0,0,0,0,240,0,0,0,0,0,0,0,0,1,0,0,0,0,0,0,2,0,0,0,0,0,0,0,160,0,0,0,0,0,0,0,176,0,0,0,0,0,0,0,15,0,0,0,0,0,0,0,63,0,0,0,0,0,0,0,58,0,0,0,0,0,0,0,192,0,0,0,0,0,0,0,29,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,175,6,0,0,0,0,0,0,27,0,0,0,0,0,0,0,164,255,159,252,155,255,239,127,3,252,253,239,255,255,255,237,255,247,191,1,248,254,55,0,216,39,253,255,228,223,0,96,255,27,0,236,127,3,128,253,111,0,176,255,253,255,247,247,255,255,255,255,255,255,251,6,248,251,223,0,96,159,244,255,147,127,3,128,253,111,0,176,255,13,0,246,73,255,63,57,233,255,39,39,253,255,228,164,255,159,156,244,255,147,147,254,127,114,210,255,79,254,251,255,255,73,255,63,57,233,255,39,39,253,255,228,164,255,159,156,244,255,147,147,254,127,242,111,0,176,255,247,255,191,255,255,255,254,183,255,223,255,230,255,251,223,252,127,255,155,255,239,127,243,255,253,111,254,191,255,205,255,247,247,255,255,255,247,255,223,255,6,248,251,223,0,127,255,27,128,239,127,3,240,253,111,0,190,191,255,255,255,191,1,192,254,55,0,216,255,251,255,255,127,255,255,255,255,255,255,125,3,128,125,0,0,130,1,0,0,0,0,0,0,49,0,0,0,0,0,0,0,181,113,82,225,221,145,0,0,82,82,82,82,82,82,82,82,82,229,114,137,133,129,82,82,114,114,114,114,114,114,114,114,114,3,122,114,114,114,114,114,122,122,122,122,122,122,122,122,122,217,98,122,122,122,122,122,98,98,98,98,98,98,98,98,98,233,106,98,98,98,98,98,106,106,106,106,106,106,106,106,106,2,86,106,106,106,106,106,86,86,86,86,86,86,86,86,86,0,118,86,86,86,86,86,118,118,118,118,118,118,118,118,118,0,102,118,118,118,118,118,102,102,102,102,102,102,102,102,102,0,110,102,102,102,102,102,110,110,110,110,110,110,110,110,110,0,90,110,110,110,110,110,90,90,90,90,90,90,90,90,90,0,130,90,90,90,90,90,130,130,130,130,130,130,130,130,130,0,94,130,130,130,130,130,94,94,94,94,94,94,94,94,94,0,126,94,94,94,94,94,126,126,126,126,126,126,126,126,126,0,66,126,126,126,126,126,66,66,66,66,66,66,66,66,66,0,70,0,213,6,125,121,70,70,70,70,70,70,70,70,70,209,74,6,0,10,70,70,74,74,74,74,74,74,74,74,74,0,78,10,0,0,74,74,78,78,78,78,78,78,78,78,78,0,50,0,0,0,78,78,109,105,101,97,93,89,50,50,50,0,54,85,22,0,0,22,54,54,54,54,54,54,54,54,54,0,58,26,26,22,0,26,58,58,58,58,58,58,58,58,58,53,62,77,41,26,81,45,62,62,62,62,62,62,62,62,62,69,29,30,30,0,0,30,38,34,34,38,0,34,73,33,46,46,42,42,46,30,42,38,0,38,18,34,117,18,0,14,0,0,46,0,42,0,0,0,0,0,0,18,0,14,0,0,0,0,0,0,0,0,0,0,8,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,175,6,0,0,0,0,0,0,27,0,0,0,0,0,0,0,91,0,96,3,100,0,16,128,252,3,2,16,0,0,0,18,0,8,64,254,7,1,200,255,39,216,2,0,27,32,255,159,0,228,255,19,128,252,127,2,144,255,79,0,2,0,8,8,0,0,0,0,0,0,4,249,7,4,32,255,159,96,11,0,108,128,252,127,2,144,255,79,0,242,255,9,182,0,192,198,22,0,216,216,2,0,27,91,0,96,99,11,0,108,108,1,128,141,45,0,176,1,4,0,0,182,0,192,198,22,0,216,216,2,0,27,91,0,96,99,11,0,108,108,1,128,13,144,255,79,0,8,0,64,0,0,0,1,72,0,32,0,25,0,4,32,3,128,0,100,0,16,128,12,0,2,144,1,64,0,50,0,8,8,0,0,0,8,0,32,0,249,7,4,32,255,128,0,228,127,16,128,252,15,2,144,255,65,64,0,0,0,64,254,63,1,200,255,39,0,4,0,0,128,0,0,0,0,0,0,130,252,127,2,0,0,59,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,87,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,10,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,27,0,0,0,0,0,0,0,33,0,0,0,0,0,0,0,39,0,0,0,0,0,0,0,45,0,0,0,0,0,0,0,51,0,0,0,0,0,0,0,57,0,0,0,0,0,0,0,63,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,19,0,0,0,0,0,0,0,68,0,0,0,0,0,0,0,73,0,0,0,0,0,0,0,77,0,0,0,0,0,0,0,81,0,0,0,0,0,0,0,85,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,13,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,255,2,0,0,0,0,0,0,12,0,0,0,0,0,0,0,1,240,255,255,255,255,255,255,255,255,255,255,159,255,255,255,255,255,255,255,255,255,255,255,255,255,255,127,0,254,255,255,255,255,31,224,15,252,129,63,240,7,254,192,31,248,255,15,224,31,252,131,255,240,31,254,195,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,255,127,100,0,0,0,0,0,0,0,10,0,0,0,0,0,0,0,116,24,208,32,20,13,241,0,68,135,1,13,66,209,152,74,167,18,29,6,52,8,197,171,97,64,168,18,29,6,52,8,64,170,18,29,6,148,42,209,172,18,29,6,180,42,209,97,18,29,6,212,42,209,97,64,43,209,97,196,74,116,24,176,209,65,43,209,49,43,209,33,0,0,0,0,0,128,66,42,6,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,17,8,0,0,0,0,0,0,33,0,0,0,0,0,0,0,0,0,0,0,0,1,0,0,0,0,4,0,0,0,0,0,0,128,0,0,0,0,0,128,0,0,0,0,64,0,0,0,0,0,0,0,0,0,16,0,0,0,0,2,0,0,64,0,0,0,0,8,32,0,0,0,0,0,0,0,0,0,0,0,0,0,0,4,0,0,0,0,32,0,0,0,0,0,0,0,0,0,8,0,0,0,4,0,0,0,128,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,128,0,0,0,0,0,0,0,0,0,0,64,0,0,0,0,8,0,0,0,128,0,0,0,0,8,0,0,0,128,0,0,0,0,8,0,0,0,128,0,0,0,0,0,0,0,16,0,0,0,0,0,8,0,0,0,128,0,0,0,0,16,0,0,0,0,1,0,0,0,16,0,0,0,0,0,0,0,0,0,128,0,0,0,2,0,0,0,0,0,0,0,0,0,8,0,0,0,0,0,0,0,32,0,0,0,0,0,0,175,6,0,0,0,0,0,0,27,0,0,0,0,0,0,0,91,0,96,3,64,0,0,0,240,3,0,16,0,0,0,16,0,0,0,0,6,0,0,0,7,216,2,0,27,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,8,0,0,0,0,0,0,0,0,0,0,0,0,0,96,11,0,108,0,0,0,0,0,0,0,0,0,0,0,182,0,192,198,22,0,216,216,2,0,27,91,0,96,99,11,0,108,108,1,128,141,45,0,176,1,4,0,0,182,0,192,198,22,0,216,216,2,0,27,91,0,96,99,11,0,108,108,1,128,13,0,0,0,0,8,0,64,0,0,0,1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,8,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,64,0,0,0,0,0,0,0,0,0,0,0,4,0,0,128,0,0,0,0,0,0,0,0,0,0,0,0,59,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,0,159,29,0,200,223,111,6,35,0,0,0,29,0,0,0,0,];
+
+    #[allow(dead_code)]
+    pub fn parse<'lexer, 'input: 'lexer>(
+        lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>, p: &(&[String], &[String]))
+          -> (::std::option::Option<Result<Program>>, ::std::vec::Vec<::lrpar::LexParseError<u32, lrlex::defaults::DefaultLexerTypes>>)
+    {
+        let (grm, stable) = ::lrpar::ctbuilder::_reconstitute(__GRM_DATA, __STABLE_DATA);
+        #[allow(clippy::type_complexity)]
+        let actions: ::std::vec::Vec<&dyn Fn(::cfgrammar::RIdx<u32>,
+                       &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                       ::cfgrammar::Span,
+                       ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                       &(&[String], &[String]))
+                    -> __GtActionsKind<'input>> = ::std::vec![&__gt_wrapper_0,
+                        &__gt_wrapper_1,
+                        &__gt_wrapper_2,
+                        &__gt_wrapper_3,
+                        &__gt_wrapper_4,
+                        &__gt_wrapper_5,
+                        &__gt_wrapper_6,
+                        &__gt_wrapper_7,
+                        &__gt_wrapper_8,
+                        &__gt_wrapper_9,
+                        &__gt_wrapper_10,
+                        &__gt_wrapper_11,
+                        &__gt_wrapper_12,
+                        &__gt_wrapper_13,
+                        &__gt_wrapper_14,
+                        &__gt_wrapper_15,
+                        &__gt_wrapper_16,
+                        &__gt_wrapper_17,
+                        &__gt_wrapper_18,
+                        &__gt_wrapper_19,
+                        &__gt_wrapper_20,
+                        &__gt_wrapper_21,
+                        &__gt_wrapper_22,
+                        &__gt_wrapper_23,
+                        &__gt_wrapper_24,
+                        &__gt_wrapper_25,
+                        &__gt_wrapper_26,
+                        &__gt_wrapper_27,
+                        &__gt_wrapper_28,
+                        &__gt_wrapper_29,
+                        &__gt_wrapper_30,
+                        &__gt_wrapper_31,
+                        &__gt_wrapper_32,
+                        &__gt_wrapper_33,
+                        &__gt_wrapper_34];
+
+        match ::lrpar::RTParserBuilder::new(&grm, &stable)
+            .recoverer(::lrpar::RecoveryKind::None)
+            .parse_actions(lexer, &actions, p) {
+                (Some(__GtActionsKind::Ak1(x)), y) => (Some(x), y),
+                (None, y) => (None, y),
+                _ => unreachable!()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub const R_LOGIC: u32 = 1;
+    #[allow(dead_code)]
+    pub const R_BOOLEXPR: u32 = 2;
+    #[allow(dead_code)]
+    pub const R_OREXPR: u32 = 3;
+    #[allow(dead_code)]
+    pub const R_ANDEXPR: u32 = 4;
+    #[allow(dead_code)]
+    pub const R_CMPEXPR: u32 = 5;
+    #[allow(dead_code)]
+    pub const R_NUMEXPR: u32 = 6;
+    #[allow(dead_code)]
+    pub const R_ADDSUBEXPR: u32 = 7;
+    #[allow(dead_code)]
+    pub const R_MULDIVEXPR: u32 = 8;
+    #[allow(dead_code)]
+    pub const R_EXPR: u32 = 9;
+    #[allow(dead_code)]
+    pub const R_FACTOR: u32 = 10;
+    #[allow(dead_code)]
+    pub const R_DEVICE: u32 = 11;
+    #[allow(dead_code)]
+    pub const R_UNKNOWN: u32 = 12;
+    const __GT_EPP: &[::std::option::Option<&str>] = &[Some("INT"), Some("FLT"), Some("IDENTIFIER"), Some("TRUE"), Some("FALSE"), Some("CONTROL"), Some("{"), Some("}"), Some("or"), Some("and"), Some("="), Some("<>"), Some(">"), Some(">="), Some("<"), Some("<="), Some("+"), Some("-"), Some("*"), Some("/"), Some("%"), Some("not"), Some("("), Some(")"), Some("STRING"), Some("COLOR"), Some(":"), Some("UNKNOWN"), None];
+
+    /// Return the %epp entry for token `tidx` (where `None` indicates "the token has no
+    /// pretty-printed value"). Panics if `tidx` doesn't exist.
+    #[allow(dead_code)]
+    pub fn token_epp<'a>(tidx: ::cfgrammar::TIdx<u32>) -> ::std::option::Option<&'a str> {
+        __GT_EPP[usize::from(tidx)]
+    }
+
+    // Wrappers
+
+    fn __gt_wrapper_0<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak2(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_4 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_5 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak1(__gt_action_0(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3, __gt_arg_4, __gt_arg_5))
+    }
+
+    fn __gt_wrapper_1<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak3(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak2(__gt_action_1(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_2<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak4(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak3(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak3(__gt_action_2(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_3<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak4(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak3(__gt_action_3(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_4<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak5(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak4(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak4(__gt_action_4(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_5<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak5(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak4(__gt_action_5(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_6<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak5(__gt_action_6(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_7<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak5(__gt_action_7(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_8<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak5(__gt_action_8(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_9<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak5(__gt_action_9(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_10<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak5(__gt_action_10(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_11<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak5(__gt_action_11(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_12<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak6(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak5(__gt_action_12(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_13<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak7(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak6(__gt_action_13(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_14<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak8(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak7(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak7(__gt_action_14(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_15<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak8(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak7(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak7(__gt_action_15(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_16<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak8(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak7(__gt_action_16(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_17<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak9(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak8(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak8(__gt_action_17(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_18<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak9(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak8(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak8(__gt_action_18(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_19<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak9(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak8(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak8(__gt_action_19(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_20<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak9(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak8(__gt_action_20(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_21<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak10(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak9(__gt_action_21(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_22<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak10(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_22(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2))
+    }
+
+    fn __gt_wrapper_23<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak2(x)) => x,
+            _ => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_23(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_24<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_24(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_25<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_25(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_26<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_26(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_27<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_27(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_28<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_28(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_29<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_29(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_30<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::ActionType(__GtActionsKind::Ak11(x)) => x,
+            _ => unreachable!()
+        };
+        __GtActionsKind::Ak10(__gt_action_30(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1))
+    }
+
+    fn __gt_wrapper_31<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_4 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_5 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak11(__gt_action_31(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3, __gt_arg_4, __gt_arg_5))
+    }
+
+    fn __gt_wrapper_32<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_2 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        let __gt_arg_3 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __GtActionsKind::Ak11(__gt_action_32(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1, __gt_arg_2, __gt_arg_3))
+    }
+
+    fn __gt_wrapper_33<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let __gt_arg_1 = match __gt_args.next().unwrap() {
+            ::lrpar::parser::AStackType::Lexeme(l) => {
+                if l.faulty() {
+                    Err(l)
+                } else {
+                    Ok(l)
+                }
+            },
+            ::lrpar::parser::AStackType::ActionType(_) => unreachable!()
+        };
+        __gt_action_33(__gt_ridx, __gt_lexer, __gt_span, p, __gt_arg_1);
+        __GtActionsKind::Ak12(())
+    }
+
+    fn __gt_wrapper_34<'lexer, 'input: 'lexer>(__gt_ridx: ::cfgrammar::RIdx<u32>,
+                      __gt_lexer: &'lexer dyn ::lrpar::NonStreamingLexer<'input, lrlex::defaults::DefaultLexerTypes>,
+                      __gt_span: ::cfgrammar::Span,
+                      mut __gt_args: ::std::vec::Drain<'_,  ::lrpar::parser::AStackType<<lrlex::defaults::DefaultLexerTypes as ::lrpar::LexerTypes>::LexemeT, __GtActionsKind<'input>>>,
+                      p: &(&[String], &[String]))
+                   -> __GtActionsKind<'input> {
+        let _ = p;
+        unreachable!()
+    }
+
+    #[allow(dead_code)]
+    enum __GtActionsKind<'input> {
+        Ak1(Result<Program>),
+        Ak2(Result<Expr>),
+        Ak3(Result<Expr>),
+        Ak4(Result<Expr>),
+        Ak5(Result<Expr>),
+        Ak6(Result<Expr>),
+        Ak7(Result<Expr>),
+        Ak8(Result<Expr>),
+        Ak9(Result<Expr>),
+        Ak10(Result<Expr>),
+        Ak11(Result<Expr>),
+        Ak12(()),
+    ___GtActionsKindHidden(::std::marker::PhantomData<&'input ()>)
+    }
+
+    } // End of `mod _parser_`
+
+    #[allow(unused_imports)]
+    pub use _parser_::*;
+    #[allow(unused_imports)]
+    use ::lrpar::Lexeme;
+} // End of `mod {mod_name}` 
+
+
+/* CACHE INFORMATION
+   Build time: "2026-10-18T06:18:00.854909756Z"
+   Grammar path: Some("/tmp/base/drmemd/src/logic/logic.y")
+   Mod name: None
+   Recoverer: None
+   YaccKind: Some(Grmtools)
+   Visibility: ""
+   Error on conflicts: true
+
+   0 'INT'
+   1 'FLT'
+   2 'IDENTIFIER'
+   3 'TRUE'
+   4 'FALSE'
+   5 'CONTROL'
+   6 'LBRACE'
+   7 'RBRACE'
+   8 'B_OR'
+   9 'B_AND'
+   10 'EQ'
+   11 'NE'
+   12 'GT'
+   13 'GT_EQ'
+   14 'LT'
+   15 'LT_EQ'
+   16 'ADD'
+   17 'SUB'
+   18 'MUL'
+   19 'DIV'
+   20 'REM'
+   21 'B_NOT'
+   22 '('
+   23 ')'
+   24 'STRING'
+   25 'COLOR'
+   26 'COLON'
+   27 'UNKNOWN'
+   28 <unknown>
+*/